@@ -1,9 +1,16 @@
+use crate::semantic::{Instance, Instances};
+
 pub struct ModuleGenerator<'a> {
-    pub(crate) _module: &'a (),
+    pub(crate) instances: &'a Instances,
 }
 
 impl<'a> ModuleGenerator<'a> {
-    pub fn new(_module: &'a ()) -> Self {
-        Self { _module }
+    pub fn new(instances: &'a Instances) -> Self {
+        Self { instances }
+    }
+
+    /// The instances to generate, each after the instances it calls.
+    pub fn worklist(&self) -> impl Iterator<Item = &'a Instance> {
+        self.instances.worklist()
     }
 }
@@ -0,0 +1,44 @@
+use std::fmt::Display;
+
+use crate::span::Span;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExpanderErrorKind {
+    /// A macro call with keyword or rest arguments, or the wrong number of arguments.
+    InvalidMacroArguments,
+    /// A macro whose body is not a single expression, called where an expression is expected.
+    ExpectedExpressionMacro,
+    /// Macro calls nested too deeply, usually from a macro that expands to a call to itself.
+    RecursionLimit,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpanderError {
+    pub(crate) kind: ExpanderErrorKind,
+    pub(crate) span: Span,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl ExpanderError {
+    pub fn new(kind: ExpanderErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl std::error::Error for ExpanderError {}
+
+impl Display for ExpanderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExpanderError")
+            .field("kind", &self.kind)
+            .field("span", &self.span)
+            .finish()
+    }
+}
@@ -0,0 +1,723 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::rc::Rc;
+
+use anyhow::Result;
+
+use crate::error;
+use crate::ir::{
+    visit, visit_mut, Argument, Ast, ClassDef, Declaration, Elif, ExceptHandler, Expr, ExprKind,
+    For, FunctionDef, Identifier, If, InterfaceDef, LambdaBody, MacroDef, MatchCase, NodeId, Param,
+    Pattern, PatternKind, Statement, StatementKind, Try, TypeAlias, TypeAnnotation,
+    TypeAnnotationKind, Visitor, VisitorMut, While, With, WithItem,
+};
+use crate::parser::target_pattern;
+use crate::span::Span;
+
+use super::errors::{ExpanderError, ExpanderErrorKind, ExpanderErrorKind::*};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// Expands the calls to `macro` definitions, between parsing and semantic analysis.
+///
+/// A macro is in scope from its definition to the end of the enclosing block. A call to it, like
+/// `swap(a, b)`, is replaced by a copy of its body where each parameter is replaced by the matching
+/// argument. As a statement, the call becomes the statements of the body. As an expression, the body
+/// must be a single expression.
+///
+/// Expansion is hygienic: the names a macro body binds are renamed with a `_n` suffix that no other
+/// identifier of the module has, so they cannot capture or shadow the names at the call site and
+/// the expanded code parses back the same. The names it uses without binding them get the
+/// definition around the macro as their [`Identifier::context`], so the names at the call site
+/// cannot capture them either. The spans of the expanded code point into the macro
+/// definition, with the call site in [`Span::call_site`].
+#[derive(Debug, Default)]
+pub struct Expander {
+    /// The macros in scope for each enclosing block, innermost last.
+    scopes: Vec<HashMap<String, Rc<Macro>>>,
+    /// The module and the definitions around the code being expanded, innermost last, which have
+    /// the scopes that the free names of the macros defined there are resolved in.
+    definitions: Vec<NodeId>,
+    /// The number of expansions so far, which keeps renamed identifiers unique.
+    expansions: usize,
+    /// The identifiers of the module and the renames so far, which a rename must not spell.
+    names: HashSet<String>,
+    /// The number of macro calls being expanded inside each other.
+    depth: usize,
+    /// The first error found, since the visitor hooks cannot return one.
+    error: Option<ExpanderError>,
+}
+
+/// A macro in scope, with the definition around it.
+#[derive(Debug)]
+struct Macro {
+    def: MacroDef,
+    context: NodeId,
+}
+
+/// Collects every identifier of a module.
+#[derive(Debug, Default)]
+struct Names {
+    names: HashSet<String>,
+}
+
+/// Collects the names a macro body binds.
+#[derive(Debug, Default)]
+struct Binders {
+    names: HashSet<String>,
+    /// The names the classes and interfaces of the body bind, with the parameters of their
+    /// methods, which are reached as attributes and keywords and so keep their names.
+    members: HashSet<String>,
+    /// Whether the names being collected are members of a class or an interface.
+    is_member: bool,
+}
+
+/// Renames the names bound by a macro body, gives the names it uses without binding them the
+/// definition around the macro, and marks its spans with the call site.
+#[derive(Debug)]
+struct Hygiene<'a> {
+    renames: HashMap<String, String>,
+    params: &'a [Identifier],
+    members: &'a HashSet<String>,
+    context: NodeId,
+    call_site: Span,
+}
+
+/// Gives the nodes of an expansion new ids, as they are copies of the macro body and arguments.
+#[derive(Debug)]
+struct Renumbering;
+
+/// Replaces the parameters of a macro with the arguments of a call.
+#[derive(Debug)]
+struct Substitution {
+    arguments: HashMap<String, Expr>,
+    /// The first argument that cannot be a target, where a parameter is assigned to.
+    invalid_target: Option<Span>,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Expander {
+    /// How deeply macro calls can expand to other macro calls.
+    const RECURSION_LIMIT: usize = 64;
+
+    /// Expands every macro call in the AST and removes the macro definitions.
+    pub fn expand(mut ast: Ast) -> Result<Ast> {
+        let mut names = Names::default();
+        names.visit_ast(&ast);
+
+        let mut expander = Expander {
+            names: names.names,
+            definitions: vec![ast.id],
+            ..Default::default()
+        };
+        expander.expand_block(&mut ast.statements);
+
+        match expander.error {
+            Some(err) => error(err),
+            None => Ok(ast),
+        }
+    }
+
+    /// Expands the statements of a block in a new macro scope.
+    fn expand_block(&mut self, statements: &mut Vec<Statement>) {
+        self.scopes.push(HashMap::new());
+        let block = mem::take(statements);
+        self.expand_statements(block, statements);
+        self.scopes.pop();
+    }
+
+    fn expand_statements(&mut self, block: Vec<Statement>, output: &mut Vec<Statement>) {
+        for mut statement in block {
+            if self.error.is_some() {
+                return;
+            }
+
+            match statement.kind {
+                StatementKind::MacroDef(definition) => {
+                    let context = *self.definitions.last().unwrap();
+                    let scope = self.scopes.last_mut().unwrap();
+                    scope.insert(
+                        definition.name.name.clone(),
+                        Rc::new(Macro {
+                            def: *definition,
+                            context,
+                        }),
+                    );
+                }
+                StatementKind::Expr(ref call) if self.find_macro(call).is_some() => {
+                    if let Some(body) = self.expand_call(call) {
+                        self.depth += 1;
+                        self.expand_statements(body, output);
+                        self.depth -= 1;
+                    }
+                }
+                _ => {
+                    self.visit_statement(&mut statement);
+                    output.push(statement);
+                }
+            }
+        }
+    }
+
+    /// Expands a block in the scope of the definition it is the body of.
+    fn expand_definition(&mut self, definition: NodeId, statements: &mut Vec<Statement>) {
+        self.definitions.push(definition);
+        self.expand_block(statements);
+        self.definitions.pop();
+    }
+
+    /// Returns the macro called by an expression, if it calls a macro in scope.
+    fn find_macro(&self, expr: &Expr) -> Option<Rc<Macro>> {
+        let name = match &expr.kind {
+            ExprKind::Call { func, .. } => match &func.kind {
+                ExprKind::Identifier(name) => name,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.name).cloned())
+    }
+
+    /// Returns the hygienic body of a macro for a call to it.
+    fn expand_call(&mut self, call: &Expr) -> Option<Vec<Statement>> {
+        let found = self.find_macro(call)?;
+        let definition = &found.def;
+        let arguments = match &call.kind {
+            ExprKind::Call { arguments, .. } => arguments,
+            _ => unreachable!(),
+        };
+
+        if self.depth >= Self::RECURSION_LIMIT {
+            self.fail(RecursionLimit, &call.span);
+            return None;
+        }
+
+        let arguments = arguments
+            .iter()
+            .map(|argument| match argument {
+                Argument::Positional(value) if !matches!(value.kind, ExprKind::Rest(_)) => {
+                    Some(value.clone())
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+
+        let arguments = match arguments {
+            Some(arguments) if arguments.len() == definition.params.len() => arguments,
+            _ => {
+                self.fail(InvalidMacroArguments, &call.span);
+                return None;
+            }
+        };
+
+        let mut body = definition.body.clone();
+        let mut binders = Binders::default();
+        body.iter()
+            .for_each(|statement| binders.visit_statement(statement));
+
+        self.expansions += 1;
+        let mut hygiene = Hygiene {
+            renames: binders
+                .names
+                .difference(&binders.members)
+                .filter(|name| !definition.params.iter().any(|param| &param.name == *name))
+                .map(|name| {
+                    let rename = self.rename(name);
+                    (name.clone(), rename)
+                })
+                .collect(),
+            params: &definition.params,
+            members: &binders.members,
+            context: found.context,
+            call_site: call.span.clone(),
+        };
+
+        // The arguments come from the call site, so they are substituted after renaming.
+        let mut substitution = Substitution {
+            arguments: definition
+                .params
+                .iter()
+                .map(|param| param.name.clone())
+                .zip(arguments)
+                .collect(),
+            invalid_target: None,
+        };
+
+        for statement in body.iter_mut() {
+            hygiene.visit_statement(statement);
+            substitution.visit_statement(statement);
+            Renumbering.visit_statement(statement);
+        }
+
+        if let Some(span) = substitution.invalid_target {
+            self.fail(InvalidMacroArguments, &span);
+            return None;
+        }
+
+        Some(body)
+    }
+
+    /// A spelling of a name bound by the current expansion that no other identifier has, like
+    /// `tmp_1` for `tmp`.
+    fn rename(&mut self, name: &str) -> String {
+        let mut rename = format!("{name}_{}", self.expansions);
+        while self.names.contains(&rename) {
+            rename.push('_');
+        }
+
+        self.names.insert(rename.clone());
+        rename
+    }
+
+    fn fail(&mut self, kind: ExpanderErrorKind, span: &Span) {
+        self.error
+            .get_or_insert(ExpanderError::new(kind, span.clone()));
+    }
+}
+
+impl VisitorMut for Expander {
+    fn visit_expr(&mut self, node: &mut Expr) {
+        if let ExprKind::Lambda(_) = node.kind {
+            self.definitions.push(node.id);
+            visit_mut::walk_expr(self, node);
+            self.definitions.pop();
+            return;
+        }
+
+        if self.find_macro(node).is_none() {
+            return visit_mut::walk_expr(self, node);
+        }
+
+        let mut body = match self.expand_call(node) {
+            Some(body) => body,
+            None => return,
+        };
+
+        match (body.len(), body.pop().map(|statement| statement.kind)) {
+            (1, Some(StatementKind::Expr(expr))) => {
+                *node = expr;
+                self.depth += 1;
+                self.visit_expr(node);
+                self.depth -= 1;
+            }
+            _ => self.fail(ExpectedExpressionMacro, &node.span),
+        }
+    }
+
+    fn visit_lambda_body(&mut self, node: &mut LambdaBody) {
+        match node {
+            LambdaBody::Block(body) => self.expand_block(body),
+            LambdaBody::Expr(_) => visit_mut::walk_lambda_body(self, node),
+        }
+    }
+
+    fn visit_if(&mut self, node: &mut If) {
+        let mut body = mem::take(&mut node.body);
+        let mut orelse = node.orelse.take();
+        visit_mut::walk_if(self, node);
+
+        self.expand_block(&mut body);
+        if let Some(orelse) = &mut orelse {
+            self.expand_block(orelse);
+        }
+
+        node.body = body;
+        node.orelse = orelse;
+    }
+
+    fn visit_elif(&mut self, node: &mut Elif) {
+        self.visit_expr(&mut node.condition);
+        self.expand_block(&mut node.body);
+    }
+
+    fn visit_while(&mut self, node: &mut While) {
+        let mut body = mem::take(&mut node.body);
+        let mut orelse = node.orelse.take();
+        visit_mut::walk_while(self, node);
+
+        self.expand_block(&mut body);
+        if let Some(orelse) = &mut orelse {
+            self.expand_block(orelse);
+        }
+
+        node.body = body;
+        node.orelse = orelse;
+    }
+
+    fn visit_for(&mut self, node: &mut For) {
+        let mut body = mem::take(&mut node.body);
+        let mut orelse = node.orelse.take();
+        visit_mut::walk_for(self, node);
+
+        self.expand_block(&mut body);
+        if let Some(orelse) = &mut orelse {
+            self.expand_block(orelse);
+        }
+
+        node.body = body;
+        node.orelse = orelse;
+    }
+
+    fn visit_try(&mut self, node: &mut Try) {
+        self.expand_block(&mut node.body);
+        node.handlers
+            .iter_mut()
+            .for_each(|handler| self.visit_except_handler(handler));
+
+        for block in [&mut node.orelse, &mut node.finalbody]
+            .into_iter()
+            .flatten()
+        {
+            self.expand_block(block);
+        }
+    }
+
+    fn visit_except_handler(&mut self, node: &mut ExceptHandler) {
+        self.expand_block(&mut node.body);
+    }
+
+    fn visit_with(&mut self, node: &mut With) {
+        let mut body = mem::take(&mut node.body);
+        visit_mut::walk_with(self, node);
+        self.expand_block(&mut body);
+        node.body = body;
+    }
+
+    fn visit_match_case(&mut self, node: &mut MatchCase) {
+        let mut body = mem::take(&mut node.body);
+        visit_mut::walk_match_case(self, node);
+        self.expand_block(&mut body);
+        node.body = body;
+    }
+
+    fn visit_function_def(&mut self, node: &mut FunctionDef) {
+        let mut body = mem::take(&mut node.body);
+        visit_mut::walk_function_def(self, node);
+        self.expand_definition(node.name.id, &mut body);
+        node.body = body;
+    }
+
+    fn visit_class_def(&mut self, node: &mut ClassDef) {
+        let mut body = mem::take(&mut node.body);
+        visit_mut::walk_class_def(self, node);
+        self.expand_definition(node.name.id, &mut body);
+        node.body = body;
+    }
+
+    fn visit_interface_def(&mut self, node: &mut InterfaceDef) {
+        let mut body = mem::take(&mut node.body);
+        visit_mut::walk_interface_def(self, node);
+        self.expand_definition(node.name.id, &mut body);
+        node.body = body;
+    }
+}
+
+impl Visitor for Names {
+    fn visit_identifier(&mut self, node: &Identifier) {
+        self.names.insert(node.name.clone());
+    }
+}
+
+impl Binders {
+    /// Collects the name an augmented or annotated assignment binds, as the other targets are
+    /// patterns.
+    fn collect_target(&mut self, target: &Expr) {
+        if let ExprKind::Identifier(name) = &target.kind {
+            self.collect(name);
+        }
+    }
+
+    fn collect(&mut self, name: &Identifier) {
+        match self.is_member {
+            true => self.members.insert(name.name.clone()),
+            false => self.names.insert(name.name.clone()),
+        };
+    }
+
+    /// Collects the names of a class or interface body as members.
+    fn collect_members(&mut self, walk: impl FnOnce(&mut Self)) {
+        let is_member = mem::replace(&mut self.is_member, true);
+        walk(self);
+        self.is_member = is_member;
+    }
+}
+
+impl Visitor for Binders {
+    fn visit_statement(&mut self, node: &Statement) {
+        match &node.kind {
+            StatementKind::AugAssign(assign) => self.collect_target(&assign.target),
+            StatementKind::AnnAssign(assign) => self.collect_target(&assign.target),
+            _ => (),
+        }
+
+        visit::walk_statement(self, node);
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        if let ExprKind::NamedExpr { name, .. } = &node.kind {
+            self.collect(name);
+        }
+
+        visit::walk_expr(self, node);
+    }
+
+    fn visit_declaration(&mut self, node: &Declaration) {
+        self.collect(&node.name);
+        visit::walk_declaration(self, node);
+    }
+
+    fn visit_type_alias(&mut self, node: &TypeAlias) {
+        self.collect(&node.name);
+        visit::walk_type_alias(self, node);
+    }
+
+    fn visit_function_def(&mut self, node: &FunctionDef) {
+        self.collect(&node.name);
+        node.decorators
+            .iter()
+            .for_each(|decorator| self.visit_decorator(decorator));
+        self.visit_params(&node.params);
+
+        let is_member = mem::replace(&mut self.is_member, false);
+        node.body
+            .iter()
+            .for_each(|statement| self.visit_statement(statement));
+        self.is_member = is_member;
+    }
+
+    fn visit_class_def(&mut self, node: &ClassDef) {
+        self.collect(&node.name);
+        self.collect_members(|this| visit::walk_class_def(this, node));
+    }
+
+    fn visit_interface_def(&mut self, node: &InterfaceDef) {
+        self.collect(&node.name);
+        self.collect_members(|this| visit::walk_interface_def(this, node));
+    }
+
+    fn visit_macro_def(&mut self, node: &MacroDef) {
+        self.collect(&node.name);
+        visit::walk_macro_def(self, node);
+    }
+
+    fn visit_param(&mut self, node: &Param) {
+        self.collect(&node.name);
+        visit::walk_param(self, node);
+    }
+
+    fn visit_with_item(&mut self, node: &WithItem) {
+        if let Some(alias) = &node.alias {
+            self.collect(alias);
+        }
+
+        visit::walk_with_item(self, node);
+    }
+
+    fn visit_except_handler(&mut self, node: &ExceptHandler) {
+        if let Some(name) = &node.name {
+            self.collect(name);
+        }
+
+        visit::walk_except_handler(self, node);
+    }
+
+    fn visit_pattern(&mut self, node: &Pattern) {
+        match &node.kind {
+            PatternKind::Capture(name) | PatternKind::As { name, .. } => self.collect(name),
+            PatternKind::Rest(Some(name))
+            | PatternKind::Mapping {
+                rest: Some(name), ..
+            } => self.collect(name),
+            _ => (),
+        }
+
+        visit::walk_pattern(self, node);
+    }
+}
+
+impl Hygiene<'_> {
+    /// Renames a bound name, or gives a free one the definition around the macro. The names of
+    /// a macro defined in another macro body keep the context they got from the outer expansion.
+    fn rename(&self, name: &mut Identifier) {
+        match self.renames.get(&name.name) {
+            Some(rename) => name.name = rename.clone(),
+            None if self.params.iter().any(|param| param.name == name.name) => (),
+            None if self.members.contains(&name.name) => (),
+            None => {
+                name.context.get_or_insert(self.context);
+            }
+        }
+    }
+}
+
+/// Renames the same positions [`Binders`] collects from, and every name that refers to them.
+impl VisitorMut for Hygiene<'_> {
+    fn visit_statement(&mut self, node: &mut Statement) {
+        if let StatementKind::Global(names) | StatementKind::Nonlocal(names) = &mut node.kind {
+            names.iter_mut().for_each(|name| self.rename(name));
+        }
+
+        visit_mut::walk_statement(self, node);
+    }
+
+    fn visit_expr(&mut self, node: &mut Expr) {
+        match &mut node.kind {
+            ExprKind::Identifier(name) | ExprKind::NamedExpr { name, .. } => self.rename(name),
+            _ => (),
+        }
+
+        visit_mut::walk_expr(self, node);
+    }
+
+    fn visit_declaration(&mut self, node: &mut Declaration) {
+        self.rename(&mut node.name);
+        visit_mut::walk_declaration(self, node);
+    }
+
+    fn visit_type_alias(&mut self, node: &mut TypeAlias) {
+        self.rename(&mut node.name);
+        visit_mut::walk_type_alias(self, node);
+    }
+
+    fn visit_function_def(&mut self, node: &mut FunctionDef) {
+        self.rename(&mut node.name);
+        visit_mut::walk_function_def(self, node);
+    }
+
+    fn visit_class_def(&mut self, node: &mut ClassDef) {
+        self.rename(&mut node.name);
+        visit_mut::walk_class_def(self, node);
+    }
+
+    fn visit_interface_def(&mut self, node: &mut InterfaceDef) {
+        self.rename(&mut node.name);
+        visit_mut::walk_interface_def(self, node);
+    }
+
+    fn visit_macro_def(&mut self, node: &mut MacroDef) {
+        self.rename(&mut node.name);
+        visit_mut::walk_macro_def(self, node);
+    }
+
+    fn visit_param(&mut self, node: &mut Param) {
+        self.rename(&mut node.name);
+        visit_mut::walk_param(self, node);
+    }
+
+    fn visit_with_item(&mut self, node: &mut WithItem) {
+        if let Some(alias) = &mut node.alias {
+            self.rename(alias);
+        }
+
+        visit_mut::walk_with_item(self, node);
+    }
+
+    fn visit_except_handler(&mut self, node: &mut ExceptHandler) {
+        if let Some(name) = &mut node.name {
+            self.rename(name);
+        }
+
+        visit_mut::walk_except_handler(self, node);
+    }
+
+    fn visit_pattern(&mut self, node: &mut Pattern) {
+        match &mut node.kind {
+            PatternKind::Capture(name) | PatternKind::As { name, .. } => self.rename(name),
+            PatternKind::Rest(Some(name))
+            | PatternKind::Mapping {
+                rest: Some(name), ..
+            } => self.rename(name),
+            _ => (),
+        }
+
+        visit_mut::walk_pattern(self, node);
+    }
+
+    fn visit_type_annotation(&mut self, node: &mut TypeAnnotation) {
+        match &mut node.kind {
+            TypeAnnotationKind::Identifier(name) | TypeAnnotationKind::Generic { name, .. } => {
+                self.rename(name)
+            }
+            TypeAnnotationKind::Path(path) => self.rename(&mut path.names[0]),
+            _ => (),
+        }
+
+        visit_mut::walk_type_annotation(self, node);
+    }
+
+    fn visit_span(&mut self, node: &mut Span) {
+        // Code from a macro defined inside another expansion keeps its first call site.
+        if node.call_site.is_none() {
+            node.call_site = Some(Box::new(self.call_site.clone()));
+        }
+    }
+}
+
+impl VisitorMut for Renumbering {
+    fn visit_statement(&mut self, node: &mut Statement) {
+        node.id = NodeId::next();
+        visit_mut::walk_statement(self, node);
+    }
+
+    fn visit_expr(&mut self, node: &mut Expr) {
+        node.id = NodeId::next();
+        visit_mut::walk_expr(self, node);
+    }
+
+    fn visit_identifier(&mut self, node: &mut Identifier) {
+        node.id = NodeId::next();
+    }
+}
+
+impl VisitorMut for Substitution {
+    fn visit_expr(&mut self, node: &mut Expr) {
+        if let ExprKind::Identifier(name) = &node.kind {
+            if let Some(argument) = self.arguments.get(&name.name) {
+                *node = argument.clone();
+                return;
+            }
+        }
+
+        visit_mut::walk_expr(self, node);
+    }
+
+    fn visit_pattern(&mut self, node: &mut Pattern) {
+        match &mut node.kind {
+            PatternKind::Capture(name) => {
+                if let Some(argument) = self.arguments.get(&name.name) {
+                    match target_pattern(argument.clone()) {
+                        Ok(pattern) => *node = pattern,
+                        Err(_) => {
+                            self.invalid_target.get_or_insert(argument.span.clone());
+                        }
+                    }
+
+                    return;
+                }
+            }
+            // `*rest` can only take a name.
+            PatternKind::Rest(Some(name)) => match self.arguments.get(&name.name) {
+                Some(Expr {
+                    kind: ExprKind::Identifier(argument),
+                    ..
+                }) => *name = argument.clone(),
+                Some(argument) => {
+                    self.invalid_target.get_or_insert(argument.span.clone());
+                }
+                None => (),
+            },
+            _ => (),
+        }
+
+        visit_mut::walk_pattern(self, node);
+    }
+}
@@ -0,0 +1,9 @@
+#![allow(clippy::module_inception)]
+mod errors;
+mod expander;
+
+pub use errors::*;
+pub use expander::*;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,207 @@
+use crate::ir::{ExprKind, StatementKind};
+use crate::parser::Parser;
+use crate::semantic::SemanticAnalyzer;
+use crate::span::Span;
+use crate::unparser::Unparser;
+
+use super::ExpanderErrorKind::*;
+
+use utils::*;
+
+#[test]
+fn can_expand_statement_macros_hygienically() {
+    let code = r#"
+macro swap(a, b):
+    tmp = a
+    a = b
+    b = tmp
+tmp = 1
+swap(tmp, y)
+"#;
+
+    assert_eq!(expand(code), "tmp = 1\ntmp_1 = tmp\ntmp = y\ny = tmp_1\n");
+}
+
+#[test]
+fn can_rename_binders_apart_from_the_module_names() {
+    let code = r#"
+macro swap(a, b):
+    tmp = a
+    a = b
+    b = tmp
+tmp_1 = 1
+swap(tmp_1, tmp_1_)
+"#;
+
+    assert_eq!(
+        expand(code),
+        "tmp_1 = 1\ntmp_1__ = tmp_1\ntmp_1 = tmp_1_\ntmp_1_ = tmp_1__\n"
+    );
+}
+
+#[test]
+fn can_parse_unparsed_expansions_back() {
+    let code = r#"
+macro swap(a, b):
+    tmp = a
+    a = b
+    b = tmp
+macro squares(xs): [x * x for x in xs]
+tmp = squares(x)
+swap(tmp, y)
+"#;
+
+    let expanded = expand(code);
+    let reparsed = Unparser::unparse(&Parser::parse(&expanded).unwrap());
+
+    assert_eq!(reparsed, expanded);
+}
+
+#[test]
+fn can_keep_the_member_names_of_classes_defined_by_macros() {
+    let code = r#"
+macro counter():
+    class Counter:
+        def __init__(self, count: int):
+            self.count = count
+        def bump(self, by: int) -> int:
+            total = self.count + by
+            return total
+    c = Counter(0)
+    c.bump(by=1)
+counter()
+"#;
+
+    let expanded = expand(code);
+
+    assert_eq!(
+        expanded,
+        "class Counter_1:\n    def __init__(self, count: int):\n        self.count = count\n    def bump(self, by: int) -> int:\n        total_1 = self.count + by\n        return total_1\nc_1 = Counter_1(0)\nc_1.bump(by=1)\n"
+    );
+    assert!(SemanticAnalyzer::analyze(&expand_ast(code))
+        .diagnostics
+        .is_empty());
+}
+
+#[test]
+fn can_substitute_destructuring_targets() {
+    let code = "macro pop(a, b): a, *b = b\npop(x.y, z)\n";
+
+    assert_eq!(expand(code), "x.y, *z = z\n");
+}
+
+#[test]
+fn can_expand_expression_macros() {
+    let code = r#"
+macro double(x): x * 2
+macro quadruple(x): double(double(x))
+y = quadruple(a + 1)
+"#;
+
+    assert_eq!(expand(code), "y = (a + 1) * 2 * 2\n");
+}
+
+#[test]
+fn can_rename_comprehension_targets() {
+    let code = "macro squares(xs): [x * x for x in xs]\ny = squares(x)\n";
+
+    assert_eq!(expand(code), "y = [x_1 * x_1 for x_1 in x]\n");
+}
+
+#[test]
+fn can_scope_macros_to_their_block() {
+    let code = r#"
+f()
+macro f(): g()
+def h():
+    macro f(): lambda x: x
+    f()
+f()
+"#;
+
+    assert_eq!(expand(code), "f()\ndef h():\n    lambda x_1: x_1\ng()\n");
+}
+
+#[test]
+fn can_point_expanded_spans_to_the_call_site() {
+    let ast = expand_ast("macro f(x): x + 1\nf(a)\n");
+
+    match &ast.statements[0].kind {
+        StatementKind::Expr(expr) => match &expr.kind {
+            ExprKind::BinaryOp { lhs, rhs, .. } => {
+                let call_site = Some(Box::new(Span::new(18, 22)));
+
+                // The argument comes from the call site, and the rest from the definition.
+                assert_eq!(lhs.span, Span::new(20, 21));
+                assert_eq!(
+                    rhs.span,
+                    Span {
+                        start: 16,
+                        end: 17,
+                        call_site: call_site.clone()
+                    }
+                );
+                assert_eq!(expr.span.call_site, call_site);
+            }
+            kind => panic!("expected a binary operation, found {kind:?}"),
+        },
+        kind => panic!("expected an expression, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_give_expanded_copies_their_own_ids() {
+    let ast = expand_ast("macro twice(x): x + x\ntwice(a)\n");
+
+    match &ast.statements[0].kind {
+        StatementKind::Expr(expr) => match &expr.kind {
+            ExprKind::BinaryOp { lhs, rhs, .. } => {
+                // Both copies of the argument span it at the call site.
+                assert_eq!(lhs.span, rhs.span);
+                assert_ne!(lhs.id, rhs.id);
+            }
+            kind => panic!("expected a binary operation, found {kind:?}"),
+        },
+        kind => panic!("expected an expression, found {kind:?}"),
+    }
+}
+
+#[test]
+fn cannot_expand_invalid_macro_calls() {
+    assert_eq!(expand_error("macro f(a): a\nf()"), InvalidMacroArguments);
+    assert_eq!(expand_error("macro f(a): a\nf(a=1)"), InvalidMacroArguments);
+    assert_eq!(expand_error("macro f(a): a\nf(*a)"), InvalidMacroArguments);
+    assert_eq!(
+        expand_error("macro f():\n    a = 1\n    b = 2\nx = f()"),
+        ExpectedExpressionMacro
+    );
+    assert_eq!(expand_error("macro f(): f()\nf()"), RecursionLimit);
+    assert_eq!(
+        expand_error("macro f(a): a = 1\nf(1)"),
+        InvalidMacroArguments
+    );
+}
+
+mod utils {
+    use crate::expander::{Expander, ExpanderError, ExpanderErrorKind};
+    use crate::ir::Ast;
+    use crate::parser::Parser;
+    use crate::unparser::Unparser;
+
+    pub(super) fn expand_ast(code: &str) -> Ast {
+        Expander::expand(Parser::parse(code).unwrap()).unwrap()
+    }
+
+    /// Expands the code and unparses the result.
+    pub(super) fn expand(code: &str) -> String {
+        Unparser::unparse(&expand_ast(code))
+    }
+
+    pub(super) fn expand_error(code: &str) -> ExpanderErrorKind {
+        Expander::expand(Parser::parse(code).unwrap())
+            .unwrap_err()
+            .downcast::<ExpanderError>()
+            .unwrap()
+            .kind
+    }
+}
@@ -1 +1,789 @@
-pub struct Ast {}
+use crate::lexer::{BytesKind, IntegerKind, StringKind};
+use crate::span::Span;
+
+use super::NodeId;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// The root of a parsed Raccoon module.
+///
+/// Check [`parser.grammar`](#parser.grammar) for the grammar each node is derived from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Ast {
+    pub statements: Vec<Statement>,
+    pub id: NodeId,
+    pub span: Span,
+}
+
+/// A name as it appears in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Identifier {
+    pub name: String,
+    pub id: NodeId,
+    pub span: Span,
+    /// The definition a free name of a macro body was written in, like the function or the module
+    /// around the `macro`, whose scope the name is resolved in instead of the scope of the call.
+    pub context: Option<NodeId>,
+}
+
+/// A dotted name like `a.b.c`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Path {
+    pub names: Vec<Identifier>,
+}
+
+//------------------------------------------------------------------------------
+// Statements
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub id: NodeId,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum StatementKind {
+    Expr(Expr),
+    Assign(Assign),
+    AugAssign(AugAssign),
+    AnnAssign(AnnAssign),
+    Declaration(Declaration),
+    TypeAlias(TypeAlias),
+    Pass,
+    Break,
+    Continue,
+    Return(Option<Expr>),
+    Raise(Raise),
+    Global(Vec<Identifier>),
+    Nonlocal(Vec<Identifier>),
+    Assert(Assert),
+    Import(Import),
+    ImportFrom(ImportFrom),
+    If(If),
+    While(While),
+    For(For),
+    Try(Try),
+    With(With),
+    Match(Match),
+    FunctionDef(Box<FunctionDef>),
+    ClassDef(Box<ClassDef>),
+    InterfaceDef(Box<InterfaceDef>),
+    MacroDef(Box<MacroDef>),
+}
+
+/// `a = b = value`, where each target is lowered to a pattern like `[a, *rest]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Assign {
+    pub targets: Vec<Pattern>,
+    pub value: Expr,
+}
+
+/// `target += value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct AugAssign {
+    pub target: Expr,
+    pub op: BinaryOp,
+    pub value: Expr,
+}
+
+/// `target: annotation = value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct AnnAssign {
+    pub target: Expr,
+    pub annotation: TypeAnnotation,
+    pub value: Option<Expr>,
+}
+
+/// `let name: annotation = value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Declaration {
+    pub kind: DeclarationKind,
+    pub name: Identifier,
+    pub annotation: Option<TypeAnnotation>,
+    pub value: Option<Expr>,
+}
+
+/// The keyword of a declaration, which decides if the name can be reassigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum DeclarationKind {
+    /// `let`, an immutable binding.
+    Let,
+    /// `var`, a mutable binding.
+    Var,
+    /// `const`, a compile-time constant that needs a value.
+    Const,
+}
+
+/// `typealias name[generics] = value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct TypeAlias {
+    pub name: Identifier,
+    pub generics: Vec<Identifier>,
+    pub value: TypeAnnotation,
+}
+
+/// `raise exception from cause`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Raise {
+    pub exception: Option<Expr>,
+    pub cause: Option<Expr>,
+}
+
+/// `assert test, message`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Assert {
+    pub test: Expr,
+    pub message: Option<Expr>,
+}
+
+/// `import path as alias`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Import {
+    pub path: ImportPath,
+    pub alias: Option<Identifier>,
+}
+
+/// `from path import names`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ImportFrom {
+    pub path: ImportPath,
+    pub names: ImportNames,
+}
+
+/// A module path with the number of leading dots of a relative import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ImportPath {
+    #[cfg_attr(
+        feature = "arbitrary",
+        arbitrary(with = |u: &mut arbitrary::Unstructured| u.int_in_range(0..=3))
+    )]
+    pub level: usize,
+    pub names: Vec<Identifier>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ImportNames {
+    /// `from path import *`
+    All,
+    Names(Vec<ImportName>),
+}
+
+/// `name as alias`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ImportName {
+    pub name: Identifier,
+    pub alias: Option<Identifier>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct If {
+    pub condition: Expr,
+    pub body: Vec<Statement>,
+    pub elifs: Vec<Elif>,
+    pub orelse: Option<Vec<Statement>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Elif {
+    pub condition: Expr,
+    pub body: Vec<Statement>,
+}
+
+/// `while condition if guard:`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct While {
+    pub condition: Expr,
+    pub guard: Option<Expr>,
+    pub body: Vec<Statement>,
+    pub orelse: Option<Vec<Statement>>,
+}
+
+/// `for target in iter if guard:`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct For {
+    /// `async for`
+    pub is_async: bool,
+    pub target: Pattern,
+    pub iter: Expr,
+    pub guard: Option<Expr>,
+    pub body: Vec<Statement>,
+    pub orelse: Option<Vec<Statement>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Try {
+    pub body: Vec<Statement>,
+    pub handlers: Vec<ExceptHandler>,
+    pub orelse: Option<Vec<Statement>>,
+    pub finalbody: Option<Vec<Statement>>,
+}
+
+/// `except exception as name:`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ExceptHandler {
+    pub exception: Identifier,
+    pub name: Option<Identifier>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct With {
+    /// `async with`
+    pub is_async: bool,
+    pub items: Vec<WithItem>,
+    pub body: Vec<Statement>,
+}
+
+/// `context as alias`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct WithItem {
+    pub context: Expr,
+    pub alias: Option<Identifier>,
+}
+
+/// `match subject:` with a block of cases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Match {
+    pub subject: Expr,
+    pub cases: Vec<MatchCase>,
+}
+
+/// `case pattern if guard:`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct MatchCase {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct FunctionDef {
+    pub decorators: Vec<Decorator>,
+    /// `abstract def`
+    pub is_abstract: bool,
+    /// `async def`
+    pub is_async: bool,
+    /// Whether the body yields, which the parser infers.
+    pub is_generator: bool,
+    pub name: Identifier,
+    pub generics: Vec<Identifier>,
+    pub bounds: Vec<GenericBound>,
+    pub params: Params,
+    pub return_type: Option<TypeAnnotation>,
+    /// Empty for a signature without a body.
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ClassDef {
+    pub decorators: Vec<Decorator>,
+    pub kind: ClassKind,
+    pub name: Identifier,
+    pub generics: Vec<Identifier>,
+    pub bounds: Vec<GenericBound>,
+    pub bases: Vec<Identifier>,
+    /// The primary constructor fields of a data class.
+    pub fields: Vec<Param>,
+    /// The variants of an enum class.
+    pub variants: Vec<Variant>,
+    /// Empty for a data class without a body.
+    pub body: Vec<Statement>,
+}
+
+/// The modifier before `class`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ClassKind {
+    Class,
+    /// `data class Name(fields)`
+    Data,
+    /// `enum class`
+    Enum,
+    /// `union class`, whose fields share the same memory.
+    Union,
+    /// `abstract class`
+    Abstract,
+}
+
+/// An enum variant like `Some(t: T)`, `Ok(T)` or `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Variant {
+    pub name: Identifier,
+    pub fields: Option<Vec<VariantField>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum VariantField {
+    /// `name: annotation`
+    Named {
+        name: Identifier,
+        annotation: TypeAnnotation,
+    },
+    Positional(TypeAnnotation),
+}
+
+/// `interface name[generics](bases):` whose body only declares signatures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct InterfaceDef {
+    pub decorators: Vec<Decorator>,
+    pub name: Identifier,
+    pub generics: Vec<Identifier>,
+    pub bounds: Vec<GenericBound>,
+    pub bases: Vec<Identifier>,
+    pub body: Vec<Statement>,
+}
+
+/// `macro name(params):`, whose body replaces the calls to it during expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct MacroDef {
+    pub name: Identifier,
+    pub params: Vec<Identifier>,
+    pub body: Vec<Statement>,
+}
+
+/// `name: bound` in a `@where(...)` clause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct GenericBound {
+    pub name: Identifier,
+    pub bound: TypeAnnotation,
+}
+
+/// `@path(arguments)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Decorator {
+    pub path: Path,
+    pub arguments: Option<Vec<Argument>>,
+    pub span: Span,
+}
+
+/// The parameters of a function or lambda.
+///
+/// `(positional_only, /, params, *vararg, keyword_only, **kwarg)`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Params {
+    pub positional_only: Vec<Param>,
+    pub params: Vec<Param>,
+    pub vararg: Option<Param>,
+    pub keyword_only: Vec<Param>,
+    pub kwarg: Option<Param>,
+}
+
+/// `ref mut name: annotation = default`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Param {
+    /// How the argument is passed. `None` passes primitives by value and complex types by reference.
+    pub mode: Option<PassingMode>,
+    pub is_mut: bool,
+    pub name: Identifier,
+    pub annotation: Option<TypeAnnotation>,
+    pub default: Option<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum PassingMode {
+    /// `ref`, a reference to the argument.
+    Ref,
+    /// `val`, a shallow copy of the stack-livable part of the argument.
+    Val,
+    /// `ptr`, a raw pointer to the argument.
+    Ptr,
+}
+
+//------------------------------------------------------------------------------
+// Expressions
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub id: NodeId,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ExprKind {
+    Identifier(Identifier),
+    Integer(String, IntegerKind),
+    Float(String),
+    Imag(String),
+    /// Adjacent string literals that get compiled as a string list.
+    Strings(Vec<StringLiteral>),
+    Bool(bool),
+    Tuple(Vec<Expr>),
+    List(Vec<Expr>),
+    Set(Vec<Expr>),
+    Dict(Vec<DictEntry>),
+    /// `[element for target in iter]`
+    ListComp(Box<Comprehension>),
+    /// `{element for target in iter}`
+    SetComp(Box<Comprehension>),
+    /// `{key: value for target in iter}`
+    DictComp(Box<DictComprehension>),
+    /// `(element for target in iter)`, whose parentheses can be left out when it is the only argument
+    /// of a call.
+    Generator(Box<Comprehension>),
+    /// `*value`
+    Rest(Box<Expr>),
+    /// `**value`
+    DictRest(Box<Expr>),
+    /// `name := value`
+    NamedExpr {
+        name: Identifier,
+        value: Box<Expr>,
+    },
+    UnaryOp {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    BinaryOp {
+        lhs: Box<Expr>,
+        op: BinaryOp,
+        rhs: Box<Expr>,
+    },
+    /// `lhs < a <= b`
+    Comparison {
+        lhs: Box<Expr>,
+        comparisons: Vec<Comparison>,
+    },
+    /// `then if condition else otherwise`
+    Ternary {
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Box<Expr>,
+    },
+    Lambda(Box<Lambda>),
+    Call {
+        func: Box<Expr>,
+        arguments: Vec<Argument>,
+    },
+    Subscript {
+        value: Box<Expr>,
+        indices: Vec<SubscriptIndex>,
+    },
+    Attribute {
+        value: Box<Expr>,
+        attribute: Identifier,
+    },
+    /// `value?`, which returns early with the error of `value`.
+    Propagate(Box<Expr>),
+    /// `await value`
+    Await(Box<Expr>),
+    /// `yield value`
+    Yield(Option<Box<Expr>>),
+    /// `yield from value`
+    YieldFrom(Box<Expr>),
+    /// A `match` in an expression, whose cases evaluate to expressions.
+    Match(Box<MatchExpr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum StringLiteral {
+    Str(String, StringKind),
+    Bytes(String, BytesKind),
+}
+
+/// `key: value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct DictEntry {
+    pub key: Expr,
+    pub value: Expr,
+}
+
+/// A comprehension is a scope of its own, so its targets do not leak into the enclosing function.
+/// The iterable of the first clause is the exception, as it is evaluated in the enclosing scope, and
+/// so are named expressions, which bind in the enclosing function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Comprehension {
+    pub element: Expr,
+    pub clauses: Vec<ComprehensionFor>,
+}
+
+/// A dict comprehension, which is scoped like a [`Comprehension`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct DictComprehension {
+    pub entry: DictEntry,
+    pub clauses: Vec<ComprehensionFor>,
+}
+
+/// `for target in iter if condition`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ComprehensionFor {
+    /// `async for`
+    pub is_async: bool,
+    pub target: Pattern,
+    pub iter: Expr,
+    pub conditions: Vec<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Comparison {
+    pub op: ComparisonOp,
+    pub rhs: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Lambda {
+    pub params: Params,
+    pub body: LambdaBody,
+    /// Whether the body yields, which the parser infers.
+    pub is_generator: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum LambdaBody {
+    Expr(Box<Expr>),
+    /// An indented block of statements.
+    Block(Vec<Statement>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Argument {
+    /// A positional argument, which may also be a `*rest` or `**rest` expression.
+    Positional(Expr),
+    Keyword {
+        name: Identifier,
+        value: Expr,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum SubscriptIndex {
+    Index(Expr),
+    /// `lower:upper:step`
+    Slice {
+        lower: Option<Expr>,
+        upper: Option<Expr>,
+        step: Option<Expr>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct MatchExpr {
+    pub subject: Expr,
+    pub cases: Vec<MatchExprCase>,
+}
+
+/// `case pattern if guard: body`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct MatchExprCase {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum UnaryOp {
+    Plus,
+    Minus,
+    BitNot,
+    Not,
+    /// Prefix `√`.
+    Sqrt,
+    /// Postfix `²`.
+    Square,
+    /// `ref value`
+    Ref,
+    /// `val value`
+    Val,
+    /// `ptr value`
+    Ptr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum BinaryOp {
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    ShiftL,
+    ShiftR,
+    Plus,
+    Minus,
+    Mul,
+    MatMul,
+    Div,
+    IntDiv,
+    Mod,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ComparisonOp {
+    Less,
+    Greater,
+    Eq,
+    GreaterEq,
+    LessEq,
+    NotEq,
+    In,
+    NotIn,
+    Is,
+    IsNot,
+}
+
+//------------------------------------------------------------------------------
+// Patterns
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum PatternKind {
+    /// `_`
+    Wildcard,
+    /// `name`, which binds the subject unless it resolves to a unit variant like `None`.
+    Capture(Identifier),
+    /// A string, boolean or optionally negated number.
+    Literal(Expr),
+    /// A dotted name like `Color.Red`.
+    Value(Path),
+    /// `[a, *rest]` or `(a, b)`
+    Sequence(Vec<Pattern>),
+    /// `*name` in a sequence pattern, with no name for `*_`.
+    Rest(Option<Identifier>),
+    /// `{key: value, **rest}`
+    Mapping {
+        entries: Vec<MappingPatternEntry>,
+        rest: Option<Identifier>,
+    },
+    /// `Name(patterns, keyword=pattern)`
+    Class {
+        path: Path,
+        patterns: Vec<Pattern>,
+        keywords: Vec<KeywordPattern>,
+    },
+    /// `a | b`
+    Or(Vec<Pattern>),
+    /// `pattern as name`
+    As {
+        pattern: Box<Pattern>,
+        name: Identifier,
+    },
+    /// An attribute or subscript like `a.b` or `a[i]`, which only assignment and `for` targets store
+    /// into.
+    Store(Box<Expr>),
+}
+
+/// `key: value`, where the key is a literal or value pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct MappingPatternEntry {
+    pub key: Pattern,
+    pub value: Pattern,
+}
+
+/// `name=pattern`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct KeywordPattern {
+    pub name: Identifier,
+    pub pattern: Pattern,
+}
+
+//------------------------------------------------------------------------------
+// Type Annotations
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct TypeAnnotation {
+    pub kind: TypeAnnotationKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum TypeAnnotationKind {
+    Identifier(Identifier),
+    /// `namespace.name`, like the enum variant `PrimaryColor.Red`.
+    Path(Path),
+    /// `name[arguments]`
+    Generic {
+        name: Identifier,
+        arguments: Vec<TypeAnnotation>,
+    },
+    /// `(params) -> return_type`
+    Function {
+        params: Vec<TypeAnnotation>,
+        return_type: Box<TypeAnnotation>,
+    },
+    /// `[element, ...]`
+    List(Vec<TypeAnnotation>),
+    /// `(element, ...)`
+    Tuple(Vec<TypeAnnotation>),
+    /// `a | b`
+    Union(Vec<TypeAnnotation>),
+    /// `a & b`
+    Intersection(Vec<TypeAnnotation>),
+    /// `a?`, which is `a` or an error.
+    Optional(Box<TypeAnnotation>),
+}
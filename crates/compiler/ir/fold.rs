@@ -0,0 +1,8 @@
+//! Owning traversal over the AST that rebuilds each node.
+//!
+//! The trait and its `fold_*` functions are generated from `ast.rs`, so every node kind gets a hook.
+
+use super::*;
+use crate::span::Span;
+
+raccoon_macros::fold!("ir/ast.rs", Span, NodeId);
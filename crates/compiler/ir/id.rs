@@ -0,0 +1,29 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// Identifies a node of a module for the tables that later passes keep about it.
+///
+/// Each [`Ast`](super::Ast), [`Statement`](super::Statement), [`Expr`](super::Expr) and
+/// [`Identifier`](super::Identifier) gets a new id when the parser, the expander or a pass that adds
+/// code makes it, so no two nodes share one, not even a parenthesized expression and the expression
+/// in it or the copies of a macro body. The span of a node is kept apart, as nodes can share it.
+///
+/// The default id is not given to any node, so code that compares ASTs can erase the ids with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct NodeId(u32);
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl NodeId {
+    /// An id that no node has yet.
+    pub fn next() -> Self {
+        static NEXT: AtomicU32 = AtomicU32::new(1);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
@@ -1,3 +1,14 @@
 mod ast;
+pub mod fold;
+mod id;
+pub mod visit;
+pub mod visit_mut;
 
 pub use ast::*;
+pub use fold::Fold;
+pub use id::*;
+pub use visit::Visitor;
+pub use visit_mut::VisitorMut;
+
+#[cfg(test)]
+mod test;
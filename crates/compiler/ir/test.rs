@@ -0,0 +1,185 @@
+use crate::span::Span;
+
+use super::{
+    fold, visit, Assign, Ast, BinaryOp, Expr, ExprKind, Fold, Identifier, NodeId, StatementKind,
+    Visitor, VisitorMut,
+};
+
+use utils::*;
+
+#[test]
+fn can_visit_nodes() {
+    struct NameCollector(Vec<String>);
+
+    impl Visitor for NameCollector {
+        fn visit_identifier(&mut self, node: &Identifier) {
+            self.0.push(node.name.clone());
+        }
+    }
+
+    let mut collector = NameCollector(vec![]);
+    collector.visit_ast(&get_ast());
+
+    assert_eq!(collector.0, vec!["x", "a", "b"]);
+}
+
+#[test]
+fn can_stop_walking_at_a_node() {
+    struct NameCollector(Vec<String>);
+
+    impl Visitor for NameCollector {
+        fn visit_identifier(&mut self, node: &Identifier) {
+            self.0.push(node.name.clone());
+        }
+
+        fn visit_expr(&mut self, node: &Expr) {
+            // Skip the right-hand side of binary operations.
+            if let ExprKind::BinaryOp { lhs, .. } = &node.kind {
+                self.visit_expr(lhs);
+            } else {
+                visit::walk_expr(self, node);
+            }
+        }
+    }
+
+    let mut collector = NameCollector(vec![]);
+    collector.visit_ast(&get_ast());
+
+    assert_eq!(collector.0, vec!["x", "a"]);
+}
+
+#[test]
+fn can_mutate_nodes() {
+    struct Renamer;
+
+    impl VisitorMut for Renamer {
+        fn visit_identifier(&mut self, node: &mut Identifier) {
+            node.name = node.name.to_uppercase();
+        }
+    }
+
+    let mut ast = get_ast();
+    Renamer.visit_ast(&mut ast);
+
+    assert_eq!(
+        ast,
+        Ast {
+            statements: vec![assign("X", binary("A", BinaryOp::Plus, "B"))],
+            id: NodeId::default(),
+            span: Span::new(0, 9),
+        }
+    );
+}
+
+#[test]
+fn can_fold_nodes() {
+    struct SpanEraser;
+
+    impl Fold for SpanEraser {
+        fn fold_span(&mut self, _node: Span) -> Span {
+            Span::new(0, 0)
+        }
+    }
+
+    struct OpSwapper;
+
+    impl Fold for OpSwapper {
+        fn fold_binary_op(&mut self, _node: BinaryOp) -> BinaryOp {
+            BinaryOp::Minus
+        }
+
+        fn fold_expr(&mut self, node: Expr) -> Expr {
+            let node = fold::fold_expr(self, node);
+            Expr {
+                span: Span::new(node.span.start, node.span.end + 1),
+                ..node
+            }
+        }
+    }
+
+    let erased = SpanEraser.fold_ast(get_ast());
+    let swapped = OpSwapper.fold_ast(get_ast());
+
+    assert!(erased
+        .statements
+        .iter()
+        .all(|statement| statement.span == Span::new(0, 0)));
+
+    match &swapped.statements[0].kind {
+        StatementKind::Assign(Assign { targets, value }) => {
+            // The target is a pattern, which `fold_expr` does not reach.
+            assert_eq!(targets[0].span, Span::new(0, 1));
+            assert!(matches!(
+                value.kind,
+                ExprKind::BinaryOp {
+                    op: BinaryOp::Minus,
+                    ..
+                }
+            ));
+            assert_eq!(value.span, Span::new(4, 10));
+        }
+        kind => panic!("expected an assignment, found {kind:?}"),
+    }
+}
+
+mod utils {
+    use crate::ir::{
+        Assign, Ast, BinaryOp, Expr, ExprKind, Identifier, NodeId, Pattern, PatternKind, Statement,
+        StatementKind,
+    };
+    use crate::span::Span;
+
+    /// Returns the AST of `x = a + b`.
+    pub(super) fn get_ast() -> Ast {
+        Ast {
+            statements: vec![assign("x", binary("a", BinaryOp::Plus, "b"))],
+            id: NodeId::default(),
+            span: Span::new(0, 9),
+        }
+    }
+
+    pub(super) fn assign(target: &str, value: Expr) -> Statement {
+        Statement {
+            kind: StatementKind::Assign(Assign {
+                targets: vec![Pattern {
+                    kind: PatternKind::Capture(Identifier {
+                        name: target.into(),
+                        id: NodeId::default(),
+                        span: Span::new(0, 1),
+                        context: None,
+                    }),
+                    span: Span::new(0, 1),
+                }],
+                value,
+            }),
+            id: NodeId::default(),
+            span: Span::new(0, 9),
+        }
+    }
+
+    pub(super) fn binary(lhs: &str, op: BinaryOp, rhs: &str) -> Expr {
+        Expr {
+            kind: ExprKind::BinaryOp {
+                lhs: Box::new(identifier(lhs, 4)),
+                op,
+                rhs: Box::new(identifier(rhs, 8)),
+            },
+            id: NodeId::default(),
+            span: Span::new(4, 9),
+        }
+    }
+
+    fn identifier(name: &str, start: u32) -> Expr {
+        let span = Span::new(start, start + 1);
+        Expr {
+            kind: ExprKind::Identifier(Identifier {
+                name: name.into(),
+                id: NodeId::default(),
+                span: span.clone(),
+                context: None,
+            }),
+            id: NodeId::default(),
+            span,
+        }
+    }
+}
@@ -0,0 +1,8 @@
+//! Read-only traversal over the AST.
+//!
+//! The trait and its `walk_*` functions are generated from `ast.rs`, so every node kind gets a hook.
+
+use super::*;
+use crate::span::Span;
+
+raccoon_macros::visitor!("ir/ast.rs", Span, NodeId);
@@ -0,0 +1,8 @@
+//! In-place traversal over the AST.
+//!
+//! The trait and its `walk_*` functions are generated from `ast.rs`, so every node kind gets a hook.
+
+use super::*;
+use crate::span::Span;
+
+raccoon_macros::visitor_mut!("ir/ast.rs", Span, NodeId);
@@ -29,20 +29,24 @@ pub struct Lexer<'a> {
     pub indent_size: i32,
     /// Token buffer for tokens like consecutive Dedents that makes sense to be lexed together.
     pub token_buffer: Vec<Token>,
+    /// The brackets and in-bracket blocks the cursor is currently in.
+    pub scopes: Vec<Scope>,
+    /// Whether the last token is a `:` that can start an indented block inside brackets.
+    pub prev_is_colon: bool,
 }
 
 /// Represents a scope that can be introduced by an indentation-preserving block or a indentation-ignoring bracket.
 #[derive(Debug)]
 pub enum Scope {
-    /// An indentation scope.
+    /// An indentation scope, like the block of a lambda, opened inside brackets.
     Indent {
-        /// The indentation count this scope started at.
+        /// The indentation count of the enclosing bracket scope.
         start_space_count: i32,
-        /// The current space count.
+        /// The indentation count of the block.
         space_count: i32,
     },
     /// A bracket scope that ignores indentation and dedentations.
-    /// Dedents that are lesser or equal to the scope start count cause an error.
+    /// A dedent that is lesser or equal to the scope start count closes any block opened in it.
     Bracket {
         /// The indentation count this scope started at.
         start_space_count: i32,
@@ -91,6 +95,8 @@ impl<'a> Lexer<'a> {
             indent_level: 0,
             indent_size: 0,
             token_buffer: Vec::new(),
+            scopes: Vec::new(),
+            prev_is_colon: false,
         }
     }
 
@@ -140,6 +146,7 @@ impl<'a> Lexer<'a> {
     fn next_token(&mut self) -> Option<Result<Token>> {
         // Check tokens in the token buffer first.
         if let Some(token) = self.token_buffer.pop() {
+            self.prev_is_colon = false;
             return Some(Ok(token));
         }
 
@@ -157,8 +164,12 @@ impl<'a> Lexer<'a> {
                     continue;
                 }
                 '\r' | '\n' => {
-                    // Lex newlines and indentation.
-                    self.tokenize_newline_or_indentation(char, start)
+                    // Lex newlines and indentation, which are skipped inside brackets.
+                    match self.tokenize_newline_or_indentation(char, start) {
+                        Ok(Some(token)) => Ok(token),
+                        Ok(None) => continue,
+                        Err(err) => Err(err),
+                    }
                 }
                 '#' => {
                     // Skip single line comments.
@@ -301,7 +312,7 @@ impl<'a> Lexer<'a> {
                             self.eat_char();
                             self.eat_char();
 
-                            Token::new(Imag(format!("0")), Span::new(start, self.cursor))
+                            Token::new(Imag("0".to_string()), Span::new(start, self.cursor))
                         } else {
                             Token::new(
                                 Integer("0".into(), IntegerKind::Dec),
@@ -420,7 +431,7 @@ impl<'a> Lexer<'a> {
                             let char = self.eat_char().unwrap();
                             Ok(self.tokenize_identifier_or_keyword(format!("f{char}"), start))
                         }
-                        _ => Ok(self.tokenize_identifier_or_keyword(format!("f"), start)),
+                        _ => Ok(self.tokenize_identifier_or_keyword("f".to_string(), start)),
                     }
                 }
                 'b' => {
@@ -458,7 +469,7 @@ impl<'a> Lexer<'a> {
                             let char = self.eat_char().unwrap();
                             Ok(self.tokenize_identifier_or_keyword(format!("b{char}"), start))
                         }
-                        _ => Ok(self.tokenize_identifier_or_keyword(format!("b"), start)),
+                        _ => Ok(self.tokenize_identifier_or_keyword("b".to_string(), start)),
                     }
                 }
                 'r' => {
@@ -528,7 +539,9 @@ impl<'a> Lexer<'a> {
                                     Ok(self
                                         .tokenize_identifier_or_keyword(format!("rb{char}"), start))
                                 }
-                                _ => Ok(self.tokenize_identifier_or_keyword(format!("rb"), start)),
+                                _ => {
+                                    Ok(self.tokenize_identifier_or_keyword("rb".to_string(), start))
+                                }
                             }
                         }
                         Some('f') => {
@@ -567,14 +580,16 @@ impl<'a> Lexer<'a> {
                                     Ok(self
                                         .tokenize_identifier_or_keyword(format!("rf{char}"), start))
                                 }
-                                _ => Ok(self.tokenize_identifier_or_keyword(format!("rf"), start)),
+                                _ => {
+                                    Ok(self.tokenize_identifier_or_keyword("rf".to_string(), start))
+                                }
                             }
                         }
                         Some('a'..='z' | 'A'..='Z' | '0'..='9' | '_') => {
                             let char = self.eat_char().unwrap();
                             Ok(self.tokenize_identifier_or_keyword(format!("r{char}"), start))
                         }
-                        _ => Ok(self.tokenize_identifier_or_keyword(format!("r"), start)),
+                        _ => Ok(self.tokenize_identifier_or_keyword("r".to_string(), start)),
                     }
                 }
                 'a'..='z' | 'A'..='Z' | '_' => {
@@ -692,7 +707,12 @@ impl<'a> Lexer<'a> {
                     }
                     Some('*') => {
                         self.eat_char();
-                        Token::new(Op(Operator::Pow), Span::new(start, self.cursor))
+                        if self.peek_char() == Some('=') {
+                            self.eat_char();
+                            Token::new(Delim(Delimiter::PowAssign), Span::new(start, self.cursor))
+                        } else {
+                            Token::new(Op(Operator::Pow), Span::new(start, self.cursor))
+                        }
                     }
                     _ => Token::new(Op(Operator::Mul), Span::new(start, self.cursor)),
                 }),
@@ -742,48 +762,57 @@ impl<'a> Lexer<'a> {
                     Op(Operator::Sqrt),
                     Span::new(start, self.cursor),
                 )),
-                '{' => Ok(Token::new(
-                    Delim(Delimiter::LBrace),
-                    Span::new(start, self.cursor),
-                )),
-                '}' => Ok(Token::new(
-                    Delim(Delimiter::RBrace),
-                    Span::new(start, self.cursor),
-                )),
-                '(' => Ok(Token::new(
-                    Delim(Delimiter::LParen),
-                    Span::new(start, self.cursor),
-                )),
-                ')' => Ok(Token::new(
-                    Delim(Delimiter::RParen),
-                    Span::new(start, self.cursor),
+                '{' => Ok(self.tokenize_opening_bracket(
+                    Delimiter::LBrace,
+                    BracketKind::SquigglyBraces,
+                    start,
                 )),
-                '[' => Ok(Token::new(
-                    Delim(Delimiter::LBracket),
-                    Span::new(start, self.cursor),
+                '}' => Ok(self.tokenize_closing_bracket(Delimiter::RBrace, start)),
+                '(' => Ok(self.tokenize_opening_bracket(
+                    Delimiter::LParen,
+                    BracketKind::Parens,
+                    start,
                 )),
-                ']' => Ok(Token::new(
-                    Delim(Delimiter::RBracket),
-                    Span::new(start, self.cursor),
+                ')' => Ok(self.tokenize_closing_bracket(Delimiter::RParen, start)),
+                '[' => Ok(self.tokenize_opening_bracket(
+                    Delimiter::LBracket,
+                    BracketKind::SquareBraces,
+                    start,
                 )),
+                ']' => Ok(self.tokenize_closing_bracket(Delimiter::RBracket, start)),
                 ',' => Ok(Token::new(
                     Delim(Delimiter::Comma),
                     Span::new(start, self.cursor),
                 )),
-                ':' => Ok(Token::new(
-                    Delim(Delimiter::Colon),
-                    Span::new(start, self.cursor),
-                )),
+                ':' => Ok(match self.peek_char() {
+                    Some('=') => {
+                        self.eat_char();
+                        Token::new(Delim(Delimiter::Walrus), Span::new(start, self.cursor))
+                    }
+                    _ => Token::new(Delim(Delimiter::Colon), Span::new(start, self.cursor)),
+                }),
                 ';' => Ok(Token::new(
                     Delim(Delimiter::SemiColon),
                     Span::new(start, self.cursor),
                 )),
+                '?' => Ok(Token::new(
+                    Delim(Delimiter::Question),
+                    Span::new(start, self.cursor),
+                )),
                 _ => error(LexerError::new(
                     InvalidCharacter,
                     Span::new(start, self.cursor),
                 )),
             };
 
+            self.prev_is_colon = matches!(
+                result,
+                Ok(Token {
+                    kind: Delim(Delimiter::Colon),
+                    ..
+                })
+            );
+
             return Some(result);
         }
 
@@ -794,7 +823,9 @@ impl<'a> Lexer<'a> {
 /// Tokenizer functions.
 impl Lexer<'_> {
     /// Tokenizes a newline character which can possibly lead to lexing indents and dedents.
-    fn tokenize_newline_or_indentation(&mut self, char: char, start: u32) -> Result<Token> {
+    ///
+    /// Returns `None` for newlines inside brackets, where they are insignificant.
+    fn tokenize_newline_or_indentation(&mut self, char: char, start: u32) -> Result<Option<Token>> {
         // Eat the next char if it is a Windows-native newline.
         if char == '\r' && self.peek_char() == Some('\n') {
             self.eat_char();
@@ -817,28 +848,53 @@ impl Lexer<'_> {
             space_count += 1;
         }
 
+        // Check if the line is empty or only has a comment.
+        let peek_char = self.peek_char();
+        let is_blank_line = matches!(peek_char, Some('\r') | Some('\n') | Some('#') | None);
+
+        // Inside brackets, only an indented line after a trailing `:` matters as it starts a block.
+        if let Some(Scope::Bracket {
+            start_space_count, ..
+        }) = self.scopes.last()
+        {
+            let start_space_count = *start_space_count;
+            if is_blank_line || !self.prev_is_colon || space_count <= start_space_count {
+                return Ok(None);
+            }
+
+            self.scopes.push(Scope::Indent {
+                start_space_count,
+                space_count,
+            });
+        }
+
         let prev_space_count = self.indent_level * self.indent_size;
         let indent_diff = space_count - prev_space_count;
         let indent_diff_abs = indent_diff.abs();
 
-        // Check if the next char is not a newline or comment delimiter.
-        let peek_char = self.peek_char();
-        if space_count > 0 && !matches!(peek_char, Some('\r') | Some('\n') | Some('#')) {
+        if !is_blank_line {
             // Check if spaces aren't mixed.
             if mixed_spaces {
                 bail!(LexerError::new(MixedSpaces, Span::new(start, self.cursor)));
             }
 
             // Check spaces remain consistent between indents.
-            let space_kind: IndentKind = prev_space.unwrap().try_into()?;
-            if self.indent_kind != IndentKind::Unknown && space_kind != self.indent_kind {
+            let space_kind: IndentKind = match prev_space {
+                Some(space) => space.into(),
+                None => IndentKind::Unknown,
+            };
+
+            if space_count > 0
+                && self.indent_kind != IndentKind::Unknown
+                && space_kind != self.indent_kind
+            {
                 bail!(LexerError::new(
                     InconsistentIndent,
                     Span::new(start, self.cursor)
                 ));
             }
 
-            match (space_count - prev_space_count).cmp(&0) {
+            match indent_diff.cmp(&0) {
                 Ordering::Greater => {
                     // An indentation.
                     // Check if it is the first indentation.
@@ -854,9 +910,21 @@ impl Lexer<'_> {
 
                     self.indent_level = space_count / self.indent_size;
 
-                    return Ok(Token::new(Indent, Span::new(start, self.cursor)));
+                    return Ok(Some(Token::new(Indent, Span::new(start, self.cursor))));
                 }
                 Ordering::Less => {
+                    // A dedentation back to the bracket a block was opened in.
+                    if let Some(Scope::Indent {
+                        start_space_count, ..
+                    }) = self.scopes.last()
+                    {
+                        if space_count <= *start_space_count {
+                            return Ok(Some(
+                                self.close_bracket_block(Span::new(start, self.cursor)),
+                            ));
+                        }
+                    }
+
                     // A dedentation.
                     if indent_diff % self.indent_size != 0 {
                         bail!(LexerError::new(
@@ -873,29 +941,78 @@ impl Lexer<'_> {
 
                     self.indent_level = space_count / self.indent_size;
 
-                    return Ok(Token::new(Dedent, Span::new(start, self.cursor)));
+                    return Ok(Some(Token::new(Dedent, Span::new(start, self.cursor))));
                 }
                 Ordering::Equal => (),
             };
-        } else if peek_char == None {
+        } else if peek_char.is_none() {
             // If the code ends with a newline, calculate dedents.
-            match indent_diff.cmp(&0) {
-                Ordering::Less => {
-                    // Add dedents in token buffer except the last.
-                    for _ in 1..(indent_diff_abs / self.indent_size) {
-                        self.token_buffer
-                            .push(Token::new(Dedent, Span::new(start, self.cursor)));
-                    }
+            if indent_diff < 0 {
+                // Add dedents in token buffer except the last.
+                for _ in 1..(indent_diff_abs / self.indent_size) {
+                    self.token_buffer
+                        .push(Token::new(Dedent, Span::new(start, self.cursor)));
+                }
 
-                    self.indent_level = space_count / self.indent_size;
+                self.indent_level = space_count / self.indent_size;
 
-                    return Ok(Token::new(Dedent, Span::new(start, self.cursor)));
-                }
-                _ => (),
+                return Ok(Some(Token::new(Dedent, Span::new(start, self.cursor))));
             }
         }
 
-        Ok(Token::new(Newline, Span::new(start, self.cursor)).into())
+        Ok(Some(Token::new(Newline, Span::new(start, self.cursor))))
+    }
+
+    /// Tokenizes an opening bracket and enters its scope.
+    fn tokenize_opening_bracket(
+        &mut self,
+        delimiter: Delimiter,
+        kind: BracketKind,
+        start: u32,
+    ) -> Token {
+        self.scopes.push(Scope::Bracket {
+            start_space_count: self.indent_level * self.indent_size,
+            kind,
+        });
+
+        Token::new(Delim(delimiter), Span::new(start, self.cursor))
+    }
+
+    /// Tokenizes a closing bracket and leaves its scope, closing any block still open in it.
+    fn tokenize_closing_bracket(&mut self, delimiter: Delimiter, start: u32) -> Token {
+        let token = Token::new(Delim(delimiter), Span::new(start, self.cursor));
+
+        if matches!(self.scopes.last(), Some(Scope::Indent { .. })) {
+            // The closing bracket comes after the dedents.
+            self.token_buffer.push(token);
+            let dedent = self.close_bracket_block(Span::new(start, start));
+            self.scopes.pop();
+
+            return dedent;
+        }
+
+        self.scopes.pop();
+
+        token
+    }
+
+    /// Leaves the block opened in the innermost bracket scope and returns the first of its dedents.
+    fn close_bracket_block(&mut self, span: Span) -> Token {
+        if let Some(Scope::Indent {
+            start_space_count, ..
+        }) = self.scopes.pop()
+        {
+            let level = start_space_count / self.indent_size;
+
+            // Add dedents in token buffer except the last.
+            for _ in 1..(self.indent_level - level) {
+                self.token_buffer.push(Token::new(Dedent, span.clone()));
+            }
+
+            self.indent_level = level;
+        }
+
+        Token::new(Dedent, span)
     }
 
     /// Tokenizes integers that start with `0b | 0o | 0x`.
@@ -1110,7 +1227,7 @@ impl Lexer<'_> {
                 _ => {
                     use crate::lexer::Keyword;
                     let kind = Keyword::try_from(&*initial_string)
-                        .map(|keyword| TokenKind::Keyword(keyword))
+                        .map(TokenKind::Keyword)
                         .unwrap_or(TokenKind::Identifier(initial_string));
 
                     break Token::new(kind, Span::new(start, self.cursor));
@@ -1124,7 +1241,7 @@ impl Lexer<'_> {
 impl Lexer<'_> {
     /// Lexes `"." digit_part exponent?`
     fn lex_float_fraction(&mut self, start: u32) -> Result<String> {
-        let mut fraction = format!(".");
+        let mut fraction = ".".to_string();
 
         // Lex compulsory `digit_part = digit ("_"? digit)*`
         match self.peek_char() {
@@ -1174,7 +1291,7 @@ impl Lexer<'_> {
                                 UnterminatedString,
                                 Span::new(start, self.cursor)
                             ));
-                        } else {
+                        } else if long {
                             self.eat_char();
                             self.eat_char();
                         };
@@ -1227,7 +1344,7 @@ impl Lexer<'_> {
                                 UnterminatedString,
                                 Span::new(start, self.cursor)
                             ));
-                        } else {
+                        } else if long {
                             self.eat_char();
                             self.eat_char();
                         };
@@ -1260,7 +1377,7 @@ impl Lexer<'_> {
 
     /// Lexes `"e" ("+" | "-")? digit_part`
     fn lex_float_exponent(&mut self, start: u32) -> Result<String> {
-        let mut exponent = format!("e");
+        let mut exponent = "e".to_string();
 
         // Lex ("+" | "-")?
         if matches!(self.peek_char(), Some('-' | '+')) {
@@ -1316,23 +1433,29 @@ impl Lexer<'_> {
 
     /// Lexes oct, hex, bin digits.
     fn lex_prefixed_digits(&mut self, char: char, start: u32, base: IntBase) -> Result<String> {
-        let mut digits = if char != '_' {
-            format!("{char}")
-        } else {
-            String::new()
+        let is_digit = |char: Option<char>| match base {
+            IntBase::Bin => matches!(char, Some('0'..='1')),
+            IntBase::Oct => matches!(char, Some('0'..='7')),
+            IntBase::Hex => matches!(char, Some('0'..='9' | 'a'..='f' | 'A'..='F')),
+            IntBase::Dec => unreachable!(),
         };
 
+        let mut digits = String::new();
+        if char != '_' {
+            digits.push(char);
+        } else if !is_digit(self.peek_char()) {
+            bail!(LexerError::new(
+                InvalidCharacterAfterUnderscoreInDigitPart,
+                Span::new(start, self.cursor),
+            ));
+        }
+
         loop {
             match self.peek_char() {
                 Some('_') => {
                     self.eat_char();
 
-                    let peek_char = self.peek_char();
-                    if !(base == IntBase::Bin && matches!(peek_char, Some('0'..='1'))
-                        || base == IntBase::Oct && matches!(peek_char, Some('0'..='7'))
-                        || base == IntBase::Hex
-                            && matches!(peek_char, Some('0'..='9' | 'a'..='f' | 'A'..='F')))
-                    {
+                    if !is_digit(self.peek_char()) {
                         bail!(LexerError::new(
                             InvalidCharacterAfterUnderscoreInDigitPart,
                             Span::new(start, self.cursor),
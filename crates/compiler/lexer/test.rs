@@ -195,6 +195,29 @@ fn can_tokenize_short_strings() {
     );
 }
 
+#[test]
+fn can_tokenize_tokens_after_short_strings() {
+    let result = get_tokens("'lorem' rb\"ipsum\" dolor");
+
+    assert_eq!(
+        result,
+        vec![
+            TokenResult::Ok(Token::new(
+                TokenKind::Str("lorem".to_string(), StringKind::Str),
+                Span::new(0, 7)
+            )),
+            TokenResult::Ok(Token::new(
+                TokenKind::ByteStr("ipsum".to_string(), BytesKind::RawBytes),
+                Span::new(8, 17)
+            )),
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("dolor".to_string()),
+                Span::new(18, 23)
+            )),
+        ]
+    );
+}
+
 #[test]
 fn can_tokenize_long_strings() {
     let result_single_quote_string: Vec<_> = get_tokens(r#"'''hello there!'''"#);
@@ -1463,6 +1486,7 @@ fn can_tokenize_prefixed_integers() {
 
     let result_bin_invalid_digits = get_tokens("0b1234");
     let result_bin_multiple_underscores = get_tokens("0b1010__1010");
+    let result_bin_underscore_without_digits = get_tokens("0b_");
 
     let result_oct_invalid_digits = get_tokens("0o1238");
     let result_oct_multiple_underscores = get_tokens("0o123_456__7");
@@ -1541,6 +1565,14 @@ fn can_tokenize_prefixed_integers() {
         ))]
     );
 
+    assert_eq!(
+        result_bin_underscore_without_digits,
+        vec![TokenResult::Err(LexerError::new(
+            InvalidCharacterAfterUnderscoreInDigitPart,
+            Span::new(0, 3)
+        ))]
+    );
+
     assert_eq!(
         result_oct_invalid_digits,
         vec![TokenResult::Err(LexerError::new(
@@ -2389,7 +2421,7 @@ fn can_tokenize_operators() {
 
 #[test]
 fn can_tokenize_delimiters() {
-    let result_valid_operators = get_tokens("( ) [ ] { } , : . ; @ = -> += -= *= /= //= %= @= &= |= ^= >>= <<=");
+    let result_valid_operators = get_tokens("( ) [ ] { } , : . ; @ = -> += -= *= /= //= %= @= &= |= ^= >>= <<= **= := ?");
 
     assert_eq!(
         result_valid_operators,
@@ -2419,10 +2451,136 @@ fn can_tokenize_delimiters() {
             TokenResult::Ok(Token::new(TokenKind::Delim(Delimiter::BitXorAssign), Span::new(55, 57))),
             TokenResult::Ok(Token::new(TokenKind::Delim(Delimiter::ShiftRAssign), Span::new(58, 61))),
             TokenResult::Ok(Token::new(TokenKind::Delim(Delimiter::ShiftLAssign), Span::new(62, 65))),
+            TokenResult::Ok(Token::new(TokenKind::Delim(Delimiter::PowAssign), Span::new(66, 69))),
+            TokenResult::Ok(Token::new(TokenKind::Delim(Delimiter::Walrus), Span::new(70, 72))),
+            TokenResult::Ok(Token::new(TokenKind::Delim(Delimiter::Question), Span::new(73, 74))),
         ]
     )
 }
 
+#[test]
+fn can_tokenize_dedents_to_the_first_column() {
+    let result = get_tokens("lorem\n  ipsum\ndolor");
+
+    assert_eq!(
+        result,
+        vec![
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("lorem".to_string()),
+                Span::new(0, 5)
+            )),
+            TokenResult::Ok(Token::new(TokenKind::Indent, Span::new(5, 8))),
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("ipsum".to_string()),
+                Span::new(8, 13)
+            )),
+            TokenResult::Ok(Token::new(TokenKind::Dedent, Span::new(13, 14))),
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("dolor".to_string()),
+                Span::new(14, 19)
+            )),
+        ]
+    );
+}
+
+#[test]
+fn can_skip_newlines_in_brackets() {
+    let result = get_tokens("(lorem,\n    ipsum\n)");
+
+    assert_eq!(
+        result,
+        vec![
+            TokenResult::Ok(Token::new(
+                TokenKind::Delim(Delimiter::LParen),
+                Span::new(0, 1)
+            )),
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("lorem".to_string()),
+                Span::new(1, 6)
+            )),
+            TokenResult::Ok(Token::new(
+                TokenKind::Delim(Delimiter::Comma),
+                Span::new(6, 7)
+            )),
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("ipsum".to_string()),
+                Span::new(12, 17)
+            )),
+            TokenResult::Ok(Token::new(
+                TokenKind::Delim(Delimiter::RParen),
+                Span::new(18, 19)
+            )),
+        ]
+    );
+}
+
+#[test]
+fn can_tokenize_blocks_in_brackets() {
+    let result_dedent = get_tokens("(lorem:\n  ipsum\n  dolor\n)");
+    let result_closing_bracket = get_tokens("[lorem:\n  ipsum]");
+
+    assert_eq!(
+        result_dedent,
+        vec![
+            TokenResult::Ok(Token::new(
+                TokenKind::Delim(Delimiter::LParen),
+                Span::new(0, 1)
+            )),
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("lorem".to_string()),
+                Span::new(1, 6)
+            )),
+            TokenResult::Ok(Token::new(
+                TokenKind::Delim(Delimiter::Colon),
+                Span::new(6, 7)
+            )),
+            TokenResult::Ok(Token::new(TokenKind::Indent, Span::new(7, 10))),
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("ipsum".to_string()),
+                Span::new(10, 15)
+            )),
+            TokenResult::Ok(Token::new(TokenKind::Newline, Span::new(15, 18))),
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("dolor".to_string()),
+                Span::new(18, 23)
+            )),
+            TokenResult::Ok(Token::new(TokenKind::Dedent, Span::new(23, 24))),
+            TokenResult::Ok(Token::new(
+                TokenKind::Delim(Delimiter::RParen),
+                Span::new(24, 25)
+            )),
+        ]
+    );
+
+    assert_eq!(
+        result_closing_bracket,
+        vec![
+            TokenResult::Ok(Token::new(
+                TokenKind::Delim(Delimiter::LBracket),
+                Span::new(0, 1)
+            )),
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("lorem".to_string()),
+                Span::new(1, 6)
+            )),
+            TokenResult::Ok(Token::new(
+                TokenKind::Delim(Delimiter::Colon),
+                Span::new(6, 7)
+            )),
+            TokenResult::Ok(Token::new(TokenKind::Indent, Span::new(7, 10))),
+            TokenResult::Ok(Token::new(
+                TokenKind::Identifier("ipsum".to_string()),
+                Span::new(10, 15)
+            )),
+            TokenResult::Ok(Token::new(TokenKind::Dedent, Span::new(15, 15))),
+            TokenResult::Ok(Token::new(
+                TokenKind::Delim(Delimiter::RBracket),
+                Span::new(15, 16)
+            )),
+        ]
+    );
+}
+
 mod utils {
     use anyhow::Result;
 
@@ -33,6 +33,7 @@ pub enum TokenKind {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum IntegerKind {
     Bin,
     Oct,
@@ -41,6 +42,7 @@ pub enum IntegerKind {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum StringKind {
     Str,
     RawStr,
@@ -49,6 +51,7 @@ pub enum StringKind {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum BytesKind {
     Bytes,
     RawBytes,
@@ -158,6 +161,11 @@ pub enum Delimiter {
     BitXorAssign,
     ShiftRAssign,
     ShiftLAssign,
+    PowAssign,
+    /// `:=`
+    Walrus,
+    /// `?`
+    Question,
 }
 
 //------------------------------------------------------------------------------
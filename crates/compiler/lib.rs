@@ -1,12 +1,18 @@
+pub mod codegen;
 mod errors;
+pub mod expander;
+pub mod ir;
 pub mod lexer;
+pub mod loader;
 pub mod parser;
 pub mod semantic;
 pub mod span;
-pub mod ir;
-pub mod codegen;
+pub mod unparser;
 
+pub use expander::Expander;
 pub use lexer::Lexer;
-// pub use parser::Parser;
+pub use loader::ModuleLoader;
+pub use parser::Parser;
+pub use unparser::Unparser;
 
 pub use errors::*;
@@ -0,0 +1,51 @@
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use crate::span::Span;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoaderErrorKind {
+    /// An import path that matches no `.ra` file or package in any search path.
+    ModuleNotFound,
+    /// A relative import with more leading dots than there are enclosing packages.
+    InvalidRelativeImport,
+    /// An import of a module that is still being loaded, which closes a cycle.
+    ImportCycle,
+    /// A `from x import *` below the top level of a module, or an `__all__` that is not a list of
+    /// string literals.
+    InvalidStarImport,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoaderError {
+    pub(crate) kind: LoaderErrorKind,
+    /// The file the span points into.
+    pub(crate) path: PathBuf,
+    pub(crate) span: Span,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl LoaderError {
+    pub fn new(kind: LoaderErrorKind, path: PathBuf, span: Span) -> Self {
+        Self { kind, path, span }
+    }
+}
+
+impl std::error::Error for LoaderError {}
+
+impl Display for LoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoaderError")
+            .field("kind", &self.kind)
+            .field("path", &self.path)
+            .field("span", &self.span)
+            .finish()
+    }
+}
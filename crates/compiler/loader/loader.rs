@@ -0,0 +1,490 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::error;
+use crate::ir::{
+    visit, Ast, ExprKind, ImportFrom, ImportNames, ImportPath, Pattern, PatternKind, Statement,
+    StatementKind, StringLiteral, Visitor,
+};
+use crate::parser::Parser;
+use crate::span::Span;
+
+use super::errors::{LoaderError, LoaderErrorKind, LoaderErrorKind::*};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// The index of a module in a [`ModuleGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModuleId(pub usize);
+
+/// A parsed `.ra` file.
+#[derive(Debug, Clone)]
+pub struct Module {
+    /// The dotted path the module was first imported by, or the file stem of the entry module.
+    pub name: String,
+    pub path: PathBuf,
+    /// Whether the module is the `mod.ra` of a package, which relative imports start from.
+    pub is_package: bool,
+    pub ast: Ast,
+    /// The modules this one imports, in the order of the imports.
+    pub imports: Vec<ModuleId>,
+    /// The names `from name import *` brings in from this module.
+    pub exports: Vec<String>,
+    /// The names the `from x import *` of this module bring in, which are the exports of each `x`.
+    pub star_names: Vec<String>,
+}
+
+/// The modules reachable from an entry module through its imports.
+#[derive(Debug, Clone)]
+pub struct ModuleGraph {
+    pub modules: Vec<Module>,
+    /// The modules with each one after the modules it imports.
+    order: Vec<ModuleId>,
+}
+
+/// Loads a module and everything it imports from disk.
+///
+/// An import path like `a.b` maps to `a/b.ra`, or to `a/b/mod.ra` if `a.b` is a package. Absolute
+/// paths are looked up in the directory of the entry module, then in each search path in order,
+/// except for `std.*` paths which always map to the standard library directory. A relative path like
+/// `..a` is looked up from the package of the importing module, one package up per extra dot.
+///
+/// `from x import y` imports the module `x.y` if there is one, and the name `y` of `x` otherwise.
+///
+/// `from x import *` is only allowed at the top level of a module. It brings in the names listed by
+/// the `__all__` of `x`, which must be a list of string literals, or else the names bound at the
+/// top level of `x` that do not start with an underscore.
+#[derive(Debug)]
+pub struct ModuleLoader {
+    /// The directories searched after the directory of the entry module, in order.
+    search_paths: Vec<PathBuf>,
+    /// The directory that `std.*` paths map to.
+    std_path: PathBuf,
+    modules: Vec<Module>,
+    /// The modules by canonical file path, so each file is loaded once.
+    ids: HashMap<PathBuf, ModuleId>,
+    /// The modules being loaded, from the entry module to the current one.
+    stack: Vec<ModuleId>,
+    order: Vec<ModuleId>,
+}
+
+/// A module file an import path maps to.
+#[derive(Debug)]
+struct ModuleFile {
+    name: String,
+    path: PathBuf,
+    is_package: bool,
+}
+
+/// An import statement found anywhere in a module.
+#[derive(Debug)]
+struct ImportSite {
+    path: ImportPath,
+    /// The names of a `from` import.
+    names: Option<ImportNames>,
+    is_top_level: bool,
+    span: Span,
+}
+
+/// Collects the import statements of a module, including the ones in nested blocks.
+#[derive(Debug, Default)]
+struct ImportCollector {
+    imports: Vec<ImportSite>,
+    depth: usize,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl ModuleGraph {
+    /// The module the graph was loaded from.
+    pub fn entry(&self) -> &Module {
+        &self.modules[0]
+    }
+
+    pub fn get(&self, id: ModuleId) -> &Module {
+        &self.modules[id.0]
+    }
+
+    /// The modules with each one after the modules it imports.
+    pub fn order(&self) -> impl Iterator<Item = &Module> {
+        self.order.iter().map(|id| self.get(*id))
+    }
+}
+
+impl ModuleLoader {
+    /// The standard library bundled with the source tree, which only exists where the compiler was
+    /// built, so a compiler run elsewhere passes its own to [`Self::with_std_path`].
+    pub const STD_PATH: &'static str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../std");
+
+    pub fn new(search_paths: Vec<PathBuf>) -> Self {
+        Self {
+            search_paths,
+            std_path: PathBuf::from(Self::STD_PATH),
+            modules: Vec::new(),
+            ids: HashMap::new(),
+            stack: Vec::new(),
+            order: Vec::new(),
+        }
+    }
+
+    /// Maps `std.*` paths to another directory than the bundled one.
+    pub fn with_std_path(mut self, std_path: impl Into<PathBuf>) -> Self {
+        self.std_path = std_path.into();
+        self
+    }
+
+    /// Loads the module at `path` and the modules it imports.
+    pub fn load(mut self, path: &Path) -> Result<ModuleGraph> {
+        let directory = match path.parent() {
+            Some(directory) if directory != Path::new("") => directory.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        self.search_paths.insert(0, directory);
+        self.load_module(ModuleFile {
+            name,
+            path: path.to_path_buf(),
+            is_package: false,
+        })?;
+
+        Ok(ModuleGraph {
+            modules: self.modules,
+            order: self.order,
+        })
+    }
+
+    fn load_module(&mut self, file: ModuleFile) -> Result<ModuleId> {
+        let canonical = fs::canonicalize(&file.path)
+            .with_context(|| format!("cannot read {}", file.path.display()))?;
+        let code = fs::read_to_string(&canonical)
+            .with_context(|| format!("cannot read {}", file.path.display()))?;
+        let ast = Parser::parse(&code).with_context(|| format!("in {}", file.path.display()))?;
+
+        let id = ModuleId(self.modules.len());
+        let mut collector = ImportCollector::default();
+        for statement in ast.statements.iter() {
+            collector.visit_statement(statement);
+        }
+
+        self.ids.insert(canonical, id);
+        self.stack.push(id);
+        self.modules.push(Module {
+            name: file.name,
+            path: file.path,
+            is_package: file.is_package,
+            ast,
+            imports: Vec::new(),
+            exports: Vec::new(),
+            star_names: Vec::new(),
+        });
+
+        let mut star_names = Vec::new();
+        for site in collector.imports {
+            for import in self.load_import(id, &site)? {
+                if !self.modules[id.0].imports.contains(&import) {
+                    self.modules[id.0].imports.push(import);
+                }
+                if let Some(ImportNames::All) = site.names {
+                    for name in self.modules[import.0].exports.iter() {
+                        if !star_names.contains(name) {
+                            star_names.push(name.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let module = &mut self.modules[id.0];
+        module.star_names = star_names.clone();
+        module.exports = match exported_names(&module.ast) {
+            Ok(Some(exports)) => exports,
+            Ok(None) => {
+                let mut names = top_level_names(&module.ast, star_names);
+                names.retain(|name| !name.starts_with('_'));
+                names
+            }
+            Err(span) => return self.error(id, InvalidStarImport, &span),
+        };
+
+        self.stack.pop();
+        self.order.push(id);
+
+        Ok(id)
+    }
+
+    /// Loads the modules an import statement depends on.
+    fn load_import(&mut self, importer: ModuleId, site: &ImportSite) -> Result<Vec<ModuleId>> {
+        let files = match &site.names {
+            None => vec![self.find(importer, &site.path, &[], &site.span)?],
+            Some(ImportNames::All) => {
+                if !site.is_top_level {
+                    return self.error(importer, InvalidStarImport, &site.span);
+                }
+                vec![self.find(importer, &site.path, &[], &site.span)?]
+            }
+            Some(ImportNames::Names(names)) => {
+                let mut files = Vec::new();
+                for import in names {
+                    let name = [import.name.name.clone()];
+                    // `from x import y` imports the submodule `y` if there is one.
+                    match self.resolve(importer, &site.path, &name, &site.span)? {
+                        Some(file) => files.push(file),
+                        None => {
+                            files.push(self.find(importer, &site.path, &[], &import.name.span)?)
+                        }
+                    }
+                }
+                files
+            }
+        };
+
+        let mut imports = Vec::new();
+        for file in files {
+            let canonical = fs::canonicalize(&file.path)
+                .with_context(|| format!("cannot read {}", file.path.display()))?;
+            let id = match self.ids.get(&canonical) {
+                Some(id) if self.stack.contains(id) => {
+                    return self.error(importer, ImportCycle, &site.span);
+                }
+                Some(id) => *id,
+                None => self.load_module(file)?,
+            };
+            imports.push(id);
+        }
+
+        Ok(imports)
+    }
+
+    /// Like [`Self::resolve`], but fails if there is no such module.
+    fn find(
+        &self,
+        importer: ModuleId,
+        path: &ImportPath,
+        suffix: &[String],
+        span: &Span,
+    ) -> Result<ModuleFile> {
+        match self.resolve(importer, path, suffix, span)? {
+            Some(file) => Ok(file),
+            None => self.error(importer, ModuleNotFound, span),
+        }
+    }
+
+    /// Maps an import path, extended with `suffix`, to the module file it names.
+    fn resolve(
+        &self,
+        importer: ModuleId,
+        path: &ImportPath,
+        suffix: &[String],
+        span: &Span,
+    ) -> Result<Option<ModuleFile>> {
+        let mut names = path
+            .names
+            .iter()
+            .map(|identifier| identifier.name.clone())
+            .collect::<Vec<_>>();
+        names.extend(suffix.iter().cloned());
+
+        if path.level == 0 {
+            let (directories, relative) = match names.first().map(String::as_str) {
+                Some("std") => (vec![&self.std_path], &names[1..]),
+                _ => (self.search_paths.iter().collect(), &names[..]),
+            };
+
+            return Ok(directories
+                .into_iter()
+                .find_map(|directory| module_file(directory, relative))
+                .map(|(path, is_package)| ModuleFile {
+                    name: names.join("."),
+                    path,
+                    is_package,
+                }));
+        }
+
+        // A relative path starts from the package of the importing module.
+        let module = &self.modules[importer.0];
+        let mut package = module.name.split('.').map(String::from).collect::<Vec<_>>();
+        if !module.is_package {
+            package.pop();
+        }
+
+        let ups = path.level - 1;
+        if ups > package.len() {
+            return self.error(importer, InvalidRelativeImport, span);
+        }
+
+        let mut directory = module.path.parent().unwrap_or(Path::new("")).to_path_buf();
+        for _ in 0..ups {
+            directory.pop();
+        }
+        package.truncate(package.len() - ups);
+        package.extend(names.iter().cloned());
+
+        Ok(
+            module_file(&directory, &names).map(|(path, is_package)| ModuleFile {
+                name: package.join("."),
+                path,
+                is_package,
+            }),
+        )
+    }
+
+    fn error<T>(&self, module: ModuleId, kind: LoaderErrorKind, span: &Span) -> Result<T> {
+        let path = self.modules[module.0].path.clone();
+        error(LoaderError::new(kind, path, span.clone()))
+    }
+}
+
+impl Visitor for ImportCollector {
+    fn visit_statement(&mut self, node: &Statement) {
+        let (path, names) = match &node.kind {
+            StatementKind::Import(import) => (import.path.clone(), None),
+            StatementKind::ImportFrom(ImportFrom { path, names }) => {
+                (path.clone(), Some(names.clone()))
+            }
+            _ => {
+                self.depth += 1;
+                visit::walk_statement(self, node);
+                self.depth -= 1;
+                return;
+            }
+        };
+
+        self.imports.push(ImportSite {
+            path,
+            names,
+            is_top_level: self.depth == 0,
+            span: node.span.clone(),
+        });
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Finds the `.ra` file or the package `mod.ra` of a dotted path under `directory`.
+fn module_file(directory: &Path, names: &[String]) -> Option<(PathBuf, bool)> {
+    let path = names
+        .iter()
+        .fold(directory.to_path_buf(), |path, name| path.join(name));
+
+    if let Some(name) = names.last() {
+        let file = path.with_file_name(format!("{name}.ra"));
+        if file.is_file() {
+            return Some((file, false));
+        }
+    }
+
+    let package = path.join("mod.ra");
+    package.is_file().then_some((package, true))
+}
+
+/// The names listed by the `__all__` of a module, or the span of an `__all__` that is not a list of
+/// string literals.
+fn exported_names(ast: &Ast) -> Result<Option<Vec<String>>, Span> {
+    let is_all = |target: &Pattern| match &target.kind {
+        PatternKind::Capture(name) => name.name == "__all__",
+        _ => false,
+    };
+    let value = ast
+        .statements
+        .iter()
+        .find_map(|statement| match &statement.kind {
+            StatementKind::Assign(assign) if assign.targets.iter().any(is_all) => {
+                Some(&assign.value)
+            }
+            _ => None,
+        });
+
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let ExprKind::List(elements) = &value.kind else {
+        return Err(value.span.clone());
+    };
+
+    elements
+        .iter()
+        .map(|element| match &element.kind {
+            ExprKind::Strings(strings) => match strings.as_slice() {
+                [StringLiteral::Str(string, _)] => Ok(string.clone()),
+                _ => Err(element.span.clone()),
+            },
+            _ => Err(element.span.clone()),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+/// The names bound by the top-level statements of a module, with `star_names` in place of its star
+/// imports.
+fn top_level_names(ast: &Ast, star_names: Vec<String>) -> Vec<String> {
+    let mut names = Vec::new();
+    for statement in ast.statements.iter() {
+        match &statement.kind {
+            StatementKind::Assign(assign) => {
+                for target in assign.targets.iter() {
+                    pattern_names(target, &mut names);
+                }
+            }
+            StatementKind::AnnAssign(assign) => {
+                if let ExprKind::Identifier(name) = &assign.target.kind {
+                    names.push(name.name.clone());
+                }
+            }
+            StatementKind::Declaration(declaration) => names.push(declaration.name.name.clone()),
+            StatementKind::TypeAlias(alias) => names.push(alias.name.name.clone()),
+            StatementKind::FunctionDef(function) => names.push(function.name.name.clone()),
+            StatementKind::ClassDef(class) => names.push(class.name.name.clone()),
+            StatementKind::InterfaceDef(interface) => names.push(interface.name.name.clone()),
+            StatementKind::MacroDef(macro_def) => names.push(macro_def.name.name.clone()),
+            StatementKind::Import(import) => {
+                let name = import.alias.as_ref().or_else(|| import.path.names.first());
+                names.extend(name.map(|name| name.name.clone()));
+            }
+            StatementKind::ImportFrom(ImportFrom {
+                names: ImportNames::Names(imports),
+                ..
+            }) => {
+                for import in imports {
+                    names.push(import.alias.as_ref().unwrap_or(&import.name).name.clone());
+                }
+            }
+            _ => (),
+        }
+    }
+
+    names.extend(star_names);
+    let mut unique = Vec::new();
+    for name in names {
+        if !unique.contains(&name) {
+            unique.push(name);
+        }
+    }
+
+    unique
+}
+
+/// Collects the names a target pattern binds.
+fn pattern_names(pattern: &Pattern, names: &mut Vec<String>) {
+    match &pattern.kind {
+        PatternKind::Capture(name) | PatternKind::Rest(Some(name)) => names.push(name.name.clone()),
+        PatternKind::Sequence(patterns) => {
+            for pattern in patterns {
+                pattern_names(pattern, names);
+            }
+        }
+        _ => (),
+    }
+}
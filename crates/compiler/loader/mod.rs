@@ -0,0 +1,9 @@
+#![allow(clippy::module_inception)]
+mod errors;
+mod loader;
+
+pub use errors::*;
+pub use loader::*;
+
+#[cfg(test)]
+mod test;
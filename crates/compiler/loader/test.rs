@@ -0,0 +1,197 @@
+use super::LoaderErrorKind::*;
+
+use utils::*;
+
+#[test]
+fn can_load_module_graph() {
+    let root = write_modules(&[
+        ("main.ra", "import a.b\nfrom a import c, d\nimport std.io\n"),
+        ("a/mod.ra", "d = 1\n"),
+        ("a/b.ra", "from . import c\nfrom .. import a\n"),
+        ("a/c.ra", "x = 1\n"),
+        ("std/io.ra", "from . import fmt\n"),
+        ("std/fmt.ra", ""),
+    ]);
+
+    let graph = load(&root, &[]).unwrap();
+
+    assert_eq!(
+        names(graph.modules.iter()),
+        ["main", "a.b", "a.c", "a", "std.io", "std.fmt"]
+    );
+    assert_eq!(
+        names(graph.order()),
+        ["a.c", "a", "a.b", "std.fmt", "std.io", "main"]
+    );
+
+    let imports = |index: usize| {
+        let imports = graph.modules[index].imports.iter();
+        names(imports.map(|id| graph.get(*id)))
+    };
+    assert_eq!(imports(0), ["a.b", "a.c", "a", "std.io"]);
+    assert_eq!(imports(1), ["a.c", "a"]);
+    assert!(graph.modules[3].is_package);
+}
+
+#[test]
+fn can_look_up_search_paths_in_order() {
+    let root = write_modules(&[
+        ("main.ra", "import a\nimport b\n"),
+        ("a.ra", ""),
+        ("first/a.ra", ""),
+        ("first/b.ra", ""),
+        ("second/b.ra", ""),
+    ]);
+
+    let graph = load(&root, &["first", "second"]).unwrap();
+    let paths = graph
+        .modules
+        .iter()
+        .map(|module| module.path.strip_prefix(&root).unwrap().to_path_buf())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        paths,
+        ["main.ra", "a.ra", "first/b.ra"].map(std::path::PathBuf::from)
+    );
+}
+
+#[test]
+fn can_export_names_to_star_imports() {
+    let root = write_modules(&[
+        ("main.ra", "from a import *\nfrom b import *\n"),
+        ("a.ra", "from c import *\nx = 1\n_y = 2\ndef f(): pass\n"),
+        ("b.ra", "__all__ = [\"z\"]\nz = 1\nw = 2\n"),
+        ("c.ra", "class C: pass\n"),
+    ]);
+
+    let graph = load(&root, &[]).unwrap();
+    let exports = |name: &str| {
+        let module = graph.modules.iter().find(|module| module.name == name);
+        module.unwrap().exports.clone()
+    };
+
+    assert_eq!(exports("a"), ["x", "f", "C"]);
+    assert_eq!(exports("b"), ["z"]);
+    assert_eq!(exports("main"), ["x", "f", "C", "z"]);
+
+    let star_names = |name: &str| {
+        let module = graph.modules.iter().find(|module| module.name == name);
+        module.unwrap().star_names.clone()
+    };
+
+    assert_eq!(star_names("main"), ["x", "f", "C", "z"]);
+    assert_eq!(star_names("a"), ["C"]);
+    assert!(star_names("b").is_empty());
+}
+
+#[test]
+fn cannot_load_invalid_imports() {
+    let error = |files: &[(&str, &str)]| load_error(&write_modules(files));
+
+    assert_eq!(error(&[("main.ra", "import a\n")]), ModuleNotFound);
+    assert_eq!(
+        error(&[("main.ra", "from a import b\n"), ("a/c.ra", "")]),
+        ModuleNotFound
+    );
+    assert_eq!(
+        error(&[("main.ra", "from .. import a\n")]),
+        InvalidRelativeImport
+    );
+    assert_eq!(
+        error(&[
+            ("main.ra", "import a\n"),
+            ("a.ra", "import b\n"),
+            ("b.ra", "import a\n")
+        ]),
+        ImportCycle
+    );
+    assert_eq!(error(&[("main.ra", "import main\n")]), ImportCycle);
+    assert_eq!(
+        error(&[("main.ra", "def f():\n    from a import *\n"), ("a.ra", "")]),
+        InvalidStarImport
+    );
+    assert_eq!(
+        error(&[
+            ("main.ra", "from a import *\n"),
+            ("a.ra", "__all__ = [x]\n")
+        ]),
+        InvalidStarImport
+    );
+}
+
+mod utils {
+    use std::fs;
+    use std::ops::Deref;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use anyhow::Result;
+
+    use crate::loader::{LoaderError, LoaderErrorKind, Module, ModuleGraph, ModuleLoader};
+
+    /// A temporary directory, removed with everything in it when dropped.
+    pub(super) struct TempDir(PathBuf);
+
+    impl Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl AsRef<Path> for TempDir {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Writes the files to a new temporary directory, with `std/` as the standard library.
+    pub(super) fn write_modules(files: &[(&str, &str)]) -> TempDir {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let root = std::env::temp_dir().join(format!(
+            "raccoon-loader-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&root);
+        let root = TempDir(root);
+
+        for (path, code) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, code).unwrap();
+        }
+
+        root
+    }
+
+    /// Loads `main.ra` from the directory.
+    pub(super) fn load(root: &Path, search_paths: &[&str]) -> Result<ModuleGraph> {
+        let search_paths = search_paths.iter().map(|path| root.join(path)).collect();
+
+        ModuleLoader::new(search_paths)
+            .with_std_path(root.join("std"))
+            .load(&root.join("main.ra"))
+    }
+
+    pub(super) fn load_error(root: &Path) -> LoaderErrorKind {
+        load(root, &[])
+            .unwrap_err()
+            .downcast::<LoaderError>()
+            .unwrap()
+            .kind
+    }
+
+    pub(super) fn names<'a>(modules: impl Iterator<Item = &'a Module>) -> Vec<String> {
+        modules.map(|module| module.name.clone()).collect()
+    }
+}
@@ -0,0 +1,53 @@
+use std::fmt::Display;
+
+use crate::span::Span;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParserErrorKind {
+    UnexpectedToken,
+    UnexpectedEndOfCode,
+    UnexpectedIndent,
+    ExpectedIndentedBlock,
+    InvalidAssignmentTarget,
+    InvalidParameterOrder,
+    InvalidRestExpression,
+    InvalidPattern,
+    InvalidInterfaceMember,
+    AwaitOutsideAsyncFunction,
+    YieldOutsideFunction,
+    YieldInComprehension,
+    /// Code nested deeper than [`Parser::MAX_NESTING`](super::Parser::MAX_NESTING), like
+    /// expressions in hundreds of parentheses.
+    NestingTooDeep,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParserError {
+    pub(crate) kind: ParserErrorKind,
+    pub(crate) span: Span,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl ParserError {
+    pub fn new(kind: ParserErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl std::error::Error for ParserError {}
+
+impl Display for ParserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserError")
+            .field("kind", &self.kind)
+            .field("span", &self.span)
+            .finish()
+    }
+}
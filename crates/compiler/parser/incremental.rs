@@ -0,0 +1,611 @@
+use std::mem;
+use std::ops::Range;
+
+use anyhow::Result;
+
+use crate::ir::{fold, Ast, ClassKind, Fold, If, NodeId, Statement, StatementKind};
+use crate::lexer::{Lexer, Token, TokenKind};
+use crate::span::Span;
+
+use super::parser::{is_generator_body, Parser};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// Keeps the code and AST of a module to reparse only what an edit touches.
+///
+/// The unit of reparsing is a run of whole lines in the innermost indented block the edit falls in,
+/// from the line of a statement before the edit to the line of a statement after it. The run is
+/// parsed under a made-up header that opens as many blocks, so it is lexed with the indentation it
+/// has in the module, and the rest of the AST is reused with its spans moved.
+///
+/// When the edit changes how the run fits in its block, like a line dedented out of it or a bracket
+/// left open, the enclosing blocks are tried next and the whole module last. So the AST is always
+/// the one a parse from scratch would give.
+#[derive(Debug)]
+pub struct IncrementalParser {
+    code: String,
+    /// The AST of the code, or the error parsing it.
+    ast: Result<Ast>,
+    /// The first indent of the code, which every later indent has to match.
+    indentation: Option<Indentation>,
+    /// The part of the code the last edit reparsed.
+    reparsed: Span,
+}
+
+/// Replaces the code in `span` with `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub span: Span,
+    pub text: String,
+}
+
+/// The character and width of an indent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Indentation {
+    char: char,
+    width: usize,
+}
+
+/// The innermost function around a block, which decides the header it is reparsed under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Module,
+    Function,
+    AsyncFunction,
+}
+
+/// A run of statements of an indented block, on whole lines that contain an edit.
+#[derive(Debug, Clone)]
+struct Region {
+    /// The index of each statement and then of its body, from the top level to the block.
+    path: Vec<(usize, usize)>,
+    /// The indentation level of the block.
+    level: usize,
+    scope: Scope,
+    /// The lines in the old code.
+    lines: Range<u32>,
+    /// The statements on the lines.
+    statements: Range<usize>,
+}
+
+/// Moves the spans after reparsed lines by the change in length of the code.
+///
+/// The ends inside the lines, which only the statements enclosing the lines have, move to where the
+/// reparsed block ends.
+#[derive(Debug)]
+struct SpanShift {
+    lines: Range<u32>,
+    delta: i64,
+    end: u32,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl IncrementalParser {
+    /// Parses the code from scratch.
+    pub fn new(code: impl Into<String>) -> Self {
+        let code = code.into();
+        let (ast, indentation) = parse(&code);
+
+        Self {
+            reparsed: Span::new(0, code.chars().count() as u32),
+            code,
+            ast,
+            indentation,
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn ast(&self) -> Result<&Ast, &anyhow::Error> {
+        self.ast.as_ref()
+    }
+
+    /// The part of the code the last edit reparsed, which is all of it after a parse from scratch.
+    pub fn reparsed(&self) -> &Span {
+        &self.reparsed
+    }
+
+    /// Applies an edit to the code and reparses it.
+    ///
+    /// Panics if the span of the edit is not in the code.
+    pub fn edit(&mut self, edit: &Edit) -> Result<&Ast, &anyhow::Error> {
+        let old = self.code.chars().collect::<Vec<_>>();
+        let (start, end) = (edit.span.start as usize, edit.span.end as usize);
+        assert!(
+            start <= end && end <= old.len(),
+            "edit {:?} is outside the code",
+            edit.span
+        );
+
+        let new = old[..start]
+            .iter()
+            .copied()
+            .chain(edit.text.chars())
+            .chain(old[end..].iter().copied())
+            .collect::<Vec<_>>();
+        let delta = new.len() as i64 - old.len() as i64;
+        self.code = new.iter().collect();
+
+        let reparsed = match &mut self.ast {
+            Ok(ast) => reparse(ast, &mut self.indentation, &old, &new, &edit.span, delta),
+            Err(_) => None,
+        };
+
+        self.reparsed = match reparsed {
+            Some(reparsed) => reparsed,
+            None => {
+                (self.ast, self.indentation) = parse(&self.code);
+                Span::new(0, new.len() as u32)
+            }
+        };
+
+        self.ast()
+    }
+}
+
+impl Fold for SpanShift {
+    fn fold_statement(&mut self, node: Statement) -> Statement {
+        let is_before = node.span.end < self.lines.start;
+        let is_replaced = self.lines.contains(&node.span.start) && node.span.end <= self.lines.end;
+
+        match is_before || is_replaced {
+            true => node,
+            false => fold::fold_statement(self, node),
+        }
+    }
+
+    fn fold_span(&mut self, node: Span) -> Span {
+        let shift = |offset: u32| (offset as i64 + self.delta) as u32;
+        let start = match node.start >= self.lines.end {
+            true => shift(node.start),
+            false => node.start,
+        };
+        let end = match node.end {
+            // Only a block that runs to the end of the code can end where the lines do.
+            end if end > self.lines.end => shift(end),
+            end if end >= self.lines.start => self.end,
+            end => end,
+        };
+
+        Span { start, end, ..node }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Parses code from scratch.
+fn parse(code: &str) -> (Result<Ast>, Option<Indentation>) {
+    let tokens = match Lexer::tokenize(code).collect::<Result<Vec<_>>>() {
+        Ok(tokens) => tokens,
+        Err(err) => return (Err(err), None),
+    };
+
+    let chars = code.chars().collect::<Vec<_>>();
+    let indentation = first_indentation(&chars, &tokens);
+
+    (
+        Parser::parse_tokens(tokens, chars.len() as u32),
+        indentation,
+    )
+}
+
+/// Reparses the innermost region around an edit that parses the way it would in the whole module,
+/// and returns the reparsed part of the new code.
+fn reparse(
+    ast: &mut Ast,
+    indentation: &mut Option<Indentation>,
+    old: &[char],
+    new: &[char],
+    edit: &Span,
+    delta: i64,
+) -> Option<Span> {
+    let (region, (statements, end, region_indentation)) = regions(ast, old, edit, *indentation)
+        .into_iter()
+        .rev()
+        .find_map(|region| {
+            let parsed = parse_region(&region, new, delta, *indentation)?;
+            Some((region, parsed))
+        })?;
+
+    let lines = Span::new(region.lines.start, (region.lines.end as i64 + delta) as u32);
+    let mut shift = SpanShift {
+        lines: region.lines.clone(),
+        delta,
+        end,
+    };
+    let old_ast = mem::replace(
+        ast,
+        Ast {
+            statements: vec![],
+            id: NodeId::default(),
+            span: Span::new(0, 0),
+        },
+    );
+
+    *ast = shift.fold_ast(old_ast);
+    ast.span = Span::new(0, new.len() as u32);
+    block_mut(&mut ast.statements, &region.path).splice(region.statements, statements);
+    update_generators(&mut ast.statements, &region.path);
+    *indentation = region_indentation;
+
+    Some(lines)
+}
+
+/// Finds the region around an edit in each block that contains it, from the top level inwards.
+fn regions(ast: &Ast, code: &[char], edit: &Span, indentation: Option<Indentation>) -> Vec<Region> {
+    let mut regions = vec![];
+    let Some(mut region) = find_region(&ast.statements, code, None, code.len() as u32, edit).map(
+        |(lines, statements)| Region {
+            path: vec![],
+            level: 0,
+            scope: Scope::Module,
+            lines,
+            statements,
+        },
+    ) else {
+        return regions;
+    };
+
+    let mut block = &ast.statements[..];
+    while let (Some(indentation), 1) = (indentation, region.statements.len()) {
+        let statement = &block[region.statements.start];
+        let scope = body_scope(&statement.kind, region.scope);
+
+        // The edit is in the region of at most one of the bodies.
+        let inner = bodies(&statement.kind)
+            .into_iter()
+            .enumerate()
+            .find_map(|(index, body)| {
+                let first = body.first()?;
+                let column = indentation_column(code, first.span.start, indentation)?;
+                let level = column / indentation.width;
+                if column % indentation.width != 0 || level <= region.level {
+                    return None;
+                }
+
+                let end = line_end(code, body.last()?.span.end);
+                let (lines, statements) =
+                    find_region(body, code, Some((indentation, column)), end, edit)?;
+
+                let mut path = region.path.clone();
+                path.push((region.statements.start, index));
+                Some((
+                    body,
+                    Region {
+                        path,
+                        level,
+                        scope,
+                        lines,
+                        statements,
+                    },
+                ))
+            });
+
+        let Some((body, inner)) = inner else {
+            break;
+        };
+
+        regions.push(mem::replace(&mut region, inner));
+        block = body;
+    }
+
+    regions.push(region);
+    regions
+}
+
+/// Finds the lines of a block that contain an edit, from the start of the line of a statement before
+/// the edit to the start of the line of a statement after it or to the end of the block.
+///
+/// The statements of a nested block are at the given column, and the top level can also start at
+/// the start of the code.
+fn find_region(
+    block: &[Statement],
+    code: &[char],
+    indentation: Option<(Indentation, usize)>,
+    end: u32,
+    edit: &Span,
+) -> Option<(Range<u32>, Range<usize>)> {
+    let mut cuts = block
+        .iter()
+        .enumerate()
+        .filter_map(|(index, statement)| {
+            Some((line_start(code, statement.span.start, indentation)?, index))
+        })
+        .collect::<Vec<_>>();
+
+    if indentation.is_none() {
+        cuts.insert(0, (0, 0));
+    }
+
+    let start = cuts
+        .iter()
+        .rev()
+        .find(|(offset, _)| *offset < edit.start || *offset == 0)?;
+    let end = match cuts.iter().find(|(offset, _)| *offset > edit.end) {
+        Some(cut) => *cut,
+        None if edit.end < end || end as usize == code.len() => (end, block.len()),
+        None => return None,
+    };
+
+    Some((start.0..end.0, start.1..end.1))
+}
+
+/// Parses the new lines of a region under a header that opens as many blocks as enclose it, and
+/// returns its statements if they fit in the block the old ones were in, along with where the
+/// statements enclosing the block now end.
+fn parse_region(
+    region: &Region,
+    code: &[char],
+    delta: i64,
+    indentation: Option<Indentation>,
+) -> Option<(Vec<Statement>, u32, Option<Indentation>)> {
+    let start = region.lines.start as usize;
+    let end = (region.lines.end as i64 + delta) as usize;
+    let lines = &code[start..end];
+
+    // A line continuation would join the line after the region.
+    if is_continued(lines) {
+        return None;
+    }
+
+    let header = header(region.level, region.scope, indentation);
+    let text = header
+        .chars()
+        .chain(lines.iter().copied())
+        .collect::<Vec<_>>();
+    let tokens = Lexer::tokenize(&text.iter().collect::<String>())
+        .collect::<Result<Vec<_>>>()
+        .ok()?;
+
+    // The first indent of the module decides the size of every other.
+    let indentation = match (indentation, first_indentation(&text, &tokens)) {
+        (indentation, None) => indentation,
+        (None, Some(indentation)) => Some(indentation),
+        (Some(indentation), Some(first)) if indentation == first => Some(indentation),
+        _ => return None,
+    };
+
+    let ast = Parser::parse_tokens(tokens, text.len() as u32).ok()?;
+    let mut statements = ast.statements;
+    let mut end = statements.last().map_or(0, |statement| statement.span.end);
+    for _ in 0..region.level {
+        let [statement]: [Statement; 1] = statements.try_into().ok()?;
+        end = statement.span.end;
+        statements = match statement.kind {
+            StatementKind::If(If {
+                body,
+                elifs,
+                orelse: None,
+                ..
+            }) if elifs.is_empty() => body,
+            StatementKind::FunctionDef(function) => function.body,
+            _ => return None,
+        };
+    }
+
+    let mut shift = SpanShift {
+        lines: 0..0,
+        delta: start as i64 - header.chars().count() as i64,
+        end: 0,
+    };
+    let statements = statements
+        .into_iter()
+        .map(|statement| shift.fold_statement(statement))
+        .collect();
+
+    Some((
+        statements,
+        shift.fold_span(Span::new(0, end)).end,
+        indentation,
+    ))
+}
+
+/// Makes up the lines that open `level` blocks, the innermost of which is in `scope`.
+fn header(level: usize, scope: Scope, indentation: Option<Indentation>) -> String {
+    let mut header = String::new();
+    for depth in 0..level {
+        if let Some(indentation) = indentation {
+            let indent = indentation.char.to_string();
+            header.push_str(&indent.repeat(depth * indentation.width));
+        }
+
+        header.push_str(match (depth + 1 == level, scope) {
+            (true, Scope::Function) => "def _():\n",
+            (true, Scope::AsyncFunction) => "async def _():\n",
+            _ => "if 1:\n",
+        });
+    }
+
+    header
+}
+
+/// The indentation of the first indent token, which the lexer holds every later indent to.
+fn first_indentation(code: &[char], tokens: &[Token]) -> Option<Indentation> {
+    let token = tokens
+        .iter()
+        .find(|token| token.kind == TokenKind::Indent)?;
+    let spaces = code[token.span.start as usize..token.span.end as usize]
+        .iter()
+        .filter(|char| matches!(char, ' ' | '\t'))
+        .collect::<Vec<_>>();
+
+    Some(Indentation {
+        char: **spaces.first()?,
+        width: spaces.len(),
+    })
+}
+
+/// The start of the line of a statement, if the statement is the first on a line and only indented
+/// to the column of its block.
+fn line_start(
+    code: &[char],
+    offset: u32,
+    indentation: Option<(Indentation, usize)>,
+) -> Option<u32> {
+    let start = match indentation {
+        Some((indentation, column)) => {
+            if indentation_column(code, offset, indentation)? != column {
+                return None;
+            }
+
+            offset as usize - column
+        }
+        None => offset as usize,
+    };
+
+    match start == 0 || (code[start - 1] == '\n' && !is_continued(&code[..start])) {
+        true => Some(start as u32),
+        false => None,
+    }
+}
+
+/// The number of indentation characters between the start of a line and an offset, if there is
+/// nothing else.
+fn indentation_column(code: &[char], offset: u32, indentation: Indentation) -> Option<usize> {
+    let offset = offset as usize;
+    let start = code[..offset]
+        .iter()
+        .rposition(|char| *char != indentation.char)
+        .map_or(0, |index| index + 1);
+
+    match start == 0 || code[start - 1] == '\n' {
+        true => Some(offset - start),
+        false => None,
+    }
+}
+
+/// The start of the line after an offset, or the end of the code.
+fn line_end(code: &[char], offset: u32) -> u32 {
+    let offset = offset as usize;
+    match code[offset..].iter().position(|char| *char == '\n') {
+        Some(index) => (offset + index + 1) as u32,
+        None => code.len() as u32,
+    }
+}
+
+/// Checks if code ends with a line continuation.
+fn is_continued(code: &[char]) -> bool {
+    let mut chars = code.iter().rev().peekable();
+    if chars.next() != Some(&'\n') {
+        return false;
+    }
+
+    chars.next_if_eq(&&'\r');
+    chars.next() == Some(&'\\')
+}
+
+/// The innermost function around the bodies of a statement, given the one around the statement.
+fn body_scope(kind: &StatementKind, scope: Scope) -> Scope {
+    match kind {
+        StatementKind::FunctionDef(function) if function.is_async => Scope::AsyncFunction,
+        StatementKind::FunctionDef(_) => Scope::Function,
+        StatementKind::ClassDef(_) => Scope::Module,
+        _ => scope,
+    }
+}
+
+/// The bodies of a compound statement that are parsed as blocks of statements.
+fn bodies(kind: &StatementKind) -> Vec<&Vec<Statement>> {
+    match kind {
+        StatementKind::If(statement) => [Some(&statement.body)]
+            .into_iter()
+            .chain(statement.elifs.iter().map(|elif| Some(&elif.body)))
+            .chain([statement.orelse.as_ref()])
+            .flatten()
+            .collect(),
+        StatementKind::While(statement) => [Some(&statement.body), statement.orelse.as_ref()]
+            .into_iter()
+            .flatten()
+            .collect(),
+        StatementKind::For(statement) => [Some(&statement.body), statement.orelse.as_ref()]
+            .into_iter()
+            .flatten()
+            .collect(),
+        StatementKind::Try(statement) => [Some(&statement.body)]
+            .into_iter()
+            .chain(statement.handlers.iter().map(|handler| Some(&handler.body)))
+            .chain([statement.orelse.as_ref(), statement.finalbody.as_ref()])
+            .flatten()
+            .collect(),
+        StatementKind::With(statement) => vec![&statement.body],
+        StatementKind::Match(statement) => statement.cases.iter().map(|case| &case.body).collect(),
+        StatementKind::FunctionDef(function) => vec![&function.body],
+        // The body of an enum class also has variants.
+        StatementKind::ClassDef(class) if class.kind != ClassKind::Enum => vec![&class.body],
+        _ => vec![],
+    }
+}
+
+/// Like [`bodies`], but mutable.
+fn bodies_mut(kind: &mut StatementKind) -> Vec<&mut Vec<Statement>> {
+    match kind {
+        StatementKind::If(statement) => [Some(&mut statement.body)]
+            .into_iter()
+            .chain(statement.elifs.iter_mut().map(|elif| Some(&mut elif.body)))
+            .chain([statement.orelse.as_mut()])
+            .flatten()
+            .collect(),
+        StatementKind::While(statement) => [Some(&mut statement.body), statement.orelse.as_mut()]
+            .into_iter()
+            .flatten()
+            .collect(),
+        StatementKind::For(statement) => [Some(&mut statement.body), statement.orelse.as_mut()]
+            .into_iter()
+            .flatten()
+            .collect(),
+        StatementKind::Try(statement) => [Some(&mut statement.body)]
+            .into_iter()
+            .chain(
+                statement
+                    .handlers
+                    .iter_mut()
+                    .map(|handler| Some(&mut handler.body)),
+            )
+            .chain([statement.orelse.as_mut(), statement.finalbody.as_mut()])
+            .flatten()
+            .collect(),
+        StatementKind::With(statement) => vec![&mut statement.body],
+        StatementKind::Match(statement) => statement
+            .cases
+            .iter_mut()
+            .map(|case| &mut case.body)
+            .collect(),
+        StatementKind::FunctionDef(function) => vec![&mut function.body],
+        StatementKind::ClassDef(class) if class.kind != ClassKind::Enum => vec![&mut class.body],
+        _ => vec![],
+    }
+}
+
+/// The block at the end of a region path.
+fn block_mut<'a>(
+    mut block: &'a mut Vec<Statement>,
+    path: &[(usize, usize)],
+) -> &'a mut Vec<Statement> {
+    for (statement, body) in path {
+        block = bodies_mut(&mut block[*statement].kind).swap_remove(*body);
+    }
+
+    block
+}
+
+/// Infers again if the functions on a region path are generators, as the region can add or remove
+/// a `yield`.
+fn update_generators(mut block: &mut Vec<Statement>, path: &[(usize, usize)]) {
+    for (statement, body) in path {
+        let statement = &mut block[*statement];
+        if let StatementKind::FunctionDef(function) = &mut statement.kind {
+            function.is_generator = is_generator_body(&function.body);
+        }
+
+        block = bodies_mut(&mut statement.kind).swap_remove(*body);
+    }
+}
@@ -1,4 +1,11 @@
 #![allow(clippy::module_inception)]
+mod errors;
+mod incremental;
 mod parser;
 
+pub use errors::*;
+pub use incremental::*;
 pub use parser::*;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,2874 @@
+use crate::error;
+use crate::ir::{
+    visit, AnnAssign, Argument, Assert, Assign, Ast, AugAssign, BinaryOp, ClassDef, ClassKind,
+    Comparison, ComparisonOp, Comprehension, ComprehensionFor, Declaration, DeclarationKind,
+    Decorator, DictComprehension, DictEntry, Elif, ExceptHandler, Expr, ExprKind, For, FunctionDef,
+    GenericBound, Identifier, If, Import, ImportFrom, ImportName, ImportNames, ImportPath,
+    InterfaceDef, KeywordPattern, Lambda, LambdaBody, MacroDef, MappingPatternEntry, Match,
+    MatchCase, MatchExpr, MatchExprCase, NodeId, Param, Params, PassingMode, Path, Pattern,
+    PatternKind, Raise, Statement, StatementKind, StringLiteral, SubscriptIndex, Try, TypeAlias,
+    TypeAnnotation, TypeAnnotationKind, UnaryOp, Variant, VariantField, Visitor, While, With,
+    WithItem,
+};
+use crate::lexer::{Delimiter, Keyword, Lexer, Operator, Token, TokenKind};
+use crate::span::Span;
+use anyhow::Result;
+
+use super::errors::{ParserError, ParserErrorKind::*};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// An implementation of Raccoon's parser.
+///
+/// It is a recursive descent parser over the tokens of the [`Lexer`].
+/// Check [`parser.grammar`](#parser.grammar) for the language's parser grammar specification.
+#[derive(Debug)]
+pub struct Parser {
+    /// The tokens of the source code.
+    tokens: Vec<Token>,
+    /// The index of the next token.
+    cursor: usize,
+    /// The end of the last token that is not a newline, indent or dedent.
+    prev_end: u32,
+    /// The length of the source code.
+    end: u32,
+    /// The innermost function being parsed, which is `None` at the top level and in class bodies.
+    function: Option<FunctionContext>,
+    /// How many expressions, blocks, patterns, types and operations of chains enclose the code being
+    /// parsed.
+    depth: usize,
+}
+
+/// What the body of a function allows and contains.
+#[derive(Debug, Default, Clone, Copy)]
+struct FunctionContext {
+    /// Whether the body can `await`.
+    is_async: bool,
+    /// Whether the body has a `yield`.
+    is_generator: bool,
+}
+
+/// Finds a `yield` that belongs to the scope being searched, which excludes nested functions, classes
+/// and lambdas.
+#[derive(Default)]
+struct YieldFinder {
+    span: Option<Span>,
+}
+
+/// A function that parses an expression.
+type ParseFn = fn(&mut Parser) -> Result<Expr>;
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Parser {
+    /// How deep code can nest before parsing it would overflow the stack.
+    pub const MAX_NESTING: usize = 64;
+
+    fn new(tokens: Vec<Token>, end: u32) -> Self {
+        Self {
+            tokens,
+            cursor: 0,
+            prev_end: 0,
+            end,
+            function: None,
+            depth: 0,
+        }
+    }
+
+    /// Parses the source code into an AST.
+    pub fn parse(code: &str) -> Result<Ast> {
+        let tokens = Lexer::tokenize(code).collect::<Result<Vec<_>>>()?;
+        Self::parse_tokens(tokens, code.chars().count() as u32)
+    }
+
+    /// Parses the tokens of source code of length `end` into an AST.
+    pub(super) fn parse_tokens(tokens: Vec<Token>, end: u32) -> Result<Ast> {
+        let mut parser = Parser::new(tokens, end);
+
+        let statements = parser.parse_statements()?;
+        if parser.peek().is_some() {
+            return parser.unexpected();
+        }
+
+        Ok(Ast {
+            statements,
+            id: NodeId::next(),
+            span: Span::new(0, parser.end),
+        })
+    }
+
+    // Returns the next token kind without advancing the cursor.
+    fn peek(&self) -> Option<&TokenKind> {
+        self.peek_nth(0)
+    }
+
+    // Returns the token kind `n` tokens after the next without advancing the cursor.
+    fn peek_nth(&self, n: usize) -> Option<&TokenKind> {
+        self.tokens.get(self.cursor + n).map(|token| &token.kind)
+    }
+
+    // Returns the next token and advances the cursor.
+    fn eat(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.cursor).cloned()?;
+        self.cursor += 1;
+
+        if !matches!(
+            token.kind,
+            TokenKind::Newline | TokenKind::Indent | TokenKind::Dedent
+        ) {
+            self.prev_end = token.span.end;
+        }
+
+        Some(token)
+    }
+
+    // Returns the start of the next token.
+    fn start(&self) -> u32 {
+        match self.tokens.get(self.cursor) {
+            Some(token) => token.span.start,
+            None => self.end,
+        }
+    }
+
+    // Returns a span from `start` to the end of the last eaten token.
+    fn span_from(&self, start: u32) -> Span {
+        Span::new(start, self.prev_end.max(start))
+    }
+
+    // Returns the span of the last eaten token.
+    fn prev_span(&self) -> Span {
+        self.tokens[self.cursor - 1].span.clone()
+    }
+
+    fn is(&self, kind: &TokenKind) -> bool {
+        self.peek() == Some(kind)
+    }
+
+    fn is_delim(&self, delimiter: Delimiter) -> bool {
+        self.is(&TokenKind::Delim(delimiter))
+    }
+
+    fn is_keyword(&self, keyword: Keyword) -> bool {
+        self.is(&TokenKind::Keyword(keyword))
+    }
+
+    fn is_op(&self, operator: Operator) -> bool {
+        self.is(&TokenKind::Op(operator))
+    }
+
+    // Eats the next token if it is of the given kind.
+    fn eat_if(&mut self, kind: &TokenKind) -> bool {
+        let matched = self.is(kind);
+        if matched {
+            self.eat();
+        }
+
+        matched
+    }
+
+    fn eat_delim(&mut self, delimiter: Delimiter) -> bool {
+        self.eat_if(&TokenKind::Delim(delimiter))
+    }
+
+    fn eat_keyword(&mut self, keyword: Keyword) -> bool {
+        self.eat_if(&TokenKind::Keyword(keyword))
+    }
+
+    fn eat_op(&mut self, operator: Operator) -> bool {
+        self.eat_if(&TokenKind::Op(operator))
+    }
+
+    fn expect_delim(&mut self, delimiter: Delimiter) -> Result<()> {
+        match self.eat_delim(delimiter) {
+            true => Ok(()),
+            false => self.unexpected(),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: Keyword) -> Result<()> {
+        match self.eat_keyword(keyword) {
+            true => Ok(()),
+            false => self.unexpected(),
+        }
+    }
+
+    /// Expects an identifier that acts as a keyword in its position, like `case`.
+    fn expect_soft_keyword(&mut self, keyword: &str) -> Result<()> {
+        match self.peek() {
+            Some(TokenKind::Identifier(name)) if name == keyword => {
+                self.eat();
+                Ok(())
+            }
+            _ => self.unexpected(),
+        }
+    }
+
+    fn expect_identifier(&mut self) -> Result<Identifier> {
+        match self.peek() {
+            Some(TokenKind::Identifier(_)) => {
+                let token = self.eat().unwrap();
+                match token.kind {
+                    TokenKind::Identifier(name) => Ok(Identifier {
+                        name,
+                        id: NodeId::next(),
+                        span: token.span,
+                        context: None,
+                    }),
+                    _ => unreachable!(),
+                }
+            }
+            _ => self.unexpected(),
+        }
+    }
+
+    // Returns an error for the next token.
+    fn unexpected<T>(&self) -> Result<T> {
+        match self.tokens.get(self.cursor) {
+            Some(token) if token.kind == TokenKind::Indent => {
+                error(ParserError::new(UnexpectedIndent, token.span.clone()))
+            }
+            Some(token) => error(ParserError::new(UnexpectedToken, token.span.clone())),
+            None => error(ParserError::new(
+                UnexpectedEndOfCode,
+                Span::new(self.end, self.end),
+            )),
+        }
+    }
+
+    // Checks if the last eaten token is a dedent, like the end of a lambda block.
+    fn prev_is_dedent(&self) -> bool {
+        self.cursor > 0 && self.tokens[self.cursor - 1].kind == TokenKind::Dedent
+    }
+
+    // Checks if the next token ends a small statement.
+    fn is_statement_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            None | Some(TokenKind::Newline)
+                | Some(TokenKind::Dedent)
+                | Some(TokenKind::Delim(Delimiter::SemiColon))
+        ) || self.prev_is_dedent()
+    }
+
+    // Checks if the next token can start an expression.
+    fn can_start_expr(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(
+                TokenKind::Identifier(_)
+                    | TokenKind::Integer(..)
+                    | TokenKind::Float(_)
+                    | TokenKind::Imag(_)
+                    | TokenKind::Str(..)
+                    | TokenKind::ByteStr(..)
+                    | TokenKind::Op(
+                        Operator::Plus
+                            | Operator::Minus
+                            | Operator::BitNot
+                            | Operator::Sqrt
+                            | Operator::Mul
+                            | Operator::Pow
+                    )
+                    | TokenKind::Delim(Delimiter::LParen | Delimiter::LBracket | Delimiter::LBrace)
+                    | TokenKind::Keyword(
+                        Keyword::Not
+                            | Keyword::Ref
+                            | Keyword::Val
+                            | Keyword::Ptr
+                            | Keyword::Lambda
+                            | Keyword::Match
+                            | Keyword::Await
+                            | Keyword::True
+                            | Keyword::False
+                    )
+            )
+        )
+    }
+
+    // Checks if the next token ends a line, which compound statements without a block need.
+    fn is_line_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            None | Some(TokenKind::Newline) | Some(TokenKind::Dedent)
+        )
+    }
+
+    fn expect_line_end(&self) -> Result<()> {
+        match self.is_line_end() {
+            true => Ok(()),
+            false => self.unexpected(),
+        }
+    }
+
+    // Checks if the next tokens start a function, class or interface definition, including its modifiers.
+    fn is_definition(&self) -> bool {
+        match self.peek() {
+            Some(TokenKind::Keyword(
+                Keyword::Def | Keyword::Class | Keyword::Enum | Keyword::Interface,
+            )) => true,
+            Some(TokenKind::Keyword(Keyword::Async)) => {
+                self.peek_nth(1) == Some(&TokenKind::Keyword(Keyword::Def))
+            }
+            Some(TokenKind::Identifier(name)) => matches!(
+                (class_kind(name), self.peek_nth(1)),
+                (Some(_), Some(TokenKind::Keyword(Keyword::Class)))
+                    | (
+                        Some(ClassKind::Abstract),
+                        Some(TokenKind::Keyword(Keyword::Def | Keyword::Async))
+                    )
+            ),
+            _ => false,
+        }
+    }
+
+    /// Parses with `function` as the innermost function and returns what it ended up containing.
+    fn parse_in_function<T>(
+        &mut self,
+        function: Option<FunctionContext>,
+        parse: impl FnOnce(&mut Parser) -> Result<T>,
+    ) -> Result<(T, Option<FunctionContext>)> {
+        let outer = std::mem::replace(&mut self.function, function);
+        let result = parse(self);
+        let function = std::mem::replace(&mut self.function, outer);
+
+        Ok((result?, function))
+    }
+
+    /// Parses code nested one level deeper, up to [`Parser::MAX_NESTING`].
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Parser) -> Result<T>) -> Result<T> {
+        self.chain(|this| {
+            this.deepen()?;
+            parse(this)
+        })
+    }
+
+    /// Parses a left-associative chain like `a + b + c` or `a.b()`, which is parsed in a loop but
+    /// nests one level deeper with each operation, and so calls [`Parser::deepen`] for each.
+    fn chain<T>(&mut self, parse: impl FnOnce(&mut Parser) -> Result<T>) -> Result<T> {
+        let depth = self.depth;
+        let result = parse(self);
+        self.depth = depth;
+        result
+    }
+
+    /// Goes one level deeper, up to [`Parser::MAX_NESTING`].
+    fn deepen(&mut self) -> Result<()> {
+        if self.depth == Self::MAX_NESTING {
+            let span = match self.tokens.get(self.cursor) {
+                Some(token) => token.span.clone(),
+                None => Span::new(self.end, self.end),
+            };
+            return error(ParserError::new(NestingTooDeep, span));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Eats an `await` keyword or an `async` modifier if there is one, which needs an async function.
+    fn eat_async_keyword(&mut self, keyword: Keyword) -> Result<bool> {
+        if !self.is_keyword(keyword) {
+            return Ok(false);
+        }
+
+        if !self.function.is_some_and(|function| function.is_async) {
+            let span = self.tokens[self.cursor].span.clone();
+            return error(ParserError::new(AwaitOutsideAsyncFunction, span));
+        }
+
+        self.eat();
+        Ok(true)
+    }
+
+    // Checks if the next tokens start a line of enum variants, like `Some(t: T)` or `None`.
+    fn is_variant(&self) -> bool {
+        matches!(self.peek(), Some(TokenKind::Identifier(_)))
+            && matches!(
+                self.peek_nth(1),
+                None | Some(TokenKind::Newline)
+                    | Some(TokenKind::Dedent)
+                    | Some(TokenKind::Delim(Delimiter::LParen | Delimiter::Comma))
+            )
+    }
+
+    // Skips newlines if they are followed by the clause keyword, like `else` after a one-line block.
+    fn peek_clause(&mut self, keyword: Keyword) -> bool {
+        let mut n = 0;
+        while self.peek_nth(n) == Some(&TokenKind::Newline) {
+            n += 1;
+        }
+
+        if self.peek_nth(n) != Some(&TokenKind::Keyword(keyword)) {
+            return false;
+        }
+
+        for _ in 0..n {
+            self.eat();
+        }
+
+        true
+    }
+}
+
+/// Statement parsing functions.
+impl Parser {
+    /// Parses statements until the end of the code or of the current block.
+    fn parse_statements(&mut self) -> Result<Vec<Statement>> {
+        let mut statements = vec![];
+        loop {
+            match self.peek() {
+                Some(TokenKind::Newline) => {
+                    self.eat();
+                }
+                None | Some(TokenKind::Dedent) => break,
+                Some(TokenKind::Indent) => return self.unexpected(),
+                _ => statements.extend(self.parse_statement()?),
+            }
+        }
+
+        Ok(statements)
+    }
+
+    /// Parses a compound statement or a line of small statements.
+    fn parse_statement(&mut self) -> Result<Vec<Statement>> {
+        let statement = match self.peek() {
+            Some(TokenKind::Keyword(Keyword::If)) => self.parse_if()?,
+            Some(TokenKind::Keyword(Keyword::While)) => self.parse_while()?,
+            Some(TokenKind::Keyword(Keyword::For)) => self.parse_for()?,
+            Some(TokenKind::Keyword(Keyword::Try)) => self.parse_try()?,
+            Some(TokenKind::Keyword(Keyword::With)) => self.parse_with()?,
+            Some(TokenKind::Keyword(Keyword::Async)) => match self.peek_nth(1) {
+                Some(TokenKind::Keyword(Keyword::For)) => self.parse_for()?,
+                Some(TokenKind::Keyword(Keyword::With)) => self.parse_with()?,
+                _ => {
+                    let start = self.start();
+                    self.parse_definition(vec![], vec![], start)?
+                }
+            },
+            Some(TokenKind::Keyword(Keyword::Match)) => self.parse_match()?,
+            Some(TokenKind::Keyword(Keyword::Macro)) => self.parse_macro_def()?,
+            Some(TokenKind::Delim(Delimiter::At)) => self.parse_decorated()?,
+            _ if self.is_definition() => {
+                let start = self.start();
+                self.parse_definition(vec![], vec![], start)?
+            }
+            _ => return self.parse_simple_statement(),
+        };
+
+        Ok(vec![statement])
+    }
+
+    /// Parses `;` separated small statements on a line.
+    fn parse_simple_statement(&mut self) -> Result<Vec<Statement>> {
+        let mut statements = vec![self.parse_small_statement()?];
+        while self.eat_delim(Delimiter::SemiColon) {
+            if self.is_statement_end() {
+                break;
+            }
+
+            statements.push(self.parse_small_statement()?);
+        }
+
+        match self.peek() {
+            Some(TokenKind::Newline) => {
+                self.eat();
+            }
+            None | Some(TokenKind::Dedent) => (),
+            _ if self.prev_is_dedent() => (),
+            _ => return self.unexpected(),
+        }
+
+        Ok(statements)
+    }
+
+    fn parse_small_statement(&mut self) -> Result<Statement> {
+        let start = self.start();
+        let kind = match self.peek() {
+            Some(TokenKind::Keyword(Keyword::Pass)) => {
+                self.eat();
+                StatementKind::Pass
+            }
+            Some(TokenKind::Keyword(Keyword::Break)) => {
+                self.eat();
+                StatementKind::Break
+            }
+            Some(TokenKind::Keyword(Keyword::Continue)) => {
+                self.eat();
+                StatementKind::Continue
+            }
+            Some(TokenKind::Keyword(Keyword::Return)) => {
+                self.eat();
+                match self.is_statement_end() {
+                    true => StatementKind::Return(None),
+                    false => StatementKind::Return(Some(self.parse_exprs()?)),
+                }
+            }
+            Some(TokenKind::Keyword(Keyword::Raise)) => {
+                self.eat();
+                let mut raise = Raise {
+                    exception: None,
+                    cause: None,
+                };
+
+                if !self.is_statement_end() {
+                    raise.exception = Some(self.parse_expr()?);
+                    if self.eat_keyword(Keyword::From) {
+                        raise.cause = Some(self.parse_expr()?);
+                    }
+                }
+
+                StatementKind::Raise(raise)
+            }
+            Some(TokenKind::Keyword(Keyword::Global)) => {
+                self.eat();
+                StatementKind::Global(self.parse_names()?)
+            }
+            Some(TokenKind::Keyword(Keyword::Nonlocal)) => {
+                self.eat();
+                StatementKind::Nonlocal(self.parse_names()?)
+            }
+            Some(TokenKind::Keyword(Keyword::Assert)) => {
+                self.eat();
+                let test = self.parse_expr()?;
+                let message = match self.eat_delim(Delimiter::Comma) {
+                    true => Some(self.parse_expr()?),
+                    false => None,
+                };
+
+                StatementKind::Assert(Assert { test, message })
+            }
+            Some(TokenKind::Keyword(Keyword::Import)) => {
+                self.eat();
+                let path = self.parse_import_path()?;
+                let alias = match self.eat_keyword(Keyword::As) {
+                    true => Some(self.expect_identifier()?),
+                    false => None,
+                };
+
+                StatementKind::Import(Import { path, alias })
+            }
+            Some(TokenKind::Keyword(Keyword::From)) => {
+                self.eat();
+                let path = self.parse_import_path()?;
+                self.expect_keyword(Keyword::Import)?;
+                let names = self.parse_import_names()?;
+
+                StatementKind::ImportFrom(ImportFrom { path, names })
+            }
+            Some(TokenKind::Keyword(Keyword::Let | Keyword::Var | Keyword::Const)) => {
+                self.parse_declaration()?
+            }
+            Some(TokenKind::Keyword(Keyword::Typealias)) => {
+                self.eat();
+                let name = self.expect_identifier()?;
+                let generics = self.parse_generics()?;
+                self.expect_delim(Delimiter::Assign)?;
+                let value = self.parse_type()?;
+
+                StatementKind::TypeAlias(TypeAlias {
+                    name,
+                    generics,
+                    value,
+                })
+            }
+            _ => self.parse_expr_statement()?,
+        };
+
+        Ok(Statement {
+            kind,
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses `let name: annotation = value`, where a `const` needs a value.
+    fn parse_declaration(&mut self) -> Result<StatementKind> {
+        let kind = match self.eat().map(|token| token.kind) {
+            Some(TokenKind::Keyword(Keyword::Let)) => DeclarationKind::Let,
+            Some(TokenKind::Keyword(Keyword::Var)) => DeclarationKind::Var,
+            Some(TokenKind::Keyword(Keyword::Const)) => DeclarationKind::Const,
+            _ => unreachable!(),
+        };
+
+        let name = self.expect_identifier()?;
+        let annotation = match self.eat_delim(Delimiter::Colon) {
+            true => Some(self.parse_type()?),
+            false => None,
+        };
+
+        if kind == DeclarationKind::Const && !self.is_delim(Delimiter::Assign) {
+            return self.unexpected();
+        }
+
+        let value = match self.eat_delim(Delimiter::Assign) {
+            true => Some(self.parse_value()?),
+            false => None,
+        };
+
+        Ok(StatementKind::Declaration(Declaration {
+            kind,
+            name,
+            annotation,
+            value,
+        }))
+    }
+
+    /// Parses expression statements and assignments.
+    fn parse_expr_statement(&mut self) -> Result<StatementKind> {
+        let first = self.parse_value()?;
+
+        if self.is_delim(Delimiter::Assign) {
+            let mut targets = vec![first];
+            while self.eat_delim(Delimiter::Assign) {
+                targets.push(self.parse_value()?);
+            }
+
+            let value = targets.pop().unwrap();
+            let targets = targets
+                .into_iter()
+                .map(target_pattern)
+                .collect::<Result<_>>()?;
+
+            return Ok(StatementKind::Assign(Assign { targets, value }));
+        }
+
+        if let Some(op) = self.peek().and_then(aug_assign_op) {
+            check_target(&first)?;
+            self.eat();
+            let value = self.parse_value()?;
+
+            return Ok(StatementKind::AugAssign(AugAssign {
+                target: first,
+                op,
+                value,
+            }));
+        }
+
+        if self.is_delim(Delimiter::Colon) {
+            check_target(&first)?;
+            self.eat();
+            let annotation = self.parse_type()?;
+            let value = match self.eat_delim(Delimiter::Assign) {
+                true => Some(self.parse_value()?),
+                false => None,
+            };
+
+            return Ok(StatementKind::AnnAssign(AnnAssign {
+                target: first,
+                annotation,
+                value,
+            }));
+        }
+
+        if matches!(first.kind, ExprKind::Rest(_) | ExprKind::DictRest(_)) {
+            return error(ParserError::new(InvalidRestExpression, first.span));
+        }
+
+        Ok(StatementKind::Expr(first))
+    }
+
+    /// Parses a statement value, which can also be a `yield`.
+    fn parse_value(&mut self) -> Result<Expr> {
+        match self.is_keyword(Keyword::Yield) {
+            true => self.parse_yield(),
+            false => self.parse_rest_exprs(),
+        }
+    }
+
+    /// Parses `yield`, `yield values` or `yield from value`, which makes the innermost function a generator.
+    fn parse_yield(&mut self) -> Result<Expr> {
+        let start = self.start();
+        self.expect_keyword(Keyword::Yield)?;
+
+        match self.function.as_mut() {
+            Some(function) => function.is_generator = true,
+            None => {
+                return error(ParserError::new(
+                    YieldOutsideFunction,
+                    self.span_from(start),
+                ))
+            }
+        }
+
+        let kind = if self.eat_keyword(Keyword::From) {
+            ExprKind::YieldFrom(Box::new(self.parse_expr()?))
+        } else if self.can_start_expr() {
+            ExprKind::Yield(Some(Box::new(self.parse_rest_exprs()?)))
+        } else {
+            ExprKind::Yield(None)
+        };
+
+        Ok(Expr {
+            kind,
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses an indented block or a simple statement after a `:`.
+    fn parse_block(&mut self) -> Result<Vec<Statement>> {
+        self.expect_delim(Delimiter::Colon)?;
+
+        match self.peek() {
+            Some(TokenKind::Indent) => {
+                self.eat();
+                let statements = self.nested(Parser::parse_statements)?;
+
+                // The code can end without closing the block.
+                self.eat_if(&TokenKind::Dedent);
+
+                Ok(statements)
+            }
+            None | Some(TokenKind::Newline) | Some(TokenKind::Dedent) => {
+                let span = Span::new(self.prev_end, self.start());
+                error(ParserError::new(ExpectedIndentedBlock, span))
+            }
+            _ => self.parse_simple_statement(),
+        }
+    }
+
+    fn parse_else(&mut self) -> Result<Option<Vec<Statement>>> {
+        match self.peek_clause(Keyword::Else) {
+            true => {
+                self.eat();
+                Ok(Some(self.parse_block()?))
+            }
+            false => Ok(None),
+        }
+    }
+
+    /// Parses `if guard` of a `for` or `while` statement.
+    fn parse_guard(&mut self) -> Result<Option<Expr>> {
+        match self.eat_keyword(Keyword::If) {
+            true => Ok(Some(
+                self.parse_named_expr_or(Parser::parse_test, Parser::parse_expr)?,
+            )),
+            false => Ok(None),
+        }
+    }
+
+    fn parse_if(&mut self) -> Result<Statement> {
+        let start = self.start();
+        self.expect_keyword(Keyword::If)?;
+        let condition = self.parse_named_expr_or(Parser::parse_test, Parser::parse_test)?;
+        let body = self.parse_block()?;
+
+        let mut elifs = vec![];
+        while self.peek_clause(Keyword::Elif) {
+            self.eat();
+            let condition = self.parse_named_expr_or(Parser::parse_test, Parser::parse_test)?;
+            let body = self.parse_block()?;
+            elifs.push(Elif { condition, body });
+        }
+
+        let orelse = self.parse_else()?;
+
+        Ok(Statement {
+            kind: StatementKind::If(If {
+                condition,
+                body,
+                elifs,
+                orelse,
+            }),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_while(&mut self) -> Result<Statement> {
+        let start = self.start();
+        self.expect_keyword(Keyword::While)?;
+
+        // The condition is an `or_test` so that it does not take the guard as a ternary.
+        let condition = self.parse_named_expr_or(Parser::parse_or_test, Parser::parse_or_test)?;
+        let guard = self.parse_guard()?;
+        let body = self.parse_block()?;
+        let orelse = self.parse_else()?;
+
+        Ok(Statement {
+            kind: StatementKind::While(While {
+                condition,
+                guard,
+                body,
+                orelse,
+            }),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_for(&mut self) -> Result<Statement> {
+        let start = self.start();
+        let is_async = self.eat_async_keyword(Keyword::Async)?;
+        self.expect_keyword(Keyword::For)?;
+        let target = self.parse_for_target()?;
+        self.expect_keyword(Keyword::In)?;
+
+        // The elements are `or_test`s so that they do not take the guard as a ternary.
+        let iter = self.parse_expr_list(Parser::parse_or_test)?;
+        let guard = self.parse_guard()?;
+        let body = self.parse_block()?;
+        let orelse = self.parse_else()?;
+
+        Ok(Statement {
+            kind: StatementKind::For(For {
+                is_async,
+                target,
+                iter,
+                guard,
+                body,
+                orelse,
+            }),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses the targets of a `for` statement or clause, which stop before `in`.
+    fn parse_for_target(&mut self) -> Result<Pattern> {
+        let target = self.parse_expr_list(Parser::parse_star_target)?;
+        target_pattern(target)
+    }
+
+    /// Parses `*target` or a target without comparisons, so that it does not take the `in`.
+    fn parse_star_target(&mut self) -> Result<Expr> {
+        let start = self.start();
+        if !self.eat_op(Operator::Mul) {
+            return self.parse_or_expr();
+        }
+
+        let value = self.parse_or_expr()?;
+
+        Ok(Expr {
+            kind: ExprKind::Rest(Box::new(value)),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_try(&mut self) -> Result<Statement> {
+        let start = self.start();
+        self.expect_keyword(Keyword::Try)?;
+        let body = self.parse_block()?;
+
+        let mut handlers = vec![];
+        while self.peek_clause(Keyword::Except) {
+            self.eat();
+            let exception = self.expect_identifier()?;
+            let name = match self.eat_keyword(Keyword::As) {
+                true => Some(self.expect_identifier()?),
+                false => None,
+            };
+            let body = self.parse_block()?;
+
+            handlers.push(ExceptHandler {
+                exception,
+                name,
+                body,
+            });
+        }
+
+        let orelse = match handlers.is_empty() {
+            true => None,
+            false => self.parse_else()?,
+        };
+
+        let finalbody = match self.peek_clause(Keyword::Finally) {
+            true => {
+                self.eat();
+                Some(self.parse_block()?)
+            }
+            false => None,
+        };
+
+        if handlers.is_empty() && finalbody.is_none() {
+            return self.unexpected();
+        }
+
+        Ok(Statement {
+            kind: StatementKind::Try(Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            }),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_with(&mut self) -> Result<Statement> {
+        let start = self.start();
+        let is_async = self.eat_async_keyword(Keyword::Async)?;
+        self.expect_keyword(Keyword::With)?;
+
+        let mut items = vec![];
+        loop {
+            let context = self.parse_expr()?;
+            let alias = match self.eat_keyword(Keyword::As) {
+                true => Some(self.expect_identifier()?),
+                false => None,
+            };
+
+            items.push(WithItem { context, alias });
+
+            if !self.eat_delim(Delimiter::Comma) || self.is_delim(Delimiter::Colon) {
+                break;
+            }
+        }
+
+        let body = self.parse_block()?;
+
+        Ok(Statement {
+            kind: StatementKind::With(With {
+                is_async,
+                items,
+                body,
+            }),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_match(&mut self) -> Result<Statement> {
+        let start = self.start();
+        let subject = self.parse_match_subject()?;
+        let cases = self.parse_cases(Parser::parse_match_case)?;
+
+        Ok(Statement {
+            kind: StatementKind::Match(Match { subject, cases }),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses `match subject:` up to the indent of its cases.
+    fn parse_match_subject(&mut self) -> Result<Expr> {
+        self.expect_keyword(Keyword::Match)?;
+        let subject = self.parse_named_expr_or(Parser::parse_test, Parser::parse_test)?;
+        self.expect_delim(Delimiter::Colon)?;
+
+        match self.eat_if(&TokenKind::Indent) {
+            true => Ok(subject),
+            false => {
+                let span = Span::new(self.prev_end, self.start());
+                error(ParserError::new(ExpectedIndentedBlock, span))
+            }
+        }
+    }
+
+    /// Parses the cases of a `match` up to the dedent that closes them.
+    fn parse_cases<T>(&mut self, parse_case: fn(&mut Parser) -> Result<T>) -> Result<Vec<T>> {
+        let mut cases = vec![];
+        loop {
+            match self.peek() {
+                Some(TokenKind::Newline) => {
+                    self.eat();
+                }
+                None | Some(TokenKind::Dedent) if !cases.is_empty() => break,
+                _ => cases.push(parse_case(self)?),
+            }
+        }
+
+        // The code can end without closing the block.
+        self.eat_if(&TokenKind::Dedent);
+
+        Ok(cases)
+    }
+
+    fn parse_match_case(&mut self) -> Result<MatchCase> {
+        let (pattern, guard) = self.parse_case_head()?;
+        let body = self.parse_block()?;
+
+        Ok(MatchCase {
+            pattern,
+            guard,
+            body,
+        })
+    }
+
+    /// Parses `case pattern if guard` up to the `:`.
+    fn parse_case_head(&mut self) -> Result<(Pattern, Option<Expr>)> {
+        self.expect_soft_keyword("case")?;
+        let pattern = self.parse_case_pattern()?;
+        let guard = self.parse_guard()?;
+
+        Ok((pattern, guard))
+    }
+
+    /// Parses `macro name(params):` and its block.
+    fn parse_macro_def(&mut self) -> Result<Statement> {
+        let start = self.start();
+        self.expect_keyword(Keyword::Macro)?;
+        let name = self.expect_identifier()?;
+
+        self.expect_delim(Delimiter::LParen)?;
+        let mut params = vec![];
+        while !self.is_delim(Delimiter::RParen) {
+            params.push(self.expect_identifier()?);
+            if !self.eat_delim(Delimiter::Comma) {
+                break;
+            }
+        }
+
+        self.expect_delim(Delimiter::RParen)?;
+        let body = self.parse_block()?;
+
+        Ok(Statement {
+            kind: StatementKind::MacroDef(Box::new(MacroDef { name, params, body })),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses decorators and the definition they decorate.
+    ///
+    /// The `@where(T: Seq)` clauses are collected as the generic bounds of the definition.
+    fn parse_decorated(&mut self) -> Result<Statement> {
+        let start = self.start();
+        let mut decorators = vec![];
+        let mut bounds = vec![];
+
+        while self.is_delim(Delimiter::At) {
+            let decorator_start = self.start();
+            self.eat();
+
+            if self.eat_keyword(Keyword::Where) {
+                bounds.extend(self.parse_bounds()?);
+            } else {
+                let path = self.parse_path()?;
+                let arguments = match self.eat_delim(Delimiter::LParen) {
+                    true => {
+                        let arguments = self.parse_arguments()?;
+                        self.expect_delim(Delimiter::RParen)?;
+                        Some(arguments)
+                    }
+                    false => None,
+                };
+
+                decorators.push(Decorator {
+                    path,
+                    arguments,
+                    span: self.span_from(decorator_start),
+                });
+            }
+
+            if !self.eat_if(&TokenKind::Newline) {
+                return self.unexpected();
+            }
+
+            while self.eat_if(&TokenKind::Newline) {}
+        }
+
+        match self.is_definition() {
+            true => self.parse_definition(decorators, bounds, start),
+            false => self.unexpected(),
+        }
+    }
+
+    /// Parses `(T: Seq, U: Seq)` after `@where`.
+    fn parse_bounds(&mut self) -> Result<Vec<GenericBound>> {
+        self.expect_delim(Delimiter::LParen)?;
+
+        let mut bounds = vec![];
+        loop {
+            let name = self.expect_identifier()?;
+            self.expect_delim(Delimiter::Colon)?;
+            let bound = self.parse_type()?;
+            bounds.push(GenericBound { name, bound });
+
+            if !self.eat_delim(Delimiter::Comma) || self.is_delim(Delimiter::RParen) {
+                break;
+            }
+        }
+
+        self.expect_delim(Delimiter::RParen)?;
+
+        Ok(bounds)
+    }
+
+    /// Parses a function, class or interface definition with its modifiers, like `abstract async def` or
+    /// `data class`.
+    fn parse_definition(
+        &mut self,
+        decorators: Vec<Decorator>,
+        bounds: Vec<GenericBound>,
+        start: u32,
+    ) -> Result<Statement> {
+        if self.is_keyword(Keyword::Interface) {
+            return self.parse_interface_def(decorators, bounds, start);
+        }
+
+        let kind = match self.peek() {
+            Some(TokenKind::Keyword(Keyword::Enum)) => Some(ClassKind::Enum),
+            Some(TokenKind::Identifier(name)) => class_kind(name),
+            _ => None,
+        };
+
+        if kind.is_some() {
+            self.eat();
+        }
+
+        match (kind, self.peek()) {
+            (
+                None | Some(ClassKind::Abstract),
+                Some(TokenKind::Keyword(Keyword::Def | Keyword::Async)),
+            ) => self.parse_function_def(decorators, bounds, kind.is_some(), start),
+            _ => self.parse_class_def(decorators, bounds, kind.unwrap_or(ClassKind::Class), start),
+        }
+    }
+
+    fn parse_function_def(
+        &mut self,
+        decorators: Vec<Decorator>,
+        bounds: Vec<GenericBound>,
+        is_abstract: bool,
+        start: u32,
+    ) -> Result<Statement> {
+        let is_async = self.eat_keyword(Keyword::Async);
+        self.expect_keyword(Keyword::Def)?;
+        let name = self.expect_identifier()?;
+        let generics = self.parse_generics()?;
+
+        self.expect_delim(Delimiter::LParen)?;
+        let params = self.parse_params(Delimiter::RParen, true)?;
+        self.expect_delim(Delimiter::RParen)?;
+
+        let return_type = match self.eat_delim(Delimiter::Arrow) {
+            true => Some(self.parse_type()?),
+            false => None,
+        };
+
+        let function = FunctionContext {
+            is_async,
+            is_generator: false,
+        };
+
+        // A signature without a body, like the methods of an abstract class.
+        let (body, function) = match self.is_delim(Delimiter::Colon) {
+            true => self.parse_in_function(Some(function), Parser::parse_block)?,
+            false => {
+                self.expect_line_end()?;
+                (vec![], Some(function))
+            }
+        };
+
+        Ok(Statement {
+            kind: StatementKind::FunctionDef(Box::new(FunctionDef {
+                decorators,
+                is_abstract,
+                is_async,
+                is_generator: function.is_some_and(|function| function.is_generator),
+                name,
+                generics,
+                bounds,
+                params,
+                return_type,
+                body,
+            })),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_class_def(
+        &mut self,
+        decorators: Vec<Decorator>,
+        bounds: Vec<GenericBound>,
+        kind: ClassKind,
+        start: u32,
+    ) -> Result<Statement> {
+        self.expect_keyword(Keyword::Class)?;
+        let name = self.expect_identifier()?;
+        let generics = self.parse_generics()?;
+
+        let mut bases = vec![];
+        let mut fields = vec![];
+        match kind {
+            ClassKind::Data => fields = self.parse_fields()?,
+            _ => bases = self.parse_bases()?,
+        }
+
+        // A class body starts a new scope outside of any function.
+        let ((variants, body), _) = self.parse_in_function(None, |this| match kind {
+            ClassKind::Enum => this.parse_enum_block(),
+            ClassKind::Data if !this.is_delim(Delimiter::Colon) => {
+                this.expect_line_end()?;
+                Ok((vec![], vec![]))
+            }
+            _ => Ok((vec![], this.parse_block()?)),
+        })?;
+
+        Ok(Statement {
+            kind: StatementKind::ClassDef(Box::new(ClassDef {
+                decorators,
+                kind,
+                name,
+                generics,
+                bounds,
+                bases,
+                fields,
+                variants,
+                body,
+            })),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses an interface whose body only declares method signatures and fields.
+    fn parse_interface_def(
+        &mut self,
+        decorators: Vec<Decorator>,
+        bounds: Vec<GenericBound>,
+        start: u32,
+    ) -> Result<Statement> {
+        self.expect_keyword(Keyword::Interface)?;
+        let name = self.expect_identifier()?;
+        let generics = self.parse_generics()?;
+        let bases = self.parse_bases()?;
+
+        let (body, _) = self.parse_in_function(None, Parser::parse_block)?;
+        body.iter().try_for_each(check_interface_member)?;
+
+        Ok(Statement {
+            kind: StatementKind::InterfaceDef(Box::new(InterfaceDef {
+                decorators,
+                name,
+                generics,
+                bounds,
+                bases,
+                body,
+            })),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses the optional `(A, B)` bases of a class or interface.
+    fn parse_bases(&mut self) -> Result<Vec<Identifier>> {
+        let mut bases = vec![];
+        if self.eat_delim(Delimiter::LParen) {
+            while !self.is_delim(Delimiter::RParen) {
+                bases.push(self.expect_identifier()?);
+                if !self.eat_delim(Delimiter::Comma) {
+                    break;
+                }
+            }
+
+            self.expect_delim(Delimiter::RParen)?;
+        }
+
+        Ok(bases)
+    }
+
+    /// Parses the primary constructor fields of a data class, like `(x: int, y = 0)`.
+    fn parse_fields(&mut self) -> Result<Vec<Param>> {
+        self.expect_delim(Delimiter::LParen)?;
+
+        let mut fields = vec![];
+        while !self.is_delim(Delimiter::RParen) {
+            fields.push(self.parse_param(true)?);
+            if !self.eat_delim(Delimiter::Comma) {
+                break;
+            }
+        }
+
+        self.expect_delim(Delimiter::RParen)?;
+
+        Ok(fields)
+    }
+
+    /// Parses the block of an enum class, where lines of variants can appear between statements.
+    fn parse_enum_block(&mut self) -> Result<(Vec<Variant>, Vec<Statement>)> {
+        self.expect_delim(Delimiter::Colon)?;
+
+        let mut variants = vec![];
+        let mut body = vec![];
+        match self.peek() {
+            Some(TokenKind::Indent) => {
+                self.eat();
+                loop {
+                    match self.peek() {
+                        Some(TokenKind::Newline) => {
+                            self.eat();
+                        }
+                        None | Some(TokenKind::Dedent) => break,
+                        Some(TokenKind::Indent) => return self.unexpected(),
+                        _ if self.is_variant() => variants.extend(self.parse_variants()?),
+                        _ => body.extend(self.parse_statement()?),
+                    }
+                }
+
+                // The code can end without closing the block.
+                self.eat_if(&TokenKind::Dedent);
+            }
+            None | Some(TokenKind::Newline) | Some(TokenKind::Dedent) => {
+                let span = Span::new(self.prev_end, self.start());
+                return error(ParserError::new(ExpectedIndentedBlock, span));
+            }
+            _ if self.is_variant() => variants = self.parse_variants()?,
+            _ => body = self.parse_simple_statement()?,
+        }
+
+        Ok((variants, body))
+    }
+
+    /// Parses a line of `,` separated enum variants.
+    fn parse_variants(&mut self) -> Result<Vec<Variant>> {
+        let mut variants = vec![];
+        loop {
+            variants.push(self.parse_variant()?);
+            if !self.eat_delim(Delimiter::Comma) || self.is_line_end() {
+                break;
+            }
+        }
+
+        self.expect_line_end()?;
+
+        Ok(variants)
+    }
+
+    fn parse_variant(&mut self) -> Result<Variant> {
+        let start = self.start();
+        let name = self.expect_identifier()?;
+
+        let fields = match self.eat_delim(Delimiter::LParen) {
+            true => {
+                let mut fields = vec![];
+                while !self.is_delim(Delimiter::RParen) {
+                    fields.push(self.parse_variant_field()?);
+                    if !self.eat_delim(Delimiter::Comma) {
+                        break;
+                    }
+                }
+
+                self.expect_delim(Delimiter::RParen)?;
+                Some(fields)
+            }
+            false => None,
+        };
+
+        Ok(Variant {
+            name,
+            fields,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_variant_field(&mut self) -> Result<VariantField> {
+        if !matches!(self.peek(), Some(TokenKind::Identifier(_)))
+            || self.peek_nth(1) != Some(&TokenKind::Delim(Delimiter::Colon))
+        {
+            return Ok(VariantField::Positional(self.parse_type()?));
+        }
+
+        let name = self.expect_identifier()?;
+        self.eat();
+        let annotation = self.parse_type()?;
+
+        Ok(VariantField::Named { name, annotation })
+    }
+
+    /// Parses `[T, U]` after the name of a function or class.
+    fn parse_generics(&mut self) -> Result<Vec<Identifier>> {
+        let mut generics = vec![];
+        if self.eat_delim(Delimiter::LBracket) {
+            loop {
+                generics.push(self.expect_identifier()?);
+                if !self.eat_delim(Delimiter::Comma) || self.is_delim(Delimiter::RBracket) {
+                    break;
+                }
+            }
+
+            self.expect_delim(Delimiter::RBracket)?;
+        }
+
+        Ok(generics)
+    }
+
+    /// Parses the parameters of a function or lambda up to the `closing` delimiter.
+    fn parse_params(&mut self, closing: Delimiter, annotated: bool) -> Result<Params> {
+        let mut params = Params::default();
+        let mut has_slash = false;
+
+        while !self.is_delim(closing.clone()) {
+            if self.eat_op(Operator::Div) {
+                if has_slash || params.vararg.is_some() || params.params.is_empty() {
+                    return error(ParserError::new(InvalidParameterOrder, self.prev_span()));
+                }
+
+                params.positional_only = std::mem::take(&mut params.params);
+                has_slash = true;
+            } else if self.eat_op(Operator::Mul) {
+                if params.vararg.is_some() {
+                    return error(ParserError::new(InvalidParameterOrder, self.prev_span()));
+                }
+
+                params.vararg = Some(self.parse_param(annotated)?);
+            } else if self.eat_op(Operator::Pow) {
+                params.kwarg = Some(self.parse_param(annotated)?);
+                self.eat_delim(Delimiter::Comma);
+                break;
+            } else {
+                let param = self.parse_param(annotated)?;
+                match params.vararg {
+                    Some(_) => params.keyword_only.push(param),
+                    None => params.params.push(param),
+                }
+            }
+
+            if !self.eat_delim(Delimiter::Comma) {
+                break;
+            }
+        }
+
+        Ok(params)
+    }
+
+    fn parse_param(&mut self, annotated: bool) -> Result<Param> {
+        let start = self.start();
+        let mode = match self.peek() {
+            Some(TokenKind::Keyword(Keyword::Ref)) => Some(PassingMode::Ref),
+            Some(TokenKind::Keyword(Keyword::Val)) => Some(PassingMode::Val),
+            Some(TokenKind::Keyword(Keyword::Ptr)) => Some(PassingMode::Ptr),
+            _ => None,
+        };
+
+        if mode.is_some() {
+            self.eat();
+        }
+
+        let is_mut = self.eat_keyword(Keyword::Mut);
+        let name = self.expect_identifier()?;
+
+        let annotation = match annotated && self.eat_delim(Delimiter::Colon) {
+            true => Some(self.parse_type()?),
+            false => None,
+        };
+
+        let default = match self.eat_delim(Delimiter::Assign) {
+            true => Some(self.parse_expr()?),
+            false => None,
+        };
+
+        Ok(Param {
+            mode,
+            is_mut,
+            name,
+            annotation,
+            default,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_import_path(&mut self) -> Result<ImportPath> {
+        let mut level = 0;
+        while self.eat_delim(Delimiter::Dot) {
+            level += 1;
+        }
+
+        let mut names = vec![];
+        if level == 0 || matches!(self.peek(), Some(TokenKind::Identifier(_))) {
+            names = self.parse_path()?.names;
+        }
+
+        Ok(ImportPath { level, names })
+    }
+
+    fn parse_import_names(&mut self) -> Result<ImportNames> {
+        if self.eat_op(Operator::Mul) {
+            return Ok(ImportNames::All);
+        }
+
+        let parenthesized = self.eat_delim(Delimiter::LParen);
+        let mut names = vec![];
+        loop {
+            let name = self.expect_identifier()?;
+            let alias = match self.eat_keyword(Keyword::As) {
+                true => Some(self.expect_identifier()?),
+                false => None,
+            };
+
+            names.push(ImportName { name, alias });
+
+            if !self.eat_delim(Delimiter::Comma)
+                || !matches!(self.peek(), Some(TokenKind::Identifier(_)))
+            {
+                break;
+            }
+        }
+
+        if parenthesized {
+            self.expect_delim(Delimiter::RParen)?;
+        }
+
+        Ok(ImportNames::Names(names))
+    }
+
+    /// Parses `a.b.c`.
+    fn parse_path(&mut self) -> Result<Path> {
+        let mut names = vec![self.expect_identifier()?];
+        while self.eat_delim(Delimiter::Dot) {
+            names.push(self.expect_identifier()?);
+        }
+
+        Ok(Path { names })
+    }
+
+    /// Parses `a, b, c` of a `global` or `nonlocal` statement.
+    fn parse_names(&mut self) -> Result<Vec<Identifier>> {
+        let mut names = vec![self.expect_identifier()?];
+        while self.eat_delim(Delimiter::Comma) {
+            names.push(self.expect_identifier()?);
+        }
+
+        Ok(names)
+    }
+}
+
+/// Expression parsing functions.
+impl Parser {
+    /// Parses comma separated elements, which make a tuple unless there is only one without a trailing comma.
+    fn parse_expr_list(&mut self, parse_element: ParseFn) -> Result<Expr> {
+        let start = self.start();
+        let first = parse_element(self)?;
+        if !self.is_delim(Delimiter::Comma) {
+            return Ok(first);
+        }
+
+        let mut elements = vec![first];
+        while self.eat_delim(Delimiter::Comma) && self.can_start_expr() {
+            elements.push(parse_element(self)?);
+        }
+
+        Ok(Expr {
+            kind: ExprKind::Tuple(elements),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_exprs(&mut self) -> Result<Expr> {
+        self.parse_expr_list(Parser::parse_expr)
+    }
+
+    fn parse_rest_exprs(&mut self) -> Result<Expr> {
+        self.parse_expr_list(Parser::parse_rest_expr)
+    }
+
+    /// Parses `name := value` or falls back to `parse`.
+    fn parse_named_expr_or(&mut self, parse_value: ParseFn, parse: ParseFn) -> Result<Expr> {
+        if !matches!(self.peek(), Some(TokenKind::Identifier(_)))
+            || self.peek_nth(1) != Some(&TokenKind::Delim(Delimiter::Walrus))
+        {
+            return parse(self);
+        }
+
+        let start = self.start();
+        let name = self.expect_identifier()?;
+        self.eat();
+        let value = parse_value(self)?;
+
+        Ok(Expr {
+            kind: ExprKind::NamedExpr {
+                name,
+                value: Box::new(value),
+            },
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses an element of a tuple, list or set.
+    fn parse_element(&mut self) -> Result<Expr> {
+        self.parse_named_expr_or(Parser::parse_test, Parser::parse_rest_expr)
+    }
+
+    /// Parses `*value`, `**value` or an expression.
+    fn parse_rest_expr(&mut self) -> Result<Expr> {
+        let start = self.start();
+        let rest = match self.peek() {
+            Some(TokenKind::Op(Operator::Mul)) => ExprKind::Rest,
+            Some(TokenKind::Op(Operator::Pow)) => ExprKind::DictRest,
+            _ => return self.parse_expr(),
+        };
+
+        self.eat();
+        let value = self.parse_expr()?;
+
+        Ok(Expr {
+            kind: rest(Box::new(value)),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some(TokenKind::Keyword(Keyword::Lambda)) => self.nested(Parser::parse_lambda),
+            Some(TokenKind::Keyword(Keyword::Match)) => self.nested(Parser::parse_match_expr),
+            _ => self.parse_test(),
+        }
+    }
+
+    fn parse_match_expr(&mut self) -> Result<Expr> {
+        let start = self.start();
+        let subject = self.parse_match_subject()?;
+        let cases = self.parse_cases(Parser::parse_match_expr_case)?;
+
+        Ok(Expr {
+            kind: ExprKind::Match(Box::new(MatchExpr { subject, cases })),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_match_expr_case(&mut self) -> Result<MatchExprCase> {
+        let (pattern, guard) = self.parse_case_head()?;
+        self.expect_delim(Delimiter::Colon)?;
+        let body = self.parse_expr()?;
+
+        Ok(MatchExprCase {
+            pattern,
+            guard,
+            body,
+        })
+    }
+
+    fn parse_lambda(&mut self) -> Result<Expr> {
+        let start = self.start();
+        self.expect_keyword(Keyword::Lambda)?;
+
+        let params = match self.eat_delim(Delimiter::LParen) {
+            true => {
+                let params = self.parse_params(Delimiter::RParen, true)?;
+                self.expect_delim(Delimiter::RParen)?;
+                params
+            }
+            false => self.parse_params(Delimiter::Colon, false)?,
+        };
+
+        self.expect_delim(Delimiter::Colon)?;
+
+        let (body, function) =
+            self.parse_in_function(Some(FunctionContext::default()), |this| {
+                Ok(match this.eat_if(&TokenKind::Indent) {
+                    true => {
+                        let statements = this.parse_statements()?;
+                        this.eat_if(&TokenKind::Dedent);
+                        LambdaBody::Block(statements)
+                    }
+                    false => LambdaBody::Expr(Box::new(this.parse_expr()?)),
+                })
+            })?;
+
+        Ok(Expr {
+            kind: ExprKind::Lambda(Box::new(Lambda {
+                params,
+                body,
+                is_generator: function.is_some_and(|function| function.is_generator),
+            })),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses `then if condition else otherwise`.
+    fn parse_test(&mut self) -> Result<Expr> {
+        let start = self.start();
+        let then = self.parse_or_test()?;
+        if !self.eat_keyword(Keyword::If) {
+            return Ok(then);
+        }
+
+        let condition = self.nested(Parser::parse_expr)?;
+        self.expect_keyword(Keyword::Else)?;
+        let otherwise = self.nested(Parser::parse_expr)?;
+
+        Ok(Expr {
+            kind: ExprKind::Ternary {
+                condition: Box::new(condition),
+                then: Box::new(then),
+                otherwise: Box::new(otherwise),
+            },
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses left-associative binary operations whose operators are mapped by `to_op`.
+    fn parse_binary(
+        &mut self,
+        parse_operand: ParseFn,
+        to_op: fn(&TokenKind) -> Option<BinaryOp>,
+    ) -> Result<Expr> {
+        let start = self.start();
+        self.chain(|this| {
+            let mut lhs = parse_operand(this)?;
+
+            while let Some(op) = this.peek().and_then(to_op) {
+                this.deepen()?;
+                this.eat();
+                let rhs = parse_operand(this)?;
+                lhs = Expr {
+                    kind: ExprKind::BinaryOp {
+                        lhs: Box::new(lhs),
+                        op,
+                        rhs: Box::new(rhs),
+                    },
+                    id: NodeId::next(),
+                    span: this.span_from(start),
+                };
+            }
+
+            Ok(lhs)
+        })
+    }
+
+    fn parse_or_test(&mut self) -> Result<Expr> {
+        self.parse_binary(Parser::parse_and_test, |kind| match kind {
+            TokenKind::Keyword(Keyword::Or) => Some(BinaryOp::Or),
+            _ => None,
+        })
+    }
+
+    fn parse_and_test(&mut self) -> Result<Expr> {
+        self.parse_binary(Parser::parse_not_test, |kind| match kind {
+            TokenKind::Keyword(Keyword::And) => Some(BinaryOp::And),
+            _ => None,
+        })
+    }
+
+    fn parse_not_test(&mut self) -> Result<Expr> {
+        if !self.is_keyword(Keyword::Not) {
+            return self.parse_comparison();
+        }
+
+        let start = self.start();
+        self.eat();
+        let operand = self.nested(Parser::parse_not_test)?;
+
+        Ok(Expr {
+            kind: ExprKind::UnaryOp {
+                op: UnaryOp::Not,
+                operand: Box::new(operand),
+            },
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr> {
+        let start = self.start();
+        let lhs = self.parse_or_expr()?;
+
+        let mut comparisons = vec![];
+        while let Some(op) = self.eat_comparison_op() {
+            let rhs = self.parse_or_expr()?;
+            comparisons.push(Comparison { op, rhs });
+        }
+
+        if comparisons.is_empty() {
+            return Ok(lhs);
+        }
+
+        Ok(Expr {
+            kind: ExprKind::Comparison {
+                lhs: Box::new(lhs),
+                comparisons,
+            },
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    fn eat_comparison_op(&mut self) -> Option<ComparisonOp> {
+        let op = match self.peek()? {
+            TokenKind::Op(Operator::Less) => ComparisonOp::Less,
+            TokenKind::Op(Operator::Greater) => ComparisonOp::Greater,
+            TokenKind::Op(Operator::Eq) => ComparisonOp::Eq,
+            TokenKind::Op(Operator::GreaterEq) => ComparisonOp::GreaterEq,
+            TokenKind::Op(Operator::LessEq) => ComparisonOp::LessEq,
+            TokenKind::Op(Operator::NotEq) => ComparisonOp::NotEq,
+            TokenKind::Keyword(Keyword::In) => ComparisonOp::In,
+            TokenKind::Keyword(Keyword::Not)
+                if self.peek_nth(1) == Some(&TokenKind::Keyword(Keyword::In)) =>
+            {
+                self.eat();
+                ComparisonOp::NotIn
+            }
+            TokenKind::Keyword(Keyword::Is)
+                if self.peek_nth(1) == Some(&TokenKind::Keyword(Keyword::Not)) =>
+            {
+                self.eat();
+                ComparisonOp::IsNot
+            }
+            TokenKind::Keyword(Keyword::Is) => ComparisonOp::Is,
+            _ => return None,
+        };
+
+        self.eat();
+
+        Some(op)
+    }
+
+    fn parse_or_expr(&mut self) -> Result<Expr> {
+        self.parse_binary(Parser::parse_xor_expr, |kind| match kind {
+            TokenKind::Op(Operator::BitOr) => Some(BinaryOp::BitOr),
+            _ => None,
+        })
+    }
+
+    fn parse_xor_expr(&mut self) -> Result<Expr> {
+        self.parse_binary(Parser::parse_and_expr, |kind| match kind {
+            TokenKind::Op(Operator::BitXor) => Some(BinaryOp::BitXor),
+            _ => None,
+        })
+    }
+
+    fn parse_and_expr(&mut self) -> Result<Expr> {
+        self.parse_binary(Parser::parse_shift_expr, |kind| match kind {
+            TokenKind::Op(Operator::BitAnd) => Some(BinaryOp::BitAnd),
+            _ => None,
+        })
+    }
+
+    fn parse_shift_expr(&mut self) -> Result<Expr> {
+        self.parse_binary(Parser::parse_sum_expr, |kind| match kind {
+            TokenKind::Op(Operator::ShiftL) => Some(BinaryOp::ShiftL),
+            TokenKind::Op(Operator::ShiftR) => Some(BinaryOp::ShiftR),
+            _ => None,
+        })
+    }
+
+    fn parse_sum_expr(&mut self) -> Result<Expr> {
+        self.parse_binary(Parser::parse_mul_expr, |kind| match kind {
+            TokenKind::Op(Operator::Plus) => Some(BinaryOp::Plus),
+            TokenKind::Op(Operator::Minus) => Some(BinaryOp::Minus),
+            _ => None,
+        })
+    }
+
+    fn parse_mul_expr(&mut self) -> Result<Expr> {
+        self.parse_binary(Parser::parse_unary_expr, |kind| match kind {
+            TokenKind::Op(Operator::Mul) => Some(BinaryOp::Mul),
+            TokenKind::Delim(Delimiter::At) => Some(BinaryOp::MatMul),
+            TokenKind::Op(Operator::Div) => Some(BinaryOp::Div),
+            TokenKind::Op(Operator::IntDiv) => Some(BinaryOp::IntDiv),
+            TokenKind::Op(Operator::Mod) => Some(BinaryOp::Mod),
+            _ => None,
+        })
+    }
+
+    fn parse_unary_expr(&mut self) -> Result<Expr> {
+        let op = match self.peek() {
+            Some(TokenKind::Op(Operator::Plus)) => UnaryOp::Plus,
+            Some(TokenKind::Op(Operator::Minus)) => UnaryOp::Minus,
+            Some(TokenKind::Op(Operator::BitNot)) => UnaryOp::BitNot,
+            Some(TokenKind::Keyword(Keyword::Ref)) => UnaryOp::Ref,
+            Some(TokenKind::Keyword(Keyword::Val)) => UnaryOp::Val,
+            Some(TokenKind::Keyword(Keyword::Ptr)) => UnaryOp::Ptr,
+            _ => return self.parse_power_expr(),
+        };
+
+        let start = self.start();
+        self.eat();
+        let operand = self.nested(Parser::parse_unary_expr)?;
+
+        Ok(Expr {
+            kind: ExprKind::UnaryOp {
+                op,
+                operand: Box::new(operand),
+            },
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses `√value`, `value ** exponent` and `value²`.
+    fn parse_power_expr(&mut self) -> Result<Expr> {
+        let start = self.start();
+        let sqrt = self.eat_op(Operator::Sqrt);
+        let mut value = self.nested(Parser::parse_atom_expr)?;
+
+        if sqrt {
+            value = Expr {
+                kind: ExprKind::UnaryOp {
+                    op: UnaryOp::Sqrt,
+                    operand: Box::new(value),
+                },
+                id: NodeId::next(),
+                span: self.span_from(start),
+            };
+        }
+
+        let kind = if self.eat_op(Operator::Pow) {
+            ExprKind::BinaryOp {
+                lhs: Box::new(value),
+                op: BinaryOp::Pow,
+                rhs: Box::new(self.nested(Parser::parse_unary_expr)?),
+            }
+        } else if self.eat_op(Operator::Square) {
+            ExprKind::UnaryOp {
+                op: UnaryOp::Square,
+                operand: Box::new(value),
+            }
+        } else {
+            return Ok(value);
+        };
+
+        Ok(Expr {
+            kind,
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses an atom followed by calls, subscripts, attribute accesses and `?` propagations.
+    fn parse_atom_expr(&mut self) -> Result<Expr> {
+        let start = self.start();
+        let is_await = self.eat_async_keyword(Keyword::Await)?;
+        let mut value = self.chain(|this| {
+            let mut value = this.parse_atom()?;
+
+            loop {
+                let kind = if this.eat_delim(Delimiter::LParen) {
+                    let arguments = this.parse_arguments()?;
+                    this.expect_delim(Delimiter::RParen)?;
+                    ExprKind::Call {
+                        func: Box::new(value),
+                        arguments,
+                    }
+                } else if this.eat_delim(Delimiter::LBracket) {
+                    let indices = this.parse_subscript_indices()?;
+                    this.expect_delim(Delimiter::RBracket)?;
+                    ExprKind::Subscript {
+                        value: Box::new(value),
+                        indices,
+                    }
+                } else if this.eat_delim(Delimiter::Dot) {
+                    ExprKind::Attribute {
+                        value: Box::new(value),
+                        attribute: this.expect_identifier()?,
+                    }
+                } else if this.eat_delim(Delimiter::Question) {
+                    ExprKind::Propagate(Box::new(value))
+                } else {
+                    break;
+                };
+
+                this.deepen()?;
+                value = Expr {
+                    kind,
+                    id: NodeId::next(),
+                    span: this.span_from(start),
+                };
+            }
+
+            Ok(value)
+        })?;
+
+        // `await` applies to the whole primary, like `await a.b()`.
+        if is_await {
+            value = Expr {
+                kind: ExprKind::Await(Box::new(value)),
+                id: NodeId::next(),
+                span: self.span_from(start),
+            };
+        }
+
+        Ok(value)
+    }
+
+    fn parse_atom(&mut self) -> Result<Expr> {
+        let start = self.start();
+        let kind = match self.peek() {
+            Some(TokenKind::Delim(Delimiter::LParen)) => return self.parse_parenthesized(),
+            Some(TokenKind::Delim(Delimiter::LBracket)) => self.parse_list()?,
+            Some(TokenKind::Delim(Delimiter::LBrace)) => self.parse_dict_or_set()?,
+            Some(TokenKind::Identifier(_)) => ExprKind::Identifier(self.expect_identifier()?),
+            Some(TokenKind::Keyword(Keyword::True)) => {
+                self.eat();
+                ExprKind::Bool(true)
+            }
+            Some(TokenKind::Keyword(Keyword::False)) => {
+                self.eat();
+                ExprKind::Bool(false)
+            }
+            Some(TokenKind::Str(..) | TokenKind::ByteStr(..)) => {
+                let mut strings = vec![];
+                while let Some(TokenKind::Str(..) | TokenKind::ByteStr(..)) = self.peek() {
+                    strings.push(match self.eat().unwrap().kind {
+                        TokenKind::Str(string, kind) => StringLiteral::Str(string, kind),
+                        TokenKind::ByteStr(string, kind) => StringLiteral::Bytes(string, kind),
+                        _ => unreachable!(),
+                    });
+                }
+
+                ExprKind::Strings(strings)
+            }
+            Some(TokenKind::Integer(..) | TokenKind::Float(_) | TokenKind::Imag(_)) => {
+                match self.eat().unwrap().kind {
+                    TokenKind::Integer(digits, kind) => ExprKind::Integer(digits, kind),
+                    TokenKind::Float(string) => ExprKind::Float(string),
+                    TokenKind::Imag(string) => ExprKind::Imag(string),
+                    _ => unreachable!(),
+                }
+            }
+            _ => return self.unexpected(),
+        };
+
+        Ok(Expr {
+            kind,
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses a parenthesized expression or a tuple.
+    fn parse_parenthesized(&mut self) -> Result<Expr> {
+        let start = self.start();
+        self.expect_delim(Delimiter::LParen)?;
+
+        if self.is_keyword(Keyword::Yield) {
+            let value = self.parse_yield()?;
+            self.expect_delim(Delimiter::RParen)?;
+            return Ok(value);
+        }
+
+        if !self.is_delim(Delimiter::RParen) {
+            let first = self.parse_element()?;
+            if self.is_comprehension_for() {
+                let comprehension = self.parse_comprehension(first)?;
+                self.expect_delim(Delimiter::RParen)?;
+
+                return Ok(Expr {
+                    kind: ExprKind::Generator(Box::new(comprehension)),
+                    id: NodeId::next(),
+                    span: self.span_from(start),
+                });
+            }
+
+            if self.eat_delim(Delimiter::RParen) {
+                return Ok(first);
+            }
+
+            self.expect_delim(Delimiter::Comma)?;
+
+            let mut elements = vec![first];
+            elements.extend(self.parse_elements(Delimiter::RParen)?);
+            self.expect_delim(Delimiter::RParen)?;
+
+            return Ok(Expr {
+                kind: ExprKind::Tuple(elements),
+                id: NodeId::next(),
+                span: self.span_from(start),
+            });
+        }
+
+        self.eat();
+
+        Ok(Expr {
+            kind: ExprKind::Tuple(vec![]),
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses a list or a list comprehension.
+    fn parse_list(&mut self) -> Result<ExprKind> {
+        self.expect_delim(Delimiter::LBracket)?;
+
+        if self.eat_delim(Delimiter::RBracket) {
+            return Ok(ExprKind::List(vec![]));
+        }
+
+        let first = self.parse_element()?;
+        if self.is_comprehension_for() {
+            let comprehension = self.parse_comprehension(first)?;
+            self.expect_delim(Delimiter::RBracket)?;
+
+            return Ok(ExprKind::ListComp(Box::new(comprehension)));
+        }
+
+        let mut elements = vec![first];
+        if self.eat_delim(Delimiter::Comma) {
+            elements.extend(self.parse_elements(Delimiter::RBracket)?);
+        }
+
+        self.expect_delim(Delimiter::RBracket)?;
+
+        Ok(ExprKind::List(elements))
+    }
+
+    // Checks if the next tokens start a `for` or `async for` clause of a comprehension.
+    fn is_comprehension_for(&self) -> bool {
+        match self.peek() {
+            Some(TokenKind::Keyword(Keyword::For)) => true,
+            Some(TokenKind::Keyword(Keyword::Async)) => {
+                self.peek_nth(1) == Some(&TokenKind::Keyword(Keyword::For))
+            }
+            _ => false,
+        }
+    }
+
+    /// Parses the clauses of a comprehension over the already parsed `element`.
+    fn parse_comprehension(&mut self, element: Expr) -> Result<Comprehension> {
+        if matches!(element.kind, ExprKind::Rest(_) | ExprKind::DictRest(_)) {
+            return error(ParserError::new(InvalidRestExpression, element.span));
+        }
+
+        let clauses = self.parse_comprehension_fors()?;
+        check_comprehension_scope(&[&element], &clauses)?;
+
+        Ok(Comprehension { element, clauses })
+    }
+
+    /// Parses `for target in iter if condition` clauses, where each `iter` and `condition` is an
+    /// `or_test` so that they do not take the next clause as a ternary.
+    fn parse_comprehension_fors(&mut self) -> Result<Vec<ComprehensionFor>> {
+        let mut clauses = vec![];
+        while self.is_comprehension_for() {
+            let is_async = self.eat_async_keyword(Keyword::Async)?;
+            self.expect_keyword(Keyword::For)?;
+            let target = self.parse_for_target()?;
+            self.expect_keyword(Keyword::In)?;
+            let iter = self.parse_or_test()?;
+
+            let mut conditions = vec![];
+            while self.eat_keyword(Keyword::If) {
+                conditions
+                    .push(self.parse_named_expr_or(Parser::parse_or_test, Parser::parse_or_test)?);
+            }
+
+            clauses.push(ComprehensionFor {
+                is_async,
+                target,
+                iter,
+                conditions,
+            });
+        }
+
+        Ok(clauses)
+    }
+
+    /// Parses comma separated elements up to the `closing` delimiter.
+    fn parse_elements(&mut self, closing: Delimiter) -> Result<Vec<Expr>> {
+        let mut elements = vec![];
+        while !self.is_delim(closing.clone()) {
+            elements.push(self.parse_element()?);
+            if !self.eat_delim(Delimiter::Comma) {
+                break;
+            }
+        }
+
+        Ok(elements)
+    }
+
+    fn parse_dict_or_set(&mut self) -> Result<ExprKind> {
+        self.expect_delim(Delimiter::LBrace)?;
+
+        if self.eat_delim(Delimiter::RBrace) {
+            return Ok(ExprKind::Dict(vec![]));
+        }
+
+        // Unparenthesized named and rest expressions can only be set elements.
+        let is_element = matches!(
+            self.peek(),
+            Some(TokenKind::Op(Operator::Mul | Operator::Pow))
+        ) || self.peek_nth(1) == Some(&TokenKind::Delim(Delimiter::Walrus));
+
+        let first = self.parse_element()?;
+        if self.is_comprehension_for() {
+            let comprehension = self.parse_comprehension(first)?;
+            self.expect_delim(Delimiter::RBrace)?;
+
+            return Ok(ExprKind::SetComp(Box::new(comprehension)));
+        }
+
+        if is_element || !self.is_delim(Delimiter::Colon) {
+            let mut elements = vec![first];
+            if self.eat_delim(Delimiter::Comma) {
+                elements.extend(self.parse_elements(Delimiter::RBrace)?);
+            }
+
+            self.expect_delim(Delimiter::RBrace)?;
+
+            return Ok(ExprKind::Set(elements));
+        }
+
+        self.eat();
+        let entry = DictEntry {
+            key: first,
+            value: self.parse_expr()?,
+        };
+
+        if self.is_comprehension_for() {
+            let clauses = self.parse_comprehension_fors()?;
+            check_comprehension_scope(&[&entry.key, &entry.value], &clauses)?;
+            self.expect_delim(Delimiter::RBrace)?;
+
+            return Ok(ExprKind::DictComp(Box::new(DictComprehension {
+                entry,
+                clauses,
+            })));
+        }
+
+        let mut entries = vec![entry];
+
+        while self.eat_delim(Delimiter::Comma) && !self.is_delim(Delimiter::RBrace) {
+            let key = self.parse_test()?;
+            self.expect_delim(Delimiter::Colon)?;
+            let value = self.parse_expr()?;
+            entries.push(DictEntry { key, value });
+        }
+
+        self.expect_delim(Delimiter::RBrace)?;
+
+        Ok(ExprKind::Dict(entries))
+    }
+
+    /// Parses the arguments of a call up to the closing parenthesis.
+    fn parse_arguments(&mut self) -> Result<Vec<Argument>> {
+        let mut arguments = vec![];
+        while !self.is_delim(Delimiter::RParen) {
+            let is_keyword = matches!(self.peek(), Some(TokenKind::Identifier(_)))
+                && self.peek_nth(1) == Some(&TokenKind::Delim(Delimiter::Assign));
+
+            arguments.push(match is_keyword {
+                true => {
+                    let name = self.expect_identifier()?;
+                    self.eat();
+                    Argument::Keyword {
+                        name,
+                        value: self.parse_expr()?,
+                    }
+                }
+                false => {
+                    let start = self.start();
+                    let value = self.parse_rest_expr()?;
+
+                    // `f(x for x in xs)` is a generator without its own parentheses.
+                    if arguments.is_empty() && self.is_comprehension_for() {
+                        let comprehension = self.parse_comprehension(value)?;
+                        arguments.push(Argument::Positional(Expr {
+                            kind: ExprKind::Generator(Box::new(comprehension)),
+                            id: NodeId::next(),
+                            span: self.span_from(start),
+                        }));
+                        break;
+                    }
+
+                    Argument::Positional(value)
+                }
+            });
+
+            if !self.eat_delim(Delimiter::Comma) {
+                break;
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    fn parse_subscript_indices(&mut self) -> Result<Vec<SubscriptIndex>> {
+        let mut indices = vec![];
+        loop {
+            indices.push(self.parse_subscript_index()?);
+            if !self.eat_delim(Delimiter::Comma) || self.is_delim(Delimiter::RBracket) {
+                break;
+            }
+        }
+
+        Ok(indices)
+    }
+
+    /// Parses `index` or `lower:upper:step`.
+    fn parse_subscript_index(&mut self) -> Result<SubscriptIndex> {
+        let lower = match self.is_delim(Delimiter::Colon) {
+            true => None,
+            false => Some(self.parse_test()?),
+        };
+
+        if !self.eat_delim(Delimiter::Colon) {
+            return Ok(SubscriptIndex::Index(lower.unwrap()));
+        }
+
+        let upper = match self.is_slice_part_end() {
+            true => None,
+            false => Some(self.parse_test()?),
+        };
+
+        let step = match self.eat_delim(Delimiter::Colon) && !self.is_slice_part_end() {
+            true => Some(self.parse_test()?),
+            false => None,
+        };
+
+        Ok(SubscriptIndex::Slice { lower, upper, step })
+    }
+
+    fn is_slice_part_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(TokenKind::Delim(
+                Delimiter::Colon | Delimiter::Comma | Delimiter::RBracket
+            ))
+        )
+    }
+}
+
+/// Pattern parsing functions.
+impl Parser {
+    /// Parses the pattern of a `case`, where unbracketed `a, b` is a sequence pattern.
+    fn parse_case_pattern(&mut self) -> Result<Pattern> {
+        let start = self.start();
+        let first = self.parse_sequence_element()?;
+        if !self.is_delim(Delimiter::Comma) {
+            return match first.kind {
+                PatternKind::Rest(_) => error(ParserError::new(InvalidPattern, first.span)),
+                _ => Ok(first),
+            };
+        }
+
+        let mut patterns = vec![first];
+        while self.eat_delim(Delimiter::Comma)
+            && !self.is_delim(Delimiter::Colon)
+            && !self.is_keyword(Keyword::If)
+        {
+            patterns.push(self.parse_sequence_element()?);
+        }
+
+        sequence_pattern(patterns, self.span_from(start))
+    }
+
+    /// Parses `pattern as name`.
+    fn parse_pattern(&mut self) -> Result<Pattern> {
+        let start = self.start();
+        let pattern = self.nested(Parser::parse_or_pattern)?;
+        if !self.eat_keyword(Keyword::As) {
+            return Ok(pattern);
+        }
+
+        let name = self.expect_identifier()?;
+
+        Ok(Pattern {
+            kind: PatternKind::As {
+                pattern: Box::new(pattern),
+                name,
+            },
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_or_pattern(&mut self) -> Result<Pattern> {
+        let start = self.start();
+        let first = self.parse_closed_pattern()?;
+        if !self.is_op(Operator::BitOr) {
+            return Ok(first);
+        }
+
+        let mut patterns = vec![first];
+        while self.eat_op(Operator::BitOr) {
+            patterns.push(self.parse_closed_pattern()?);
+        }
+
+        Ok(Pattern {
+            kind: PatternKind::Or(patterns),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_closed_pattern(&mut self) -> Result<Pattern> {
+        let start = self.start();
+        let kind = match self.peek() {
+            Some(TokenKind::Delim(Delimiter::LParen)) => {
+                self.eat();
+                let mut patterns = vec![];
+                if !self.is_delim(Delimiter::RParen) {
+                    let first = self.parse_sequence_element()?;
+
+                    // A single pattern without a trailing comma is only parenthesized.
+                    if !matches!(first.kind, PatternKind::Rest(_))
+                        && self.eat_delim(Delimiter::RParen)
+                    {
+                        return Ok(first);
+                    }
+
+                    patterns.push(first);
+                    if self.eat_delim(Delimiter::Comma) {
+                        patterns.extend(self.parse_sequence_elements(Delimiter::RParen)?);
+                    }
+                }
+
+                self.expect_delim(Delimiter::RParen)?;
+                return sequence_pattern(patterns, self.span_from(start));
+            }
+            Some(TokenKind::Delim(Delimiter::LBracket)) => {
+                self.eat();
+                let patterns = self.parse_sequence_elements(Delimiter::RBracket)?;
+                self.expect_delim(Delimiter::RBracket)?;
+                return sequence_pattern(patterns, self.span_from(start));
+            }
+            Some(TokenKind::Delim(Delimiter::LBrace)) => self.parse_mapping_pattern()?,
+            Some(TokenKind::Identifier(_)) => {
+                let path = self.parse_path()?;
+                if self.eat_delim(Delimiter::LParen) {
+                    self.parse_class_pattern(path)?
+                } else if path.names.len() > 1 {
+                    PatternKind::Value(path)
+                } else if path.names[0].name == "_" {
+                    PatternKind::Wildcard
+                } else {
+                    PatternKind::Capture(path.names.into_iter().next().unwrap())
+                }
+            }
+            _ => PatternKind::Literal(self.parse_literal_pattern()?),
+        };
+
+        Ok(Pattern {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses a string, boolean or optionally negated number.
+    fn parse_literal_pattern(&mut self) -> Result<Expr> {
+        let start = self.start();
+        let negated = self.eat_op(Operator::Minus);
+
+        match self.peek() {
+            Some(TokenKind::Integer(..) | TokenKind::Float(_) | TokenKind::Imag(_)) => (),
+            Some(
+                TokenKind::Str(..)
+                | TokenKind::ByteStr(..)
+                | TokenKind::Keyword(Keyword::True | Keyword::False),
+            ) if !negated => (),
+            _ => return self.unexpected(),
+        }
+
+        let literal = self.parse_atom()?;
+        if !negated {
+            return Ok(literal);
+        }
+
+        Ok(Expr {
+            kind: ExprKind::UnaryOp {
+                op: UnaryOp::Minus,
+                operand: Box::new(literal),
+            },
+            id: NodeId::next(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses a pattern that can be in a sequence, including `*rest`.
+    fn parse_sequence_element(&mut self) -> Result<Pattern> {
+        let start = self.start();
+        if !self.eat_op(Operator::Mul) {
+            return self.parse_pattern();
+        }
+
+        let name = self.expect_identifier()?;
+
+        Ok(Pattern {
+            kind: PatternKind::Rest(Some(name).filter(|name| name.name != "_")),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses comma separated patterns up to the `closing` delimiter.
+    fn parse_sequence_elements(&mut self, closing: Delimiter) -> Result<Vec<Pattern>> {
+        let mut patterns = vec![];
+        while !self.is_delim(closing.clone()) {
+            patterns.push(self.parse_sequence_element()?);
+            if !self.eat_delim(Delimiter::Comma) {
+                break;
+            }
+        }
+
+        Ok(patterns)
+    }
+
+    fn parse_mapping_pattern(&mut self) -> Result<PatternKind> {
+        self.expect_delim(Delimiter::LBrace)?;
+
+        let mut entries = vec![];
+        let mut rest = None;
+        while !self.is_delim(Delimiter::RBrace) {
+            // A `**rest` can only be the last entry.
+            if self.eat_op(Operator::Pow) {
+                rest = Some(self.expect_identifier()?);
+                self.eat_delim(Delimiter::Comma);
+                break;
+            }
+
+            let key = self.parse_closed_pattern()?;
+            if !matches!(key.kind, PatternKind::Literal(_) | PatternKind::Value(_)) {
+                return error(ParserError::new(InvalidPattern, key.span));
+            }
+
+            self.expect_delim(Delimiter::Colon)?;
+            let value = self.parse_pattern()?;
+            entries.push(MappingPatternEntry { key, value });
+
+            if !self.eat_delim(Delimiter::Comma) {
+                break;
+            }
+        }
+
+        self.expect_delim(Delimiter::RBrace)?;
+
+        Ok(PatternKind::Mapping { entries, rest })
+    }
+
+    /// Parses the arguments of a class pattern after the `(`.
+    fn parse_class_pattern(&mut self, path: Path) -> Result<PatternKind> {
+        let mut patterns = vec![];
+        let mut keywords = vec![];
+        while !self.is_delim(Delimiter::RParen) {
+            if matches!(self.peek(), Some(TokenKind::Identifier(_)))
+                && self.peek_nth(1) == Some(&TokenKind::Delim(Delimiter::Assign))
+            {
+                let name = self.expect_identifier()?;
+                self.eat();
+                let pattern = self.parse_pattern()?;
+                keywords.push(KeywordPattern { name, pattern });
+            } else {
+                let pattern = self.parse_pattern()?;
+
+                // Positional patterns cannot follow keyword patterns.
+                if !keywords.is_empty() {
+                    return error(ParserError::new(InvalidPattern, pattern.span));
+                }
+
+                patterns.push(pattern);
+            }
+
+            if !self.eat_delim(Delimiter::Comma) {
+                break;
+            }
+        }
+
+        self.expect_delim(Delimiter::RParen)?;
+
+        Ok(PatternKind::Class {
+            path,
+            patterns,
+            keywords,
+        })
+    }
+}
+
+/// Type annotation parsing functions.
+impl Parser {
+    /// Parses `a | b & c`.
+    fn parse_type(&mut self) -> Result<TypeAnnotation> {
+        let start = self.start();
+        let first = self.nested(Parser::parse_intersection_type)?;
+        if !self.is_op(Operator::BitOr) {
+            return Ok(first);
+        }
+
+        let mut types = vec![first];
+        while self.eat_op(Operator::BitOr) {
+            types.push(self.parse_intersection_type()?);
+        }
+
+        Ok(TypeAnnotation {
+            kind: TypeAnnotationKind::Union(types),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_intersection_type(&mut self) -> Result<TypeAnnotation> {
+        let start = self.start();
+        let first = self.parse_optional_type()?;
+        if !self.is_op(Operator::BitAnd) {
+            return Ok(first);
+        }
+
+        let mut types = vec![first];
+        while self.eat_op(Operator::BitAnd) {
+            types.push(self.parse_optional_type()?);
+        }
+
+        Ok(TypeAnnotation {
+            kind: TypeAnnotationKind::Intersection(types),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses `a?`, which binds tighter than `&` and `|`.
+    fn parse_optional_type(&mut self) -> Result<TypeAnnotation> {
+        let start = self.start();
+        let annotation = self.parse_atom_type()?;
+        if !self.eat_delim(Delimiter::Question) {
+            return Ok(annotation);
+        }
+
+        Ok(TypeAnnotation {
+            kind: TypeAnnotationKind::Optional(Box::new(annotation)),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_atom_type(&mut self) -> Result<TypeAnnotation> {
+        let start = self.start();
+        let kind = if self.eat_delim(Delimiter::LParen) {
+            let types = self.parse_types(Delimiter::RParen)?;
+            self.expect_delim(Delimiter::RParen)?;
+
+            match self.eat_delim(Delimiter::Arrow) {
+                true => TypeAnnotationKind::Function {
+                    params: types,
+                    return_type: Box::new(self.parse_type()?),
+                },
+                false => TypeAnnotationKind::Tuple(types),
+            }
+        } else if self.eat_delim(Delimiter::LBracket) {
+            let types = self.parse_types(Delimiter::RBracket)?;
+            self.expect_delim(Delimiter::RBracket)?;
+            TypeAnnotationKind::List(types)
+        } else {
+            let name = self.expect_identifier()?;
+            match self.eat_delim(Delimiter::LBracket) {
+                true => {
+                    let arguments = self.parse_types(Delimiter::RBracket)?;
+                    self.expect_delim(Delimiter::RBracket)?;
+                    TypeAnnotationKind::Generic { name, arguments }
+                }
+                false if self.is_delim(Delimiter::Dot) => {
+                    let mut names = vec![name];
+                    while self.eat_delim(Delimiter::Dot) {
+                        names.push(self.expect_identifier()?);
+                    }
+
+                    TypeAnnotationKind::Path(Path { names })
+                }
+                false => TypeAnnotationKind::Identifier(name),
+            }
+        };
+
+        Ok(TypeAnnotation {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses comma separated types up to the `closing` delimiter.
+    fn parse_types(&mut self, closing: Delimiter) -> Result<Vec<TypeAnnotation>> {
+        let mut types = vec![];
+        while !self.is_delim(closing.clone()) {
+            types.push(self.parse_type()?);
+            if !self.eat_delim(Delimiter::Comma) {
+                break;
+            }
+        }
+
+        Ok(types)
+    }
+}
+
+impl Visitor for YieldFinder {
+    // Only the bodies of nested definitions are scopes of their own; decorators, defaults and
+    // fields are evaluated in the enclosing function.
+    fn visit_function_def(&mut self, node: &FunctionDef) {
+        node.decorators.iter().for_each(|d| self.visit_decorator(d));
+        self.visit_params(&node.params);
+    }
+
+    fn visit_class_def(&mut self, node: &ClassDef) {
+        node.decorators.iter().for_each(|d| self.visit_decorator(d));
+        node.fields.iter().for_each(|f| self.visit_param(f));
+    }
+
+    fn visit_interface_def(&mut self, node: &InterfaceDef) {
+        node.decorators.iter().for_each(|d| self.visit_decorator(d));
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        match &node.kind {
+            ExprKind::Yield(_) | ExprKind::YieldFrom(_) if self.span.is_none() => {
+                self.span = Some(node.span.clone());
+            }
+            // A lambda body is a function of its own, but not its defaults.
+            ExprKind::Lambda(lambda) => self.visit_params(&lambda.params),
+            _ => visit::walk_expr(self, node),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Maps an augmented assignment delimiter to its binary operator.
+fn aug_assign_op(kind: &TokenKind) -> Option<BinaryOp> {
+    Some(match kind {
+        TokenKind::Delim(Delimiter::PlusAssign) => BinaryOp::Plus,
+        TokenKind::Delim(Delimiter::MinusAssign) => BinaryOp::Minus,
+        TokenKind::Delim(Delimiter::MulAssign) => BinaryOp::Mul,
+        TokenKind::Delim(Delimiter::AtAssign) => BinaryOp::MatMul,
+        TokenKind::Delim(Delimiter::DivAssign) => BinaryOp::Div,
+        TokenKind::Delim(Delimiter::IntDivAssign) => BinaryOp::IntDiv,
+        TokenKind::Delim(Delimiter::ModAssign) => BinaryOp::Mod,
+        TokenKind::Delim(Delimiter::BitAndAssign) => BinaryOp::BitAnd,
+        TokenKind::Delim(Delimiter::BitOrAssign) => BinaryOp::BitOr,
+        TokenKind::Delim(Delimiter::BitXorAssign) => BinaryOp::BitXor,
+        TokenKind::Delim(Delimiter::ShiftLAssign) => BinaryOp::ShiftL,
+        TokenKind::Delim(Delimiter::ShiftRAssign) => BinaryOp::ShiftR,
+        TokenKind::Delim(Delimiter::PowAssign) => BinaryOp::Pow,
+        _ => return None,
+    })
+}
+
+/// Makes a sequence pattern, which can only have one `*rest`.
+fn sequence_pattern(patterns: Vec<Pattern>, span: Span) -> Result<Pattern> {
+    if let Some(rest) = patterns
+        .iter()
+        .filter(|pattern| matches!(pattern.kind, PatternKind::Rest(_)))
+        .nth(1)
+    {
+        return error(ParserError::new(InvalidPattern, rest.span.clone()));
+    }
+
+    Ok(Pattern {
+        kind: PatternKind::Sequence(patterns),
+        span,
+    })
+}
+
+/// Returns the class kind of a soft keyword like `data` in `data class`.
+fn class_kind(name: &str) -> Option<ClassKind> {
+    match name {
+        "data" => Some(ClassKind::Data),
+        "union" => Some(ClassKind::Union),
+        "abstract" => Some(ClassKind::Abstract),
+        _ => None,
+    }
+}
+
+/// Checks that a statement in an interface body is a signature, a field annotation, a type alias,
+/// a docstring or `pass`.
+fn check_interface_member(statement: &Statement) -> Result<()> {
+    match &statement.kind {
+        StatementKind::FunctionDef(def) if def.body.is_empty() => Ok(()),
+        StatementKind::AnnAssign(AnnAssign { value: None, .. })
+        | StatementKind::TypeAlias(_)
+        | StatementKind::Pass => Ok(()),
+        StatementKind::Expr(Expr {
+            kind: ExprKind::Strings(_),
+            ..
+        }) => Ok(()),
+        _ => error(ParserError::new(
+            InvalidInterfaceMember,
+            statement.span.clone(),
+        )),
+    }
+}
+
+/// Checks if a function body has a `yield` of its own, which makes the function a generator.
+pub(super) fn is_generator_body(body: &[Statement]) -> bool {
+    let mut finder = YieldFinder::default();
+    body.iter()
+        .for_each(|statement| finder.visit_statement(statement));
+
+    finder.span.is_some()
+}
+
+/// Checks that the parts of a comprehension evaluated in its own scope do not `yield`, as the
+/// comprehension is not the function that would become a generator.
+fn check_comprehension_scope(elements: &[&Expr], clauses: &[ComprehensionFor]) -> Result<()> {
+    let mut finder = YieldFinder::default();
+    elements
+        .iter()
+        .for_each(|element| finder.visit_expr(element));
+
+    for (index, clause) in clauses.iter().enumerate() {
+        // The first iterable is evaluated in the enclosing scope.
+        if index > 0 {
+            finder.visit_expr(&clause.iter);
+        }
+
+        clause
+            .conditions
+            .iter()
+            .for_each(|condition| finder.visit_expr(condition));
+    }
+
+    match finder.span {
+        Some(span) => error(ParserError::new(YieldInComprehension, span)),
+        None => Ok(()),
+    }
+}
+
+/// Checks the target of an augmented or annotated assignment, which cannot be unpacked.
+fn check_target(target: &Expr) -> Result<()> {
+    match &target.kind {
+        ExprKind::Identifier(_) => Ok(()),
+        ExprKind::Attribute { value, .. } | ExprKind::Subscript { value, .. }
+            if is_rooted_at_identifier(value) =>
+        {
+            Ok(())
+        }
+        _ => error(ParserError::new(
+            InvalidAssignmentTarget,
+            target.span.clone(),
+        )),
+    }
+}
+
+/// Lowers an assignment or `for` target to the pattern it destructures with, like `a, [b, *c]`.
+pub(crate) fn target_pattern(target: Expr) -> Result<Pattern> {
+    let span = target.span;
+    let kind = match target.kind {
+        ExprKind::Identifier(name) if name.name == "_" => PatternKind::Wildcard,
+        ExprKind::Identifier(name) => PatternKind::Capture(name),
+        ExprKind::Attribute { ref value, .. } | ExprKind::Subscript { ref value, .. }
+            if is_rooted_at_identifier(value) =>
+        {
+            PatternKind::Store(Box::new(Expr {
+                span: span.clone(),
+                ..target
+            }))
+        }
+        ExprKind::Tuple(elements) | ExprKind::List(elements) if !elements.is_empty() => {
+            let patterns = elements
+                .into_iter()
+                .map(|element| match element.kind {
+                    ExprKind::Rest(value) => match value.kind {
+                        ExprKind::Identifier(name) => Ok(Pattern {
+                            kind: PatternKind::Rest(Some(name).filter(|name| name.name != "_")),
+                            span: element.span,
+                        }),
+                        _ => error(ParserError::new(InvalidAssignmentTarget, value.span)),
+                    },
+                    _ => target_pattern(element),
+                })
+                .collect::<Result<_>>()?;
+
+            return sequence_pattern(patterns, span);
+        }
+        _ => return error(ParserError::new(InvalidAssignmentTarget, span)),
+    };
+
+    Ok(Pattern { kind, span })
+}
+
+/// Checks if an expression is an identifier followed by calls, subscripts, attribute accesses or
+/// `?` propagations.
+fn is_rooted_at_identifier(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Identifier(_) => true,
+        ExprKind::Attribute { value, .. }
+        | ExprKind::Subscript { value, .. }
+        | ExprKind::Call { func: value, .. }
+        | ExprKind::Propagate(value) => is_rooted_at_identifier(value),
+        _ => false,
+    }
+}
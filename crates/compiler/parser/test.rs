@@ -0,0 +1,1197 @@
+use std::collections::HashSet;
+
+use crate::ir::{
+    Argument, Assign, Ast, BinaryOp, ClassKind, ComparisonOp, DeclarationKind, Expr, ExprKind, For,
+    Identifier, If, LambdaBody, NodeId, PassingMode, Pattern, PatternKind, Statement,
+    StatementKind, TypeAnnotationKind, UnaryOp, VariantField, While,
+};
+use crate::span::Span;
+
+use super::ParserErrorKind::*;
+use super::{IncrementalParser, Parser};
+
+use utils::*;
+
+#[test]
+fn can_parse_assignments() {
+    let result = parse("x = a + b\n");
+
+    assert_eq!(
+        result,
+        Ast {
+            statements: vec![Statement {
+                kind: StatementKind::Assign(Assign {
+                    targets: vec![Pattern {
+                        kind: PatternKind::Capture(Identifier {
+                            name: "x".into(),
+                            id: NodeId::default(),
+                            span: Span::new(0, 1),
+                            context: None,
+                        }),
+                        span: Span::new(0, 1),
+                    }],
+                    value: expr(ExprKind::BinaryOp {
+                        lhs: Box::new(name("a").with_span(4, 5)),
+                        op: BinaryOp::Plus,
+                        rhs: Box::new(name("b").with_span(8, 9)),
+                    })
+                    .with_span(4, 9),
+                }),
+                id: NodeId::default(),
+                span: Span::new(0, 9),
+            }],
+            id: NodeId::default(),
+            span: Span::new(0, 10),
+        }
+    );
+}
+
+#[test]
+fn can_parse_chained_and_augmented_assignments() {
+    let result_chained = parse_erased("a = b.c = d[0] = (e, f)");
+    let result_augmented = parse_erased("a **= 2; b //= 3");
+    let result_annotated = parse_erased("a: int | str = 1");
+
+    match &result_chained.statements[0].kind {
+        StatementKind::Assign(Assign { targets, value }) => {
+            assert_eq!(targets.len(), 3);
+            assert!(matches!(value.kind, ExprKind::Tuple(_)));
+        }
+        kind => panic!("expected an assignment, found {kind:?}"),
+    }
+
+    assert_eq!(result_augmented.statements.len(), 2);
+    assert!(matches!(
+        &result_augmented.statements[0].kind,
+        StatementKind::AugAssign(assign) if assign.op == BinaryOp::Pow
+    ));
+    assert!(matches!(
+        &result_augmented.statements[1].kind,
+        StatementKind::AugAssign(assign) if assign.op == BinaryOp::IntDiv
+    ));
+    assert!(matches!(
+        &result_annotated.statements[0].kind,
+        StatementKind::AnnAssign(assign) if assign.value.is_some()
+    ));
+}
+
+#[test]
+fn can_parse_operator_precedence() {
+    assert_eq!(
+        parse_expr("a + b * c ** -d²"),
+        binary(
+            name("a"),
+            BinaryOp::Plus,
+            binary(
+                name("b"),
+                BinaryOp::Mul,
+                binary(
+                    name("c"),
+                    BinaryOp::Pow,
+                    unary(UnaryOp::Minus, unary(UnaryOp::Square, name("d")))
+                )
+            )
+        )
+    );
+
+    assert_eq!(
+        parse_expr("not a or b and c"),
+        binary(
+            unary(UnaryOp::Not, name("a")),
+            BinaryOp::Or,
+            binary(name("b"), BinaryOp::And, name("c"))
+        )
+    );
+
+    assert_eq!(
+        parse_expr("a | b ^ c & d << e"),
+        binary(
+            name("a"),
+            BinaryOp::BitOr,
+            binary(
+                name("b"),
+                BinaryOp::BitXor,
+                binary(
+                    name("c"),
+                    BinaryOp::BitAnd,
+                    binary(name("d"), BinaryOp::ShiftL, name("e"))
+                )
+            )
+        )
+    );
+
+    assert_eq!(
+        parse_expr("a - b - c"),
+        binary(
+            binary(name("a"), BinaryOp::Minus, name("b")),
+            BinaryOp::Minus,
+            name("c")
+        )
+    );
+
+    assert_eq!(
+        parse_expr("√a²"),
+        unary(UnaryOp::Square, unary(UnaryOp::Sqrt, name("a")))
+    );
+
+    assert_eq!(parse_expr("((a))"), name("a"));
+}
+
+#[test]
+fn can_parse_propagations_and_optional_types() {
+    let propagate = |value: Expr| expr(ExprKind::Propagate(Box::new(value)));
+
+    assert_eq!(
+        parse_expr("-a? ** b"),
+        unary(
+            UnaryOp::Minus,
+            binary(propagate(name("a")), BinaryOp::Pow, name("b"))
+        )
+    );
+
+    match parse_expr("a if b else c?").kind {
+        ExprKind::Ternary {
+            then, otherwise, ..
+        } => {
+            assert_eq!(*then, name("a"));
+            assert_eq!(*otherwise, propagate(name("c")));
+        }
+        kind => panic!("expected a ternary, found {kind:?}"),
+    }
+
+    assert!(matches!(
+        parse_expr("get_value()?.b").kind,
+        ExprKind::Attribute { value, .. } if matches!(value.kind, ExprKind::Propagate(_))
+    ));
+
+    let result = parse_erased(
+        "def f(parent: Node?) -> int | str? & A: pass
+f()?.x = 1
+",
+    );
+    match &result.statements[0].kind {
+        StatementKind::FunctionDef(function) => {
+            assert!(matches!(
+                function.params.params[0].annotation.as_ref().unwrap().kind,
+                TypeAnnotationKind::Optional(_)
+            ));
+            match &function.return_type.as_ref().unwrap().kind {
+                TypeAnnotationKind::Union(types) => assert!(matches!(
+                    &types[1].kind,
+                    TypeAnnotationKind::Intersection(types)
+                        if matches!(types[0].kind, TypeAnnotationKind::Optional(_))
+                )),
+                kind => panic!("expected a union, found {kind:?}"),
+            }
+        }
+        kind => panic!("expected a function, found {kind:?}"),
+    }
+
+    assert!(matches!(
+        result.statements[1].kind,
+        StatementKind::Assign(_)
+    ));
+}
+
+#[test]
+fn can_parse_async_functions_and_generators() {
+    let result = parse_erased(
+        "async def f():
+    async for a in b: await a.c()
+    async with d: pass
+def g():
+    x = yield
+    yield from h
+    lambda: (yield 1)
+abstract async def k(self)
+",
+    );
+
+    match &result.statements[0].kind {
+        StatementKind::FunctionDef(function) => {
+            assert!(function.is_async && !function.is_generator);
+            match &function.body[0].kind {
+                StatementKind::For(for_) => {
+                    assert!(for_.is_async);
+                    match &for_.body[0].kind {
+                        StatementKind::Expr(expr) => assert!(matches!(
+                            &expr.kind,
+                            ExprKind::Await(value) if matches!(value.kind, ExprKind::Call { .. })
+                        )),
+                        kind => panic!("expected an expression, found {kind:?}"),
+                    }
+                }
+                kind => panic!("expected a for statement, found {kind:?}"),
+            }
+            assert!(matches!(&function.body[1].kind, StatementKind::With(with) if with.is_async));
+        }
+        kind => panic!("expected a function, found {kind:?}"),
+    }
+
+    match &result.statements[1].kind {
+        StatementKind::FunctionDef(function) => {
+            assert!(!function.is_async && function.is_generator);
+            assert!(matches!(
+                &function.body[0].kind,
+                StatementKind::Assign(assign) if matches!(assign.value.kind, ExprKind::Yield(None))
+            ));
+            assert!(matches!(
+                &function.body[1].kind,
+                StatementKind::Expr(expr) if matches!(expr.kind, ExprKind::YieldFrom(_))
+            ));
+            assert!(matches!(
+                &function.body[2].kind,
+                StatementKind::Expr(expr)
+                    if matches!(&expr.kind, ExprKind::Lambda(lambda) if lambda.is_generator)
+            ));
+        }
+        kind => panic!("expected a function, found {kind:?}"),
+    }
+
+    assert!(matches!(
+        &result.statements[2].kind,
+        StatementKind::FunctionDef(function)
+            if function.is_abstract && function.is_async && function.body.is_empty()
+    ));
+}
+
+#[test]
+fn can_parse_destructuring_targets() {
+    let capture = |name: &str| pattern(PatternKind::Capture(identifier(name)));
+    let result = parse_erased("a, [b, *c], d.e = f = g\nfor (h, *_), i[0] in j: pass\n");
+
+    match &result.statements[0].kind {
+        StatementKind::Assign(assign) => {
+            assert_eq!(
+                assign.targets[0].kind,
+                PatternKind::Sequence(vec![
+                    capture("a"),
+                    pattern(PatternKind::Sequence(vec![
+                        capture("b"),
+                        pattern(PatternKind::Rest(Some(identifier("c"))))
+                    ])),
+                    pattern(PatternKind::Store(Box::new(expr(ExprKind::Attribute {
+                        value: Box::new(name("d")),
+                        attribute: identifier("e"),
+                    }))))
+                ])
+            );
+            assert_eq!(assign.targets[1], capture("f"));
+            assert_eq!(assign.value, name("g"));
+        }
+        kind => panic!("expected an assignment, found {kind:?}"),
+    }
+
+    match &result.statements[1].kind {
+        StatementKind::For(for_) => match &for_.target.kind {
+            PatternKind::Sequence(patterns) => {
+                assert_eq!(
+                    patterns[0].kind,
+                    PatternKind::Sequence(vec![capture("h"), pattern(PatternKind::Rest(None))])
+                );
+                assert!(matches!(patterns[1].kind, PatternKind::Store(_)));
+            }
+            kind => panic!("expected a sequence pattern, found {kind:?}"),
+        },
+        kind => panic!("expected a for statement, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_comprehensions() {
+    match parse_expr("[a for a, b in c if a if (d := b) for e in a]").kind {
+        ExprKind::ListComp(comprehension) => {
+            assert_eq!(comprehension.element, name("a"));
+            assert_eq!(comprehension.clauses.len(), 2);
+
+            let clause = &comprehension.clauses[0];
+            assert!(!clause.is_async);
+            assert!(
+                matches!(&clause.target.kind, PatternKind::Sequence(names) if names.len() == 2)
+            );
+            assert_eq!(clause.iter, name("c"));
+            assert_eq!(clause.conditions[0], name("a"));
+            assert!(matches!(
+                clause.conditions[1].kind,
+                ExprKind::NamedExpr { .. }
+            ));
+        }
+        kind => panic!("expected a list comprehension, found {kind:?}"),
+    }
+
+    assert!(matches!(
+        parse_expr("{a for a in b if c := a}").kind,
+        ExprKind::SetComp(comprehension)
+            if matches!(comprehension.clauses[0].conditions[0].kind, ExprKind::NamedExpr { .. })
+    ));
+    assert!(matches!(
+        parse_expr("{a: b for a, b in c}").kind,
+        ExprKind::DictComp(comprehension) if comprehension.entry.value == name("b")
+    ));
+    assert!(matches!(
+        parse_expr("(a for a in b)").kind,
+        ExprKind::Generator(_)
+    ));
+
+    match parse_expr("f(a for a in b)").kind {
+        ExprKind::Call { arguments, .. } => assert!(matches!(
+            &arguments[..],
+            [Argument::Positional(Expr {
+                kind: ExprKind::Generator(_),
+                ..
+            })]
+        )),
+        kind => panic!("expected a call, found {kind:?}"),
+    }
+
+    let result = parse_erased("async def f(): return [await a async for a in b]");
+    match &result.statements[0].kind {
+        StatementKind::FunctionDef(function) => match &function.body[0].kind {
+            StatementKind::Return(Some(value)) => assert!(matches!(
+                &value.kind,
+                ExprKind::ListComp(comprehension)
+                    if comprehension.clauses[0].is_async
+                        && matches!(comprehension.element.kind, ExprKind::Await(_))
+            )),
+            kind => panic!("expected a return, found {kind:?}"),
+        },
+        kind => panic!("expected a function, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_comparisons() {
+    match parse_expr("a < b not in c is not d").kind {
+        ExprKind::Comparison { comparisons, .. } => assert_eq!(
+            comparisons
+                .iter()
+                .map(|comparison| comparison.op)
+                .collect::<Vec<_>>(),
+            vec![ComparisonOp::Less, ComparisonOp::NotIn, ComparisonOp::IsNot]
+        ),
+        kind => panic!("expected a comparison, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_ternaries_and_lambdas() {
+    assert!(matches!(
+        parse_expr("a if b else c").kind,
+        ExprKind::Ternary { .. }
+    ));
+
+    match parse_expr("lambda a, *b, c=1, **d: a").kind {
+        ExprKind::Lambda(lambda) => {
+            assert_eq!(lambda.params.params.len(), 1);
+            assert!(lambda.params.vararg.is_some());
+            assert_eq!(lambda.params.keyword_only.len(), 1);
+            assert!(lambda.params.kwarg.is_some());
+        }
+        kind => panic!("expected a lambda, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_block_lambdas() {
+    let result_parenthesized = parse_erased("f((lambda x:\n    y = x\n    return y\n), 1)\nz");
+    let result_bare = parse_erased("f = lambda:\n    pass\nz");
+
+    assert_eq!(result_parenthesized.statements.len(), 2);
+    match &result_parenthesized.statements[0].kind {
+        StatementKind::Expr(call) => match &call.kind {
+            ExprKind::Call { arguments, .. } => assert_eq!(arguments.len(), 2),
+            kind => panic!("expected a call, found {kind:?}"),
+        },
+        kind => panic!("expected an expression, found {kind:?}"),
+    }
+
+    assert_eq!(result_bare.statements.len(), 2);
+    match &result_bare.statements[0].kind {
+        StatementKind::Assign(Assign { value, .. }) => match &value.kind {
+            ExprKind::Lambda(lambda) => {
+                assert!(matches!(&lambda.body, LambdaBody::Block(body) if body.len() == 1))
+            }
+            kind => panic!("expected a lambda, found {kind:?}"),
+        },
+        kind => panic!("expected an assignment, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_collections() {
+    assert!(matches!(parse_expr("()").kind, ExprKind::Tuple(elements) if elements.is_empty()));
+    assert!(matches!(parse_expr("(a,)").kind, ExprKind::Tuple(elements) if elements.len() == 1));
+    assert!(matches!(parse_expr("[*a, b]").kind, ExprKind::List(elements) if elements.len() == 2));
+    assert!(matches!(parse_expr("{}").kind, ExprKind::Dict(entries) if entries.is_empty()));
+    assert!(
+        matches!(parse_expr("{a: 1, b: 2,}").kind, ExprKind::Dict(entries) if entries.len() == 2)
+    );
+    assert!(
+        matches!(parse_expr("{a, (b := 1)}").kind, ExprKind::Set(elements) if elements.len() == 2)
+    );
+    assert!(matches!(
+        parse_expr("a[1:2, ::3](b, *c, d=1, **e).f").kind,
+        ExprKind::Attribute { .. }
+    ));
+    assert!(matches!(
+        parse_expr("'a' b\"c\" rf'd'").kind,
+        ExprKind::Strings(strings) if strings.len() == 3
+    ));
+}
+
+#[test]
+fn can_parse_compound_statements() {
+    let result = parse_erased(
+        r#"
+if (a := b):
+    pass
+elif c: pass
+
+else:
+    pass
+while a if b:
+    break
+else:
+    continue
+for x, y in z if x:
+    pass
+try:
+    pass
+except E as e:
+    raise e from None
+else:
+    pass
+finally:
+    pass
+with a as b, c:
+    pass
+"#,
+    );
+
+    let kinds = result
+        .statements
+        .iter()
+        .map(|statement| &statement.kind)
+        .collect::<Vec<_>>();
+
+    assert_eq!(kinds.len(), 5);
+    assert!(matches!(
+        kinds[0],
+        StatementKind::If(If { elifs, orelse: Some(_), condition, .. })
+            if elifs.len() == 1 && matches!(condition.kind, ExprKind::NamedExpr { .. })
+    ));
+    assert!(matches!(
+        kinds[1],
+        StatementKind::While(While {
+            guard: Some(_),
+            orelse: Some(_),
+            ..
+        })
+    ));
+    assert!(matches!(
+        kinds[2],
+        StatementKind::For(For { target, guard: Some(_), .. })
+            if matches!(&target.kind, PatternKind::Sequence(names) if names.len() == 2)
+    ));
+    assert!(matches!(kinds[3], StatementKind::Try(_)));
+    assert!(matches!(kinds[4], StatementKind::With(with) if with.items.len() == 2));
+}
+
+#[test]
+fn can_parse_definitions() {
+    let result = parse_erased(
+        r#"
+@a.b(1)
+@c
+def f[T](a, /, b: int = 1, *c, d, **e) -> (T) -> [T]:
+    """Docstring."""
+    return a
+
+class A[T](B, C):
+    def g(self): pass
+"#,
+    );
+
+    match &result.statements[0].kind {
+        StatementKind::FunctionDef(function) => {
+            assert_eq!(function.decorators.len(), 2);
+            assert_eq!(function.generics.len(), 1);
+            assert_eq!(function.params.positional_only.len(), 1);
+            assert_eq!(function.params.params.len(), 1);
+            assert_eq!(function.params.keyword_only.len(), 1);
+            assert!(function.return_type.is_some());
+            assert_eq!(function.body.len(), 2);
+        }
+        kind => panic!("expected a function, found {kind:?}"),
+    }
+
+    match &result.statements[1].kind {
+        StatementKind::ClassDef(class) => {
+            assert_eq!(class.bases.len(), 2);
+            assert_eq!(class.body.len(), 1);
+        }
+        kind => panic!("expected a class, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_class_forms() {
+    let result = parse_erased(
+        r#"
+data class A[T](x: int, y: [T], z = 0)
+data class B():
+    def f(self): pass
+enum class Option[T]:
+    Some(t: T)
+    None
+
+    def unwrap(self): pass
+    Ok(T), Err(int | str),
+union class C:
+    int: i64
+abstract class D:
+    abstract def f(self)
+    def g(self) -> int
+data = abstract
+"#,
+    );
+
+    let classes = result.statements[..5]
+        .iter()
+        .map(|statement| match &statement.kind {
+            StatementKind::ClassDef(class) => class,
+            kind => panic!("expected a class, found {kind:?}"),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(classes[0].kind, ClassKind::Data);
+    assert_eq!(classes[0].fields.len(), 3);
+    assert!(classes[0].body.is_empty());
+    assert_eq!(classes[1].body.len(), 1);
+
+    assert_eq!(classes[2].kind, ClassKind::Enum);
+    assert_eq!(classes[2].body.len(), 1);
+    assert_eq!(
+        classes[2]
+            .variants
+            .iter()
+            .map(|variant| (
+                variant.name.name.as_str(),
+                variant.fields.as_ref().map(Vec::len)
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            ("Some", Some(1)),
+            ("None", None),
+            ("Ok", Some(1)),
+            ("Err", Some(1))
+        ]
+    );
+    assert!(matches!(
+        classes[2].variants[0].fields.as_deref(),
+        Some([VariantField::Named { .. }])
+    ));
+    assert!(matches!(
+        classes[2].variants[2].fields.as_deref(),
+        Some([VariantField::Positional(_)])
+    ));
+
+    assert_eq!(classes[3].kind, ClassKind::Union);
+    assert_eq!(classes[4].kind, ClassKind::Abstract);
+    match (&classes[4].body[0].kind, &classes[4].body[1].kind) {
+        (StatementKind::FunctionDef(f), StatementKind::FunctionDef(g)) => {
+            assert!(f.is_abstract && f.body.is_empty());
+            assert!(!g.is_abstract && g.body.is_empty());
+        }
+        kinds => panic!("expected methods, found {kinds:?}"),
+    }
+
+    // The modifiers are soft keywords.
+    assert!(matches!(
+        result.statements[5].kind,
+        StatementKind::Assign(_)
+    ));
+
+    // A variant names its enum in annotations.
+    let result = parse_erased("def f(self: Option.Some): pass\n");
+    match &result.statements[0].kind {
+        StatementKind::FunctionDef(function) => assert!(matches!(
+            &function.params.params[0].annotation.as_ref().unwrap().kind,
+            TypeAnnotationKind::Path(path) if path.names.len() == 2
+        )),
+        kind => panic!("expected a function, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_match_statements() {
+    let result = parse_erased(
+        r#"
+match command:
+    case [Point(x=0, y=0) | Origin(), *rest] if rest:
+        pass
+    case {"a": -1, Color.Red: _, **others}:
+        pass
+    case (a, b) | [a, b, *_] as pair:
+        pass
+    case x, 1.5, (y), 'z':
+        pass
+"#,
+    );
+
+    let cases = match &result.statements[0].kind {
+        StatementKind::Match(match_) => &match_.cases,
+        kind => panic!("expected a match, found {kind:?}"),
+    };
+
+    assert_eq!(cases.len(), 4);
+    assert!(cases[0].guard.is_some());
+
+    match &cases[0].pattern.kind {
+        PatternKind::Sequence(patterns) => {
+            assert!(matches!(&patterns[0].kind, PatternKind::Or(or) if or.len() == 2));
+            assert!(matches!(
+                &patterns[1].kind,
+                PatternKind::Rest(Some(name)) if name.name == "rest"
+            ));
+        }
+        kind => panic!("expected a sequence pattern, found {kind:?}"),
+    }
+
+    match &cases[1].pattern.kind {
+        PatternKind::Mapping { entries, rest } => {
+            assert!(matches!(entries[0].key.kind, PatternKind::Literal(_)));
+            assert!(matches!(entries[1].key.kind, PatternKind::Value(_)));
+            assert!(matches!(entries[1].value.kind, PatternKind::Wildcard));
+            assert!(rest.is_some());
+        }
+        kind => panic!("expected a mapping pattern, found {kind:?}"),
+    }
+
+    assert!(matches!(
+        &cases[2].pattern.kind,
+        PatternKind::As { pattern, .. } if matches!(pattern.kind, PatternKind::Or(_))
+    ));
+
+    match &cases[3].pattern.kind {
+        PatternKind::Sequence(patterns) => {
+            assert!(matches!(patterns[0].kind, PatternKind::Capture(_)));
+            assert!(matches!(patterns[1].kind, PatternKind::Literal(_)));
+            assert!(matches!(patterns[2].kind, PatternKind::Capture(_)));
+            assert!(matches!(patterns[3].kind, PatternKind::Literal(_)));
+        }
+        kind => panic!("expected a sequence pattern, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_match_expressions() {
+    let result = parse_erased(
+        r#"
+def to_byte(variant):
+    return match variant:
+        case Red(t): t
+        case Blue(t) | Green(t) if t: t
+    x = 1
+"#,
+    );
+
+    let body = match &result.statements[0].kind {
+        StatementKind::FunctionDef(function) => &function.body,
+        kind => panic!("expected a function, found {kind:?}"),
+    };
+
+    assert_eq!(body.len(), 2);
+    match &body[0].kind {
+        StatementKind::Return(Some(Expr {
+            kind: ExprKind::Match(match_),
+            ..
+        })) => {
+            assert_eq!(match_.cases.len(), 2);
+            assert!(matches!(
+                &match_.cases[0].pattern.kind,
+                PatternKind::Class { patterns, keywords, .. } if patterns.len() == 1 && keywords.is_empty()
+            ));
+            assert_eq!(match_.cases[1].body, name("t"));
+        }
+        kind => panic!("expected a match expression, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_declarations_and_passing_modes() {
+    let result = parse_erased(
+        "let a = ref b\nvar c: int\nconst d: str = val e.f\ndef g(ref mut h, val i, ptr j: int, mut k): pass\n",
+    );
+
+    let declarations = result.statements[..3]
+        .iter()
+        .map(|statement| match &statement.kind {
+            StatementKind::Declaration(declaration) => declaration,
+            kind => panic!("expected a declaration, found {kind:?}"),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(declarations[0].kind, DeclarationKind::Let);
+    assert_eq!(declarations[0].value, Some(unary(UnaryOp::Ref, name("b"))));
+    assert_eq!(declarations[1].kind, DeclarationKind::Var);
+    assert!(declarations[1].annotation.is_some() && declarations[1].value.is_none());
+    assert_eq!(declarations[2].kind, DeclarationKind::Const);
+    assert!(matches!(
+        declarations[2].value.as_ref().map(|value| &value.kind),
+        Some(ExprKind::UnaryOp { op: UnaryOp::Val, operand }) if matches!(operand.kind, ExprKind::Attribute { .. })
+    ));
+
+    match &result.statements[3].kind {
+        StatementKind::FunctionDef(function) => {
+            assert_eq!(
+                function
+                    .params
+                    .params
+                    .iter()
+                    .map(|param| (param.mode, param.is_mut))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (Some(PassingMode::Ref), true),
+                    (Some(PassingMode::Val), false),
+                    (Some(PassingMode::Ptr), false),
+                    (None, true),
+                ]
+            );
+        }
+        kind => panic!("expected a function, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_interfaces_type_aliases_and_bounds() {
+    let result = parse_erased(
+        r#"
+typealias PrimaryColor = Red & Green & Blue
+typealias Pair[T] = (T, T) | [T]
+interface Seq[T](Sized):
+    """A sequence."""
+    length: int
+    def get(self, index: int) -> T
+@inline
+@where(T: Seq, U: Seq & Sized,)
+def zip[T, U](a: T, b: U): pass
+@where(T: Seq)
+interface Iter[T]:
+    typealias Item = T
+    pass
+"#,
+    );
+
+    match &result.statements[0].kind {
+        StatementKind::TypeAlias(alias) => {
+            assert!(alias.generics.is_empty());
+            assert!(matches!(
+                alias.value.kind,
+                TypeAnnotationKind::Intersection(ref types) if types.len() == 3
+            ));
+        }
+        kind => panic!("expected a type alias, found {kind:?}"),
+    }
+
+    match &result.statements[1].kind {
+        StatementKind::TypeAlias(alias) => {
+            assert_eq!(alias.generics.len(), 1);
+            assert!(matches!(alias.value.kind, TypeAnnotationKind::Union(_)));
+        }
+        kind => panic!("expected a type alias, found {kind:?}"),
+    }
+
+    match &result.statements[2].kind {
+        StatementKind::InterfaceDef(interface) => {
+            assert_eq!(interface.generics.len(), 1);
+            assert_eq!(interface.bases.len(), 1);
+            assert_eq!(interface.body.len(), 3);
+        }
+        kind => panic!("expected an interface, found {kind:?}"),
+    }
+
+    match &result.statements[3].kind {
+        StatementKind::FunctionDef(function) => {
+            assert_eq!(function.decorators.len(), 1);
+            assert_eq!(
+                function
+                    .bounds
+                    .iter()
+                    .map(|bound| bound.name.name.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["T", "U"]
+            );
+        }
+        kind => panic!("expected a function, found {kind:?}"),
+    }
+
+    match &result.statements[4].kind {
+        StatementKind::InterfaceDef(interface) => {
+            assert_eq!(interface.bounds.len(), 1);
+            assert!(matches!(
+                interface.body[0].kind,
+                StatementKind::TypeAlias(_)
+            ));
+        }
+        kind => panic!("expected an interface, found {kind:?}"),
+    }
+}
+
+#[test]
+fn can_parse_macro_definitions() {
+    let result = parse_erased("macro swap(a, b,):\n    a, b = b, a\nmacro unit(): ()\n");
+
+    match &result.statements[0].kind {
+        StatementKind::MacroDef(macro_def) => {
+            assert_eq!(macro_def.name.name, "swap");
+            assert_eq!(macro_def.params.len(), 2);
+            assert_eq!(macro_def.body.len(), 1);
+        }
+        kind => panic!("expected a macro, found {kind:?}"),
+    }
+
+    assert!(matches!(
+        &result.statements[1].kind,
+        StatementKind::MacroDef(macro_def) if macro_def.params.is_empty()
+    ));
+}
+
+#[test]
+fn can_parse_imports_and_simple_statements() {
+    let result = parse_erased(
+        "import a.b as c\nfrom ..d import (e as f, g,)\nfrom . import *\nglobal a, b\nnonlocal c\nassert a, b\nreturn\n",
+    );
+
+    assert_eq!(result.statements.len(), 7);
+}
+
+#[test]
+fn can_close_blocks_at_the_end_of_code() {
+    let result = parse_erased("if a:\n    if b:\n        c");
+
+    assert_eq!(result.statements.len(), 1);
+}
+
+#[test]
+fn can_span_statements_without_trailing_layout() {
+    let result = parse("if a:\n    b\nc\n");
+
+    assert_eq!(result.statements[0].span, Span::new(0, 11));
+    assert_eq!(result.statements[1].span, Span::new(12, 13));
+}
+
+#[test]
+fn can_give_nodes_that_share_a_span_their_own_ids() {
+    let ast = Parser::parse("f((x))\n").unwrap();
+    let ids = node_ids(&ast);
+
+    // The statement spans exactly its call.
+    match &ast.statements[0].kind {
+        StatementKind::Expr(expr) => assert_eq!(expr.span, ast.statements[0].span),
+        kind => panic!("expected an expression, found {kind:?}"),
+    }
+
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+}
+
+#[test]
+fn can_reparse_only_the_edited_lines() {
+    let mut parser = IncrementalParser::new("def f():\n    x = 1\n    y = 2\nz = 3\n");
+
+    // The line of `x` in the body, which makes `f` a generator.
+    assert_eq!(reparse(&mut parser, 17, 18, "yield 1"), Span::new(9, 25));
+    // Dedenting `y` moves it out of the body, so the top level is reparsed up to `z`.
+    assert_eq!(reparse(&mut parser, 25, 29, ""), Span::new(0, 31));
+    assert_eq!(reparse(&mut parser, 35, 36, "4"), Span::new(31, 37));
+    assert_eq!(parser.code(), "def f():\n    x = yield 1\ny = 2\nz = 4\n");
+
+    // An unclosed bracket cannot be reparsed on its own.
+    assert_eq!(reparse(&mut parser, 29, 30, "(2"), Span::new(0, 38));
+    assert_eq!(reparse(&mut parser, 29, 31, "2"), Span::new(0, 37));
+}
+
+#[test]
+fn cannot_parse_invalid_code() {
+    assert_eq!(parse_error("a +"), UnexpectedEndOfCode);
+    assert_eq!(parse_error("a b"), UnexpectedToken);
+    assert_eq!(parse_error("if a:\nb"), ExpectedIndentedBlock);
+    assert_eq!(parse_error("a\n    b"), UnexpectedIndent);
+    assert_eq!(parse_error("a + b = c"), InvalidAssignmentTarget);
+    assert_eq!(parse_error("f() = c"), InvalidAssignmentTarget);
+    assert_eq!(parse_error("(a, b) += c"), InvalidAssignmentTarget);
+    assert_eq!(parse_error("def f(*a, *b): pass"), InvalidParameterOrder);
+    assert_eq!(parse_error("def f(/, a): pass"), InvalidParameterOrder);
+    assert_eq!(parse_error("*a"), InvalidRestExpression);
+    assert_eq!(parse_error("try:\n    pass\nx"), UnexpectedToken);
+    assert_eq!(parse_error("match a:\n    b"), UnexpectedToken);
+    assert_eq!(parse_error("match a: case b: pass"), ExpectedIndentedBlock);
+    assert_eq!(parse_error("match a:\n    case *b: pass"), InvalidPattern);
+    assert_eq!(
+        parse_error("match a:\n    case [*b, *c]: pass"),
+        InvalidPattern
+    );
+    assert_eq!(
+        parse_error("match a:\n    case A(b=1, c): pass"),
+        InvalidPattern
+    );
+    assert_eq!(
+        parse_error("match a:\n    case {b: 1}: pass"),
+        InvalidPattern
+    );
+    assert_eq!(parse_error("const a: int"), UnexpectedEndOfCode);
+    assert_eq!(parse_error("let a.b = 1"), UnexpectedToken);
+    assert_eq!(parse_error("def f(mut ref a): pass"), UnexpectedToken);
+    assert_eq!(parse_error("data class A"), UnexpectedEndOfCode);
+    assert_eq!(parse_error("data class A(x) y"), UnexpectedToken);
+    assert_eq!(parse_error("enum def f(): pass"), UnexpectedToken);
+    assert_eq!(parse_error("enum class A:\n    B(x) + 1"), UnexpectedToken);
+    assert_eq!(parse_error("a? = 1"), InvalidAssignmentTarget);
+    assert_eq!(parse_error("a: int??"), UnexpectedToken);
+    assert_eq!(parse_error("macro f(a.b): pass"), UnexpectedToken);
+    assert_eq!(parse_error("macro f"), UnexpectedEndOfCode);
+    assert_eq!(parse_error("typealias A"), UnexpectedEndOfCode);
+    assert_eq!(parse_error("typealias A = 1"), UnexpectedToken);
+    assert_eq!(parse_error("@where(T)\ndef f(): pass"), UnexpectedToken);
+    assert_eq!(parse_error("@where(T: A)\na = 1"), UnexpectedToken);
+    assert_eq!(
+        parse_error("interface A:\n    def f(self): pass"),
+        InvalidInterfaceMember
+    );
+    assert_eq!(
+        parse_error("interface A:\n    x: int = 1"),
+        InvalidInterfaceMember
+    );
+    assert_eq!(parse_error("await a"), AwaitOutsideAsyncFunction);
+    assert_eq!(
+        parse_error("def f():\n    await a"),
+        AwaitOutsideAsyncFunction
+    );
+    assert_eq!(
+        parse_error("async def f(): lambda: await a"),
+        AwaitOutsideAsyncFunction
+    );
+    assert_eq!(
+        parse_error("def f():\n    async for a in b: pass"),
+        AwaitOutsideAsyncFunction
+    );
+    assert_eq!(parse_error("yield a"), YieldOutsideFunction);
+    assert_eq!(
+        parse_error("def f():\n    class A:\n        yield"),
+        YieldOutsideFunction
+    );
+    assert_eq!(parse_error("def f(): a + yield b"), UnexpectedToken);
+    assert_eq!(parse_error("async a = 1"), UnexpectedToken);
+    assert_eq!(parse_error("[*a for a in b]"), InvalidRestExpression);
+    assert_eq!(parse_error("*a = b"), InvalidAssignmentTarget);
+    assert_eq!(parse_error("[*a.b] = c"), InvalidAssignmentTarget);
+    assert_eq!(parse_error("a, (b, 1) = c"), InvalidAssignmentTarget);
+    assert_eq!(parse_error("a, *b, *c = d"), InvalidPattern);
+    assert_eq!(parse_error("for a + b in c: pass"), InvalidAssignmentTarget);
+    assert_eq!(parse_error("[a for f() in b]"), InvalidAssignmentTarget);
+    assert_eq!(parse_error("f(a for a in b, c)"), UnexpectedToken);
+    assert_eq!(parse_error("f(a, b for b in c)"), UnexpectedToken);
+    assert_eq!(parse_error("[a for a in b if c else d]"), UnexpectedToken);
+    assert_eq!(
+        parse_error("def f(): [a async for a in b]"),
+        AwaitOutsideAsyncFunction
+    );
+    assert_eq!(
+        parse_error("def f(): {a: (yield) for a in b}"),
+        YieldInComprehension
+    );
+    assert_eq!(
+        parse_error("def f(): [a for a in b for c in (yield)]"),
+        YieldInComprehension
+    );
+}
+
+#[test]
+fn cannot_parse_code_nested_too_deeply() {
+    let parenthesized = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+    let chained = |depth: usize| format!("a{}", " + a".repeat(depth));
+    let nested =
+        |open: &str, close: &str| format!("{}a{}", open.repeat(10_000), close.repeat(10_000));
+    let blocks = (0..200)
+        .map(|depth| format!("{}if a:\n", "    ".repeat(depth)))
+        .collect::<String>();
+
+    parse(&parenthesized(Parser::MAX_NESTING - 1));
+    assert_eq!(
+        parse_error(&parenthesized(Parser::MAX_NESTING)),
+        NestingTooDeep
+    );
+    parse(&chained(Parser::MAX_NESTING - 1));
+    assert_eq!(parse_error(&chained(Parser::MAX_NESTING)), NestingTooDeep);
+    assert_eq!(parse_error(&nested("(", ")")), NestingTooDeep);
+    assert_eq!(parse_error(&nested("f(", ")")), NestingTooDeep);
+    assert_eq!(parse_error(&nested("[a for a in ", "]")), NestingTooDeep);
+    assert_eq!(parse_error(&nested("-", "")), NestingTooDeep);
+    assert_eq!(parse_error(&nested("not ", "")), NestingTooDeep);
+    assert_eq!(parse_error(&nested("lambda: ", "")), NestingTooDeep);
+    assert_eq!(parse_error(&nested("a if a else ", "")), NestingTooDeep);
+    assert_eq!(parse_error(&nested("a + ", "")), NestingTooDeep);
+    assert_eq!(parse_error(&nested("a or ", "")), NestingTooDeep);
+    assert_eq!(parse_error(&nested("", ".a")), NestingTooDeep);
+    assert_eq!(parse_error(&nested("", "()")), NestingTooDeep);
+    assert_eq!(
+        parse_error(&format!("a: {}", nested("list[", "]"))),
+        NestingTooDeep
+    );
+    assert_eq!(
+        parse_error(&format!("match a:\n    case {}: pass", nested("[", "]"))),
+        NestingTooDeep
+    );
+    assert_eq!(
+        parse_error(&format!("{blocks}{}pass", "    ".repeat(200))),
+        NestingTooDeep
+    );
+}
+
+mod utils {
+    use crate::ir::{
+        Ast, BinaryOp, Expr, ExprKind, Fold, Identifier, NodeId, Pattern, PatternKind,
+        StatementKind, UnaryOp, Visitor,
+    };
+    use crate::parser::{Edit, IncrementalParser, Parser, ParserError, ParserErrorKind};
+    use crate::span::Span;
+
+    struct SpanEraser;
+
+    struct NodeIds(Vec<NodeId>);
+
+    /// Erases the ids of the nodes, which differ between parses of the same code.
+    struct IdEraser;
+
+    impl Fold for SpanEraser {
+        fn fold_span(&mut self, _node: Span) -> Span {
+            Span::new(0, 0)
+        }
+    }
+
+    impl Fold for IdEraser {
+        fn fold_node_id(&mut self, _node: NodeId) -> NodeId {
+            NodeId::default()
+        }
+    }
+
+    impl Visitor for NodeIds {
+        fn visit_node_id(&mut self, node: &NodeId) {
+            self.0.push(*node);
+        }
+    }
+
+    pub(super) trait WithSpan {
+        fn with_span(self, start: u32, end: u32) -> Self;
+    }
+
+    impl WithSpan for Expr {
+        fn with_span(mut self, start: u32, end: u32) -> Self {
+            if let ExprKind::Identifier(identifier) = &mut self.kind {
+                identifier.span = Span::new(start, end);
+            }
+
+            self.span = Span::new(start, end);
+            self
+        }
+    }
+
+    pub(super) fn parse(code: &str) -> Ast {
+        IdEraser.fold_ast(Parser::parse(code).unwrap())
+    }
+
+    /// The ids of every node of the AST.
+    pub(super) fn node_ids(ast: &Ast) -> Vec<NodeId> {
+        let mut ids = NodeIds(vec![]);
+        ids.visit_ast(ast);
+        ids.0
+    }
+
+    pub(super) fn parse_erased(code: &str) -> Ast {
+        SpanEraser.fold_ast(parse(code))
+    }
+
+    /// Parses a single expression statement with its spans erased.
+    pub(super) fn parse_expr(code: &str) -> Expr {
+        match parse_erased(code).statements.remove(0).kind {
+            StatementKind::Expr(expr) => expr,
+            kind => panic!("expected an expression, found {kind:?}"),
+        }
+    }
+
+    pub(super) fn parse_error(code: &str) -> ParserErrorKind {
+        Parser::parse(code)
+            .unwrap_err()
+            .downcast::<ParserError>()
+            .unwrap()
+            .kind
+    }
+
+    /// Edits the code, checks the AST is the same as when parsing from scratch and returns the
+    /// reparsed span.
+    pub(super) fn reparse(
+        parser: &mut IncrementalParser,
+        start: u32,
+        end: u32,
+        text: &str,
+    ) -> Span {
+        let edit = Edit {
+            span: Span::new(start, end),
+            text: text.into(),
+        };
+        let ast = parser
+            .edit(&edit)
+            .ok()
+            .cloned()
+            .map(|ast| IdEraser.fold_ast(ast));
+        let parsed = Parser::parse(parser.code())
+            .ok()
+            .map(|ast| IdEraser.fold_ast(ast));
+
+        assert_eq!(ast, parsed);
+        parser.reparsed().clone()
+    }
+
+    pub(super) fn expr(kind: ExprKind) -> Expr {
+        Expr {
+            kind,
+            id: NodeId::default(),
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub(super) fn identifier(name: &str) -> Identifier {
+        Identifier {
+            name: name.into(),
+            id: NodeId::default(),
+            span: Span::new(0, 0),
+            context: None,
+        }
+    }
+
+    pub(super) fn name(name: &str) -> Expr {
+        expr(ExprKind::Identifier(identifier(name)))
+    }
+
+    pub(super) fn pattern(kind: PatternKind) -> Pattern {
+        Pattern {
+            kind,
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub(super) fn binary(lhs: Expr, op: BinaryOp, rhs: Expr) -> Expr {
+        expr(ExprKind::BinaryOp {
+            lhs: Box::new(lhs),
+            op,
+            rhs: Box::new(rhs),
+        })
+    }
+
+    pub(super) fn unary(op: UnaryOp, operand: Expr) -> Expr {
+        expr(ExprKind::UnaryOp {
+            op,
+            operand: Box::new(operand),
+        })
+    }
+}
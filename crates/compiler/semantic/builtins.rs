@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+
+use super::types::Type;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// A builtin type with the methods it implements.
+struct BuiltinType {
+    /// The names that share the methods, like the integer types.
+    names: &'static [&'static str],
+    /// The generic parameters, which stand for the generic arguments of the receiver.
+    generics: &'static [&'static str],
+    /// The methods as `(name, signature)`, where the signature leaves out the receiver and `Self`
+    /// stands for it. A method can have more than one signature.
+    methods: &'static [(&'static str, &'static str)],
+}
+
+/// The parameters and return type of a builtin method, without the receiver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Signature {
+    pub(crate) params: Vec<Type>,
+    pub(crate) returns: Type,
+}
+
+const INTEGERS: &[&str] = &[
+    "int", "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "byte",
+];
+
+const FLOATS: &[&str] = &["float", "f32", "f64"];
+
+const NUMBERS: &[(&str, &str)] = &[
+    ("__plus__", "(Self) -> Self"),
+    ("__minus__", "(Self) -> Self"),
+    ("__mul__", "(Self) -> Self"),
+    ("__div__", "(Self) -> float"),
+    ("__intdiv__", "(Self) -> Self"),
+    ("__mod__", "(Self) -> Self"),
+    ("__pow__", "(Self) -> Self"),
+    ("__pos__", "() -> Self"),
+    ("__neg__", "() -> Self"),
+    ("__abs__", "() -> Self"),
+    ("__square__", "() -> Self"),
+    ("__sqrt__", "() -> float"),
+    ("__lt__", "(Self) -> bool"),
+    ("__gt__", "(Self) -> bool"),
+    ("__le__", "(Self) -> bool"),
+    ("__ge__", "(Self) -> bool"),
+];
+
+const BITS: &[(&str, &str)] = &[
+    ("__and__", "(Self) -> Self"),
+    ("__or__", "(Self) -> Self"),
+    ("__xor__", "(Self) -> Self"),
+    ("__lshift__", "(Self) -> Self"),
+    ("__rshift__", "(Self) -> Self"),
+    ("__invert__", "() -> Self"),
+];
+
+/// The arithmetic of `int` with `float`, which gives a `float`.
+const MIXED: &[(&str, &str)] = &[
+    ("__plus__", "(float) -> float"),
+    ("__minus__", "(float) -> float"),
+    ("__mul__", "(float) -> float"),
+    ("__div__", "(float) -> float"),
+];
+
+/// The arithmetic of `float` with `int`.
+const MIXED_FLOAT: &[(&str, &str)] = &[
+    ("__plus__", "(int) -> float"),
+    ("__minus__", "(int) -> float"),
+    ("__mul__", "(int) -> float"),
+    ("__div__", "(int) -> float"),
+    ("__pow__", "(int) -> float"),
+];
+
+const BUILTIN_TYPES: &[BuiltinType] = &[
+    BuiltinType {
+        names: INTEGERS,
+        generics: &[],
+        methods: NUMBERS,
+    },
+    BuiltinType {
+        names: INTEGERS,
+        generics: &[],
+        methods: BITS,
+    },
+    BuiltinType {
+        names: &["int"],
+        generics: &[],
+        methods: MIXED,
+    },
+    BuiltinType {
+        names: FLOATS,
+        generics: &[],
+        methods: NUMBERS,
+    },
+    BuiltinType {
+        names: &["float"],
+        generics: &[],
+        methods: MIXED_FLOAT,
+    },
+    BuiltinType {
+        names: &["bool"],
+        generics: &[],
+        methods: &[
+            ("__and__", "(bool) -> bool"),
+            ("__or__", "(bool) -> bool"),
+            ("__xor__", "(bool) -> bool"),
+        ],
+    },
+    BuiltinType {
+        names: &["str"],
+        generics: &[],
+        methods: &[
+            ("__plus__", "(str) -> str"),
+            ("__mul__", "(int) -> str"),
+            ("__len__", "() -> int"),
+            ("__iter__", "() -> str_iterator"),
+            ("__getitem__", "(int) -> str"),
+            ("__getitem__", "(slice) -> str"),
+            ("__contains__", "(str) -> bool"),
+            ("__lt__", "(str) -> bool"),
+            ("__gt__", "(str) -> bool"),
+            ("__le__", "(str) -> bool"),
+            ("__ge__", "(str) -> bool"),
+            ("upper", "() -> str"),
+            ("lower", "() -> str"),
+            ("strip", "() -> str"),
+            ("split", "() -> list[str]"),
+            ("split", "(str) -> list[str]"),
+            ("join", "(list[str]) -> str"),
+            ("startswith", "(str) -> bool"),
+            ("endswith", "(str) -> bool"),
+            ("find", "(str) -> int"),
+            ("replace", "(str, str) -> str"),
+        ],
+    },
+    BuiltinType {
+        names: &["str_iterator"],
+        generics: &[],
+        methods: &[("__iter__", "() -> Self"), ("__next__", "() -> str")],
+    },
+    BuiltinType {
+        names: &["list"],
+        generics: &["T"],
+        methods: &[
+            ("__len__", "() -> int"),
+            ("__iter__", "() -> list_iterator[T]"),
+            ("__getitem__", "(int) -> T"),
+            ("__getitem__", "(slice) -> list[T]"),
+            ("__setitem__", "(int, T) -> None"),
+            ("__contains__", "(T) -> bool"),
+            ("__plus__", "(list[T]) -> list[T]"),
+            ("__mul__", "(int) -> list[T]"),
+            ("append", "(T) -> None"),
+            ("insert", "(int, T) -> None"),
+            ("extend", "(list[T]) -> None"),
+            ("pop", "() -> T"),
+            ("pop", "(int) -> T"),
+            ("index", "(T) -> int"),
+            ("copy", "() -> list[T]"),
+            ("clear", "() -> None"),
+            ("reverse", "() -> None"),
+            ("sort", "() -> None"),
+        ],
+    },
+    BuiltinType {
+        names: &["list_iterator"],
+        generics: &["T"],
+        methods: &[("__iter__", "() -> Self"), ("__next__", "() -> T")],
+    },
+    BuiltinType {
+        names: &["set"],
+        generics: &["T"],
+        methods: &[
+            ("__len__", "() -> int"),
+            ("__iter__", "() -> set_iterator[T]"),
+            ("__contains__", "(T) -> bool"),
+            ("__or__", "(set[T]) -> set[T]"),
+            ("__and__", "(set[T]) -> set[T]"),
+            ("__xor__", "(set[T]) -> set[T]"),
+            ("__minus__", "(set[T]) -> set[T]"),
+            ("add", "(T) -> None"),
+            ("remove", "(T) -> None"),
+            ("discard", "(T) -> None"),
+            ("clear", "() -> None"),
+        ],
+    },
+    BuiltinType {
+        names: &["set_iterator"],
+        generics: &["T"],
+        methods: &[("__iter__", "() -> Self"), ("__next__", "() -> T")],
+    },
+    BuiltinType {
+        names: &["dict"],
+        generics: &["K", "V"],
+        methods: &[
+            ("__len__", "() -> int"),
+            ("__iter__", "() -> dict_iterator[K]"),
+            ("__getitem__", "(K) -> V"),
+            ("__setitem__", "(K, V) -> None"),
+            ("__contains__", "(K) -> bool"),
+            ("get", "(K) -> V?"),
+            ("pop", "(K) -> V"),
+            ("keys", "() -> list[K]"),
+            ("values", "() -> list[V]"),
+            ("items", "() -> list[tuple[K, V]]"),
+            ("clear", "() -> None"),
+        ],
+    },
+    BuiltinType {
+        names: &["dict_iterator"],
+        generics: &["K"],
+        methods: &[("__iter__", "() -> Self"), ("__next__", "() -> K")],
+    },
+    BuiltinType {
+        names: &["tuple"],
+        generics: &[],
+        methods: &[("__len__", "() -> int")],
+    },
+];
+
+/// The methods every type has unless it defines its own.
+const OBJECT: &[(&str, &str)] = &[
+    ("__str__", "() -> str"),
+    ("__repr__", "() -> str"),
+    ("__eq__", "(object) -> bool"),
+    ("__ne__", "(object) -> bool"),
+    ("__hash__", "() -> int"),
+];
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Signature {
+    fn parse(spec: &str, bindings: &HashMap<&str, Type>) -> Self {
+        let (params, returns) = spec.split_once(" -> ").unwrap();
+        let params = params.trim_start_matches('(').trim_end_matches(')');
+        Self {
+            params: split(params)
+                .into_iter()
+                .map(|param| parse_type(param, bindings))
+                .collect(),
+            returns: parse_type(returns, bindings),
+        }
+    }
+
+    /// The return type for the types of the arguments, which is unknown while it names a generic
+    /// parameter the receiver and arguments leave open. An argument whose type is unknown fits any
+    /// parameter.
+    ///
+    /// Returns the index of the first argument that does not fit otherwise.
+    pub(crate) fn returns(&self, arguments: &[Option<Type>]) -> Result<Option<Type>, usize> {
+        let mut bindings = HashMap::new();
+        for (index, (param, argument)) in self.params.iter().zip(arguments.iter()).enumerate() {
+            if let Some(argument) = argument {
+                if !fits(param, argument, &mut bindings) {
+                    return Err(index);
+                }
+            }
+        }
+
+        Ok(substitute(&self.returns, &bindings))
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Whether a name is a builtin type with a method table.
+pub(crate) fn is_builtin(name: &str) -> bool {
+    BUILTIN_TYPES
+        .iter()
+        .any(|builtin| builtin.names.contains(&name))
+}
+
+/// Whether a builtin type is an integer type, which an `int` literal converts to.
+pub(crate) fn is_integer(name: &str) -> bool {
+    INTEGERS.contains(&name)
+}
+
+/// Whether a builtin type is copied by value, like the numbers and `bool`.
+pub(crate) fn is_primitive(name: &str) -> bool {
+    INTEGERS.contains(&name)
+        || FLOATS.contains(&name)
+        || matches!(name, "bool" | "complex" | "None")
+}
+
+/// The names of the methods of a builtin type, including the ones every type has.
+pub(crate) fn methods(name: &str) -> Vec<&'static str> {
+    let mut methods = vec![];
+    let tables = BUILTIN_TYPES
+        .iter()
+        .filter(|builtin| builtin.names.contains(&name))
+        .map(|builtin| builtin.methods)
+        .chain([OBJECT]);
+
+    for (method, _) in tables.flatten() {
+        if !methods.contains(method) {
+            methods.push(*method);
+        }
+    }
+
+    methods
+}
+
+/// The signatures of a method of a builtin type, with the generic arguments of the receiver in
+/// place of its generic parameters.
+pub(crate) fn signatures(receiver: &Type, method: &str) -> Vec<Signature> {
+    let (name, arguments) = match receiver {
+        Type::Named { name, arguments } => (name.as_str(), arguments),
+        _ => return vec![],
+    };
+
+    let mut signatures = vec![];
+    for builtin in BUILTIN_TYPES
+        .iter()
+        .filter(|builtin| builtin.names.contains(&name))
+    {
+        let mut bindings = builtin
+            .generics
+            .iter()
+            .zip(arguments.iter())
+            .map(|(generic, argument)| (*generic, argument.clone()))
+            .collect::<HashMap<_, _>>();
+        bindings.insert("Self", receiver.clone());
+
+        signatures.extend(
+            builtin
+                .methods
+                .iter()
+                .filter(|(existing, _)| *existing == method)
+                .map(|(_, spec)| Signature::parse(spec, &bindings)),
+        );
+    }
+
+    signatures
+}
+
+/// The signature of a method every type has, like `__str__`.
+pub(crate) fn object_signature(receiver: &Type, method: &str) -> Option<Signature> {
+    let bindings = HashMap::from([("Self", receiver.clone())]);
+    OBJECT
+        .iter()
+        .find(|(existing, _)| *existing == method)
+        .map(|(_, spec)| Signature::parse(spec, &bindings))
+}
+
+/// Whether a type is a generic parameter of a builtin type, which is still open.
+fn is_generic(ty: &Type) -> bool {
+    matches!(ty.name(), Some("T" | "K" | "V"))
+}
+
+/// Whether an argument fits a parameter, binding the open generic parameters it names.
+fn fits(param: &Type, argument: &Type, bindings: &mut HashMap<String, Type>) -> bool {
+    match (param, argument) {
+        (param, argument) if is_generic(param) => {
+            let name = param.name().unwrap().to_string();
+            match bindings.get(&name) {
+                Some(bound) => bound == argument,
+                None => {
+                    bindings.insert(name, argument.clone());
+                    true
+                }
+            }
+        }
+        (Type::Named { name, .. }, _) if name == "object" => true,
+        (
+            Type::Named { name, arguments },
+            Type::Named {
+                name: other,
+                arguments: others,
+            },
+        ) => {
+            // A collection literal does not know the types of its elements.
+            name == other
+                && (others.is_empty()
+                    || arguments.len() == others.len()
+                        && arguments
+                            .iter()
+                            .zip(others.iter())
+                            .all(|(param, argument)| fits(param, argument, bindings)))
+        }
+        (Type::Optional(param), argument) => {
+            argument.name() == Some("None") || fits(param, argument, bindings)
+        }
+        _ => param == argument,
+    }
+}
+
+/// A type with the bound generic parameters in place, which is unknown if it names an open one.
+fn substitute(ty: &Type, bindings: &HashMap<String, Type>) -> Option<Type> {
+    let all = |types: &[Type]| {
+        types
+            .iter()
+            .map(|ty| substitute(ty, bindings))
+            .collect::<Option<Vec<_>>>()
+    };
+
+    let ty = match ty {
+        ty if is_generic(ty) => bindings.get(ty.name().unwrap())?.clone(),
+        Type::Named { name, arguments } => Type::Named {
+            name: name.clone(),
+            arguments: all(arguments)?,
+        },
+        Type::Optional(inner) => Type::Optional(Box::new(substitute(inner, bindings)?)),
+        ty => ty.clone(),
+    };
+
+    Some(ty)
+}
+
+/// Parses the type of a signature, like `list[T]` or `V?`.
+fn parse_type(spec: &str, bindings: &HashMap<&str, Type>) -> Type {
+    if let Some(inner) = spec.strip_suffix('?') {
+        return Type::Optional(Box::new(parse_type(inner, bindings)));
+    }
+
+    let (name, arguments) = match spec.split_once('[') {
+        Some((name, rest)) => (name, split(rest.strip_suffix(']').unwrap())),
+        None => (spec, vec![]),
+    };
+
+    match bindings.get(name) {
+        Some(bound) if arguments.is_empty() => bound.clone(),
+        _ => Type::Named {
+            name: name.into(),
+            arguments: arguments
+                .into_iter()
+                .map(|argument| parse_type(argument, bindings))
+                .collect(),
+        },
+    }
+}
+
+/// Splits a list of types at the commas that are not in brackets.
+fn split(types: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, char) in types.char_indices() {
+        match char {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(types[start..index].trim());
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+
+    let last = types[start..].trim();
+    if !last.is_empty() {
+        parts.push(last);
+    }
+
+    parts
+}
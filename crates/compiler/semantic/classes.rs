@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+
+use crate::ir::{
+    Argument, Ast, ClassDef, ClassKind, ExprKind, FunctionDef, StatementKind, Variant, VariantField,
+};
+use crate::span::Span;
+
+use super::types::Type;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// A class at the top of a module, with what instantiating its methods needs to know. The
+/// definitions of a class with the same name are blocks of the one class, like the `@implements`
+/// blocks for different instantiations of an abstract class.
+#[derive(Debug, Clone)]
+pub(crate) struct Class {
+    pub(crate) name: String,
+    pub(crate) kind: ClassKind,
+    pub(crate) generics: Vec<String>,
+    /// The classes it names as bases or in `@implements` and `@inherits`, which it conforms to.
+    pub(crate) bases: Vec<String>,
+    /// The classes it names in `@implements` and `@inherits`, with their generic arguments.
+    pub(crate) conformances: Vec<Conformance>,
+    /// The primary constructor fields of a data class.
+    pub(crate) params: Vec<Field>,
+    /// The annotated fields of the body.
+    pub(crate) fields: Vec<Field>,
+    /// The methods it defines, in the order of its blocks.
+    pub(crate) methods: Vec<Method>,
+    /// The data classes an enum desugars its variants into, like `PrimaryColor.Red`.
+    pub(crate) variants: Vec<String>,
+}
+
+/// A class that a block of a class implements or inherits, like `Abstract[int]`.
+#[derive(Debug, Clone)]
+pub(crate) struct Conformance {
+    pub(crate) ty: Type,
+    /// The generic parameters of the block, like `T` for `class Foo[T]`.
+    pub(crate) generics: Vec<String>,
+    pub(crate) block: usize,
+    pub(crate) span: Span,
+}
+
+/// A method with the number of arguments it takes with the receiver.
+#[derive(Debug, Clone)]
+pub(crate) struct Method {
+    pub(crate) name: String,
+    pub(crate) arity: usize,
+    /// The number of positional arguments without a default.
+    pub(crate) required: usize,
+    pub(crate) is_variadic: bool,
+    /// Whether it is `abstract def` or a signature without a body in an abstract class.
+    pub(crate) is_abstract: bool,
+    pub(crate) block: usize,
+    pub(crate) span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Field {
+    pub(crate) name: String,
+    pub(crate) annotation: Option<Type>,
+    pub(crate) has_default: bool,
+}
+
+/// The classes of a module.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Classes {
+    classes: Vec<Class>,
+}
+
+/// Why a class does not implement an abstract method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Unimplemented {
+    /// The class has no method of the name.
+    Missing { method: String, arity: usize },
+    /// The method of the class does not take the number of arguments of the abstract one.
+    Arity {
+        method: String,
+        takes: usize,
+        expected: usize,
+    },
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Class {
+    fn new(def: &ClassDef, block: usize) -> Self {
+        let generics = def
+            .generics
+            .iter()
+            .map(|name| name.name.clone())
+            .collect::<Vec<_>>();
+
+        let mut bases = def
+            .bases
+            .iter()
+            .map(|base| base.name.clone())
+            .collect::<Vec<_>>();
+
+        let mut conformances = vec![];
+        for decorator in def.decorators.iter() {
+            let is_conformance = matches!(
+                decorator.path.names.last().map(|name| name.name.as_str()),
+                Some("implements" | "inherits")
+            );
+
+            if is_conformance {
+                for argument in decorator.arguments.iter().flatten() {
+                    if let Some(ty) = conformance_type(argument) {
+                        bases.extend(ty.name().map(String::from));
+                        conformances.push(Conformance {
+                            ty,
+                            generics: generics.clone(),
+                            block,
+                            span: decorator.span.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        let params = def
+            .fields
+            .iter()
+            .map(|field| Field {
+                name: field.name.name.clone(),
+                annotation: field.annotation.as_ref().map(Type::from_annotation),
+                has_default: field.default.is_some(),
+            })
+            .collect();
+
+        let mut fields = vec![];
+        let mut methods = vec![];
+        for statement in def.body.iter() {
+            match &statement.kind {
+                StatementKind::FunctionDef(function) => {
+                    methods.push(Method::new(function, def.kind, block))
+                }
+                StatementKind::AnnAssign(assign) => {
+                    if let ExprKind::Identifier(name) = &assign.target.kind {
+                        fields.push(Field {
+                            name: name.name.clone(),
+                            annotation: Some(Type::from_annotation(&assign.annotation)),
+                            has_default: assign.value.is_some(),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let variants = def
+            .variants
+            .iter()
+            .map(|variant| format!("{}.{}", def.name.name, variant.name.name))
+            .collect();
+
+        Self {
+            name: def.name.name.clone(),
+            kind: def.kind,
+            generics,
+            bases,
+            conformances,
+            params,
+            fields,
+            methods,
+            variants,
+        }
+    }
+
+    /// The data class of an enum variant, which takes its fields as primary constructor fields,
+    /// with `_0`, `_1` and so on for positional ones, and has the enum as its base.
+    fn variant(enum_class: &Class, variant: &Variant) -> Self {
+        let params = variant
+            .fields
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, field)| match field {
+                VariantField::Named { name, annotation } => Field {
+                    name: name.name.clone(),
+                    annotation: Some(Type::from_annotation(annotation)),
+                    has_default: false,
+                },
+                VariantField::Positional(annotation) => Field {
+                    name: format!("_{index}"),
+                    annotation: Some(Type::from_annotation(annotation)),
+                    has_default: false,
+                },
+            })
+            .collect();
+
+        Self {
+            name: format!("{}.{}", enum_class.name, variant.name.name),
+            kind: ClassKind::Data,
+            generics: enum_class.generics.clone(),
+            bases: vec![enum_class.name.clone()],
+            conformances: vec![],
+            params,
+            fields: vec![],
+            methods: vec![],
+            variants: vec![],
+        }
+    }
+
+    /// Adds another block of the class, whose bases, fields and methods the class gets too.
+    fn merge(&mut self, other: Class) {
+        for base in other.bases {
+            if !self.bases.contains(&base) {
+                self.bases.push(base);
+            }
+        }
+
+        if self.generics.is_empty() {
+            self.generics = other.generics;
+        }
+
+        if self.params.is_empty() {
+            self.params = other.params;
+        }
+
+        self.conformances.extend(other.conformances);
+        self.fields.extend(other.fields);
+        self.methods.extend(other.methods);
+    }
+
+    /// Whether a type is a generic parameter of the class.
+    pub(crate) fn is_generic(&self, ty: &Type) -> bool {
+        matches!(ty, Type::Named { name, arguments } if arguments.is_empty() && self.generics.contains(name))
+    }
+}
+
+impl Method {
+    fn new(def: &FunctionDef, kind: ClassKind, block: usize) -> Self {
+        let params = &def.params;
+        let positional = params
+            .positional_only
+            .iter()
+            .chain(params.params.iter())
+            .collect::<Vec<_>>();
+
+        Self {
+            name: def.name.name.clone(),
+            arity: positional.len(),
+            required: positional
+                .iter()
+                .filter(|param| param.default.is_none())
+                .count(),
+            is_variadic: params.vararg.is_some(),
+            is_abstract: def.is_abstract || kind == ClassKind::Abstract && def.body.is_empty(),
+            block,
+            span: def.name.span.clone(),
+        }
+    }
+
+    /// Whether the method can be called with the number of arguments of another.
+    fn takes(&self, arity: usize) -> bool {
+        self.required <= arity && (arity <= self.arity || self.is_variadic)
+    }
+}
+
+impl Classes {
+    pub(crate) fn get(&self, name: &str) -> Option<&Class> {
+        self.classes.iter().find(|class| class.name == name)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Class> {
+        self.classes.iter()
+    }
+
+    /// Whether a class is another or names it as a base, directly or through its bases.
+    pub(crate) fn conforms(&self, name: &str, to: &str) -> bool {
+        self.ancestry(name).iter().any(|class| class.name == to)
+    }
+
+    /// The class that defines a method for a class, which is the class itself or one of its bases
+    /// in the order they are named. Abstract methods have no body and no owner.
+    pub(crate) fn method_owner(&self, name: &str, method: &str) -> Option<&Class> {
+        self.ancestry(name).into_iter().find(|class| {
+            class
+                .methods
+                .iter()
+                .any(|existing| existing.name == method && !existing.is_abstract)
+        })
+    }
+
+    /// The names of the methods a class defines or gets from its bases.
+    pub(crate) fn methods(&self, name: &str) -> Vec<String> {
+        let mut methods = vec![];
+        for class in self.ancestry(name) {
+            for method in class.methods.iter() {
+                if !methods.contains(&method.name) {
+                    methods.push(method.name.clone());
+                }
+            }
+        }
+
+        methods
+    }
+
+    /// The abstract methods of an abstract class and its bases that a class does not implement
+    /// with the same number of arguments. The class implements the ones it defines or gets from
+    /// its other bases.
+    pub(crate) fn unimplemented(&self, name: &str, abstract_class: &str) -> Vec<Unimplemented> {
+        let required = self
+            .ancestry(abstract_class)
+            .into_iter()
+            .filter(|class| class.kind == ClassKind::Abstract)
+            .flat_map(|class| class.methods.iter())
+            .filter(|method| method.is_abstract)
+            .collect::<Vec<_>>();
+
+        let provided = self
+            .ancestry(name)
+            .into_iter()
+            .flat_map(|class| class.methods.iter())
+            .filter(|method| !method.is_abstract)
+            .collect::<Vec<_>>();
+
+        let mut unimplemented = vec![];
+        for method in required {
+            let candidates = provided
+                .iter()
+                .filter(|existing| existing.name == method.name)
+                .collect::<Vec<_>>();
+
+            let issue = match candidates.first() {
+                None => Unimplemented::Missing {
+                    method: method.name.clone(),
+                    arity: method.arity,
+                },
+                Some(_)
+                    if candidates
+                        .iter()
+                        .any(|existing| existing.takes(method.arity)) =>
+                {
+                    continue
+                }
+                Some(existing) => Unimplemented::Arity {
+                    method: method.name.clone(),
+                    takes: existing.arity,
+                    expected: method.arity,
+                },
+            };
+
+            if !unimplemented.contains(&issue) {
+                unimplemented.push(issue);
+            }
+        }
+
+        unimplemented
+    }
+
+    /// Whether a class implements an abstract class, by naming it or by having its methods.
+    pub(crate) fn implements(&self, name: &str, abstract_class: &str) -> bool {
+        let is_abstract = self
+            .get(abstract_class)
+            .is_some_and(|class| class.kind == ClassKind::Abstract);
+
+        self.conforms(name, abstract_class)
+            || is_abstract
+                && self.get(name).is_some()
+                && self.unimplemented(name, abstract_class).is_empty()
+    }
+
+    /// The intersection of the variants an enum stands for, like `PrimaryColor.Red &
+    /// PrimaryColor.Green` for `PrimaryColor`.
+    pub(crate) fn alias(&self, name: &str) -> Option<Type> {
+        match self.get(name) {
+            Some(class) if class.kind == ClassKind::Enum => {
+                Type::intersection(class.variants.iter().map(Type::named))
+            }
+            _ => None,
+        }
+    }
+
+    /// A type with the enums it names replaced by the intersections of their variants.
+    pub(crate) fn expand(&self, ty: &Type) -> Type {
+        let all = |types: &[Type]| types.iter().map(|ty| self.expand(ty)).collect();
+        match ty {
+            Type::Named { name, arguments } => match self.alias(name) {
+                Some(alias) => alias,
+                None => Type::Named {
+                    name: name.clone(),
+                    arguments: all(arguments),
+                },
+            },
+            Type::Function {
+                params,
+                return_type,
+            } => Type::Function {
+                params: all(params),
+                return_type: Box::new(self.expand(return_type)),
+            },
+            Type::List(types) => Type::List(all(types)),
+            Type::Tuple(types) => Type::Tuple(all(types)),
+            Type::Union(types) => Type::Union(all(types)),
+            Type::Intersection(types) => Type::Intersection(all(types)),
+            Type::Optional(inner) => Type::Optional(Box::new(self.expand(inner))),
+        }
+    }
+
+    /// The class and its bases, depth first without repeats.
+    fn ancestry(&self, name: &str) -> Vec<&Class> {
+        let mut classes = vec![];
+        let mut pending = vec![name];
+        while let Some(name) = pending.pop() {
+            let class = match self.get(name) {
+                Some(class) if !classes.iter().any(|seen: &&Class| seen.name == name) => class,
+                _ => continue,
+            };
+
+            classes.push(class);
+            pending.extend(class.bases.iter().rev().map(String::as_str));
+        }
+
+        classes
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Collects the classes at the top of a module, with a data class for each variant of an enum.
+/// The definitions of a class with the same name are merged as its blocks.
+pub(crate) fn collect_classes(ast: &Ast) -> Classes {
+    let mut classes: Vec<Class> = vec![];
+    let mut blocks = HashMap::<String, usize>::new();
+    for statement in ast.statements.iter() {
+        if let StatementKind::ClassDef(def) = &statement.kind {
+            let block = blocks.entry(def.name.name.clone()).or_default();
+            let class = Class::new(def, *block);
+            *block += 1;
+
+            let variants = def
+                .variants
+                .iter()
+                .map(|variant| Class::variant(&class, variant))
+                .collect::<Vec<_>>();
+
+            match classes
+                .iter_mut()
+                .find(|existing| existing.name == class.name)
+            {
+                Some(existing) => existing.merge(class),
+                None => classes.push(class),
+            }
+
+            classes.extend(variants);
+        }
+    }
+
+    Classes { classes }
+}
+
+/// The type an `@implements` or `@inherits` argument names, like `Abstract[int]`.
+fn conformance_type(argument: &Argument) -> Option<Type> {
+    match argument {
+        Argument::Positional(expr) => Type::from_expr(expr),
+        Argument::Keyword { .. } => None,
+    }
+}
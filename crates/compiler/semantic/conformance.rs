@@ -0,0 +1,152 @@
+use crate::ir::ClassKind;
+
+use super::classes::{Class, Classes, Conformance, Unimplemented};
+use super::errors::{SemanticError, SemanticErrorKind};
+use super::types::Type;
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Checks that the classes implement the abstract classes they name in `@implements` and
+/// `@inherits` with methods that take the same number of arguments, and that the blocks of a
+/// class do not overlap.
+///
+/// Blocks overlap when they implement the same instantiation of an abstract class, like
+/// `Abstract[T]` twice, or define a method of the same name that is not a method of abstract
+/// classes they implement for different instantiations, like `Abstract[T]` and `Abstract[int]`.
+pub(crate) fn check_conformances(classes: &Classes, diagnostics: &mut Vec<SemanticError>) {
+    for class in classes.iter() {
+        for conformance in class.conformances.iter() {
+            check_conformance(classes, class, conformance, diagnostics);
+        }
+
+        check_overlaps(classes, class, diagnostics);
+    }
+}
+
+fn check_conformance(
+    classes: &Classes,
+    class: &Class,
+    conformance: &Conformance,
+    diagnostics: &mut Vec<SemanticError>,
+) {
+    let name = match conformance.ty.name() {
+        Some(name) => name,
+        None => return,
+    };
+
+    let is_abstract = classes
+        .get(name)
+        .is_some_and(|other| other.kind == ClassKind::Abstract);
+    if !is_abstract || class.kind == ClassKind::Abstract {
+        return;
+    }
+
+    for unimplemented in classes.unimplemented(&class.name, name) {
+        let (kind, step) = match unimplemented {
+            Unimplemented::Missing { method, arity } => (
+                SemanticErrorKind::UnimplementedMethod,
+                format!("{} does not implement {method}.{arity}", class.name),
+            ),
+            Unimplemented::Arity {
+                method,
+                takes,
+                expected,
+            } => (
+                SemanticErrorKind::WrongArgumentCount,
+                format!(
+                    "{}.{method} takes {takes} arguments but {name}.{method} takes {expected}",
+                    class.name
+                ),
+            ),
+        };
+
+        let chain = vec![
+            format!("{} implements {}", class.name, conformance.ty),
+            step,
+        ];
+        diagnostics.push(SemanticError::with_chain(
+            kind,
+            conformance.span.clone(),
+            chain,
+        ));
+    }
+}
+
+fn check_overlaps(classes: &Classes, class: &Class, diagnostics: &mut Vec<SemanticError>) {
+    let conformances = &class.conformances;
+    for (index, conformance) in conformances.iter().enumerate() {
+        let erased = erase(&conformance.ty, &conformance.generics);
+        let is_repeated = conformances[..index].iter().any(|earlier| {
+            earlier.block != conformance.block && erase(&earlier.ty, &earlier.generics) == erased
+        });
+
+        if is_repeated {
+            diagnostics.push(SemanticError::with_chain(
+                SemanticErrorKind::ConflictingImplementations,
+                conformance.span.clone(),
+                vec![format!(
+                    "{} implements {} in more than one block",
+                    class.name, conformance.ty
+                )],
+            ));
+        }
+    }
+
+    let methods = &class.methods;
+    for (index, method) in methods.iter().enumerate() {
+        let is_repeated = methods[..index].iter().any(|earlier| {
+            earlier.name == method.name
+                && earlier.block != method.block
+                && !is_specialized(classes, class, &method.name, earlier.block, method.block)
+        });
+
+        if is_repeated {
+            diagnostics.push(SemanticError::with_chain(
+                SemanticErrorKind::ConflictingImplementations,
+                method.span.clone(),
+                vec![format!(
+                    "{}.{} is defined in more than one block",
+                    class.name, method.name
+                )],
+            ));
+        }
+    }
+}
+
+/// Whether two blocks define a method for different instantiations of an abstract class that has
+/// the method.
+fn is_specialized(classes: &Classes, class: &Class, method: &str, a: usize, b: usize) -> bool {
+    let of_block = |block: usize| {
+        class
+            .conformances
+            .iter()
+            .filter(move |conformance| conformance.block == block)
+    };
+
+    of_block(a).any(|first| {
+        of_block(b).any(|second| match (first.ty.name(), second.ty.name()) {
+            (Some(name), Some(other)) if name == other => classes
+                .get(name)
+                .is_some_and(|class| class.methods.iter().any(|existing| existing.name == method)),
+            _ => false,
+        })
+    })
+}
+
+/// A type with the generic parameters of a block replaced by `_`, so that `Abstract[T]` and
+/// `Abstract[U]` are the same.
+fn erase(ty: &Type, generics: &[String]) -> Type {
+    match ty {
+        Type::Named { name, arguments } if arguments.is_empty() && generics.contains(name) => {
+            Type::named("_")
+        }
+        Type::Named { name, arguments } => Type::Named {
+            name: name.clone(),
+            arguments: arguments.iter().map(|ty| erase(ty, generics)).collect(),
+        },
+        Type::Tuple(types) => Type::Tuple(types.iter().map(|ty| erase(ty, generics)).collect()),
+        ty => ty.clone(),
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::{self, Display};
+
+use super::types::Type;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// A container literal of the module, like `[StringGiver(), IntGiver()]`, with the types its
+/// elements are stored as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Container {
+    /// The type of the container with the types of its elements, like
+    /// `list[StringGiver & IntGiver]`.
+    pub ty: Type,
+    /// The types its elements are stored as, one for each generic argument of the container, like
+    /// the keys and the values of a dict.
+    pub elements: Vec<Element>,
+}
+
+/// How a container stores its elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Element {
+    /// Elements of one concrete type, stored as `T`.
+    Mono(Type),
+    /// Elements of more than one concrete type, dispatched dynamically.
+    Dyn(Dyn),
+}
+
+/// A dynamically dispatched element type, like `dyn gift.1` for elements that are `StringGiver`
+/// or `IntGiver` and only have `gift` called on them. It is `dyn _` before any method is called.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dyn {
+    /// The concrete types behind it.
+    pub types: Vec<Type>,
+    /// The methods the module calls on the elements, with the number of arguments they take with
+    /// the receiver.
+    pub methods: Vec<(String, usize)>,
+}
+
+/// The container literals of a module, whose element types are resolved from the calls all the
+/// instances make on the elements.
+#[derive(Debug, Clone, Default)]
+pub struct Containers {
+    containers: Vec<Container>,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Containers {
+    pub fn iter(&self) -> impl Iterator<Item = &Container> {
+        self.containers.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.containers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+    }
+
+    /// Adds a container of a type like `list[int & str]`, once for each type.
+    pub(crate) fn add(&mut self, ty: &Type) {
+        let arguments = match ty {
+            Type::Named { name, arguments } if name != "tuple" && !arguments.is_empty() => {
+                arguments
+            }
+            _ => return,
+        };
+
+        if self.containers.iter().any(|container| container.ty == *ty) {
+            return;
+        }
+
+        let elements = arguments
+            .iter()
+            .map(|argument| match argument.variants().as_slice() {
+                [ty] => Element::Mono((*ty).clone()),
+                types => Element::Dyn(Dyn {
+                    types: types.iter().map(|ty| (*ty).clone()).collect(),
+                    methods: vec![],
+                }),
+            })
+            .collect();
+
+        self.containers.push(Container {
+            ty: ty.clone(),
+            elements,
+        });
+    }
+
+    /// Records a method called on a receiver, which the elements of the containers whose concrete
+    /// types are the variants of the receiver must dispatch dynamically. The containers of the same
+    /// concrete types share the methods.
+    pub(crate) fn call(&mut self, receiver: &Type, method: &str, arity: usize) {
+        let variants = receiver.variants();
+        let elements = self
+            .containers
+            .iter_mut()
+            .flat_map(|container| container.elements.iter_mut());
+
+        for element in elements {
+            if let Element::Dyn(dyn_type) = element {
+                let is_same = dyn_type.types.len() == variants.len()
+                    && variants
+                        .iter()
+                        .all(|variant| dyn_type.types.contains(variant));
+                let method = (method.to_string(), arity);
+                if is_same && !dyn_type.methods.contains(&method) {
+                    dyn_type.methods.push(method);
+                }
+            }
+        }
+    }
+}
+
+impl Display for Container {
+    /// The container as its elements are stored, like `list[dyn gift.1]` for
+    /// `list[StringGiver & IntGiver]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.ty.name().unwrap_or_default();
+        let elements = self
+            .elements
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "{name}[{elements}]")
+    }
+}
+
+impl Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mono(ty) => write!(f, "{ty}"),
+            Self::Dyn(dyn_type) => write!(f, "{dyn_type}"),
+        }
+    }
+}
+
+impl Display for Dyn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.methods.is_empty() {
+            return write!(f, "dyn _");
+        }
+
+        let methods = self
+            .methods
+            .iter()
+            .map(|(name, arity)| format!("{name}.{arity}"))
+            .collect::<Vec<_>>()
+            .join(" + ");
+
+        write!(f, "dyn {methods}")
+    }
+}
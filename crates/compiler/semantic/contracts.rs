@@ -0,0 +1,365 @@
+use std::fmt::{self, Display};
+
+use crate::ir::NodeId;
+use crate::span::Span;
+
+use super::types::Type;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// The interface contract of a function, which its body derives from how it uses its arguments.
+///
+/// `def add(a, b): return a + b` has the contract
+/// `[T: impl __plus__.2](a: any T.__plus__.0, b: any T.__plus__.1, @returns T.__plus__)`. `T` is
+/// the type of `a`, which must have a `__plus__` method that takes 2 arguments, and `b` must fit
+/// the second argument of it. Each position a value is passed at, and each field read from or
+/// written to it, adds to what the value must be. The values returned by methods can be passed
+/// on in turn, which gives `@where` chains like `T.gift returns any U.__iter__.0`.
+///
+/// The contract is displayed in that notation.
+#[derive(Debug, Clone)]
+pub struct Contract {
+    /// The name of the function, after the names of the classes it is a method of, like
+    /// `Point.__plus__`.
+    pub name: String,
+    /// The name of the function definition.
+    pub id: NodeId,
+    /// Where the name of the function definition is.
+    pub span: Span,
+    /// The types the contract talks about, which are the unknown types of the values the function
+    /// calls methods on, its generic parameters, the known types and the functions it calls.
+    pub interfaces: Vec<Interface>,
+    pub values: Vec<Value>,
+    pub params: Vec<ContractParam>,
+    /// The values the function can return, or its return annotation.
+    pub returns: Vec<ValueId>,
+    /// The values the function returns, which a return annotation stands in for in
+    /// [`Contract::returns`].
+    pub returned: Vec<ValueId>,
+    /// The calls the function makes, in the order it makes them.
+    pub calls: Vec<Call>,
+    /// The `cast[T]`, `isinstance` checks and class patterns that narrow values.
+    pub casts: Vec<Cast>,
+    /// The values assigned to names with annotations, which must fit them.
+    pub annotated: Vec<Annotated>,
+    /// The literals and instances the function makes, in the order it makes them.
+    pub allocations: Vec<Allocation>,
+}
+
+/// The index of an interface in a [`Contract`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InterfaceId(pub usize);
+
+/// The index of a value in a [`Contract`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValueId(pub usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interface {
+    pub name: String,
+    pub kind: InterfaceKind,
+    /// The methods the function calls on the type, with the number of arguments they take
+    /// including the receiver.
+    pub methods: Vec<Method>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterfaceKind {
+    /// The unknown type of a value, named `T`, `U` and so on.
+    Inferred,
+    /// A generic parameter of the function.
+    Generic,
+    /// A type the function names, like the annotation of a parameter or the class it constructs.
+    Concrete(Type),
+    /// A function of the module that the function calls, whose arguments are positions like
+    /// `add.0`.
+    Function,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Method {
+    pub name: String,
+    pub arity: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractParam {
+    pub name: String,
+    pub kind: ParamKind,
+    /// The value of the default of the parameter, which an omitted argument takes the type of.
+    pub default: Option<ValueId>,
+    pub value: ValueId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Positional,
+    /// A parameter after `*`, which is only passed by keyword.
+    Keyword,
+    /// `*name`
+    Vararg,
+    /// `**name`
+    Kwarg,
+}
+
+/// A value the function handles, with what it must be.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Value {
+    pub origin: Origin,
+    /// The type of the value, once the function calls a method on it or if it is known.
+    pub interface: Option<InterfaceId>,
+    /// The arguments of methods the value is passed as.
+    pub positions: Vec<Position>,
+    /// The fields the function reads from or writes to the value.
+    pub fields: Vec<(String, ValueId)>,
+    /// The elements of a list or set literal, or the keys and values in turn of a dict literal.
+    pub elements: Vec<ValueId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// The parameter at an index of [`Contract::params`].
+    Param(usize),
+    /// The value a method of an interface returns, or a function for an empty method name.
+    Returned(InterfaceId, String),
+    /// A field of another value.
+    Field(ValueId, String),
+    /// A literal or an instance the function constructs.
+    Literal(Type),
+    /// Another value narrowed to a type by a `cast[T]`, an `isinstance` check or a class pattern.
+    Narrowed(ValueId, Type),
+    /// Another value assigned to a name with an annotation, like `y: int & str = value`, which has
+    /// the type of the annotation.
+    Annotated(ValueId, Type),
+    /// A value the contract says nothing about, like a global.
+    Unknown,
+}
+
+/// A literal or an instance that an expression makes, like `"John"` or `Person(name, age)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Allocation {
+    pub value: ValueId,
+    /// The code of the expression.
+    pub code: String,
+    /// Whether the code puts it in a `Box` itself.
+    pub is_boxed: bool,
+    pub span: Span,
+}
+
+/// A value narrowed from another, which a `cast[T]` requires to be able to have the type it is
+/// narrowed to. An `isinstance` check or class pattern that cannot succeed only never runs its
+/// code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cast {
+    /// The narrowed value, whose origin is [`Origin::Narrowed`].
+    pub value: ValueId,
+    /// Whether it is an `isinstance` check or a class pattern rather than a `cast[T]`.
+    pub is_check: bool,
+    pub span: Span,
+}
+
+/// A value assigned to a name with an annotation, which an instantiation checks against the
+/// annotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotated {
+    /// The value of the name, whose origin is [`Origin::Annotated`].
+    pub value: ValueId,
+    /// The code of the name, like `port` or `self.port`.
+    pub name: String,
+    pub span: Span,
+}
+
+/// A call of a method or function, which an instantiation checks with the types of its arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub interface: InterfaceId,
+    /// The name of the method, which is empty for a function.
+    pub method: String,
+    /// The positional arguments, after the receiver for a method.
+    pub arguments: Vec<ValueId>,
+    pub keywords: Vec<(String, ValueId)>,
+    pub result: ValueId,
+    pub span: Span,
+}
+
+/// The argument at an index of a method of an interface, where the receiver is at 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    pub interface: InterfaceId,
+    pub method: String,
+    pub index: usize,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Contract {
+    pub fn interface(&self, id: InterfaceId) -> &Interface {
+        &self.interfaces[id.0]
+    }
+
+    pub fn value(&self, id: ValueId) -> &Value {
+        &self.values[id.0]
+    }
+
+    pub fn param(&self, name: &str) -> Option<&ContractParam> {
+        self.params.iter().find(|param| param.name == name)
+    }
+
+    /// The `T.m.i` or `f.i` notation of a position.
+    pub fn position(&self, position: &Position) -> String {
+        let interface = self.interface(position.interface);
+        match interface.kind {
+            InterfaceKind::Function => format!("{}.{}", interface.name, position.index),
+            _ => format!("{}.{}.{}", interface.name, position.method, position.index),
+        }
+    }
+
+    /// What a value must be, like `any T.__plus__.0` or `any { name: T.__str__.0 }`.
+    pub fn describe(&self, id: ValueId) -> String {
+        self.describe_value(id, true)
+    }
+
+    /// The name the contract gives a value, like the parameter it comes from or `T.__plus__` for
+    /// the value a method returns.
+    pub fn reference(&self, id: ValueId) -> String {
+        match &self.value(id).origin {
+            Origin::Param(index) => self.params[*index].name.clone(),
+            Origin::Returned(interface, method) => {
+                let interface = self.interface(*interface);
+                match interface.kind {
+                    InterfaceKind::Function => interface.name.clone(),
+                    _ => format!("{}.{method}", interface.name),
+                }
+            }
+            Origin::Field(value, name) => format!("{}.{name}", self.reference(*value)),
+            Origin::Literal(ty) => ty.to_string(),
+            Origin::Narrowed(value, ty) => format!("cast[{ty}]({})", self.reference(*value)),
+            Origin::Annotated(_, ty) => ty.to_string(),
+            Origin::Unknown => "any".into(),
+        }
+    }
+
+    /// The values methods return that the function passes on, as `T.m returns any U.n.0`.
+    pub fn chains(&self) -> Vec<String> {
+        self.values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| {
+                matches!(value.origin, Origin::Returned(..))
+                    && (!value.positions.is_empty() || !value.fields.is_empty())
+            })
+            .map(|(index, _)| {
+                format!(
+                    "{} returns {}",
+                    self.reference(ValueId(index)),
+                    self.describe(ValueId(index))
+                )
+            })
+            .collect()
+    }
+
+    fn describe_value(&self, id: ValueId, is_top_level: bool) -> String {
+        let value = self.value(id);
+        if let Some(interface) = value.interface {
+            let interface = self.interface(interface);
+            if let InterfaceKind::Concrete(_) | InterfaceKind::Generic = interface.kind {
+                return interface.name.clone();
+            }
+        }
+
+        let mut parts = value
+            .positions
+            .iter()
+            .map(|position| self.position(position))
+            .collect::<Vec<_>>();
+
+        if !value.fields.is_empty() {
+            let fields = value
+                .fields
+                .iter()
+                .map(|(name, field)| format!("{name}: {}", self.describe_value(*field, false)))
+                .collect::<Vec<_>>();
+            parts.push(format!("{{ {} }}", fields.join(", ")));
+        }
+
+        // Fields are values too, but only the top-level values say so.
+        match (parts.is_empty(), is_top_level) {
+            (true, _) => "any".into(),
+            (false, true) => format!("any {}", parts.join(" + ")),
+            (false, false) => parts.join(" + "),
+        }
+    }
+}
+
+impl Display for Contract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let generics = self
+            .interfaces
+            .iter()
+            .filter(|interface| {
+                matches!(
+                    interface.kind,
+                    InterfaceKind::Inferred | InterfaceKind::Generic
+                )
+            })
+            .map(|interface| match interface.methods.is_empty() {
+                true => interface.name.clone(),
+                false => format!(
+                    "{}: impl {}",
+                    interface.name,
+                    join_methods(&interface.methods)
+                ),
+            })
+            .collect::<Vec<_>>();
+
+        if !generics.is_empty() {
+            write!(f, "[{}]", generics.join(", "))?;
+        }
+
+        let mut entries = self
+            .params
+            .iter()
+            .map(|param| {
+                let prefix = match param.kind {
+                    ParamKind::Vararg => "*",
+                    ParamKind::Kwarg => "**",
+                    _ => "",
+                };
+
+                format!("{prefix}{}: {}", param.name, self.describe(param.value))
+            })
+            .collect::<Vec<_>>();
+
+        let chains = self.chains();
+        if !chains.is_empty() {
+            entries.push(format!("@where [{}]", chains.join(", ")));
+        }
+
+        if !self.returns.is_empty() {
+            let returns = self
+                .returns
+                .iter()
+                .map(|value| self.reference(*value))
+                .collect::<Vec<_>>();
+            entries.push(format!("@returns {}", returns.join(" & ")));
+        }
+
+        write!(f, "({})", entries.join(", "))
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+fn join_methods(methods: &[Method]) -> String {
+    methods
+        .iter()
+        .map(|method| format!("{}.{}", method.name, method.arity))
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
@@ -0,0 +1,102 @@
+use std::fmt::Display;
+
+use crate::span::Span;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SemanticErrorKind {
+    /// A name that no enclosing scope binds and that is not a builtin.
+    UndefinedName,
+    /// A name used in its scope before any of the bindings of that scope take effect, like `x` in
+    /// `print(x); x = 1`.
+    UsedBeforeDefinition,
+    /// A `nonlocal` name that no enclosing function binds, or a `nonlocal` outside of functions.
+    InvalidNonlocal,
+    /// A `global` or `nonlocal` name that its scope already used or bound, like a parameter.
+    DeclarationAfterUse,
+    /// A binding of a builtin name like `list`, which hides the builtin in its scope. Only a
+    /// warning.
+    ShadowedBuiltin,
+    /// A method that an instantiation calls on a type that does not implement it, like `__iter__`
+    /// on the `int` that `IntGiver.gift` returns.
+    UnimplementedMethod,
+    /// An argument whose type does not fit the annotation of its parameter or the signature of a
+    /// builtin method.
+    MismatchedArgument,
+    /// A call with more arguments than the function takes, or without one it needs.
+    WrongArgumentCount,
+    /// A field that an instantiation reads from a class that does not have it.
+    MissingField,
+    /// A method called on an intersection type that a combination of its variants and the variants
+    /// of the arguments does not fit, like `__plus__` on `int & str` with `int & str`.
+    IncompatibleVariants,
+    /// Blocks of a class that implement the same instantiation of an abstract class, or that
+    /// define the same method outside of implementations for different instantiations.
+    ConflictingImplementations,
+    /// A `cast[T]`, `isinstance` check or class pattern that can never succeed, like `cast[float]`
+    /// of an `int & str`.
+    ImpossibleCast,
+    /// A value assigned to a name whose type does not fit the annotation of the name, like `"a"`
+    /// in `y: int = "a"`.
+    MismatchedAnnotation,
+    /// A class that holds itself inline in every instance through its fields, like
+    /// `data class Loop(next: Loop)`, which no `Box` can make finite as no instance can be made.
+    InfiniteSize,
+    /// A field whose value holds a reference to the instance it is a field of, like
+    /// `self.module = Module(self)`, which no `Box` of a field keeps from dangling.
+    SelfReference,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemanticError {
+    pub(crate) kind: SemanticErrorKind,
+    pub(crate) span: Span,
+    /// The steps that lead to an error found while instantiating a function, from the call in the
+    /// module to the failing call, like `giver: IntGiver` and `T.gift returns int`.
+    pub(crate) chain: Vec<String>,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl SemanticErrorKind {
+    /// Whether the diagnostic lets compilation go on.
+    pub fn is_warning(&self) -> bool {
+        matches!(self, Self::ShadowedBuiltin)
+    }
+}
+
+impl SemanticError {
+    pub fn new(kind: SemanticErrorKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            chain: vec![],
+        }
+    }
+
+    pub fn with_chain(kind: SemanticErrorKind, span: Span, chain: Vec<String>) -> Self {
+        Self { kind, span, chain }
+    }
+
+    /// Whether the diagnostic lets compilation go on.
+    pub fn is_warning(&self) -> bool {
+        self.kind.is_warning()
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+impl Display for SemanticError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SemanticError")
+            .field("kind", &self.kind)
+            .field("span", &self.span)
+            .field("chain", &self.chain)
+            .finish()
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display};
+
+use crate::ir::{fold, Argument, Ast, Expr, ExprKind, Fold, Identifier, NodeId};
+use crate::span::Span;
+use crate::unparser::Unparser;
+
+use super::builtins::is_primitive;
+use super::contracts::{Contract, InterfaceKind, Origin, ValueId};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// Where an allocation lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Storage {
+    /// A primitive, which is copied wherever it goes.
+    Value,
+    /// The frame of the function that makes it, which outlives every use of it.
+    Stack,
+    /// A `Box`, as a value that outlives the function captures it.
+    Heap,
+}
+
+/// Where an allocation of a function lives, with why.
+///
+/// `name = "John"` in `def get_person(): ...; return Person(name, age)` is on the heap, as the
+/// `Person` it is captured by is returned and outlives the frame of `get_person`. The code gets
+/// `name = Box("John")` for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    /// The name of the function, or `<module>` for the code at the top of the module.
+    pub function: String,
+    /// The code of the allocation, like `"John"` or `Person(name, age)`.
+    pub code: String,
+    pub span: Span,
+    pub storage: Storage,
+    /// Why it lives there, like `returned by get_person`.
+    pub reason: String,
+    /// Whether the code puts it in a `Box` itself, so the compiler does not have to.
+    pub is_boxed: bool,
+}
+
+/// The allocations of a module with where they live, in the order of the contracts.
+#[derive(Debug, Clone, Default)]
+pub struct Placements {
+    placements: Vec<Placement>,
+}
+
+/// The parameters of a function with whether the function lets them outlive its call, by
+/// returning them or storing them in other parameters.
+type Summary = Vec<(String, bool)>;
+
+/// Finds the values of a contract that outlive the call of its function, starting from the values
+/// it returns and its parameters.
+struct EscapeAnalyzer<'a> {
+    contract: &'a Contract,
+    summaries: &'a HashMap<String, Summary>,
+    /// Why each value outlives the call.
+    reasons: Vec<Option<String>>,
+    /// Why each value is captured by a value that outlives the call, which a value the function
+    /// only returns is not, as it is copied to the caller.
+    captures: Vec<Option<String>>,
+    /// Whether each parameter is returned or captured by a value that outlives the call.
+    escaping: Vec<bool>,
+    queue: VecDeque<ValueId>,
+}
+
+/// Wraps the allocations that must live on the heap in a `Box`.
+struct Boxer<'a> {
+    placements: &'a [&'a Placement],
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Placements {
+    pub fn iter(&self) -> impl Iterator<Item = &Placement> {
+        self.placements.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.placements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.placements.is_empty()
+    }
+
+    /// The allocations that must live on the heap and that the code does not box itself.
+    pub fn boxed(&self) -> impl Iterator<Item = &Placement> {
+        self.placements
+            .iter()
+            .filter(|placement| placement.storage == Storage::Heap && !placement.is_boxed)
+    }
+
+    /// Wraps each allocation of a module that must live on the heap in a `Box`, like
+    /// `name = Box("John")` for `name = "John"`.
+    pub fn insert_boxes(&self, ast: Ast) -> Ast {
+        let placements = self.boxed().collect::<Vec<_>>();
+        Boxer {
+            placements: &placements,
+        }
+        .fold_ast(ast)
+    }
+}
+
+impl<'a> EscapeAnalyzer<'a> {
+    fn analyze(contract: &'a Contract, summaries: &'a HashMap<String, Summary>) -> Self {
+        let mut analyzer = Self {
+            contract,
+            summaries,
+            reasons: vec![None; contract.values.len()],
+            captures: vec![None; contract.values.len()],
+            escaping: vec![false; contract.params.len()],
+            queue: VecDeque::new(),
+        };
+
+        for value in contract.returned.iter() {
+            analyzer.outlive(*value, format!("returned by {}", contract.name), false);
+        }
+
+        // The parameters outlive the call, but only what is stored in them escapes with them.
+        for param in contract.params.iter() {
+            if analyzer.reasons[param.value.0].is_none() {
+                analyzer.reasons[param.value.0] = Some(format!("a parameter of {}", contract.name));
+                analyzer.queue.push_back(param.value);
+            }
+        }
+
+        while let Some(value) = analyzer.queue.pop_front() {
+            analyzer.propagate(value);
+        }
+
+        analyzer
+    }
+
+    fn summary(&self) -> Summary {
+        self.contract
+            .params
+            .iter()
+            .zip(self.escaping.iter())
+            .map(|(param, is_escaping)| (param.name.clone(), *is_escaping))
+            .collect()
+    }
+
+    /// Records that a value outlives the call, once, and whether a value that outlives the call
+    /// captures it.
+    fn outlive(&mut self, id: ValueId, reason: String, is_captured: bool) {
+        if let Origin::Param(index) = self.contract.value(id).origin {
+            self.escaping[index] = true;
+        }
+
+        if is_captured && self.captures[id.0].is_none() {
+            self.captures[id.0] = Some(reason.clone());
+        }
+
+        if self.reasons[id.0].is_none() {
+            self.reasons[id.0] = Some(reason);
+            self.queue.push_back(id);
+        }
+    }
+
+    /// Lets the values that a value which outlives the call holds on to outlive it too.
+    fn propagate(&mut self, id: ValueId) {
+        let contract = self.contract;
+        let value = contract.value(id);
+        let reason = self.reasons[id.0].clone().unwrap_or_default();
+        let stored = format!("stored in {}, which is {reason}", self.describe(id));
+        for (_, field) in value.fields.iter() {
+            self.outlive(*field, stored.clone(), true);
+        }
+
+        for element in value.elements.iter() {
+            self.outlive(*element, stored.clone(), true);
+        }
+
+        if let Origin::Narrowed(parent, _) | Origin::Annotated(parent, _) = value.origin {
+            let is_captured = self.captures[id.0].is_some();
+            self.outlive(parent, reason.clone(), is_captured);
+        }
+
+        for call in contract.calls.iter() {
+            let interface = contract.interface(call.interface);
+            let callee = match &interface.kind {
+                InterfaceKind::Function => Some(interface.name.clone()),
+                InterfaceKind::Concrete(ty) => Some(format!("{ty}.{}", call.method)),
+                _ => None,
+            };
+
+            let summary = callee.as_ref().and_then(|name| self.summaries.get(name));
+            let arguments = call
+                .arguments
+                .iter()
+                .enumerate()
+                .map(|(index, argument)| (*argument, summary.map(|summary| summary.get(index))))
+                .chain(call.keywords.iter().map(|(name, argument)| {
+                    let param =
+                        summary.map(|summary| summary.iter().find(|(param, _)| param == name));
+                    (*argument, param)
+                }))
+                .map(|(argument, param)| match param {
+                    // Without a body to look at, what a method is called on captures the rest.
+                    None => (argument, !call.method.is_empty()),
+                    Some(param) => (argument, param.is_some_and(|(_, is_escaping)| *is_escaping)),
+                })
+                .collect::<Vec<_>>();
+
+            let is_argument = arguments.iter().any(|(argument, _)| *argument == id);
+            let is_receiver = call.arguments.first() == Some(&id) && !call.method.is_empty();
+            let reason = match (&callee, summary) {
+                (Some(callee), Some(_)) if call.result == id => {
+                    format!("kept by the result of {callee}, which is {reason}")
+                }
+                (_, Some(_)) if is_argument => {
+                    format!("captured by {}, which is {reason}", self.describe(id))
+                }
+                (_, None) if is_receiver => {
+                    format!("captured by {}, which is {reason}", self.describe(id))
+                }
+                _ => continue,
+            };
+
+            for (argument, is_escaping) in arguments {
+                if is_escaping && argument != id {
+                    self.outlive(argument, reason.clone(), true);
+                }
+            }
+        }
+    }
+
+    /// The code of a value, like `Person(name, age)` or `self.name`.
+    fn describe(&self, id: ValueId) -> String {
+        let contract = self.contract;
+        if let Some(allocation) = contract
+            .allocations
+            .iter()
+            .find(|allocation| allocation.value == id)
+        {
+            return allocation.code.clone();
+        }
+
+        match &contract.value(id).origin {
+            Origin::Param(index) => contract.params[*index].name.clone(),
+            Origin::Field(parent, field) => format!("{}.{field}", self.describe(*parent)),
+            Origin::Narrowed(parent, _) | Origin::Annotated(parent, _) => self.describe(*parent),
+            _ => "a value".into(),
+        }
+    }
+}
+
+impl Fold for Boxer<'_> {
+    fn fold_expr(&mut self, node: Expr) -> Expr {
+        let node = fold::fold_expr(self, node);
+        let is_boxed = self.placements.iter().any(|placement| {
+            placement.span == node.span && placement.code == Unparser::unparse_expr(&node)
+        });
+
+        if !is_boxed {
+            return node;
+        }
+
+        let span = node.span.clone();
+        Expr {
+            kind: ExprKind::Call {
+                func: Box::new(Expr {
+                    kind: ExprKind::Identifier(Identifier {
+                        name: "Box".into(),
+                        id: NodeId::next(),
+                        span: span.clone(),
+                        context: None,
+                    }),
+                    id: NodeId::next(),
+                    span: span.clone(),
+                }),
+                arguments: vec![Argument::Positional(node)],
+            },
+            id: NodeId::next(),
+            span,
+        }
+    }
+}
+
+impl Display for Storage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value => write!(f, "value"),
+            Self::Stack => write!(f, "stack"),
+            Self::Heap => write!(f, "heap"),
+        }
+    }
+}
+
+impl Display for Placement {
+    /// The allocation with where it lives and why, like
+    /// `"John" -> heap, captured by Person(name, age), which is returned by get_person`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}, {}", self.code, self.storage, self.reason)
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Decides where each allocation of a module lives.
+///
+/// An allocation lives on the stack of the function that makes it unless a value that outlives
+/// the call captures it: a value the function returns or a parameter, or a field, element or
+/// constructed instance of one. What a function lets its arguments outlive it by is summarized
+/// for its callers, until the summaries of all the functions agree. The code at the top of the
+/// module lives as long as the module, and primitives are copied by value.
+pub(crate) fn place_allocations(module: &Contract, contracts: &[Contract]) -> Placements {
+    let mut summaries = HashMap::new();
+    loop {
+        let mut next = HashMap::<String, Summary>::new();
+        for contract in contracts.iter() {
+            let summary = EscapeAnalyzer::analyze(contract, &summaries).summary();
+            match next.get_mut(&contract.name) {
+                // The blocks of a class can define a method more than once.
+                Some(existing) => {
+                    for ((_, is_escaping), (_, other)) in existing.iter_mut().zip(summary) {
+                        *is_escaping |= other;
+                    }
+                }
+                None => {
+                    next.insert(contract.name.clone(), summary);
+                }
+            }
+        }
+
+        if next == summaries {
+            break;
+        }
+
+        summaries = next;
+    }
+
+    let mut placements = vec![];
+    for contract in contracts.iter().chain([module]) {
+        let analyzer = EscapeAnalyzer::analyze(contract, &summaries);
+        for allocation in contract.allocations.iter() {
+            let is_primitive = match &contract.value(allocation.value).origin {
+                Origin::Literal(ty) => ty.name().is_some_and(is_primitive),
+                _ => false,
+            };
+
+            let id = allocation.value.0;
+            let (storage, reason) = match (&analyzer.captures[id], &analyzer.reasons[id]) {
+                _ if allocation.is_boxed => (Storage::Heap, "boxed by the code".into()),
+                _ if is_primitive => (Storage::Value, "copied by value".into()),
+                _ if contract.id == module.id => {
+                    (Storage::Stack, "lives as long as the module".into())
+                }
+                (Some(reason), _) => (Storage::Heap, reason.clone()),
+                (None, Some(reason)) => (Storage::Stack, format!("{reason}, which copies it")),
+                (None, None) => (
+                    Storage::Stack,
+                    format!("does not outlive {}", contract.name),
+                ),
+            };
+
+            placements.push(Placement {
+                function: contract.name.clone(),
+                code: allocation.code.clone(),
+                span: allocation.span.clone(),
+                storage,
+                reason,
+                is_boxed: allocation.is_boxed,
+            });
+        }
+    }
+
+    Placements { placements }
+}
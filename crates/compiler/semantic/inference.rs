@@ -0,0 +1,1575 @@
+use std::collections::HashMap;
+use std::mem;
+
+use crate::ir::{
+    Argument, Ast, BinaryOp, ComparisonOp, ComprehensionFor, Expr, ExprKind, FunctionDef,
+    Identifier, NodeId, Param, Path, Pattern, PatternKind, Statement, StatementKind, StringLiteral,
+    SubscriptIndex, UnaryOp,
+};
+use crate::lexer::StringKind;
+use crate::parser::Parser;
+use crate::span::Span;
+use crate::unparser::Unparser;
+
+use super::contracts::{
+    Allocation, Annotated, Call, Cast, Contract, ContractParam, Interface, InterfaceId,
+    InterfaceKind, Method, Origin, ParamKind, Position, Value, ValueId,
+};
+use super::symbols::{Resolution, SymbolTable};
+use super::types::Type;
+use super::utils::format_placeholders;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// The names given to the unknown types of a contract, in order.
+const NAMES: &[&str] = &[
+    "T", "U", "V", "W", "X", "Y", "Z", "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L",
+    "M", "N", "O", "P", "Q", "R", "S",
+];
+
+/// The builtin functions that call a method of their only argument.
+const METHOD_BUILTINS: &[(&str, &str)] = &[
+    ("abs", "__abs__"),
+    ("hash", "__hash__"),
+    ("iter", "__iter__"),
+    ("len", "__len__"),
+    ("next", "__next__"),
+    ("repr", "__repr__"),
+    ("str", "__str__"),
+];
+
+/// The builtin types that calls construct.
+const TYPE_BUILTINS: &[&str] = &[
+    "bool",
+    "int",
+    "float",
+    "complex",
+    "bytes",
+    "list",
+    "dict",
+    "set",
+    "tuple",
+    "object",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "f32",
+    "f64",
+    "byte",
+    "Box",
+    "Exception",
+];
+
+/// What a name at the top of the module defines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Definition {
+    Function,
+    Class,
+}
+
+/// Infers the interface contract of a function from its body.
+///
+/// The walk follows the values of the function through its names. Each method called on a value
+/// whose type is unknown gives the value an inferred interface, and passes the value and the
+/// arguments at positions of the method. Operators, `for` loops, f-strings and builtins like `len`
+/// call the methods they stand for. The walk does not go into nested functions and lambdas, and
+/// leaves the globals it uses unconstrained.
+pub(crate) struct ContractInferrer<'a> {
+    symbols: &'a SymbolTable,
+    definitions: &'a HashMap<String, Definition>,
+    contract: Contract,
+    /// The value each value was merged into, which is itself for the values that were not.
+    parents: Vec<ValueId>,
+    names: HashMap<String, ValueId>,
+    /// The value each method of an interface returns, so that calling it again returns the same.
+    returned: HashMap<(InterfaceId, String), ValueId>,
+    generics: Vec<String>,
+    /// The number of names given to inferred interfaces.
+    named: usize,
+    /// Whether the walk is in a replacement field of an f-string, whose names the symbol table does
+    /// not know.
+    is_detached: bool,
+    /// Whether the walk is at the top of the module, whose functions and classes are definitions.
+    is_module: bool,
+    /// The span of the statement or expression being walked, which the calls in it are at.
+    span: Span,
+    /// The name the latest `isinstance` check narrows and the value it narrows it to.
+    narrowing: Option<(String, ValueId)>,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl<'a> ContractInferrer<'a> {
+    fn new(
+        symbols: &'a SymbolTable,
+        definitions: &'a HashMap<String, Definition>,
+        name: String,
+        id: NodeId,
+        span: Span,
+    ) -> Self {
+        Self {
+            symbols,
+            definitions,
+            parents: vec![],
+            names: HashMap::new(),
+            returned: HashMap::new(),
+            generics: vec![],
+            named: 0,
+            is_detached: false,
+            is_module: false,
+            span: span.clone(),
+            narrowing: None,
+            contract: Contract {
+                name,
+                id,
+                span,
+                interfaces: vec![],
+                values: vec![],
+                params: vec![],
+                returns: vec![],
+                returned: vec![],
+                calls: vec![],
+                casts: vec![],
+                annotated: vec![],
+                allocations: vec![],
+            },
+        }
+    }
+
+    fn infer_module(mut self, statements: &[Statement]) -> Contract {
+        self.is_module = true;
+        self.statements(statements);
+        self.contract.returns.clear();
+        self.contract.returned.clear();
+        self.finish()
+    }
+
+    /// Infers the contract of a function, whose generic parameters include the ones of the class
+    /// it is a method of.
+    fn infer_function(mut self, def: &FunctionDef, generics: &[Identifier]) -> Contract {
+        for generic in generics.iter().chain(def.generics.iter()) {
+            self.generics.push(generic.name.clone());
+            self.add_interface(generic.name.clone(), InterfaceKind::Generic);
+        }
+
+        let params = &def.params;
+        let kinds = params
+            .positional_only
+            .iter()
+            .chain(params.params.iter())
+            .map(|param| (param, ParamKind::Positional))
+            .chain(params.vararg.iter().map(|param| (param, ParamKind::Vararg)))
+            .chain(
+                params
+                    .keyword_only
+                    .iter()
+                    .map(|param| (param, ParamKind::Keyword)),
+            )
+            .chain(params.kwarg.iter().map(|param| (param, ParamKind::Kwarg)));
+
+        for (index, (param, kind)) in kinds.enumerate() {
+            self.add_param(index, param, kind);
+        }
+
+        def.body
+            .iter()
+            .for_each(|statement| self.statement(statement));
+
+        if let Some(return_type) = &def.return_type {
+            self.contract.returns = vec![self.typed(Type::from_annotation(return_type))];
+        }
+
+        self.finish()
+    }
+
+    fn add_param(&mut self, index: usize, param: &Param, kind: ParamKind) {
+        let default = param.default.as_ref().map(|default| self.expr(default));
+        let value = self.new_value(Origin::Param(index));
+        if let Some(annotation) = &param.annotation {
+            let interface = self.annotated(Type::from_annotation(annotation));
+            self.contract.values[value.0].interface = Some(interface);
+        }
+
+        self.names.insert(param.name.name.clone(), value);
+        self.contract.params.push(ContractParam {
+            name: param.name.name.clone(),
+            kind,
+            default,
+            value,
+        });
+    }
+
+    /// Merges the values that were merged and drops the ones they were merged into.
+    fn finish(mut self) -> Contract {
+        let count = self.contract.values.len();
+        let roots = (0..count)
+            .filter(|index| self.find(ValueId(*index)).0 == *index)
+            .collect::<Vec<_>>();
+
+        let mut indices = vec![0; count];
+        for (new, old) in roots.iter().enumerate() {
+            indices[*old] = new;
+        }
+
+        let ids = (0..count)
+            .map(|index| ValueId(indices[self.find(ValueId(index)).0]))
+            .collect::<Vec<_>>();
+
+        let mut values = mem::take(&mut self.contract.values);
+        let mut contract = self.contract;
+        contract.values = roots
+            .iter()
+            .map(|root| {
+                let mut value = mem::replace(&mut values[*root], Value::new(Origin::Unknown));
+                if let Origin::Field(parent, _)
+                | Origin::Narrowed(parent, _)
+                | Origin::Annotated(parent, _) = &mut value.origin
+                {
+                    *parent = ids[parent.0];
+                }
+
+                for (_, field) in value.fields.iter_mut() {
+                    *field = ids[field.0];
+                }
+
+                for element in value.elements.iter_mut() {
+                    *element = ids[element.0];
+                }
+
+                value
+            })
+            .collect();
+
+        for param in contract.params.iter_mut() {
+            param.value = ids[param.value.0];
+            param.default = param.default.map(|default| ids[default.0]);
+        }
+
+        let remap = |values: &[ValueId]| {
+            let mut remapped = vec![];
+            for value in values.iter().map(|value| ids[value.0]) {
+                if !remapped.contains(&value) {
+                    remapped.push(value);
+                }
+            }
+
+            remapped
+        };
+
+        contract.returns = remap(&contract.returns);
+        contract.returned = remap(&contract.returned);
+        for cast in contract.casts.iter_mut() {
+            cast.value = ids[cast.value.0];
+        }
+
+        for annotated in contract.annotated.iter_mut() {
+            annotated.value = ids[annotated.value.0];
+        }
+
+        for allocation in contract.allocations.iter_mut() {
+            allocation.value = ids[allocation.value.0];
+        }
+
+        for call in contract.calls.iter_mut() {
+            call.result = ids[call.result.0];
+            for argument in call.arguments.iter_mut() {
+                *argument = ids[argument.0];
+            }
+
+            for (_, argument) in call.keywords.iter_mut() {
+                *argument = ids[argument.0];
+            }
+        }
+
+        contract
+    }
+
+    fn add_interface(&mut self, name: String, kind: InterfaceKind) -> InterfaceId {
+        self.contract.interfaces.push(Interface {
+            name,
+            kind,
+            methods: vec![],
+        });
+
+        InterfaceId(self.contract.interfaces.len() - 1)
+    }
+
+    fn find_interface(&self, name: &str, kind: &InterfaceKind) -> Option<InterfaceId> {
+        self.contract
+            .interfaces
+            .iter()
+            .position(|interface| interface.name == name && interface.kind == *kind)
+            .map(InterfaceId)
+    }
+
+    fn concrete(&mut self, ty: Type) -> InterfaceId {
+        let name = ty.to_string();
+        let kind = InterfaceKind::Concrete(ty);
+        self.find_interface(&name, &kind)
+            .unwrap_or_else(|| self.add_interface(name, kind))
+    }
+
+    /// The interface of an annotation, which is a generic parameter or a concrete type.
+    fn annotated(&mut self, ty: Type) -> InterfaceId {
+        match &ty {
+            Type::Named { name, arguments }
+                if arguments.is_empty() && self.generics.contains(name) =>
+            {
+                self.find_interface(name, &InterfaceKind::Generic).unwrap()
+            }
+            _ => self.concrete(ty),
+        }
+    }
+
+    fn inferred(&mut self) -> InterfaceId {
+        loop {
+            let letter = NAMES[self.named % NAMES.len()];
+            let name = match self.named / NAMES.len() {
+                0 => letter.to_string(),
+                round => format!("{letter}{round}"),
+            };
+
+            self.named += 1;
+            if !self.generics.contains(&name) {
+                return self.add_interface(name, InterfaceKind::Inferred);
+            }
+        }
+    }
+
+    fn new_value(&mut self, origin: Origin) -> ValueId {
+        let id = ValueId(self.contract.values.len());
+        self.contract.values.push(Value::new(origin));
+        self.parents.push(id);
+        id
+    }
+
+    fn unknown(&mut self) -> ValueId {
+        self.new_value(Origin::Unknown)
+    }
+
+    fn literal(&mut self, ty: Type) -> ValueId {
+        let interface = self.concrete(ty.clone());
+        let value = self.new_value(Origin::Literal(ty));
+        self.contract.values[value.0].interface = Some(interface);
+        value
+    }
+
+    /// A value of the type of an annotation, which can be a generic parameter.
+    fn typed(&mut self, ty: Type) -> ValueId {
+        let interface = self.annotated(ty.clone());
+        let value = self.new_value(Origin::Literal(ty));
+        self.contract.values[value.0].interface = Some(interface);
+        value
+    }
+
+    fn find(&self, mut id: ValueId) -> ValueId {
+        while self.parents[id.0] != id {
+            id = self.parents[id.0];
+        }
+
+        id
+    }
+
+    fn value_mut(&mut self, id: ValueId) -> &mut Value {
+        let id = self.find(id);
+        &mut self.contract.values[id.0]
+    }
+
+    /// Whether the contract leaves a value unconstrained, as it does not come from the arguments.
+    fn is_unknown(&self, id: ValueId) -> bool {
+        self.contract.values[self.find(id).0].origin == Origin::Unknown
+    }
+
+    /// Merges two values that must be the same, keeping the origin that says the most about them.
+    fn merge(&mut self, a: ValueId, b: ValueId) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+
+        let rank = |this: &Self, id: ValueId| match this.contract.values[id.0].origin {
+            Origin::Param(_) => 0,
+            Origin::Literal(_) | Origin::Annotated(..) => 1,
+            Origin::Returned(..) => 2,
+            Origin::Field(..) | Origin::Narrowed(..) => 3,
+            Origin::Unknown => 4,
+        };
+
+        let (root, other) = match rank(self, a) <= rank(self, b) {
+            true => (a, b),
+            false => (b, a),
+        };
+
+        self.parents[other.0] = root;
+        let other = mem::replace(
+            &mut self.contract.values[other.0],
+            Value::new(Origin::Unknown),
+        );
+        let value = &mut self.contract.values[root.0];
+        value.interface = value.interface.or(other.interface);
+        for position in other.positions {
+            if !value.positions.contains(&position) {
+                value.positions.push(position);
+            }
+        }
+
+        value.elements.extend(other.elements);
+        for (name, field) in other.fields {
+            self.set_field(root, &name, field);
+        }
+    }
+
+    /// The interface of the type of a value, which is inferred if the type is not known yet.
+    fn interface_of(&mut self, value: ValueId) -> InterfaceId {
+        match self.value_mut(value).interface {
+            Some(interface) => interface,
+            None => {
+                let interface = self.inferred();
+                self.value_mut(value).interface = Some(interface);
+                interface
+            }
+        }
+    }
+
+    fn pass(&mut self, value: ValueId, interface: InterfaceId, method: &str, index: usize) {
+        let position = Position {
+            interface,
+            method: method.into(),
+            index,
+        };
+
+        let value = self.value_mut(value);
+        if !value.positions.contains(&position) {
+            value.positions.push(position);
+        }
+    }
+
+    fn returned(&mut self, interface: InterfaceId, method: &str) -> ValueId {
+        let key = (interface, method.to_string());
+        match self.returned.get(&key) {
+            Some(value) => *value,
+            None => {
+                let value = self.new_value(Origin::Returned(interface, method.into()));
+                self.returned.insert(key, value);
+                value
+            }
+        }
+    }
+
+    fn call_method(&mut self, receiver: ValueId, method: &str, arguments: &[ValueId]) -> ValueId {
+        self.call_method_with_keywords(receiver, method, arguments, vec![])
+    }
+
+    fn call_method_with_keywords(
+        &mut self,
+        receiver: ValueId,
+        method: &str,
+        arguments: &[ValueId],
+        keywords: Vec<(String, ValueId)>,
+    ) -> ValueId {
+        if self.is_unknown(receiver) {
+            return self.unknown();
+        }
+
+        let interface = self.interface_of(receiver);
+        let arity = arguments.len() + 1;
+        let methods = &mut self.contract.interfaces[interface.0].methods;
+        if !methods
+            .iter()
+            .any(|existing| existing.name == method && existing.arity == arity)
+        {
+            methods.push(Method {
+                name: method.into(),
+                arity,
+            });
+        }
+
+        self.pass(receiver, interface, method, 0);
+        for (index, argument) in arguments.iter().enumerate() {
+            self.pass(*argument, interface, method, index + 1);
+        }
+
+        let result = self.returned(interface, method);
+        let mut arguments = arguments.to_vec();
+        arguments.insert(0, receiver);
+        self.add_call(interface, method, arguments, keywords, result);
+        result
+    }
+
+    /// Calls a function of the module, which has an interface for each call as each one can
+    /// instantiate it differently.
+    fn call_function(
+        &mut self,
+        name: &str,
+        arguments: &[ValueId],
+        keywords: Vec<(String, ValueId)>,
+    ) -> ValueId {
+        let interface = self.add_interface(name.into(), InterfaceKind::Function);
+        for (index, argument) in arguments.iter().enumerate() {
+            self.pass(*argument, interface, "", index);
+        }
+
+        let result = self.returned(interface, "");
+        self.add_call(interface, "", arguments.to_vec(), keywords, result);
+        result
+    }
+
+    /// Constructs a class, whose `__init__` takes the arguments after the instance.
+    fn construct(
+        &mut self,
+        class: &str,
+        arguments: &[ValueId],
+        keywords: Vec<(String, ValueId)>,
+    ) -> ValueId {
+        let instance = self.literal(Type::named(class));
+        self.call_method_with_keywords(instance, "__init__", arguments, keywords);
+        instance
+    }
+
+    fn add_call(
+        &mut self,
+        interface: InterfaceId,
+        method: &str,
+        arguments: Vec<ValueId>,
+        keywords: Vec<(String, ValueId)>,
+        result: ValueId,
+    ) {
+        self.contract.calls.push(Call {
+            interface,
+            method: method.into(),
+            arguments,
+            keywords,
+            result,
+            span: self.span.clone(),
+        });
+    }
+
+    /// Iterates a value, which returns the value of `__next__` on the value of its `__iter__`.
+    fn iterate(&mut self, value: ValueId) -> ValueId {
+        let iterator = self.call_method(value, "__iter__", &[]);
+        self.call_method(iterator, "__next__", &[])
+    }
+
+    fn field(&mut self, value: ValueId, name: &str) -> ValueId {
+        if self.is_unknown(value) {
+            return self.unknown();
+        }
+
+        let parent = self.find(value);
+        let existing = self.contract.values[parent.0]
+            .fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, field)| *field);
+
+        existing.unwrap_or_else(|| {
+            let field = self.new_value(Origin::Field(parent, name.into()));
+            self.contract.values[parent.0]
+                .fields
+                .push((name.into(), field));
+            field
+        })
+    }
+
+    /// Writes a field, which replaces the value it had unless only reads gave it one.
+    fn set_field(&mut self, value: ValueId, name: &str, field: ValueId) {
+        if self.is_unknown(value) {
+            return;
+        }
+
+        let parent = self.find(value);
+        let index = self.contract.values[parent.0]
+            .fields
+            .iter()
+            .position(|(existing, _)| existing == name);
+
+        match index {
+            None => self.contract.values[parent.0]
+                .fields
+                .push((name.into(), field)),
+            Some(index) => {
+                let existing = self.contract.values[parent.0].fields[index].1;
+                match self.contract.values[self.find(existing).0].origin {
+                    Origin::Field(..) => self.merge(existing, field),
+                    _ => self.contract.values[parent.0].fields[index].1 = field,
+                }
+            }
+        }
+    }
+
+    fn resolution(&self, name: &Identifier) -> Option<Resolution> {
+        match self.is_detached {
+            true => None,
+            false => self.symbols.resolution(&name.id),
+        }
+    }
+
+    /// The value of a name of the function. A free name of a macro body that resolves outside of
+    /// the function has none, even where the function binds the same name.
+    fn local(&self, name: &Identifier) -> Option<ValueId> {
+        match (name.context, self.resolution(name)) {
+            (Some(_), Some(Resolution::Global | Resolution::Builtin)) => None,
+            _ => self.names.get(&name.name).copied(),
+        }
+    }
+}
+
+/// Statement walking functions.
+impl<'a> ContractInferrer<'a> {
+    fn statement(&mut self, statement: &Statement) {
+        let span = mem::replace(&mut self.span, statement.span.clone());
+        self.statement_kind(statement);
+        self.span = span;
+    }
+
+    fn statement_kind(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::Expr(expr) => {
+                self.expr(expr);
+            }
+            StatementKind::Assign(assign) => {
+                let value = self.expr(&assign.value);
+                for target in assign.targets.iter() {
+                    self.bind(target, value);
+                }
+            }
+            StatementKind::AugAssign(assign) => {
+                let target = self.expr(&assign.target);
+                let value = self.expr(&assign.value);
+                let result = match binary_method(assign.op) {
+                    Some(method) => self.call_method(target, method, &[value]),
+                    None => self.unknown(),
+                };
+                self.store(&assign.target, result);
+            }
+            StatementKind::AnnAssign(assign) => {
+                let ty = Type::from_annotation(&assign.annotation);
+                let value = match &assign.value {
+                    Some(value) => {
+                        let value = self.expr(value);
+                        let name = Unparser::unparse_expr(&assign.target);
+                        self.annotate(value, ty, name)
+                    }
+                    None => self.typed(ty),
+                };
+
+                self.store(&assign.target, value);
+            }
+            StatementKind::Declaration(declaration) => {
+                let value = match (&declaration.value, &declaration.annotation) {
+                    (Some(value), Some(annotation)) => {
+                        let value = self.expr(value);
+                        let ty = Type::from_annotation(annotation);
+                        self.annotate(value, ty, declaration.name.name.clone())
+                    }
+                    (Some(value), None) => self.expr(value),
+                    (None, Some(annotation)) => self.typed(Type::from_annotation(annotation)),
+                    (None, None) => self.unknown(),
+                };
+
+                self.names.insert(declaration.name.name.clone(), value);
+            }
+            StatementKind::Return(Some(value)) => {
+                let value = self.expr(value);
+                self.contract.returns.push(value);
+                self.contract.returned.push(value);
+            }
+            StatementKind::Raise(raise) => {
+                raise.exception.iter().for_each(|expr| {
+                    self.expr(expr);
+                });
+                raise.cause.iter().for_each(|expr| {
+                    self.expr(expr);
+                });
+            }
+            StatementKind::Assert(assert) => {
+                self.expr(&assert.test);
+                assert.message.iter().for_each(|expr| {
+                    self.expr(expr);
+                });
+            }
+            StatementKind::If(node_if) => {
+                let narrowing = self.condition(&node_if.condition);
+                self.narrowed(narrowing, |this| this.statements(&node_if.body));
+                for elif in node_if.elifs.iter() {
+                    let narrowing = self.condition(&elif.condition);
+                    self.narrowed(narrowing, |this| this.statements(&elif.body));
+                }
+
+                self.statements(node_if.orelse.iter().flatten());
+            }
+            StatementKind::While(node_while) => {
+                self.expr(&node_while.condition);
+                node_while.guard.iter().for_each(|guard| {
+                    self.expr(guard);
+                });
+                self.statements(&node_while.body);
+                self.statements(node_while.orelse.iter().flatten());
+            }
+            StatementKind::For(node_for) => {
+                let iter = self.expr(&node_for.iter);
+                let element = self.iterate(iter);
+                self.bind(&node_for.target, element);
+                node_for.guard.iter().for_each(|guard| {
+                    self.expr(guard);
+                });
+                self.statements(&node_for.body);
+                self.statements(node_for.orelse.iter().flatten());
+            }
+            StatementKind::Try(node_try) => {
+                self.statements(&node_try.body);
+                for handler in node_try.handlers.iter() {
+                    if let Some(name) = &handler.name {
+                        let exception = self.literal(Type::named(&handler.exception.name));
+                        self.names.insert(name.name.clone(), exception);
+                    }
+
+                    self.statements(&handler.body);
+                }
+
+                self.statements(node_try.orelse.iter().flatten());
+                self.statements(node_try.finalbody.iter().flatten());
+            }
+            StatementKind::With(with) => {
+                for item in with.items.iter() {
+                    let context = self.expr(&item.context);
+                    let entered = self.call_method(context, "__enter__", &[]);
+                    if let Some(alias) = &item.alias {
+                        self.names.insert(alias.name.clone(), entered);
+                    }
+                }
+
+                self.statements(&with.body);
+            }
+            StatementKind::Match(node_match) => {
+                let subject = self.expr(&node_match.subject);
+                for case in node_match.cases.iter() {
+                    let narrowing = self.case(&node_match.subject, subject, &case.pattern);
+                    self.narrowed(narrowing, |this| {
+                        case.guard.iter().for_each(|guard| {
+                            this.expr(guard);
+                        });
+                        this.statements(&case.body);
+                    });
+                }
+            }
+            StatementKind::FunctionDef(def) if !self.is_module => {
+                let value = self.unknown();
+                self.names.insert(def.name.name.clone(), value);
+            }
+            StatementKind::ClassDef(def) if !self.is_module => {
+                let value = self.unknown();
+                self.names.insert(def.name.name.clone(), value);
+            }
+            _ => (),
+        }
+    }
+
+    fn statements<'s>(&mut self, statements: impl IntoIterator<Item = &'s Statement>) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    /// Binds the names of a pattern to the parts of a value.
+    fn bind(&mut self, pattern: &Pattern, value: ValueId) {
+        match &pattern.kind {
+            // A `None` in a `case` is the unit variant.
+            PatternKind::Capture(name) if name.name == "None" => (),
+            PatternKind::Capture(name) => {
+                self.names.insert(name.name.clone(), value);
+            }
+            PatternKind::As { pattern, name } => {
+                self.bind(pattern, value);
+                self.names.insert(name.name.clone(), value);
+            }
+            PatternKind::Or(patterns) => {
+                for pattern in patterns.iter() {
+                    self.bind(pattern, value);
+                }
+            }
+            PatternKind::Store(target) => self.store(target, value),
+            PatternKind::Rest(Some(name)) => {
+                let rest = self.literal(Type::named("list"));
+                self.names.insert(name.name.clone(), rest);
+            }
+            // The parts of a destructured value are not known, nor are the positional parts of a
+            // class pattern.
+            PatternKind::Sequence(patterns) => {
+                for pattern in patterns.iter() {
+                    let part = self.unknown();
+                    self.bind(pattern, part);
+                }
+            }
+            PatternKind::Mapping { entries, rest } => {
+                for entry in entries.iter() {
+                    let part = self.unknown();
+                    self.bind(&entry.value, part);
+                }
+
+                if let Some(rest) = rest {
+                    let rest_value = self.literal(Type::named("dict"));
+                    self.names.insert(rest.name.clone(), rest_value);
+                }
+            }
+            PatternKind::Class {
+                patterns, keywords, ..
+            } => {
+                for pattern in patterns.iter() {
+                    let part = self.unknown();
+                    self.bind(pattern, part);
+                }
+
+                for keyword in keywords.iter() {
+                    let part = self.field(value, &keyword.name.name);
+                    self.bind(&keyword.pattern, part);
+                }
+            }
+            PatternKind::Wildcard
+            | PatternKind::Rest(None)
+            | PatternKind::Literal(_)
+            | PatternKind::Value(_) => (),
+        }
+    }
+
+    /// Stores a value into a name, field or item.
+    fn store(&mut self, target: &Expr, value: ValueId) {
+        match &target.kind {
+            ExprKind::Identifier(name) => {
+                self.names.insert(name.name.clone(), value);
+            }
+            ExprKind::Attribute {
+                value: object,
+                attribute,
+            } => {
+                let object = self.expr(object);
+                self.set_field(object, &attribute.name, value);
+            }
+            ExprKind::Subscript {
+                value: object,
+                indices,
+            } => {
+                let object = self.expr(object);
+                let index = self.indices(indices);
+                self.call_method(object, "__setitem__", &[index, value]);
+            }
+            _ => {
+                self.expr(target);
+            }
+        }
+    }
+}
+
+/// Expression walking functions.
+impl<'a> ContractInferrer<'a> {
+    fn expr(&mut self, expr: &Expr) -> ValueId {
+        if self.is_detached {
+            return self.expr_kind(expr);
+        }
+
+        let span = mem::replace(&mut self.span, expr.span.clone());
+        let count = self.contract.values.len();
+        let value = self.expr_kind(expr);
+        self.span = span;
+
+        // A literal made by the expression rather than by one inside it is an allocation.
+        let is_new = value.0 >= count
+            && matches!(self.contract.values[value.0].origin, Origin::Literal(_))
+            && !self
+                .contract
+                .allocations
+                .iter()
+                .any(|allocation| allocation.value == value);
+        if is_new {
+            self.contract.allocations.push(Allocation {
+                value,
+                code: Unparser::unparse_expr(expr),
+                is_boxed: false,
+                span: expr.span.clone(),
+            });
+        }
+
+        value
+    }
+
+    fn expr_kind(&mut self, expr: &Expr) -> ValueId {
+        match &expr.kind {
+            ExprKind::Identifier(name) => match self.local(name) {
+                Some(value) => value,
+                None => self.unknown(),
+            },
+            ExprKind::Integer(..) => self.literal(Type::named("int")),
+            ExprKind::Float(_) => self.literal(Type::named("float")),
+            ExprKind::Imag(_) => self.literal(Type::named("complex")),
+            ExprKind::Bool(_) => self.literal(Type::named("bool")),
+            ExprKind::Strings(strings) => self.strings(strings),
+            ExprKind::Tuple(elements) => self.collection("tuple", elements),
+            ExprKind::List(elements) => self.collection("list", elements),
+            ExprKind::Set(elements) => self.collection("set", elements),
+            ExprKind::Dict(entries) => {
+                let mut elements = vec![];
+                for entry in entries.iter() {
+                    elements.push(self.expr(&entry.key));
+                    elements.push(self.expr(&entry.value));
+                }
+
+                let dict = self.literal(Type::named("dict"));
+                self.contract.values[dict.0].elements = elements;
+                dict
+            }
+            ExprKind::ListComp(comprehension) => {
+                self.comprehension(&comprehension.clauses, |this| {
+                    this.expr(&comprehension.element);
+                });
+                self.literal(Type::named("list"))
+            }
+            ExprKind::SetComp(comprehension) => {
+                self.comprehension(&comprehension.clauses, |this| {
+                    this.expr(&comprehension.element);
+                });
+                self.literal(Type::named("set"))
+            }
+            ExprKind::Generator(comprehension) => {
+                self.comprehension(&comprehension.clauses, |this| {
+                    this.expr(&comprehension.element);
+                });
+                self.unknown()
+            }
+            ExprKind::DictComp(comprehension) => {
+                self.comprehension(&comprehension.clauses, |this| {
+                    this.expr(&comprehension.entry.key);
+                    this.expr(&comprehension.entry.value);
+                });
+                self.literal(Type::named("dict"))
+            }
+            ExprKind::NamedExpr { name, value } => {
+                let value = self.expr(value);
+                self.names.insert(name.name.clone(), value);
+                value
+            }
+            ExprKind::UnaryOp { op, operand } => {
+                let operand = self.expr(operand);
+                match unary_method(*op) {
+                    Some(method) => self.call_method(operand, method, &[]),
+                    None if *op == UnaryOp::Not => self.literal(Type::named("bool")),
+                    None => operand,
+                }
+            }
+            ExprKind::BinaryOp { lhs, op, rhs } => {
+                let lhs = self.expr(lhs);
+                let rhs = self.expr(rhs);
+                match binary_method(*op) {
+                    Some(method) => self.call_method(lhs, method, &[rhs]),
+                    None => self.unknown(),
+                }
+            }
+            ExprKind::Comparison { lhs, comparisons } => {
+                let mut lhs = self.expr(lhs);
+                for comparison in comparisons.iter() {
+                    let rhs = self.expr(&comparison.rhs);
+                    match (comparison.op, comparison_method(comparison.op)) {
+                        (ComparisonOp::In | ComparisonOp::NotIn, _) => {
+                            self.call_method(rhs, "__contains__", &[lhs]);
+                        }
+                        (_, Some(method)) => {
+                            self.call_method(lhs, method, &[rhs]);
+                        }
+                        (_, None) => (),
+                    }
+
+                    lhs = rhs;
+                }
+
+                self.literal(Type::named("bool"))
+            }
+            ExprKind::Ternary {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.expr(condition);
+                let then = self.expr(then);
+                let otherwise = self.expr(otherwise);
+                match self.find(then) == self.find(otherwise) {
+                    true => then,
+                    false => self.unknown(),
+                }
+            }
+            ExprKind::Call { func, arguments } => self.call(func, arguments),
+            ExprKind::Subscript { value, indices } => {
+                let value = self.expr(value);
+                let index = self.indices(indices);
+                self.call_method(value, "__getitem__", &[index])
+            }
+            ExprKind::Attribute { value, attribute } => match self.variant(value, attribute) {
+                Some(variant) => self.construct(&variant, &[], vec![]),
+                None => {
+                    let value = self.expr(value);
+                    self.field(value, &attribute.name)
+                }
+            },
+            ExprKind::Lambda(lambda) => {
+                let params = &lambda.params;
+                let defaults = params
+                    .positional_only
+                    .iter()
+                    .chain(params.params.iter())
+                    .chain(params.keyword_only.iter())
+                    .filter_map(|param| param.default.as_ref());
+                for default in defaults {
+                    self.expr(default);
+                }
+
+                self.unknown()
+            }
+            ExprKind::Rest(value)
+            | ExprKind::DictRest(value)
+            | ExprKind::Propagate(value)
+            | ExprKind::Await(value)
+            | ExprKind::YieldFrom(value) => {
+                self.expr(value);
+                self.unknown()
+            }
+            ExprKind::Yield(value) => {
+                value.iter().for_each(|value| {
+                    self.expr(value);
+                });
+                self.unknown()
+            }
+            ExprKind::Match(node_match) => {
+                let subject = self.expr(&node_match.subject);
+                for case in node_match.cases.iter() {
+                    let narrowing = self.case(&node_match.subject, subject, &case.pattern);
+                    self.narrowed(narrowing, |this| {
+                        case.guard.iter().for_each(|guard| {
+                            this.expr(guard);
+                        });
+                        this.expr(&case.body);
+                    });
+                }
+
+                self.unknown()
+            }
+        }
+    }
+
+    fn call(&mut self, func: &Expr, arguments: &[Argument]) -> ValueId {
+        match (&func.kind, arguments) {
+            (ExprKind::Subscript { value, indices }, [Argument::Positional(argument)])
+                if self.is_builtin(value, "cast") =>
+            {
+                let ty = match indices.as_slice() {
+                    [SubscriptIndex::Index(ty)] => cast_type(ty),
+                    _ => None,
+                };
+
+                let value = self.expr(argument);
+                if let Some(ty) = ty {
+                    return self.narrow(value, ty, false);
+                }
+            }
+            (_, [Argument::Positional(argument), Argument::Positional(ty)])
+                if self.is_builtin(func, "isinstance") =>
+            {
+                let value = self.expr(argument);
+                if let Some(ty) = cast_type(ty) {
+                    let narrowed = self.narrow(value, ty, true);
+                    if let ExprKind::Identifier(name) = &argument.kind {
+                        self.narrowing = Some((name.name.clone(), narrowed));
+                    }
+                }
+
+                return self.literal(Type::named("bool"));
+            }
+            (_, [Argument::Positional(argument)]) if self.is_builtin(func, "Box") => {
+                // A box stores its value on the heap and passes for it otherwise.
+                let value = self.expr(argument);
+                let allocation = self
+                    .contract
+                    .allocations
+                    .iter_mut()
+                    .find(|allocation| allocation.value == value);
+                if let Some(allocation) = allocation {
+                    allocation.is_boxed = true;
+                }
+
+                return value;
+            }
+            _ => (),
+        }
+
+        match &func.kind {
+            ExprKind::Attribute { value, attribute } => {
+                if let Some(variant) = self.variant(value, attribute) {
+                    let (arguments, keywords) = self.arguments(arguments);
+                    return self.construct(&variant, &arguments, keywords);
+                }
+
+                let receiver = self.expr(value);
+                let (arguments, keywords) = self.arguments(arguments);
+                self.call_method_with_keywords(receiver, &attribute.name, &arguments, keywords)
+            }
+            ExprKind::Identifier(name) if self.local(name).is_none() => {
+                let (arguments, keywords) = self.arguments(arguments);
+                self.call_global(name, &arguments, keywords)
+            }
+            _ => {
+                let callee = self.expr(func);
+                let (arguments, keywords) = self.arguments(arguments);
+                self.call_method_with_keywords(callee, "__call__", &arguments, keywords)
+            }
+        }
+    }
+
+    /// Calls a function, class or builtin of the module.
+    fn call_global(
+        &mut self,
+        name: &Identifier,
+        arguments: &[ValueId],
+        keywords: Vec<(String, ValueId)>,
+    ) -> ValueId {
+        let resolution = self.resolution(name);
+        if let Some(Resolution::Global) | None = resolution {
+            match self.definitions.get(&name.name) {
+                Some(Definition::Function) => {
+                    return self.call_function(&name.name, arguments, keywords)
+                }
+                Some(Definition::Class) => return self.construct(&name.name, arguments, keywords),
+                None => (),
+            }
+        }
+
+        if let Some(Resolution::Builtin) | None = resolution {
+            let name = name.name.as_str();
+            let method = METHOD_BUILTINS
+                .iter()
+                .find(|(builtin, _)| *builtin == name)
+                .map(|(_, method)| *method);
+
+            match (name, method, arguments) {
+                ("print", ..) => {
+                    for argument in arguments.iter() {
+                        self.call_method(*argument, "__str__", &[]);
+                    }
+                }
+                (_, Some(method), [argument]) => return self.call_method(*argument, method, &[]),
+                _ if TYPE_BUILTINS.contains(&name) => return self.literal(Type::named(name)),
+                _ => (),
+            }
+        }
+
+        self.unknown()
+    }
+
+    /// Whether an expression is a builtin function that the module does not rebind.
+    fn is_builtin(&self, expr: &Expr, builtin: &str) -> bool {
+        match &expr.kind {
+            ExprKind::Identifier(name) => {
+                name.name == builtin
+                    && self.local(name).is_none()
+                    && matches!(self.resolution(name), Some(Resolution::Builtin) | None)
+            }
+            _ => false,
+        }
+    }
+
+    /// Narrows a value to a type, with a `cast[T]` or with a check that is not always true.
+    fn narrow(&mut self, value: ValueId, ty: Type, is_check: bool) -> ValueId {
+        let parent = self.find(value);
+        let narrowed = self.new_value(Origin::Narrowed(parent, ty));
+        self.contract.casts.push(Cast {
+            value: narrowed,
+            is_check,
+            span: self.span.clone(),
+        });
+        narrowed
+    }
+
+    /// Gives a value assigned to a name the type of the annotation of the name, once the value is
+    /// checked to fit it.
+    fn annotate(&mut self, value: ValueId, ty: Type, name: String) -> ValueId {
+        let parent = self.find(value);
+        let interface = self.annotated(ty.clone());
+        let annotated = self.new_value(Origin::Annotated(parent, ty));
+        self.contract.values[annotated.0].interface = Some(interface);
+        self.contract.annotated.push(Annotated {
+            value: annotated,
+            name,
+            span: self.span.clone(),
+        });
+        annotated
+    }
+
+    /// Walks the condition of an `if`, which narrows a name in its body when it is an `isinstance`
+    /// check of the name.
+    fn condition(&mut self, condition: &Expr) -> Option<(String, ValueId)> {
+        self.narrowing = None;
+        self.expr(condition);
+        let narrowing = self.narrowing.take();
+        narrowing.filter(|_| matches!(condition.kind, ExprKind::Call { .. }))
+    }
+
+    /// Binds the pattern of a `case`, which narrows the subject to the class of a class pattern.
+    /// The subject is narrowed in the body of the case when it is a name.
+    fn case(
+        &mut self,
+        subject_expr: &Expr,
+        subject: ValueId,
+        pattern: &Pattern,
+    ) -> Option<(String, ValueId)> {
+        let class = match &pattern.kind {
+            PatternKind::Class { path, .. } => Some(path),
+            PatternKind::As { pattern, .. } => match &pattern.kind {
+                PatternKind::Class { path, .. } => Some(path),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let ty = class.and_then(|path| self.pattern_type(path));
+        let value = match ty {
+            Some(ty) => self.narrow(subject, ty, true),
+            None => subject,
+        };
+
+        self.bind(pattern, value);
+        match &subject_expr.kind {
+            ExprKind::Identifier(name) if value != subject => Some((name.name.clone(), value)),
+            _ => None,
+        }
+    }
+
+    /// The class a class pattern names, which is a class or builtin type of the module.
+    fn pattern_type(&self, path: &Path) -> Option<Type> {
+        let name = path
+            .names
+            .iter()
+            .map(|name| name.name.as_str())
+            .collect::<Vec<_>>()
+            .join(".");
+
+        match self.definitions.contains_key(&name) || TYPE_BUILTINS.contains(&name.as_str()) {
+            true => Some(Type::named(name)),
+            false => None,
+        }
+    }
+
+    /// Walks code where a name is narrowed, after which the name has its value back unless the code
+    /// rebinds it.
+    fn narrowed(&mut self, narrowing: Option<(String, ValueId)>, walk: impl FnOnce(&mut Self)) {
+        let (name, narrowed) = match narrowing {
+            Some(narrowing) => narrowing,
+            None => return walk(self),
+        };
+
+        let previous = self.names.insert(name.clone(), narrowed);
+        walk(self);
+        if let Some(previous) = previous {
+            if self.names.get(&name) == Some(&narrowed) {
+                self.names.insert(name, previous);
+            }
+        }
+    }
+
+    /// The name of the enum variant an attribute names, like `PrimaryColor.Red`.
+    fn variant(&self, value: &Expr, attribute: &Identifier) -> Option<String> {
+        let name = match &value.kind {
+            ExprKind::Identifier(name) if self.local(name).is_none() => name,
+            _ => return None,
+        };
+
+        let variant = format!("{}.{}", name.name, attribute.name);
+        match (self.resolution(name), self.definitions.get(&variant)) {
+            (Some(Resolution::Global) | None, Some(Definition::Class)) => Some(variant),
+            _ => None,
+        }
+    }
+
+    /// The values of the positional and keyword arguments of a call. The `*rest` and `**rest`
+    /// arguments are walked but left out.
+    fn arguments(&mut self, arguments: &[Argument]) -> (Vec<ValueId>, Vec<(String, ValueId)>) {
+        let mut values = vec![];
+        let mut keywords = vec![];
+        for argument in arguments.iter() {
+            match argument {
+                Argument::Positional(expr) => {
+                    let value = self.expr(expr);
+                    if !matches!(expr.kind, ExprKind::Rest(_) | ExprKind::DictRest(_)) {
+                        values.push(value);
+                    }
+                }
+                Argument::Keyword { name, value } => {
+                    let value = self.expr(value);
+                    keywords.push((name.name.clone(), value));
+                }
+            }
+        }
+
+        (values, keywords)
+    }
+
+    /// The value of the indices of a subscript, which is a tuple for more than one.
+    fn indices(&mut self, indices: &[SubscriptIndex]) -> ValueId {
+        let mut values = vec![];
+        for index in indices.iter() {
+            let value = match index {
+                SubscriptIndex::Index(expr) => self.expr(expr),
+                SubscriptIndex::Slice { lower, upper, step } => {
+                    for expr in [lower, upper, step].into_iter().flatten() {
+                        self.expr(expr);
+                    }
+
+                    self.literal(Type::named("slice"))
+                }
+            };
+
+            values.push(value);
+        }
+
+        match values[..] {
+            [value] => value,
+            _ => self.literal(Type::named("tuple")),
+        }
+    }
+
+    /// A collection literal, which keeps its elements unless it spreads another collection.
+    fn collection(&mut self, name: &str, elements: &[Expr]) -> ValueId {
+        let mut values = vec![];
+        for element in elements.iter() {
+            let value = self.expr(element);
+            if !matches!(element.kind, ExprKind::Rest(_)) {
+                values.push(value);
+            }
+        }
+
+        let collection = self.literal(Type::named(name));
+        if values.len() == elements.len() {
+            self.contract.values[collection.0].elements = values;
+        }
+
+        collection
+    }
+
+    /// Walks the clauses of a comprehension, whose targets do not outlive it.
+    fn comprehension(
+        &mut self,
+        clauses: &[ComprehensionFor],
+        walk_element: impl FnOnce(&mut Self),
+    ) {
+        let names = self.names.clone();
+        for clause in clauses.iter() {
+            let iter = self.expr(&clause.iter);
+            let element = self.iterate(iter);
+            self.bind(&clause.target, element);
+            for condition in clause.conditions.iter() {
+                self.expr(condition);
+            }
+        }
+
+        walk_element(self);
+        self.names = names;
+    }
+
+    /// Walks the replacement fields of f-strings, whose values are converted with `__str__`.
+    fn strings(&mut self, strings: &[StringLiteral]) -> ValueId {
+        for string in strings.iter() {
+            if let StringLiteral::Str(text, StringKind::Format | StringKind::RawFormat) = string {
+                for placeholder in format_placeholders(text) {
+                    if let Some(expr) = parse_placeholder(&placeholder) {
+                        let is_detached = mem::replace(&mut self.is_detached, true);
+                        let value = self.expr(&expr);
+                        self.is_detached = is_detached;
+                        self.call_method(value, "__str__", &[]);
+                    }
+                }
+            }
+        }
+
+        match strings.first() {
+            Some(StringLiteral::Bytes(..)) => self.literal(Type::named("bytes")),
+            _ => self.literal(Type::named("str")),
+        }
+    }
+}
+
+impl Value {
+    fn new(origin: Origin) -> Self {
+        Self {
+            origin,
+            interface: None,
+            positions: vec![],
+            fields: vec![],
+            elements: vec![],
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Infers the contracts of the functions of a module and the methods of its classes, in source
+/// order.
+pub(crate) fn infer_contracts(ast: &Ast, symbols: &SymbolTable) -> Vec<Contract> {
+    let definitions = module_definitions(ast);
+    let mut contracts = vec![];
+    collect_contracts(
+        &ast.statements,
+        "",
+        &[],
+        symbols,
+        &definitions,
+        &mut contracts,
+    );
+    contracts
+}
+
+/// Infers the contract of the code at the top of a module, which takes no arguments and whose calls
+/// instantiate the functions of the module.
+pub(crate) fn infer_module_contract(ast: &Ast, symbols: &SymbolTable) -> Contract {
+    let definitions = module_definitions(ast);
+    ContractInferrer::new(
+        symbols,
+        &definitions,
+        "<module>".into(),
+        ast.id,
+        ast.span.clone(),
+    )
+    .infer_module(&ast.statements)
+}
+
+/// The functions and classes at the top of a module, with the variants of its enums as classes
+/// like `PrimaryColor.Red`.
+fn module_definitions(ast: &Ast) -> HashMap<String, Definition> {
+    let mut definitions = HashMap::new();
+    for statement in ast.statements.iter() {
+        match &statement.kind {
+            StatementKind::FunctionDef(def) => {
+                definitions.insert(def.name.name.clone(), Definition::Function);
+            }
+            StatementKind::ClassDef(def) => {
+                definitions.insert(def.name.name.clone(), Definition::Class);
+                for variant in def.variants.iter() {
+                    let name = format!("{}.{}", def.name.name, variant.name.name);
+                    definitions.insert(name, Definition::Class);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    definitions
+}
+
+fn collect_contracts(
+    statements: &[Statement],
+    prefix: &str,
+    generics: &[Identifier],
+    symbols: &SymbolTable,
+    definitions: &HashMap<String, Definition>,
+    contracts: &mut Vec<Contract>,
+) {
+    for statement in statements.iter() {
+        match &statement.kind {
+            StatementKind::FunctionDef(def) => {
+                let name = format!("{prefix}{}", def.name.name);
+                let inferrer = ContractInferrer::new(
+                    symbols,
+                    definitions,
+                    name,
+                    def.name.id,
+                    def.name.span.clone(),
+                );
+                contracts.push(inferrer.infer_function(def, generics));
+            }
+            StatementKind::ClassDef(def) => {
+                let prefix = format!("{prefix}{}.", def.name.name);
+                collect_contracts(
+                    &def.body,
+                    &prefix,
+                    &def.generics,
+                    symbols,
+                    definitions,
+                    contracts,
+                );
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The type a `cast[T]` or `isinstance` names, which is the union of the types of a tuple.
+fn cast_type(expr: &Expr) -> Option<Type> {
+    match Type::from_expr(expr)? {
+        Type::Tuple(types) => Some(Type::Union(types)),
+        ty => Some(ty),
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Option<Expr> {
+    let mut ast = Parser::parse(&format!("{placeholder}\n")).ok()?;
+    match ast.statements.pop()?.kind {
+        StatementKind::Expr(expr) if ast.statements.is_empty() => Some(expr),
+        _ => None,
+    }
+}
+
+fn unary_method(op: UnaryOp) -> Option<&'static str> {
+    match op {
+        UnaryOp::Plus => Some("__pos__"),
+        UnaryOp::Minus => Some("__neg__"),
+        UnaryOp::BitNot => Some("__invert__"),
+        UnaryOp::Sqrt => Some("__sqrt__"),
+        UnaryOp::Square => Some("__square__"),
+        UnaryOp::Not | UnaryOp::Ref | UnaryOp::Val | UnaryOp::Ptr => None,
+    }
+}
+
+/// The method an operator calls, which the logical operators do not.
+fn binary_method(op: BinaryOp) -> Option<&'static str> {
+    let method = match op {
+        BinaryOp::Or | BinaryOp::And => return None,
+        BinaryOp::BitOr => "__or__",
+        BinaryOp::BitXor => "__xor__",
+        BinaryOp::BitAnd => "__and__",
+        BinaryOp::ShiftL => "__lshift__",
+        BinaryOp::ShiftR => "__rshift__",
+        BinaryOp::Plus => "__plus__",
+        BinaryOp::Minus => "__minus__",
+        BinaryOp::Mul => "__mul__",
+        BinaryOp::MatMul => "__matmul__",
+        BinaryOp::Div => "__div__",
+        BinaryOp::IntDiv => "__intdiv__",
+        BinaryOp::Mod => "__mod__",
+        BinaryOp::Pow => "__pow__",
+    };
+
+    Some(method)
+}
+
+/// The method a comparison calls on its left side, which `in` calls on its right side instead and
+/// `is` does not call.
+fn comparison_method(op: ComparisonOp) -> Option<&'static str> {
+    let method = match op {
+        ComparisonOp::Less => "__lt__",
+        ComparisonOp::Greater => "__gt__",
+        ComparisonOp::Eq => "__eq__",
+        ComparisonOp::GreaterEq => "__ge__",
+        ComparisonOp::LessEq => "__le__",
+        ComparisonOp::NotEq => "__ne__",
+        ComparisonOp::In | ComparisonOp::NotIn | ComparisonOp::Is | ComparisonOp::IsNot => {
+            return None
+        }
+    };
+
+    Some(method)
+}
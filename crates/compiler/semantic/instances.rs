@@ -0,0 +1,142 @@
+use std::fmt::{self, Display};
+
+use super::types::Type;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// A concrete function that a call instantiates from the contract of a function, like
+/// `add#1(int, int) -> int` for `add(1, 2)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instance {
+    /// The name of the function, like `Point.__plus__` for a method.
+    pub name: String,
+    /// The number of the instance among the instances of the function, from 1.
+    pub number: usize,
+    /// The index of the contract of the function.
+    pub contract: usize,
+    /// The types of the arguments, which are unknown where the caller does not know them.
+    pub arguments: Vec<Option<Type>>,
+    /// The type the instance returns, which is an intersection for more than one type.
+    pub returns: Option<Type>,
+    /// The instances the instance calls.
+    pub callees: Vec<InstanceId>,
+}
+
+/// The index of an instance in [`Instances`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstanceId(pub usize);
+
+/// The instances of the functions of a module, each made once for the same argument types.
+#[derive(Debug, Clone, Default)]
+pub struct Instances {
+    instances: Vec<Instance>,
+    /// The instances in the order they are finished, which has the callees first.
+    finished: Vec<InstanceId>,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Instances {
+    pub fn get(&self, id: InstanceId) -> &Instance {
+        &self.instances[id.0]
+    }
+
+    /// The instances in the order the calls made them.
+    pub fn iter(&self) -> impl Iterator<Item = &Instance> {
+        self.instances.iter()
+    }
+
+    /// The instances of a function, like `add#1` and `add#2` for `add`.
+    pub fn of<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Instance> {
+        self.instances
+            .iter()
+            .filter(move |instance| instance.name == name)
+    }
+
+    /// The instances for codegen to generate, each after the instances it calls. The instances in
+    /// a cycle of calls come after the ones they call from outside the cycle.
+    pub fn worklist(&self) -> impl Iterator<Item = &Instance> {
+        self.finished.iter().map(|id| self.get(*id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// The instance of a contract for the types of the arguments, if there is one already.
+    pub(crate) fn find(&self, contract: usize, arguments: &[Option<Type>]) -> Option<InstanceId> {
+        self.instances
+            .iter()
+            .position(|instance| instance.contract == contract && instance.arguments == arguments)
+            .map(InstanceId)
+    }
+
+    /// Adds an instance that is not finished yet, which returns an unknown type until it is.
+    pub(crate) fn add(
+        &mut self,
+        name: String,
+        contract: usize,
+        arguments: Vec<Option<Type>>,
+    ) -> InstanceId {
+        let number = self.of(&name).count() + 1;
+        self.instances.push(Instance {
+            name,
+            number,
+            contract,
+            arguments,
+            returns: None,
+            callees: vec![],
+        });
+
+        InstanceId(self.instances.len() - 1)
+    }
+
+    pub(crate) fn finish(
+        &mut self,
+        id: InstanceId,
+        returns: Option<Type>,
+        callees: Vec<InstanceId>,
+    ) {
+        let instance = &mut self.instances[id.0];
+        instance.returns = returns;
+        instance.callees = callees;
+        self.finished.push(id);
+    }
+}
+
+impl Display for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let returns = describe(&self.returns);
+        write!(
+            f,
+            "{}#{}({}) -> {returns}",
+            self.name,
+            self.number,
+            describe_all(&self.arguments)
+        )
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// A type, or `any` for an unknown type.
+pub(crate) fn describe(ty: &Option<Type>) -> String {
+    match ty {
+        Some(ty) => ty.to_string(),
+        None => "any".into(),
+    }
+}
+
+pub(crate) fn describe_all(types: &[Option<Type>]) -> String {
+    types.iter().map(describe).collect::<Vec<_>>().join(", ")
+}
@@ -0,0 +1,812 @@
+use std::collections::VecDeque;
+use std::fmt::{self, Display};
+
+use crate::ir::{
+    fold, visit, Argument, Assign, Ast, ClassDef, Expr, ExprKind, Fold, FunctionDef, Identifier,
+    NodeId, Pattern, PatternKind, StatementKind, TypeAnnotation, TypeAnnotationKind, VariantField,
+    Visitor,
+};
+use crate::span::Span;
+
+use super::errors::{SemanticError, SemanticErrorKind};
+use super::types::Type;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// A field that the layout of its class stores behind a `Box`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Indirection {
+    /// A field whose type holds its class again, which would make the class infinitely large, like
+    /// `parent` of `data class Node(parent: Node?, children: [Node])`. Its type becomes
+    /// `Box[Node?]`.
+    Recursive {
+        class: String,
+        field: String,
+        /// The type of the field, like `Node?`.
+        ty: Type,
+    },
+    /// A field that other fields of the instance hold a reference to, like `context` for
+    /// `self.module = Module(self.context)`, which would dangle when the instance moves. It is
+    /// assigned `Box(Context())` and the other fields read it as `self.context.val`.
+    Sibling {
+        class: String,
+        field: String,
+        /// The fields that hold the reference, like `module`.
+        siblings: Vec<String>,
+    },
+}
+
+/// The fields of the classes of a module that their layout stores behind a `Box`.
+#[derive(Debug, Clone, Default)]
+pub struct Layouts {
+    indirections: Vec<Indirection>,
+}
+
+/// A class at the top of a module or an enum variant, with the fields it stores inline.
+struct Layout {
+    name: String,
+    fields: Vec<LayoutField>,
+    /// The variants of an enum, one of which it stores inline.
+    variants: Vec<String>,
+}
+
+struct LayoutField {
+    name: String,
+    ty: Type,
+    span: Span,
+}
+
+/// A class that a class stores inline, through one of its fields or as one of its variants.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    from: usize,
+    /// The index of the field, which is `None` for a variant.
+    field: Option<usize>,
+    to: usize,
+    /// Whether every instance of the class holds the other, which a `T?`, a union or an enum of
+    /// more than one variant does not.
+    is_required: bool,
+}
+
+/// The fields of the methods of a class that the methods assign, and the references the values
+/// assigned to them take to other fields.
+struct FieldAssignments<'a> {
+    classes: &'a [String],
+    receiver: String,
+    fields: Vec<String>,
+    /// The field assigned with the field or the receiver it holds a reference to, which is `None`
+    /// for the receiver itself.
+    references: Vec<(String, Option<String>, Span)>,
+}
+
+/// The arguments of the instances of the classes of a module that an expression constructs.
+struct Constructions<'a> {
+    classes: &'a [String],
+    arguments: Vec<Vec<Argument>>,
+}
+
+/// Rewrites the classes of a module with the indirections of their layouts.
+struct Indirector<'a> {
+    layouts: &'a Layouts,
+}
+
+/// Boxes the values assigned to the fields of a method that other fields hold a reference to, and
+/// reads them through the box in the values of those fields.
+struct SiblingBoxer<'a> {
+    receiver: String,
+    /// The boxed fields with the fields that hold a reference to them.
+    boxed: Vec<(&'a str, &'a [String])>,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Layouts {
+    pub fn iter(&self) -> impl Iterator<Item = &Indirection> {
+        self.indirections.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.indirections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indirections.is_empty()
+    }
+
+    /// Rewrites the classes of a module with the indirections, like `parent: Box[Node?]` for
+    /// `parent: Node?` and `self.context = Box(Context())` for `self.context = Context()`.
+    ///
+    /// It goes after [`Placements::insert_boxes`](super::Placements::insert_boxes), which finds
+    /// the allocations by their code, and does not box a value twice.
+    pub fn insert_indirections(&self, ast: Ast) -> Ast {
+        Indirector { layouts: self }.fold_ast(ast)
+    }
+}
+
+impl Layout {
+    /// The layouts of a class definition, which are more than one for an enum and its variants.
+    fn new(def: &ClassDef) -> Vec<Self> {
+        let name = def.name.name.clone();
+        let mut fields = def
+            .fields
+            .iter()
+            .filter_map(|param| {
+                let annotation = param.annotation.as_ref()?;
+                Some(LayoutField::new(&param.name.name, annotation))
+            })
+            .collect::<Vec<_>>();
+
+        for statement in def.body.iter() {
+            if let StatementKind::AnnAssign(assign) = &statement.kind {
+                if let ExprKind::Identifier(field) = &assign.target.kind {
+                    fields.push(LayoutField::new(&field.name, &assign.annotation));
+                }
+            }
+        }
+
+        let mut layouts = vec![];
+        for variant in def.variants.iter() {
+            let fields = variant
+                .fields
+                .iter()
+                .flatten()
+                .enumerate()
+                .map(|(index, field)| match field {
+                    VariantField::Named { name, annotation } => {
+                        LayoutField::new(&name.name, annotation)
+                    }
+                    VariantField::Positional(annotation) => {
+                        LayoutField::new(&format!("_{index}"), annotation)
+                    }
+                })
+                .collect();
+
+            layouts.push(Self {
+                name: format!("{name}.{}", variant.name.name),
+                fields,
+                variants: vec![],
+            });
+        }
+
+        let variants = layouts.iter().map(|layout| layout.name.clone()).collect();
+        layouts.insert(
+            0,
+            Self {
+                name,
+                fields,
+                variants,
+            },
+        );
+
+        layouts
+    }
+}
+
+impl LayoutField {
+    fn new(name: &str, annotation: &TypeAnnotation) -> Self {
+        Self {
+            name: name.into(),
+            ty: Type::from_annotation(annotation),
+            span: annotation.span.clone(),
+        }
+    }
+}
+
+impl FieldAssignments<'_> {
+    /// The field of the receiver that an expression names, like `context` for `self.context`.
+    fn field<'e>(&self, expr: &'e Expr) -> Option<&'e str> {
+        match &expr.kind {
+            ExprKind::Attribute { value, attribute } => match &value.kind {
+                ExprKind::Identifier(name) if name.name == self.receiver => Some(&attribute.name),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Records the references to the receiver and its fields that the instances constructed in
+    /// the value of a field take.
+    fn references(&mut self, field: &str, value: &Expr) {
+        let mut constructions = Constructions {
+            classes: self.classes,
+            arguments: vec![],
+        };
+
+        constructions.visit_expr(value);
+        for arguments in constructions.arguments {
+            for argument in arguments.iter() {
+                let argument = match argument {
+                    Argument::Positional(argument) => argument,
+                    Argument::Keyword { value, .. } => value,
+                };
+
+                let reference = match &argument.kind {
+                    ExprKind::Identifier(name) if name.name == self.receiver => None,
+                    _ => match self.field(argument) {
+                        Some(other) if other != field => Some(other.to_string()),
+                        _ => continue,
+                    },
+                };
+
+                self.references
+                    .push((field.into(), reference, argument.span.clone()));
+            }
+        }
+    }
+}
+
+impl Visitor for FieldAssignments<'_> {
+    fn visit_assign(&mut self, node: &Assign) {
+        for target in node.targets.iter() {
+            if let PatternKind::Store(target) = &target.kind {
+                if let Some(field) = self.field(target).map(String::from) {
+                    if !self.fields.contains(&field) {
+                        self.fields.push(field.clone());
+                    }
+
+                    self.references(&field, &node.value);
+                }
+            }
+        }
+
+        visit::walk_assign(self, node);
+    }
+}
+
+impl Visitor for Constructions<'_> {
+    fn visit_expr(&mut self, node: &Expr) {
+        if let ExprKind::Call { func, arguments } = &node.kind {
+            let is_class = Type::from_expr(func)
+                .and_then(|ty| ty.name().map(String::from))
+                .is_some_and(|name| self.classes.contains(&name));
+
+            if is_class {
+                self.arguments.push(arguments.clone());
+            }
+        }
+
+        visit::walk_expr(self, node);
+    }
+}
+
+impl Fold for Indirector<'_> {
+    fn fold_class_def(&mut self, mut node: ClassDef) -> ClassDef {
+        let name = node.name.name.clone();
+        let boxed = |class: &str, field: &str| {
+            self.layouts.iter().any(|indirection| match indirection {
+                Indirection::Recursive {
+                    class: boxed_class,
+                    field: boxed_field,
+                    ..
+                } => boxed_class == class && boxed_field == field,
+                _ => false,
+            })
+        };
+
+        for param in node.fields.iter_mut() {
+            if boxed(&name, &param.name.name) {
+                param.annotation = param.annotation.take().map(boxed_annotation);
+            }
+        }
+
+        for variant in node.variants.iter_mut() {
+            let class = format!("{name}.{}", variant.name.name);
+            for (index, field) in variant.fields.iter_mut().flatten().enumerate() {
+                match field {
+                    VariantField::Named { name, annotation } if boxed(&class, &name.name) => {
+                        *annotation = boxed_annotation(annotation.clone());
+                    }
+                    VariantField::Positional(annotation) if boxed(&class, &format!("_{index}")) => {
+                        *annotation = boxed_annotation(annotation.clone());
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let siblings = self
+            .layouts
+            .iter()
+            .filter_map(|indirection| match indirection {
+                Indirection::Sibling {
+                    class,
+                    field,
+                    siblings,
+                } if *class == name => Some((field.as_str(), siblings.as_slice())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for statement in node.body.iter_mut() {
+            match &mut statement.kind {
+                StatementKind::AnnAssign(assign) => {
+                    let is_boxed = match &assign.target.kind {
+                        ExprKind::Identifier(field) => boxed(&name, &field.name),
+                        _ => false,
+                    };
+
+                    if is_boxed {
+                        assign.annotation = boxed_annotation(assign.annotation.clone());
+                    }
+                }
+                StatementKind::FunctionDef(def) if !siblings.is_empty() => {
+                    if let Some(receiver) = receiver(def) {
+                        let mut boxer = SiblingBoxer {
+                            receiver,
+                            boxed: siblings.clone(),
+                        };
+
+                        **def = boxer.fold_function_def((**def).clone());
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        node
+    }
+}
+
+impl SiblingBoxer<'_> {
+    /// The field of the receiver that a pattern stores into, like `context` for `self.context`.
+    fn target<'p>(&self, pattern: &'p Pattern) -> Option<&'p str> {
+        match &pattern.kind {
+            PatternKind::Store(target) => match &target.kind {
+                ExprKind::Attribute { value, attribute } => match &value.kind {
+                    ExprKind::Identifier(name) if name.name == self.receiver => {
+                        Some(&attribute.name)
+                    }
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Fold for SiblingBoxer<'_> {
+    fn fold_assign(&mut self, node: Assign) -> Assign {
+        let mut node = fold::fold_assign(self, node);
+        let targets = node
+            .targets
+            .iter()
+            .filter_map(|target| self.target(target))
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        let read = self
+            .boxed
+            .iter()
+            .filter(|(_, siblings)| targets.iter().any(|target| siblings.contains(target)))
+            .map(|(field, _)| *field)
+            .collect::<Vec<_>>();
+
+        if !read.is_empty() {
+            node.value = read_through_boxes(node.value, &self.receiver, &read);
+        }
+
+        let is_boxed = self
+            .boxed
+            .iter()
+            .any(|(field, _)| targets.iter().any(|target| target == field));
+        // The value can be boxed already, by the code or as an allocation that lives on the heap.
+        let is_box = match &node.value.kind {
+            ExprKind::Call { func, .. } => {
+                matches!(&func.kind, ExprKind::Identifier(name) if name.name == "Box")
+            }
+            _ => false,
+        };
+
+        if is_boxed && !is_box {
+            let span = node.value.span.clone();
+            node.value = Expr {
+                kind: ExprKind::Call {
+                    func: Box::new(Expr {
+                        kind: ExprKind::Identifier(Identifier {
+                            name: "Box".into(),
+                            id: NodeId::next(),
+                            span: span.clone(),
+                            context: None,
+                        }),
+                        id: NodeId::next(),
+                        span: span.clone(),
+                    }),
+                    arguments: vec![Argument::Positional(node.value)],
+                },
+                id: NodeId::next(),
+                span,
+            };
+        }
+
+        node
+    }
+}
+
+impl Display for Indirection {
+    /// The indirection like `Node.parent: Node? -> Box[Node?]` or
+    /// `Engine.context -> Box, referenced by Engine.module`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Recursive { class, field, ty } => {
+                write!(f, "{class}.{field}: {ty} -> Box[{ty}]")
+            }
+            Self::Sibling {
+                class,
+                field,
+                siblings,
+            } => {
+                let siblings = siblings
+                    .iter()
+                    .map(|sibling| format!("{class}.{sibling}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                write!(f, "{class}.{field} -> Box, referenced by {siblings}")
+            }
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Finds the fields of the classes of a module that must be stored behind a `Box`.
+///
+/// A class that holds itself inline through its fields, directly or through other classes, has no
+/// finite size, so the first field of each such cycle is boxed, like `parent: Box[Node?]`. Lists
+/// and other containers already store their elements on the heap. A cycle that every instance
+/// goes around, like `data class Loop(next: Loop)`, cannot be fixed by a box, as no instance can
+/// be constructed, and is reported.
+///
+/// A field whose value holds a reference to another field of the instance, like
+/// `self.module = Module(self.context)`, would dangle when the instance moves, so the other field
+/// is boxed. A field whose value holds a reference to the instance itself is reported.
+pub(crate) fn check_layouts(ast: &Ast, diagnostics: &mut Vec<SemanticError>) -> Layouts {
+    let mut layouts = Vec::<Layout>::new();
+    for statement in ast.statements.iter() {
+        if let StatementKind::ClassDef(def) = &statement.kind {
+            for layout in Layout::new(def) {
+                // The blocks of a class add to its fields.
+                match layouts.iter_mut().find(|other| other.name == layout.name) {
+                    Some(other) => other.fields.extend(layout.fields),
+                    None => layouts.push(layout),
+                }
+            }
+        }
+    }
+
+    let mut indirections = recursive_fields(&layouts, diagnostics);
+    let classes = layouts
+        .iter()
+        .map(|layout| layout.name.clone())
+        .collect::<Vec<_>>();
+
+    for statement in ast.statements.iter() {
+        if let StatementKind::ClassDef(def) = &statement.kind {
+            sibling_fields(def, &classes, &mut indirections, diagnostics);
+        }
+    }
+
+    Layouts { indirections }
+}
+
+/// Boxes the first field of each cycle of classes that hold each other inline, and reports the
+/// cycles that every instance goes around.
+fn recursive_fields(layouts: &[Layout], diagnostics: &mut Vec<SemanticError>) -> Vec<Indirection> {
+    let index = |name: &str| layouts.iter().position(|layout| layout.name == name);
+    let mut edges = vec![];
+    for (from, layout) in layouts.iter().enumerate() {
+        for (field, layout_field) in layout.fields.iter().enumerate() {
+            for (to, is_required) in inline_classes(&layout_field.ty, true) {
+                if let Some(to) = index(&to) {
+                    edges.push(Edge {
+                        from,
+                        field: Some(field),
+                        to,
+                        is_required,
+                    });
+                }
+            }
+        }
+
+        for variant in layout.variants.iter().filter_map(|variant| index(variant)) {
+            edges.push(Edge {
+                from,
+                field: None,
+                to: variant,
+                is_required: layout.variants.len() == 1,
+            });
+        }
+    }
+
+    let required = edges
+        .iter()
+        .filter(|edge| edge.is_required)
+        .copied()
+        .collect::<Vec<_>>();
+
+    let mut reported = vec![false; layouts.len()];
+    for class in 0..layouts.len() {
+        if reported[class] {
+            continue;
+        }
+
+        if let Some(cycle) = path(&required, layouts.len(), class, class) {
+            let chain = cycle
+                .iter()
+                .map(|edge| {
+                    let (from, to) = (&layouts[edge.from].name, &layouts[edge.to].name);
+                    match edge.field {
+                        Some(field) => format!(
+                            "{from}.{} holds a {to}",
+                            layouts[edge.from].fields[field].name
+                        ),
+                        None => format!("{from} can only be {to}"),
+                    }
+                })
+                .collect();
+
+            // A cycle goes through a field, as variants only hold their enum through fields.
+            let span = match cycle
+                .iter()
+                .find_map(|edge| Some(layouts[edge.from].fields[edge.field?].span.clone()))
+            {
+                Some(span) => span,
+                None => continue,
+            };
+
+            diagnostics.push(SemanticError::with_chain(
+                SemanticErrorKind::InfiniteSize,
+                span,
+                chain,
+            ));
+
+            for edge in cycle {
+                reported[edge.from] = true;
+            }
+        }
+    }
+
+    // Boxing a field that is on a cycle breaks the cycle, and the fields checked earlier stay off
+    // the cycles that are left.
+    let mut indirections = vec![];
+    let mut boxed = vec![];
+    for edge in edges.iter() {
+        let field = match edge.field {
+            Some(field) if !reported[edge.from] => field,
+            _ => continue,
+        };
+
+        if boxed.contains(&(edge.from, field)) {
+            continue;
+        }
+
+        let remaining = edges
+            .iter()
+            .filter(|edge| {
+                !edge
+                    .field
+                    .is_some_and(|field| boxed.contains(&(edge.from, field)))
+            })
+            .copied()
+            .collect::<Vec<_>>();
+
+        if path(&remaining, layouts.len(), edge.to, edge.from).is_some() {
+            let layout = &layouts[edge.from];
+            boxed.push((edge.from, field));
+            indirections.push(Indirection::Recursive {
+                class: layout.name.clone(),
+                field: layout.fields[field].name.clone(),
+                ty: layout.fields[field].ty.clone(),
+            });
+        }
+    }
+
+    indirections
+}
+
+/// Boxes the fields of a class that other fields hold a reference to, and reports the fields that
+/// hold a reference to the instance.
+fn sibling_fields(
+    def: &ClassDef,
+    classes: &[String],
+    indirections: &mut Vec<Indirection>,
+    diagnostics: &mut Vec<SemanticError>,
+) {
+    let mut fields = vec![];
+    let mut references = vec![];
+    for statement in def.body.iter() {
+        if let StatementKind::FunctionDef(method) = &statement.kind {
+            let receiver = match receiver(method) {
+                Some(receiver) => receiver,
+                None => continue,
+            };
+
+            let mut assignments = FieldAssignments {
+                classes,
+                receiver,
+                fields: vec![],
+                references: vec![],
+            };
+
+            method
+                .body
+                .iter()
+                .for_each(|statement| assignments.visit_statement(statement));
+            fields.extend(assignments.fields);
+            references.extend(assignments.references);
+        }
+    }
+
+    let class = &def.name.name;
+    for (field, reference, span) in references {
+        let reference = match reference {
+            Some(reference) if fields.contains(&reference) => reference,
+            Some(_) => continue,
+            None => {
+                diagnostics.push(SemanticError::with_chain(
+                    SemanticErrorKind::SelfReference,
+                    span,
+                    vec![format!("{class}.{field} holds a reference to its {class}")],
+                ));
+                continue;
+            }
+        };
+
+        let existing = indirections
+            .iter_mut()
+            .find_map(|indirection| match indirection {
+                Indirection::Sibling {
+                    class: boxed_class,
+                    field: boxed_field,
+                    siblings,
+                } if boxed_class == class && *boxed_field == reference => Some(siblings),
+                _ => None,
+            });
+
+        match existing {
+            Some(siblings) if !siblings.contains(&field) => siblings.push(field),
+            Some(_) => (),
+            None => indirections.push(Indirection::Sibling {
+                class: class.clone(),
+                field: reference,
+                siblings: vec![field],
+            }),
+        }
+    }
+}
+
+/// The classes a type stores inline, with whether every value of the type holds them.
+fn inline_classes(ty: &Type, is_required: bool) -> Vec<(String, bool)> {
+    match ty {
+        Type::Named { name, arguments } => match name.as_str() {
+            "tuple" => arguments
+                .iter()
+                .flat_map(|argument| inline_classes(argument, is_required))
+                .collect(),
+            "Option" | "Result" => arguments
+                .iter()
+                .flat_map(|argument| inline_classes(argument, false))
+                .collect(),
+            // A box and the containers store what they hold on the heap.
+            "Box" | "list" | "dict" | "set" => vec![],
+            _ => vec![(name.clone(), is_required)],
+        },
+        Type::Tuple(types) => types
+            .iter()
+            .flat_map(|ty| inline_classes(ty, is_required))
+            .collect(),
+        Type::Union(types) | Type::Intersection(types) => types
+            .iter()
+            .flat_map(|ty| inline_classes(ty, is_required && types.len() == 1))
+            .collect(),
+        Type::Optional(inner) => inline_classes(inner, false),
+        Type::List(_) | Type::Function { .. } => vec![],
+    }
+}
+
+/// The shortest path of edges from one of a number of classes to another, if there is one.
+fn path(edges: &[Edge], count: usize, from: usize, to: usize) -> Option<Vec<Edge>> {
+    let mut previous = vec![None::<Edge>; count];
+    let mut visited = vec![false; count];
+    let mut queue = VecDeque::from([from]);
+    while let Some(class) = queue.pop_front() {
+        for edge in edges.iter().filter(|edge| edge.from == class) {
+            if edge.to == to {
+                let mut path = vec![*edge];
+                let mut class = class;
+                while class != from {
+                    let edge = previous[class]?;
+                    path.insert(0, edge);
+                    class = edge.from;
+                }
+
+                return Some(path);
+            }
+
+            if !visited[edge.to] {
+                visited[edge.to] = true;
+                previous[edge.to] = Some(*edge);
+                queue.push_back(edge.to);
+            }
+        }
+    }
+
+    None
+}
+
+/// The name of the receiver of a method, like `self`.
+fn receiver(def: &FunctionDef) -> Option<String> {
+    let params = &def.params;
+    params
+        .positional_only
+        .iter()
+        .chain(params.params.iter())
+        .next()
+        .map(|param| param.name.name.clone())
+}
+
+/// `Box[annotation]`
+fn boxed_annotation(annotation: TypeAnnotation) -> TypeAnnotation {
+    let span = annotation.span.clone();
+    TypeAnnotation {
+        kind: TypeAnnotationKind::Generic {
+            name: Identifier {
+                name: "Box".into(),
+                id: NodeId::next(),
+                span: span.clone(),
+                context: None,
+            },
+            arguments: vec![annotation],
+        },
+        span,
+    }
+}
+
+/// Reads the boxed fields of a receiver through their box, like `self.context.val` for
+/// `self.context`.
+fn read_through_boxes(expr: Expr, receiver: &str, fields: &[&str]) -> Expr {
+    struct BoxReader<'a> {
+        receiver: &'a str,
+        fields: &'a [&'a str],
+    }
+
+    impl Fold for BoxReader<'_> {
+        fn fold_expr(&mut self, node: Expr) -> Expr {
+            let is_boxed = match &node.kind {
+                ExprKind::Attribute { value, attribute } => {
+                    matches!(&value.kind, ExprKind::Identifier(name) if name.name == self.receiver)
+                        && self.fields.contains(&attribute.name.as_str())
+                }
+                _ => false,
+            };
+
+            if !is_boxed {
+                return fold::fold_expr(self, node);
+            }
+
+            let span = node.span.clone();
+            Expr {
+                kind: ExprKind::Attribute {
+                    value: Box::new(node),
+                    attribute: Identifier {
+                        name: "val".into(),
+                        id: NodeId::next(),
+                        span: span.clone(),
+                        context: None,
+                    },
+                },
+                id: NodeId::next(),
+                span,
+            }
+        }
+    }
+
+    BoxReader { receiver, fields }.fold_expr(expr)
+}
@@ -1,4 +1,30 @@
 #![allow(clippy::module_inception)]
+mod builtins;
+mod classes;
+mod conformance;
+mod containers;
+mod contracts;
+mod errors;
+mod escape;
+mod inference;
+mod instances;
+mod layout;
+mod monomorphizer;
+mod resolver;
 mod semantic;
+mod symbols;
+mod types;
+mod utils;
 
+pub use containers::*;
+pub use contracts::*;
+pub use errors::*;
+pub use escape::*;
+pub use instances::*;
+pub use layout::*;
 pub use semantic::*;
+pub use symbols::*;
+pub use types::*;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,1357 @@
+use std::collections::HashMap;
+
+use crate::ir::ClassKind;
+use crate::span::Span;
+
+use super::builtins;
+use super::classes::{Class, Classes};
+use super::containers::Containers;
+use super::contracts::{
+    Annotated, Call, Cast, Contract, ContractParam, InterfaceKind, Origin, ParamKind, ValueId,
+};
+use super::errors::{SemanticError, SemanticErrorKind};
+use super::instances::{describe, describe_all, InstanceId, Instances};
+use super::types::Type;
+use super::utils::site;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// Instantiates the functions of a module for the types of the arguments of their calls.
+///
+/// The walk starts at the calls of the module, whose arguments have the types of literals and of
+/// the instances the module constructs. Each call of a function instantiates the function once for
+/// the types of its arguments, and the types flow through the contract of the function to the
+/// calls it makes in turn. A method called on a builtin type is checked against the signatures of
+/// the builtin, and one called on a class instantiates the method of the class. A call that a type
+/// does not fit is an error with the steps that lead to it. Calls on values whose types are not
+/// known are left unchecked. The methods of enums are checked before the walk, against the members
+/// that the variants of the enum share, and the functions that nothing calls are checked after it
+/// with the types of their annotations, when all their parameters have one.
+pub(crate) struct Monomorphizer<'a> {
+    contracts: &'a [Contract],
+    classes: &'a Classes,
+    instances: Instances,
+    /// The container literals, with the methods called on their elements.
+    containers: Containers,
+    /// The types of the fields of each class, which are known once the class is constructed.
+    fields: HashMap<String, Vec<(String, Option<Type>)>>,
+    /// The instances being made, like `iterate_gift(IntGiver)`, from the outermost.
+    stack: Vec<String>,
+    /// The call in the module that the instances being made come from, where errors are reported.
+    site: Span,
+    diagnostics: &'a mut Vec<SemanticError>,
+}
+
+/// The types of the values of a contract in one instance.
+struct Frame<'c> {
+    contract: &'c Contract,
+    types: HashMap<ValueId, Option<Type>>,
+    /// The types the generic parameters of the function are bound to.
+    generics: HashMap<String, Type>,
+    callees: Vec<InstanceId>,
+}
+
+/// Why a method does not fit a receiver and its arguments.
+enum Mismatch {
+    Unimplemented,
+    /// The method takes a number of arguments with the receiver.
+    ArgumentCount(usize),
+    /// The argument at an index after the receiver does not fit.
+    Argument(usize),
+}
+
+/// What an annotation asks of the types it accepts.
+struct Members {
+    methods: Vec<String>,
+    fields: Vec<(String, Option<Type>)>,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl<'a> Monomorphizer<'a> {
+    fn new(
+        contracts: &'a [Contract],
+        classes: &'a Classes,
+        site: Span,
+        diagnostics: &'a mut Vec<SemanticError>,
+    ) -> Self {
+        Self {
+            contracts,
+            classes,
+            instances: Instances::default(),
+            containers: Containers::default(),
+            fields: HashMap::new(),
+            stack: vec![],
+            site,
+            diagnostics,
+        }
+    }
+
+    /// The instance of a function for the types of its arguments, which is made on the first call.
+    fn instantiate(&mut self, index: usize, arguments: Vec<Option<Type>>) -> InstanceId {
+        if let Some(id) = self.instances.find(index, &arguments) {
+            return id;
+        }
+
+        let contracts = self.contracts;
+        let contract = &contracts[index];
+        let id = self
+            .instances
+            .add(contract.name.clone(), index, arguments.clone());
+        self.stack
+            .push(format!("{}({})", contract.name, describe_all(&arguments)));
+
+        let mut frame = Frame::new(contract);
+        self.bind_params(&mut frame, &arguments);
+        self.calls(&mut frame);
+        if contract.name.ends_with(".__init__") {
+            self.record_fields(&mut frame);
+        }
+
+        let returns = self.returns(&mut frame);
+        self.stack.pop();
+        self.instances.finish(id, returns, frame.callees);
+        id
+    }
+
+    fn bind_params(&mut self, frame: &mut Frame, arguments: &[Option<Type>]) {
+        let contract = frame.contract;
+        for (param, argument) in contract.params.iter().zip(arguments.iter()) {
+            let mut ty = argument.clone();
+            let interface = contract.value(param.value).interface;
+            match interface.map(|interface| &contract.interface(interface).kind) {
+                Some(InterfaceKind::Generic) => {
+                    if let Some(argument) = argument {
+                        let name = &contract.interface(interface.unwrap()).name;
+                        frame.generics.insert(name.clone(), argument.clone());
+                    }
+                }
+                // The instance goes on with the annotation after a mismatch, so that the error does
+                // not lead to more.
+                Some(InterfaceKind::Concrete(annotation)) => match argument {
+                    Some(argument) if !self.accepts(frame, annotation, argument) => {
+                        self.fail(
+                            SemanticErrorKind::MismatchedArgument,
+                            vec![
+                                format!("{}: {argument}", param.name),
+                                format!("{argument} is not {annotation}"),
+                            ],
+                        );
+                        ty = Some(annotation.clone());
+                    }
+                    // What the body does with an intersection must fit all of its variants, not
+                    // only the one passed.
+                    Some(_) if matches!(annotation, Type::Intersection(_)) => {
+                        ty = Some(self.classes.expand(annotation))
+                    }
+                    None => ty = Some(self.classes.expand(annotation)),
+                    _ => (),
+                },
+                _ => (),
+            }
+
+            frame.types.insert(param.value, ty);
+        }
+    }
+
+    fn calls(&mut self, frame: &mut Frame) {
+        for call in frame.contract.calls.iter() {
+            self.call(frame, call);
+        }
+
+        for cast in frame.contract.casts.iter() {
+            self.cast(frame, cast);
+        }
+
+        for annotated in frame.contract.annotated.iter() {
+            self.annotated(frame, annotated);
+        }
+    }
+
+    /// Checks that a `cast[T]` can succeed, which it cannot when no variant of the value can have
+    /// the type it is cast to.
+    fn cast(&mut self, frame: &mut Frame, cast: &Cast) {
+        if cast.is_check {
+            return;
+        }
+
+        if self.stack.is_empty() {
+            self.site = site(&cast.span).clone();
+        }
+
+        let (parent, ty) = match &frame.contract.value(cast.value).origin {
+            Origin::Narrowed(parent, ty) => (*parent, ty),
+            _ => return,
+        };
+
+        let target = self.classes.expand(&frame.substitute(ty));
+        let actual = match self.type_of(frame, parent) {
+            Some(actual) => actual,
+            None => return,
+        };
+
+        if self.narrowed_type(frame, &actual, &target).is_none() {
+            let mut chain = self.explain(frame, parent);
+            chain.push(format!("{actual} can never be {ty}"));
+            self.fail(SemanticErrorKind::ImpossibleCast, chain);
+        }
+    }
+
+    /// Checks that a value assigned to a name fits the annotation of the name, like an argument
+    /// fits the annotation of its parameter.
+    fn annotated(&mut self, frame: &mut Frame, annotated: &Annotated) {
+        if self.stack.is_empty() {
+            self.site = site(&annotated.span).clone();
+        }
+
+        let (parent, ty) = match &frame.contract.value(annotated.value).origin {
+            Origin::Annotated(parent, ty) => (*parent, ty),
+            _ => return,
+        };
+
+        let expected = self.classes.expand(&frame.substitute(ty));
+        let actual = match self.type_of(frame, parent) {
+            Some(actual) => actual,
+            None => return,
+        };
+
+        if !self.accepts(frame, &expected, &actual) && !self.converts(frame, parent, &expected) {
+            let mut chain = self.explain(frame, parent);
+            chain.push(format!("{}: {actual}", annotated.name));
+            chain.push(format!("{actual} is not {ty}"));
+            self.fail(SemanticErrorKind::MismatchedAnnotation, chain);
+        }
+    }
+
+    /// The type a cast leaves of a value, which has the variants of the value that are of the type
+    /// and the variants of the type that are of a variant of the value, like `int` for `int & str`
+    /// cast to `int`, or `IntGiver` for a `Giver`. It is nothing for a cast that cannot succeed.
+    fn narrowed_type(&self, frame: &Frame, actual: &Type, target: &Type) -> Option<Type> {
+        let alternatives = match target {
+            Type::Union(types) => types.iter().collect(),
+            target => target.variants(),
+        };
+
+        let mut types = vec![];
+        for variant in actual.variants() {
+            if alternatives
+                .iter()
+                .any(|alternative| self.accepts(frame, alternative, variant))
+            {
+                types.push(variant.clone());
+                continue;
+            }
+
+            types.extend(
+                alternatives
+                    .iter()
+                    .filter(|alternative| self.accepts(frame, variant, alternative))
+                    .map(|alternative| (*alternative).clone()),
+            );
+        }
+
+        Type::intersection(types)
+    }
+
+    fn call(&mut self, frame: &mut Frame, call: &Call) {
+        if self.stack.is_empty() {
+            self.site = site(&call.span).clone();
+        }
+
+        // An `int` literal passed to a function takes the integer type of its parameter.
+        let interface = frame.contract.interface(call.interface);
+        let contracts = self.contracts;
+        let callee = match interface.kind {
+            InterfaceKind::Function => self
+                .contract_index(&interface.name)
+                .map(|index| &contracts[index]),
+            _ => None,
+        };
+        let positional = callee.map_or(vec![], |callee| {
+            callee
+                .params
+                .iter()
+                .filter(|param| param.kind == ParamKind::Positional)
+                .collect()
+        });
+
+        let arguments = call
+            .arguments
+            .iter()
+            .enumerate()
+            .map(|(index, argument)| {
+                let annotation = callee.zip(positional.get(index).copied());
+                let annotation =
+                    annotation.and_then(|(callee, param)| annotation_of(callee, param));
+                self.converted_type(frame, *argument, annotation)
+            })
+            .collect::<Vec<_>>();
+        let keywords = call
+            .keywords
+            .iter()
+            .map(|(name, argument)| {
+                let param = callee.and_then(|callee| {
+                    let param = callee.params.iter().find(|param| &param.name == name)?;
+                    annotation_of(callee, param)
+                });
+                (name.clone(), self.converted_type(frame, *argument, param))
+            })
+            .collect::<Vec<_>>();
+
+        let result = match (&interface.kind, arguments.split_first()) {
+            (InterfaceKind::Function, _) => {
+                self.call_function(frame, &interface.name, arguments, keywords)
+            }
+            (_, Some((Some(receiver), rest))) => {
+                self.call_method(frame, call, receiver, rest, keywords)
+            }
+            _ => None,
+        };
+
+        // Calls of the same method on the same interface share their result, which has the type
+        // of the latest one.
+        if result.is_some() || !frame.types.contains_key(&call.result) {
+            frame.types.insert(call.result, result);
+        }
+    }
+
+    fn call_function(
+        &mut self,
+        frame: &mut Frame,
+        name: &str,
+        arguments: Vec<Option<Type>>,
+        keywords: Vec<(String, Option<Type>)>,
+    ) -> Option<Type> {
+        let index = self.contract_index(name)?;
+        let arguments = self.arrange(index, arguments, keywords)?;
+        let id = self.instantiate(index, arguments);
+        frame.add_callee(id);
+        self.instances.get(id).returns.clone()
+    }
+
+    /// Calls a method, which must fit every combination of the variants of the receiver and the
+    /// arguments when they are intersections.
+    fn call_method(
+        &mut self,
+        frame: &mut Frame,
+        call: &Call,
+        receiver: &Type,
+        arguments: &[Option<Type>],
+        keywords: Vec<(String, Option<Type>)>,
+    ) -> Option<Type> {
+        let variants = arguments
+            .iter()
+            .map(|argument| match argument {
+                Some(argument) => argument.variants().into_iter().cloned().map(Some).collect(),
+                None => vec![None],
+            })
+            .collect::<Vec<Vec<_>>>();
+
+        let receivers = receiver.variants();
+        if receivers.len() == 1 && variants.iter().all(|variants| variants.len() == 1) {
+            return match self.dispatch(frame, call, receiver, arguments, keywords) {
+                Ok(returns) => returns,
+                Err(mismatch) => {
+                    self.report(frame, call, receiver, arguments, mismatch);
+                    None
+                }
+            };
+        }
+
+        let mut returns = vec![];
+        let mut is_known = true;
+        let mut missing = vec![];
+        for variant in receivers {
+            for combination in combinations(&variants) {
+                match self.dispatch(frame, call, variant, &combination, keywords.clone()) {
+                    Ok(Some(ty)) => returns.push(ty),
+                    Ok(None) => is_known = false,
+                    Err(_) => missing.push(format!(
+                        "{}[{variant}{}]",
+                        call.method,
+                        combination
+                            .iter()
+                            .map(|argument| format!(", {}", describe(argument)))
+                            .collect::<String>()
+                    )),
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let mut chain = self.explain_all(frame, &call.arguments);
+            chain.push(format!("{receiver} has no {}", missing.join(" or ")));
+            self.fail(SemanticErrorKind::IncompatibleVariants, chain);
+            return None;
+        }
+
+        if receiver.variants().len() > 1 {
+            self.containers
+                .call(receiver, &call.method, call.arguments.len());
+        }
+
+        match is_known {
+            true => Type::intersection(returns),
+            false => None,
+        }
+    }
+
+    /// Calls a method on a receiver that is not an intersection. A class instantiates the method
+    /// of the class or of the base that defines it, and a builtin type takes the signature that the
+    /// arguments fit.
+    fn dispatch(
+        &mut self,
+        frame: &mut Frame,
+        call: &Call,
+        receiver: &Type,
+        arguments: &[Option<Type>],
+        keywords: Vec<(String, Option<Type>)>,
+    ) -> Result<Option<Type>, Mismatch> {
+        let method = &call.method;
+        let name = match receiver.name() {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+
+        let classes = self.classes;
+        if let Some(class) = classes.get(name) {
+            if let Some(owner) = classes.method_owner(name, method) {
+                let mut all = vec![Some(receiver.clone())];
+                all.extend(arguments.iter().cloned());
+                return Ok(self.call_class_method(frame, &owner.name, method, all, keywords));
+            }
+
+            // `self.__super__()` constructs the instance as the class it inherits from.
+            if method == "__super__" {
+                let base = class.bases.iter().find(|base| {
+                    classes
+                        .get(base)
+                        .is_some_and(|base| base.kind != ClassKind::Abstract)
+                });
+
+                if let Some(owner) = base.and_then(|base| classes.method_owner(base, "__init__")) {
+                    let mut all = vec![Some(receiver.clone())];
+                    all.extend(arguments.iter().cloned());
+                    self.call_class_method(frame, &owner.name, "__init__", all, keywords);
+                }
+
+                return Ok(Some(Type::named("None")));
+            }
+
+            // The abstract methods have no body to instantiate.
+            if classes
+                .methods(name)
+                .iter()
+                .any(|existing| existing == method)
+            {
+                return Ok(None);
+            }
+
+            if method == "__init__" {
+                self.construct(frame, call, class, arguments, keywords);
+                return Ok(Some(Type::named("None")));
+            }
+        }
+
+        let mut signatures = builtins::signatures(receiver, method);
+        signatures.extend(builtins::object_signature(receiver, method));
+        if classes.get(name).is_none() && !builtins::is_builtin(name) {
+            // The methods of types without a method table are not known.
+            return Ok(signatures
+                .first()
+                .and_then(|signature| signature.returns(arguments).ok().flatten()));
+        }
+
+        if signatures.is_empty() {
+            return Err(Mismatch::Unimplemented);
+        }
+
+        let candidates = signatures
+            .iter()
+            .filter(|signature| signature.params.len() == arguments.len())
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            return Err(Mismatch::ArgumentCount(signatures[0].params.len() + 1));
+        }
+
+        let mut mismatch = 0;
+        for signature in candidates.iter() {
+            match signature.returns(arguments) {
+                Ok(returns) => return Ok(returns),
+                Err(index) => mismatch = index,
+            }
+        }
+
+        Err(Mismatch::Argument(mismatch))
+    }
+
+    /// Instantiates the method of a class for the receiver and the arguments. A method that blocks
+    /// of the class define for different instantiations of an abstract class is the one whose
+    /// annotations fit the arguments, with the most annotations fitting.
+    fn call_class_method(
+        &mut self,
+        frame: &mut Frame,
+        owner: &str,
+        method: &str,
+        arguments: Vec<Option<Type>>,
+        keywords: Vec<(String, Option<Type>)>,
+    ) -> Option<Type> {
+        let name = format!("{owner}.{method}");
+        let mut candidates = vec![];
+        for (index, contract) in self.contracts.iter().enumerate() {
+            if contract.name != name {
+                continue;
+            }
+
+            let arranged = match self.arrange(index, arguments.clone(), keywords.clone()) {
+                Some(arranged) => arranged,
+                None => continue,
+            };
+
+            let specific = Frame::new(contract);
+            let mut fitting = Some(0);
+            for (param, argument) in contract.params.iter().zip(arranged.iter()) {
+                let interface = contract.value(param.value).interface;
+                if let Some(InterfaceKind::Concrete(annotation)) =
+                    interface.map(|interface| &contract.interface(interface).kind)
+                {
+                    fitting = match argument {
+                        Some(argument) if !self.accepts(&specific, annotation, argument) => None,
+                        _ => fitting.map(|count| count + 1),
+                    };
+                }
+            }
+
+            candidates.push((index, arranged, fitting));
+        }
+
+        // Without a candidate that fits, the first one reports the mismatch.
+        let fits = candidates
+            .iter()
+            .filter(|(.., fitting)| fitting.is_some())
+            .max_by_key(|(index, _, fitting)| (*fitting, std::cmp::Reverse(*index)));
+
+        let (index, arguments) = match fits.or(candidates.first()) {
+            Some((index, arguments, _)) => (*index, arguments.clone()),
+            None => return None,
+        };
+
+        let id = self.instantiate(index, arguments);
+        frame.add_callee(id);
+        self.instances.get(id).returns.clone()
+    }
+
+    fn report(
+        &mut self,
+        frame: &Frame,
+        call: &Call,
+        receiver: &Type,
+        arguments: &[Option<Type>],
+        mismatch: Mismatch,
+    ) {
+        let method = &call.method;
+        let (kind, values, step) = match mismatch {
+            Mismatch::Unimplemented => (
+                SemanticErrorKind::UnimplementedMethod,
+                &call.arguments[..1],
+                format!(
+                    "{receiver} does not implement {method}.{}",
+                    arguments.len() + 1
+                ),
+            ),
+            Mismatch::ArgumentCount(takes) => (
+                SemanticErrorKind::WrongArgumentCount,
+                &call.arguments[..1],
+                format!(
+                    "{receiver}.{method} takes {takes} arguments but {} were given",
+                    arguments.len() + 1
+                ),
+            ),
+            Mismatch::Argument(index) => (
+                SemanticErrorKind::MismatchedArgument,
+                &[call.arguments[0], call.arguments[index + 1]][..],
+                format!(
+                    "{receiver}.{method} does not take {} at {}",
+                    describe(&arguments[index]),
+                    index + 1
+                ),
+            ),
+        };
+
+        let mut chain = self.explain_all(frame, values);
+        chain.push(step);
+        self.fail(kind, chain);
+    }
+
+    /// Constructs a class without an `__init__`, which takes the primary constructor fields of a
+    /// data class and nothing otherwise.
+    fn construct(
+        &mut self,
+        frame: &mut Frame,
+        call: &Call,
+        class: &Class,
+        arguments: &[Option<Type>],
+        keywords: Vec<(String, Option<Type>)>,
+    ) {
+        let params = match class.kind {
+            ClassKind::Data => class.params.as_slice(),
+            _ => &[],
+        };
+
+        if arguments.len() > params.len() {
+            self.fail(
+                SemanticErrorKind::WrongArgumentCount,
+                vec![format!(
+                    "{} takes {} arguments but {} were given",
+                    class.name,
+                    params.len(),
+                    arguments.len()
+                )],
+            );
+            return;
+        }
+
+        let mut types = vec![None; params.len()];
+        for (index, argument) in arguments.iter().enumerate() {
+            types[index] = Some((argument.clone(), call.arguments[index + 1]));
+        }
+
+        for (index, (name, argument)) in keywords.into_iter().enumerate() {
+            match params.iter().position(|param| param.name == name) {
+                Some(position) if types[position].is_none() => {
+                    types[position] = Some((argument, call.keywords[index].1))
+                }
+                _ => {
+                    self.fail(
+                        SemanticErrorKind::WrongArgumentCount,
+                        vec![format!("{} has no field {name} to pass", class.name)],
+                    );
+                    return;
+                }
+            }
+        }
+
+        let mut fields = vec![];
+        for (param, ty) in params.iter().zip(types) {
+            let ty = match (ty, &param.annotation) {
+                (None, _) if !param.has_default => {
+                    self.fail(
+                        SemanticErrorKind::WrongArgumentCount,
+                        vec![format!("{} is missing field {}", class.name, param.name)],
+                    );
+                    return;
+                }
+                // An `int` literal takes the integer type of the field, like `byte`.
+                (Some((Some(_), value)), Some(annotation))
+                    if self.converts(frame, value, annotation) =>
+                {
+                    Some(annotation.clone())
+                }
+                (Some((Some(actual), value)), Some(annotation))
+                    if !class.is_generic(annotation)
+                        && !self.accepts(frame, annotation, &actual) =>
+                {
+                    let mut chain = self.explain(frame, value);
+                    chain.push(format!(
+                        "{}.{}: {actual} is not {annotation}",
+                        class.name, param.name
+                    ));
+                    self.fail(SemanticErrorKind::MismatchedArgument, chain);
+                    return;
+                }
+                (Some((Some(actual), _)), _) => Some(actual),
+                (_, annotation) => annotation.as_ref().map(|ty| self.classes.expand(ty)),
+            };
+
+            fields.push((param.name.clone(), ty));
+        }
+
+        fields.extend(
+            class
+                .fields
+                .iter()
+                .map(|field| (field.name.clone(), field.annotation.clone())),
+        );
+        self.fields.entry(class.name.clone()).or_insert(fields);
+    }
+
+    /// Records the fields an `__init__` gives its instance, for the class the instance is of.
+    fn record_fields(&mut self, frame: &mut Frame) {
+        let contract = frame.contract;
+        let receiver = match contract.params.first() {
+            Some(param) => param.value,
+            None => return,
+        };
+
+        let class = match frame.types.get(&receiver).cloned().flatten() {
+            Some(Type::Named { name, .. }) => name,
+            _ => return,
+        };
+
+        if self.fields.contains_key(&class) {
+            return;
+        }
+
+        let mut fields = contract
+            .value(receiver)
+            .fields
+            .iter()
+            .map(|(name, value)| (name.clone(), self.type_of(frame, *value)))
+            .collect::<Vec<_>>();
+
+        if let Some(class) = self.classes.get(&class) {
+            for field in class.fields.iter() {
+                if !fields.iter().any(|(name, _)| *name == field.name) {
+                    fields.push((field.name.clone(), field.annotation.clone()));
+                }
+            }
+        }
+
+        self.fields.insert(class, fields);
+    }
+
+    /// The types the instance can return, which is an intersection for more than one.
+    fn returns(&mut self, frame: &mut Frame) -> Option<Type> {
+        let contract = frame.contract;
+        if contract.returns.is_empty() {
+            return Some(Type::named("None"));
+        }
+
+        let types = contract
+            .returns
+            .iter()
+            .filter_map(|value| self.type_of(frame, *value))
+            .collect::<Vec<_>>();
+
+        Type::intersection(types)
+    }
+
+    /// The type of a value in the instance, which is unknown for a value the calls do not type.
+    fn type_of(&mut self, frame: &mut Frame, value: ValueId) -> Option<Type> {
+        if let Some(ty) = frame.types.get(&value) {
+            return ty.clone();
+        }
+
+        let ty = match &frame.contract.value(value).origin {
+            Origin::Literal(ty) => {
+                let ty = frame.substitute(ty);
+                Some(self.collection_type(frame, value, ty))
+            }
+            Origin::Field(parent, name) => {
+                let parent_type = self.type_of(frame, *parent)?;
+                self.field_type(frame, &parent_type, name, *parent)
+            }
+            Origin::Narrowed(parent, ty) => {
+                let target = self.classes.expand(&frame.substitute(ty));
+                match self.type_of(frame, *parent) {
+                    Some(actual) => self.narrowed_type(frame, &actual, &target).or(Some(target)),
+                    None => Some(target),
+                }
+            }
+            Origin::Annotated(_, ty) => Some(self.classes.expand(&frame.substitute(ty))),
+            Origin::Param(_) | Origin::Returned(..) | Origin::Unknown => None,
+        };
+
+        frame.types.insert(value, ty.clone());
+        ty
+    }
+
+    /// The type of a collection literal with the types of its elements, like `list[int & str]`
+    /// for `[5, "Hello"]`.
+    fn collection_type(&mut self, frame: &mut Frame, value: ValueId, ty: Type) -> Type {
+        let elements = &frame.contract.value(value).elements;
+        let types = elements
+            .iter()
+            .map(|element| self.type_of(frame, *element))
+            .collect::<Option<Vec<_>>>();
+
+        let (name, types) = match (ty.name(), types) {
+            (Some(name), Some(types)) if !types.is_empty() => (name.to_string(), types),
+            _ => return ty,
+        };
+
+        let arguments = match name.as_str() {
+            "tuple" => types,
+            "dict" => {
+                let keys = types.iter().step_by(2).cloned();
+                let values = types.iter().skip(1).step_by(2).cloned();
+                vec![
+                    Type::intersection(keys).unwrap(),
+                    Type::intersection(values).unwrap(),
+                ]
+            }
+            _ => vec![Type::intersection(types).unwrap()],
+        };
+
+        let ty = Type::Named { name, arguments };
+        self.containers.add(&ty);
+        ty
+    }
+
+    /// The type of a field, which every variant of an intersection must have wherever it has it.
+    fn field_type(
+        &mut self,
+        frame: &mut Frame,
+        parent_type: &Type,
+        name: &str,
+        parent: ValueId,
+    ) -> Option<Type> {
+        let mut types = vec![];
+        let mut is_known = true;
+        for variant in parent_type.variants() {
+            let class = match variant.name().and_then(|name| self.classes.get(name)) {
+                Some(class) => class,
+                None => {
+                    is_known = false;
+                    continue;
+                }
+            };
+
+            match self.class_field(class, name) {
+                Ok(Some(ty)) => types.push(ty),
+                Ok(None) => is_known = false,
+                Err(()) => {
+                    let mut chain = self.explain(frame, parent);
+                    chain.push(format!("{} has no field {name}", class.name));
+                    self.fail(SemanticErrorKind::MissingField, chain);
+                    return None;
+                }
+            }
+        }
+
+        match is_known {
+            true => Type::intersection(types),
+            false => None,
+        }
+    }
+
+    /// Checks that the methods of the enums only use the members that every variant has, or the
+    /// members of the variant their receiver is annotated with.
+    fn check_enums(&mut self) {
+        let classes = self.classes;
+        let enums = classes.iter().filter(|class| class.kind == ClassKind::Enum);
+
+        for class in enums {
+            for method in class.methods.iter() {
+                if let Some(index) = self.contract_index(&format!("{}.{}", class.name, method.name))
+                {
+                    self.check_enum_method(class, index);
+                }
+            }
+        }
+    }
+
+    /// Instantiates the functions that nothing calls and whose parameters are all annotated, with
+    /// the types of their annotations, so that their bodies are checked too.
+    fn check_uncalled(&mut self) {
+        let contracts = self.contracts;
+        for (index, contract) in contracts.iter().enumerate() {
+            let annotations = contract
+                .params
+                .iter()
+                .map(|param| {
+                    let interface = contract.value(param.value).interface?;
+                    match &contract.interface(interface).kind {
+                        InterfaceKind::Concrete(annotation) => Some(Some(annotation.clone())),
+                        _ => None,
+                    }
+                })
+                .collect::<Option<Vec<_>>>();
+
+            if let Some(arguments) = annotations {
+                if self.instances.of(&contract.name).next().is_none() {
+                    self.site = contract.span.clone();
+                    self.instantiate(index, arguments);
+                }
+            }
+        }
+    }
+
+    fn check_enum_method(&mut self, class: &Class, index: usize) {
+        let contract = &self.contracts[index];
+        let param = match contract.params.first() {
+            Some(param) if !class.variants.is_empty() => param,
+            _ => return,
+        };
+
+        let receiver = contract.value(param.value);
+        let annotation = match receiver.interface.map(|id| &contract.interface(id).kind) {
+            Some(InterfaceKind::Concrete(annotation)) => annotation.clone(),
+            _ => Type::named(&class.name),
+        };
+
+        let ty = self.classes.expand(&annotation);
+        self.site = contract.span.clone();
+        let header = vec![
+            contract.name.clone(),
+            format!("{}: {annotation}", param.name),
+        ];
+        for (name, _) in receiver.fields.iter() {
+            let missing = ty
+                .variants()
+                .into_iter()
+                .filter_map(|variant| self.classes.get(variant.name()?))
+                .filter(|variant| self.class_field(variant, name).is_err())
+                .map(|variant| format!("{} has no field {name}", variant.name))
+                .collect::<Vec<_>>();
+
+            if !missing.is_empty() {
+                let mut chain = header.clone();
+                chain.extend(missing);
+                self.fail(SemanticErrorKind::MissingField, chain);
+            }
+        }
+
+        let calls = contract.calls.iter().filter(|call| {
+            contract.interface(call.interface).kind != InterfaceKind::Function
+                && call.arguments.first() == Some(&param.value)
+        });
+
+        for call in calls {
+            let missing = ty
+                .variants()
+                .into_iter()
+                .filter(|variant| {
+                    self.members(variant)
+                        .is_some_and(|members| !members.methods.contains(&call.method))
+                })
+                .map(|variant| {
+                    let arity = call.arguments.len();
+                    format!("{variant} does not implement {}.{arity}", call.method)
+                })
+                .collect::<Vec<_>>();
+
+            if !missing.is_empty() {
+                let mut chain = header.clone();
+                chain.extend(missing);
+                self.fail(SemanticErrorKind::UnimplementedMethod, chain);
+            }
+        }
+    }
+
+    /// The type of a field of a class, which is an error for a class whose fields are all known.
+    /// The fields are known once the class is constructed, and for a data class from its
+    /// definition.
+    fn class_field(&self, class: &Class, name: &str) -> Result<Option<Type>, ()> {
+        let is_method = self.classes.method_owner(&class.name, name).is_some();
+        if let Some(fields) = self.fields.get(&class.name) {
+            return match fields.iter().find(|(field, _)| field == name) {
+                Some((_, ty)) => Ok(ty.clone()),
+                None if is_method => Ok(None),
+                None => Err(()),
+            };
+        }
+
+        let field = class
+            .params
+            .iter()
+            .chain(class.fields.iter())
+            .find(|field| field.name == name);
+
+        match field {
+            Some(field) => Ok(field.annotation.as_ref().map(|ty| self.classes.expand(ty))),
+            None if class.kind == ClassKind::Data && !is_method => Err(()),
+            None => Ok(None),
+        }
+    }
+
+    /// The methods and fields of a type, which are the ones all variants have for an intersection
+    /// and the ones any has for a union.
+    fn members(&self, ty: &Type) -> Option<Members> {
+        match ty {
+            Type::Named { name, .. } => {
+                let class = match self.classes.get(name) {
+                    Some(class) => class,
+                    None if builtins::is_builtin(name) => {
+                        return Some(Members {
+                            methods: builtins::methods(name)
+                                .into_iter()
+                                .map(String::from)
+                                .collect(),
+                            fields: vec![],
+                        })
+                    }
+                    None => return None,
+                };
+
+                let mut methods = self.classes.methods(name);
+                methods.extend(builtins::methods("object").into_iter().map(String::from));
+                let fields = match self.fields.get(name) {
+                    Some(fields) => fields.clone(),
+                    None => class
+                        .params
+                        .iter()
+                        .chain(class.fields.iter())
+                        .map(|field| (field.name.clone(), field.annotation.clone()))
+                        .collect(),
+                };
+
+                Some(Members { methods, fields })
+            }
+            Type::Intersection(types) => {
+                let mut all = types.iter().map(|ty| self.members(ty));
+                let mut members = all.next()??;
+                for other in all {
+                    let other = other?;
+                    members
+                        .methods
+                        .retain(|method| other.methods.contains(method));
+                    members.fields.retain(|field| other.fields.contains(field));
+                }
+
+                Some(members)
+            }
+            Type::Union(types) => {
+                let mut members = Members {
+                    methods: vec![],
+                    fields: vec![],
+                };
+
+                for other in types.iter() {
+                    let other = self.members(other)?;
+                    members.methods.extend(other.methods);
+                    members.fields.extend(other.fields);
+                }
+
+                Some(members)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether a type has the members of another, whose fields it can have at any position.
+    fn has_members(&self, expected: &Type, actual: &Type) -> bool {
+        match (self.members(expected), self.members(actual)) {
+            (Some(expected), Some(actual)) => {
+                expected
+                    .methods
+                    .iter()
+                    .all(|method| actual.methods.contains(method))
+                    && expected.fields.iter().all(|(name, ty)| {
+                        actual.fields.iter().any(|(other, other_ty)| {
+                            other == name && (ty.is_none() || other_ty.is_none() || ty == other_ty)
+                        })
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// Matches the arguments of a call to the parameters of a function, giving the types of the
+    /// parameters in order. A parameter that is left to its default has the type of the default,
+    /// which is unknown unless the default is a literal.
+    fn arrange(
+        &mut self,
+        index: usize,
+        arguments: Vec<Option<Type>>,
+        keywords: Vec<(String, Option<Type>)>,
+    ) -> Option<Vec<Option<Type>>> {
+        let contract = &self.contracts[index];
+        let given = arguments.len();
+        let mut slots = vec![None; contract.params.len()];
+        let mut arguments = arguments.into_iter();
+        let mut takes = 0;
+        for (slot, param) in slots.iter_mut().zip(contract.params.iter()) {
+            match param.kind {
+                ParamKind::Positional => {
+                    takes += 1;
+                    *slot = arguments.next();
+                }
+                ParamKind::Vararg => {
+                    arguments.by_ref().for_each(drop);
+                    *slot = Some(Some(Type::named("tuple")));
+                }
+                ParamKind::Kwarg => *slot = Some(Some(Type::named("dict"))),
+                ParamKind::Keyword => (),
+            }
+        }
+
+        if arguments.next().is_some() {
+            self.fail(
+                SemanticErrorKind::WrongArgumentCount,
+                vec![format!(
+                    "{} takes {takes} arguments but {given} were given",
+                    contract.name
+                )],
+            );
+            return None;
+        }
+
+        for (name, argument) in keywords {
+            let position = contract.params.iter().position(|param| {
+                param.name == name
+                    && matches!(param.kind, ParamKind::Positional | ParamKind::Keyword)
+            });
+
+            match position {
+                Some(position) if slots[position].is_none() => slots[position] = Some(argument),
+                _ if contract
+                    .params
+                    .iter()
+                    .any(|param| param.kind == ParamKind::Kwarg) => {}
+                _ => {
+                    self.fail(
+                        SemanticErrorKind::WrongArgumentCount,
+                        vec![format!("{} has no parameter {name} to pass", contract.name)],
+                    );
+                    return None;
+                }
+            }
+        }
+
+        let mut types = vec![];
+        for (slot, param) in slots.into_iter().zip(contract.params.iter()) {
+            match (slot, param.default) {
+                (Some(ty), _) => types.push(ty),
+                (None, Some(default)) => {
+                    let mut frame = Frame::new(contract);
+                    types.push(self.type_of(&mut frame, default));
+                }
+                (None, None) => {
+                    self.fail(
+                        SemanticErrorKind::WrongArgumentCount,
+                        vec![format!(
+                            "{} is missing argument {}",
+                            contract.name, param.name
+                        )],
+                    );
+                    return None;
+                }
+            }
+        }
+
+        Some(types)
+    }
+
+    /// Whether an argument fits the annotation of its parameter, which a class does if it names
+    /// the annotation as a base. A type fits an intersection if it is one of its variants or has
+    /// the members they share, and a union if it has the members of all of them.
+    fn accepts(&self, frame: &Frame, expected: &Type, actual: &Type) -> bool {
+        match (expected, actual) {
+            (Type::Named { name, .. }, _) if name == "object" || frame.is_generic(name) => true,
+            (
+                Type::Named { name, arguments },
+                Type::Named {
+                    name: other,
+                    arguments: others,
+                },
+            ) => {
+                let is_same = name == other
+                    && (arguments.is_empty()
+                        || others.is_empty()
+                        || arguments.len() == others.len()
+                            && arguments
+                                .iter()
+                                .zip(others.iter())
+                                .all(|(expected, actual)| self.accepts(frame, expected, actual)));
+
+                is_same || self.classes.implements(other, name)
+            }
+            (Type::Intersection(_), Type::Intersection(actuals)) => actuals
+                .iter()
+                .all(|actual| self.accepts(frame, expected, actual)),
+            (Type::Intersection(types), actual) => {
+                types
+                    .iter()
+                    .any(|expected| self.accepts(frame, expected, actual))
+                    || self.has_members(expected, actual)
+            }
+            (Type::Union(_), actual) => expected == actual || self.has_members(expected, actual),
+            (Type::Optional(inner), actual) => {
+                actual.name() == Some("None") || self.accepts(frame, inner, actual)
+            }
+            _ => expected == actual,
+        }
+    }
+
+    /// Whether a value is an `int` literal that converts to an integer type, like `1` to a `byte`.
+    fn converts(&self, frame: &Frame, value: ValueId, expected: &Type) -> bool {
+        matches!(
+            &frame.contract.value(value).origin,
+            Origin::Literal(ty) if ty.name() == Some("int")
+        ) && expected.name().is_some_and(builtins::is_integer)
+    }
+
+    /// The type of a value, or the integer type an `int` literal converts to for an annotation.
+    fn converted_type(
+        &mut self,
+        frame: &mut Frame,
+        value: ValueId,
+        annotation: Option<&Type>,
+    ) -> Option<Type> {
+        match annotation {
+            Some(annotation) if self.converts(frame, value, annotation) => Some(annotation.clone()),
+            _ => self.type_of(frame, value),
+        }
+    }
+
+    /// How a value of the instance got its type, like `giver: IntGiver` for a parameter and
+    /// `T.gift returns int` for the value a method returns.
+    fn explain(&self, frame: &Frame, value: ValueId) -> Vec<String> {
+        let contract = frame.contract;
+        let ty = describe(&frame.types.get(&value).cloned().flatten());
+        match &contract.value(value).origin {
+            Origin::Param(index) => vec![format!("{}: {ty}", contract.params[*index].name)],
+            Origin::Returned(..) => {
+                let producer = contract.calls.iter().find(|call| call.result == value);
+                let mut steps = match producer {
+                    Some(call)
+                        if contract.interface(call.interface).kind != InterfaceKind::Function
+                            && call.arguments[0] != value =>
+                    {
+                        self.explain(frame, call.arguments[0])
+                    }
+                    _ => vec![],
+                };
+
+                steps.push(format!("{} returns {ty}", contract.reference(value)));
+                steps
+            }
+            Origin::Field(parent, _) | Origin::Narrowed(parent, _) => {
+                let mut steps = self.explain(frame, *parent);
+                steps.push(format!("{}: {ty}", contract.reference(value)));
+                steps
+            }
+            Origin::Literal(_) | Origin::Annotated(..) | Origin::Unknown => vec![],
+        }
+    }
+
+    /// How values got their types, without repeating a step.
+    fn explain_all(&self, frame: &Frame, values: &[ValueId]) -> Vec<String> {
+        let mut chain = vec![];
+        for value in values.iter() {
+            for step in self.explain(frame, *value) {
+                if !chain.contains(&step) {
+                    chain.push(step);
+                }
+            }
+        }
+
+        chain
+    }
+
+    fn contract_index(&self, name: &str) -> Option<usize> {
+        self.contracts
+            .iter()
+            .position(|contract| contract.name == name)
+    }
+
+    /// Reports an error at the call in the module, after the instances that lead to it.
+    fn fail(&mut self, kind: SemanticErrorKind, steps: Vec<String>) {
+        let mut chain = self.stack.clone();
+        chain.extend(steps);
+        let error = SemanticError::with_chain(kind, self.site.clone(), chain);
+        if !self.diagnostics.contains(&error) {
+            self.diagnostics.push(error);
+        }
+    }
+}
+
+impl<'c> Frame<'c> {
+    fn new(contract: &'c Contract) -> Self {
+        Self {
+            contract,
+            types: HashMap::new(),
+            generics: HashMap::new(),
+            callees: vec![],
+        }
+    }
+
+    fn is_generic(&self, name: &str) -> bool {
+        self.contract
+            .interfaces
+            .iter()
+            .any(|interface| interface.kind == InterfaceKind::Generic && interface.name == name)
+    }
+
+    fn add_callee(&mut self, id: InstanceId) {
+        if !self.callees.contains(&id) {
+            self.callees.push(id);
+        }
+    }
+
+    /// A type with the generic parameters of the function bound to the types of the arguments.
+    fn substitute(&self, ty: &Type) -> Type {
+        let all = |types: &[Type]| types.iter().map(|ty| self.substitute(ty)).collect();
+        match ty {
+            Type::Named { name, arguments } if arguments.is_empty() => self
+                .generics
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| ty.clone()),
+            Type::Named { name, arguments } => Type::Named {
+                name: name.clone(),
+                arguments: all(arguments),
+            },
+            Type::Function {
+                params,
+                return_type,
+            } => Type::Function {
+                params: all(params),
+                return_type: Box::new(self.substitute(return_type)),
+            },
+            Type::List(types) => Type::List(all(types)),
+            Type::Tuple(types) => Type::Tuple(all(types)),
+            Type::Union(types) => Type::Union(all(types)),
+            Type::Intersection(types) => Type::Intersection(all(types)),
+            Type::Optional(inner) => Type::Optional(Box::new(self.substitute(inner))),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Instantiates the functions that the code at the top of a module calls, and the ones they call
+/// in turn, with the container literals they type. The functions nothing calls are instantiated
+/// for their annotations.
+pub(crate) fn monomorphize(
+    module: &Contract,
+    contracts: &[Contract],
+    classes: &Classes,
+    diagnostics: &mut Vec<SemanticError>,
+) -> (Instances, Containers) {
+    let site = module.span.clone();
+    let mut monomorphizer = Monomorphizer::new(contracts, classes, site.clone(), diagnostics);
+    monomorphizer.check_enums();
+    monomorphizer.site = site;
+
+    let mut frame = Frame::new(module);
+    monomorphizer.calls(&mut frame);
+    monomorphizer.check_uncalled();
+    (monomorphizer.instances, monomorphizer.containers)
+}
+
+/// The type a parameter of a function is annotated with, if it is not generic.
+fn annotation_of<'c>(contract: &'c Contract, param: &ContractParam) -> Option<&'c Type> {
+    let interface = contract.value(param.value).interface?;
+    match &contract.interface(interface).kind {
+        InterfaceKind::Concrete(annotation) => Some(annotation),
+        _ => None,
+    }
+}
+
+/// Every combination of one variant from each argument, in order.
+fn combinations(variants: &[Vec<Option<Type>>]) -> Vec<Vec<Option<Type>>> {
+    let mut combinations = vec![vec![]];
+    for options in variants.iter() {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                options.iter().map(move |option| {
+                    let mut combination = combination.clone();
+                    combination.push(option.clone());
+                    combination
+                })
+            })
+            .collect();
+    }
+
+    combinations
+}
@@ -0,0 +1,823 @@
+use std::collections::HashSet;
+use std::mem;
+
+use crate::ir::{
+    visit, Ast, ClassDef, ComprehensionFor, Decorator, ExceptHandler, Expr, ExprKind, FunctionDef,
+    GenericBound, Identifier, ImportNames, InterfaceDef, LambdaBody, NodeId, Params, Pattern,
+    PatternKind, Statement, StatementKind, TypeAnnotation, TypeAnnotationKind, VariantField,
+    Visitor, WithItem,
+};
+use crate::span::Span;
+
+use super::errors::{SemanticError, SemanticErrorKind, SemanticErrorKind::*};
+use super::symbols::{Resolution, ScopeId, ScopeKind, Symbol, SymbolKind, SymbolTable, BUILTINS};
+use super::utils::{all_params, comparable};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// Resolves the names of a module with Python's scoping rules.
+///
+/// A name is looked up in its own scope, then in the enclosing functions, the module and the
+/// builtins. The scopes nested in a class body skip it, while the generic parameters of a
+/// definition are seen by everything in it. Binding a name anywhere in a scope makes it local to
+/// the whole scope, unless the scope declares it `global` or `nonlocal`.
+///
+/// The walk records the names of every scope first and resolves them after, since a function can
+/// use a name that the enclosing scope only binds after the function.
+pub(crate) struct NameResolver<'a> {
+    table: &'a mut SymbolTable,
+    diagnostics: &'a mut Vec<SemanticError>,
+    /// The names the star imports of the module bring in, or `None` if they can bring in any name.
+    star_names: Option<&'a [String]>,
+    /// The scope being walked.
+    scope: ScopeId,
+    /// The code at whose end the names being bound take effect.
+    bound_at: Span,
+    /// The spans of the loops around the walk, in the current scope.
+    loops: Vec<Span>,
+    /// Whether the pattern being walked belongs to a `case`, where a unit variant is not a capture.
+    is_case_pattern: bool,
+    /// Whether the annotations being walked are of the fields of a class, which can name the class
+    /// itself and the classes after it, as the fields of recursive classes do.
+    is_field_annotation: bool,
+    /// Whether the names being bound are the variants of an enum, which live in its namespace and
+    /// so shadow no builtin, like `Some` and `None` in an `Option` of its own.
+    is_variant: bool,
+    names: Vec<Name>,
+    /// The names each scope has used or bound so far, which it cannot declare anymore.
+    seen: HashSet<(ScopeId, String)>,
+}
+
+/// A name that binds or uses a symbol, which is resolved after the walk.
+struct Name {
+    id: NodeId,
+    span: Span,
+    name: String,
+    scope: ScopeId,
+    is_binding: bool,
+    /// The loops around the name, where a binding later in the loop can reach it.
+    loops: Vec<Span>,
+    /// Whether the name is in the annotation of a field, which can use a name before its binding.
+    is_field_annotation: bool,
+    /// The definition whose scope a free name of a macro body is looked up from.
+    context: Option<NodeId>,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl<'a> NameResolver<'a> {
+    /// Fills the table with the scopes of the module and what its names refer to.
+    pub(crate) fn resolve(
+        ast: &Ast,
+        table: &'a mut SymbolTable,
+        diagnostics: &'a mut Vec<SemanticError>,
+        star_names: Option<&'a [String]>,
+    ) {
+        let mut resolver = NameResolver {
+            table,
+            diagnostics,
+            star_names,
+            scope: SymbolTable::MODULE,
+            bound_at: Span::new(0, 0),
+            loops: vec![],
+            is_case_pattern: false,
+            is_field_annotation: false,
+            is_variant: false,
+            names: vec![],
+            seen: HashSet::new(),
+        };
+
+        // The free names of the macros defined at the top of the module are resolved in it.
+        resolver.table.define(ast.id, SymbolTable::MODULE);
+        for statement in ast.statements.iter() {
+            resolver.visit_statement(statement);
+        }
+
+        resolver.check_nonlocals();
+        for name in mem::take(&mut resolver.names) {
+            let resolution = match name.is_binding {
+                true => resolver.resolve_binding(&name),
+                false => resolver.resolve_use(&name),
+            };
+
+            if let Some(resolution) = resolution {
+                resolver.table.resolve(name.id, resolution);
+            }
+        }
+    }
+
+    fn fail(&mut self, kind: SemanticErrorKind, span: &Span) {
+        self.diagnostics
+            .push(SemanticError::new(kind, span.clone()));
+    }
+
+    /// Walks a new scope nested in the current one.
+    fn enter(&mut self, kind: ScopeKind, definition: Option<NodeId>, walk: impl FnOnce(&mut Self)) {
+        let scope = self.table.push_scope(kind, self.scope);
+        if let Some(definition) = definition {
+            self.table.define(definition, scope);
+        }
+
+        let parent = mem::replace(&mut self.scope, scope);
+        let loops = mem::take(&mut self.loops);
+        walk(self);
+        self.loops = loops;
+        self.scope = parent;
+    }
+
+    /// Walks the generic parameters of a definition in a scope of their own, if it has any.
+    fn enter_generics(
+        &mut self,
+        generics: &[Identifier],
+        bounds: &[GenericBound],
+        walk: impl FnOnce(&mut Self),
+    ) {
+        if generics.is_empty() {
+            bounds
+                .iter()
+                .for_each(|bound| self.visit_generic_bound(bound));
+            return walk(self);
+        }
+
+        self.enter(ScopeKind::Generics, None, |this| {
+            this.bind_at(&Span::new(0, 0), |this| {
+                generics.iter().for_each(|name| this.bind(name))
+            });
+            bounds
+                .iter()
+                .for_each(|bound| this.visit_generic_bound(bound));
+            walk(this);
+        });
+    }
+
+    fn visit_field_annotation(&mut self, annotation: &TypeAnnotation) {
+        let is_field_annotation = mem::replace(&mut self.is_field_annotation, true);
+        self.visit_type_annotation(annotation);
+        self.is_field_annotation = is_field_annotation;
+    }
+
+    fn in_loop(&mut self, span: &Span, walk: impl FnOnce(&mut Self)) {
+        self.loops.push(span.clone());
+        walk(self);
+        self.loops.pop();
+    }
+
+    /// Binds names that take effect at the end of a span.
+    fn bind_at(&mut self, span: &Span, bind: impl FnOnce(&mut Self)) {
+        let bound_at = mem::replace(&mut self.bound_at, span.clone());
+        bind(self);
+        self.bound_at = bound_at;
+    }
+
+    fn bind(&mut self, name: &Identifier) {
+        self.bind_in(self.scope, name);
+    }
+
+    fn bind_in(&mut self, scope: ScopeId, name: &Identifier) {
+        let kind = self.add_binding(scope, name, self.bound_at.clone());
+
+        // The bindings of a `global` name are the module's, which the module sees from the start.
+        if kind == SymbolKind::Global {
+            self.add_binding(SymbolTable::MODULE, name, Span::new(0, 0));
+        }
+
+        self.seen.insert((scope, name.name.clone()));
+        self.names.push(Name {
+            id: name.id,
+            span: name.span.clone(),
+            name: name.name.clone(),
+            scope,
+            is_binding: true,
+            loops: vec![],
+            is_field_annotation: false,
+            context: None,
+        });
+    }
+
+    fn add_binding(&mut self, scope: ScopeId, name: &Identifier, defined_at: Span) -> SymbolKind {
+        let symbol = self
+            .table
+            .scope_mut(scope)
+            .symbols
+            .entry(name.name.clone())
+            .or_insert_with(|| Symbol::new(SymbolKind::Local));
+
+        let is_shadowing = symbol.kind == SymbolKind::Local
+            && symbol.bindings.is_empty()
+            && !self.is_variant
+            && BUILTINS.contains(&name.name.as_str());
+
+        symbol.bindings.push(name.id);
+        symbol.defined_at.push(defined_at);
+
+        let kind = symbol.kind;
+        if is_shadowing {
+            self.fail(ShadowedBuiltin, &name.span);
+        }
+
+        kind
+    }
+
+    fn load(&mut self, name: &Identifier) {
+        // A free name of a macro body does not use the symbol of the scope it is expanded in.
+        if name.context.is_none() {
+            self.seen.insert((self.scope, name.name.clone()));
+        }
+
+        self.names.push(Name {
+            id: name.id,
+            span: name.span.clone(),
+            name: name.name.clone(),
+            scope: self.scope,
+            is_binding: false,
+            loops: self.loops.clone(),
+            is_field_annotation: self.is_field_annotation,
+            context: name.context,
+        });
+    }
+
+    fn declare(&mut self, kind: SymbolKind, names: &[Identifier]) {
+        for name in names {
+            if self.seen.contains(&(self.scope, name.name.clone())) {
+                self.fail(DeclarationAfterUse, &name.span);
+                continue;
+            }
+
+            let symbol = self
+                .table
+                .scope_mut(self.scope)
+                .symbols
+                .entry(name.name.clone())
+                .or_insert_with(|| Symbol::new(kind));
+
+            symbol.kind = kind;
+            symbol.declaration = Some(name.id);
+            self.names.push(Name {
+                id: name.id,
+                span: name.span.clone(),
+                name: name.name.clone(),
+                scope: self.scope,
+                is_binding: true,
+                loops: vec![],
+                is_field_annotation: false,
+                context: None,
+            });
+        }
+    }
+
+    /// The scope a named expression binds in, which is the nearest one that is not a comprehension.
+    fn named_expr_scope(&self) -> ScopeId {
+        let mut scope = self.scope;
+        while self.table.scope(scope).kind == ScopeKind::Comprehension {
+            scope = self.table.scope(scope).parent.unwrap();
+        }
+
+        scope
+    }
+
+    fn function_def(&mut self, def: &FunctionDef, span: &Span) {
+        def.decorators
+            .iter()
+            .for_each(|decorator| self.visit_decorator(decorator));
+        self.visit_defaults(&def.params);
+
+        self.enter_generics(&def.generics, &def.bounds, |this| {
+            this.visit_annotations(&def.params);
+            if let Some(return_type) = &def.return_type {
+                this.visit_type_annotation(return_type);
+            }
+
+            this.enter(ScopeKind::Function, Some(def.name.id), |this| {
+                this.bind_params(&def.params);
+                def.body
+                    .iter()
+                    .for_each(|statement| this.visit_statement(statement));
+            });
+        });
+
+        self.bind_at(span, |this| this.bind(&def.name));
+    }
+
+    fn class_def(&mut self, def: &ClassDef, span: &Span) {
+        def.bases.iter().for_each(|base| self.load(base));
+
+        self.enter_generics(&def.generics, &def.bounds, |this| {
+            // The decorators see the generic parameters, like `T` in `@implements(Abstract[T])`.
+            def.decorators
+                .iter()
+                .for_each(|decorator| this.visit_decorator(decorator));
+
+            for field in def.fields.iter() {
+                if let Some(annotation) = &field.annotation {
+                    this.visit_field_annotation(annotation);
+                }
+
+                if let Some(default) = &field.default {
+                    this.visit_expr(default);
+                }
+            }
+
+            for variant in def.variants.iter() {
+                for field in variant.fields.iter().flatten() {
+                    match field {
+                        VariantField::Named { annotation, .. }
+                        | VariantField::Positional(annotation) => {
+                            this.visit_field_annotation(annotation)
+                        }
+                    }
+                }
+            }
+
+            this.enter(ScopeKind::Class, Some(def.name.id), |this| {
+                this.bind_at(&Span::new(0, 0), |this| {
+                    def.fields.iter().for_each(|field| this.bind(&field.name));
+                    this.is_variant = true;
+                    def.variants
+                        .iter()
+                        .for_each(|variant| this.bind(&variant.name));
+                    this.is_variant = false;
+                });
+
+                def.body
+                    .iter()
+                    .for_each(|statement| this.visit_statement(statement));
+            });
+        });
+
+        self.bind_at(span, |this| this.bind(&def.name));
+    }
+
+    fn interface_def(&mut self, def: &InterfaceDef, span: &Span) {
+        def.decorators
+            .iter()
+            .for_each(|decorator| self.visit_decorator(decorator));
+        def.bases.iter().for_each(|base| self.load(base));
+
+        self.enter_generics(&def.generics, &def.bounds, |this| {
+            this.enter(ScopeKind::Class, Some(def.name.id), |this| {
+                def.body
+                    .iter()
+                    .for_each(|statement| this.visit_statement(statement));
+            });
+        });
+
+        self.bind_at(span, |this| this.bind(&def.name));
+    }
+
+    /// Visits the defaults of parameters, which are evaluated where the function is defined.
+    fn visit_defaults(&mut self, params: &Params) {
+        for param in all_params(params) {
+            if let Some(default) = &param.default {
+                self.visit_expr(default);
+            }
+        }
+    }
+
+    fn visit_annotations(&mut self, params: &Params) {
+        for param in all_params(params) {
+            if let Some(annotation) = &param.annotation {
+                self.visit_type_annotation(annotation);
+            }
+        }
+    }
+
+    fn bind_params(&mut self, params: &Params) {
+        self.bind_at(&Span::new(0, 0), |this| {
+            all_params(params).for_each(|param| this.bind(&param.name));
+        });
+    }
+
+    /// Walks a comprehension, whose first iterable is evaluated in the enclosing scope.
+    fn comprehension(
+        &mut self,
+        node: &Expr,
+        clauses: &[ComprehensionFor],
+        walk_element: impl FnOnce(&mut Self),
+    ) {
+        if let Some(clause) = clauses.first() {
+            self.visit_expr(&clause.iter);
+        }
+
+        self.enter(ScopeKind::Comprehension, Some(node.id), |this| {
+            for (index, clause) in clauses.iter().enumerate() {
+                if index > 0 {
+                    this.visit_expr(&clause.iter);
+                }
+
+                this.bind_at(&Span::new(0, 0), |this| this.visit_pattern(&clause.target));
+                clause
+                    .conditions
+                    .iter()
+                    .for_each(|condition| this.visit_expr(condition));
+            }
+
+            walk_element(this);
+        });
+    }
+
+    /// Walks a `case`, whose captures are bound after its pattern.
+    fn case(&mut self, pattern: &Pattern, guard: &Option<Expr>, walk_body: impl FnOnce(&mut Self)) {
+        self.is_case_pattern = true;
+        self.bind_at(&pattern.span, |this| this.visit_pattern(pattern));
+        self.is_case_pattern = false;
+
+        if let Some(guard) = guard {
+            self.visit_expr(guard);
+        }
+
+        walk_body(self);
+    }
+
+    /// Reports the `nonlocal` names that no enclosing function binds.
+    fn check_nonlocals(&mut self) {
+        let mut invalid = vec![];
+        for (index, scope) in self.table.scopes.iter().enumerate() {
+            for (name, symbol) in scope.symbols.iter() {
+                if symbol.kind == SymbolKind::Nonlocal
+                    && self.nonlocal_scope(ScopeId(index), name).is_none()
+                {
+                    let declaration = self
+                        .names
+                        .iter()
+                        .find(|declared| Some(declared.id) == symbol.declaration);
+                    invalid.extend(declaration.map(|declared| declared.span.clone()));
+                }
+            }
+        }
+
+        for span in invalid {
+            self.fail(InvalidNonlocal, &span);
+        }
+    }
+
+    /// The enclosing function that binds a `nonlocal` name of a scope.
+    fn nonlocal_scope(&self, scope: ScopeId, name: &str) -> Option<ScopeId> {
+        let mut parent = self.table.scope(scope).parent;
+        while let Some(id) = parent {
+            let scope = self.table.scope(id);
+            match (scope.kind, scope.symbols.get(name)) {
+                (ScopeKind::Module, _) => return None,
+                (ScopeKind::Function, Some(symbol)) => match symbol.kind {
+                    SymbolKind::Local => return Some(id),
+                    SymbolKind::Global => return None,
+                    SymbolKind::Nonlocal => (),
+                },
+                _ => (),
+            }
+
+            parent = scope.parent;
+        }
+
+        None
+    }
+
+    /// Where a local symbol of a scope is, as the module's are global.
+    fn local(&self, scope: ScopeId) -> Resolution {
+        match scope == SymbolTable::MODULE {
+            true => Resolution::Global,
+            false => Resolution::Local(scope),
+        }
+    }
+
+    fn resolve_binding(&self, name: &Name) -> Option<Resolution> {
+        let symbol = &self.table.scope(name.scope).symbols[&name.name];
+        match symbol.kind {
+            SymbolKind::Local => Some(self.local(name.scope)),
+            SymbolKind::Global => Some(Resolution::Global),
+            SymbolKind::Nonlocal => self
+                .nonlocal_scope(name.scope, &name.name)
+                .map(Resolution::Enclosing),
+        }
+    }
+
+    /// Resolves a use from its scope, or from the scope of the definition around its macro, so the
+    /// bindings of the scopes between cannot capture it.
+    fn resolve_use(&mut self, name: &Name) -> Option<Resolution> {
+        let context = name
+            .context
+            .as_ref()
+            .and_then(|definition| self.table.definition_scope(definition));
+        let mut scope = Some(context.unwrap_or(name.scope));
+        while let Some(id) = scope {
+            let current = self.table.scope(id);
+            let symbol = match current.kind {
+                ScopeKind::Class if id != name.scope => None,
+                _ => current.symbols.get(&name.name),
+            };
+
+            if let Some(symbol) = symbol {
+                return match symbol.kind {
+                    SymbolKind::Global => self.resolve_global(name),
+                    SymbolKind::Nonlocal => self
+                        .nonlocal_scope(id, &name.name)
+                        .map(Resolution::Enclosing),
+                    SymbolKind::Local if id == name.scope => {
+                        if !name.is_field_annotation && !self.is_defined(name, symbol) {
+                            self.fail(UsedBeforeDefinition, &name.span);
+                        }
+
+                        Some(self.local(id))
+                    }
+                    SymbolKind::Local if id == SymbolTable::MODULE => Some(Resolution::Global),
+                    SymbolKind::Local => Some(Resolution::Enclosing(id)),
+                };
+            }
+
+            scope = current.parent;
+        }
+
+        self.resolve_global(name)
+    }
+
+    fn resolve_global(&mut self, name: &Name) -> Option<Resolution> {
+        let module = self.table.scope(SymbolTable::MODULE);
+        let is_bound = module
+            .symbols
+            .get(&name.name)
+            .is_some_and(|symbol| !symbol.bindings.is_empty());
+
+        if is_bound {
+            Some(Resolution::Global)
+        } else if BUILTINS.contains(&name.name.as_str()) {
+            Some(Resolution::Builtin)
+        } else if module.has_star_import
+            && self
+                .star_names
+                .is_none_or(|names| names.contains(&name.name))
+        {
+            Some(Resolution::Global)
+        } else {
+            self.fail(UndefinedName, &name.span);
+            None
+        }
+    }
+
+    /// Whether a binding of a local symbol takes effect before a use of it, or later in a loop
+    /// around the use. The order does not matter in a comprehension, whose element comes before
+    /// its targets.
+    fn is_defined(&self, name: &Name, symbol: &Symbol) -> bool {
+        if self.table.scope(name.scope).kind == ScopeKind::Comprehension {
+            return true;
+        }
+
+        symbol.defined_at.iter().any(|defined_at| {
+            let (defined_at, span) = comparable(defined_at, &name.span);
+            defined_at.end <= span.start
+                || name.loops.iter().any(|span| {
+                    let (defined_at, span) = comparable(defined_at, span);
+                    (span.start..=span.end).contains(&defined_at.end)
+                })
+        })
+    }
+}
+
+impl<'a> Visitor for NameResolver<'a> {
+    fn visit_statement(&mut self, node: &Statement) {
+        match &node.kind {
+            StatementKind::Assign(assign) => {
+                self.visit_expr(&assign.value);
+                self.bind_at(&node.span, |this| {
+                    assign
+                        .targets
+                        .iter()
+                        .for_each(|target| this.visit_pattern(target))
+                });
+            }
+            StatementKind::AugAssign(assign) => {
+                self.visit_expr(&assign.target);
+                self.visit_expr(&assign.value);
+                if let ExprKind::Identifier(name) = &assign.target.kind {
+                    self.bind_at(&node.span, |this| this.bind(name));
+                }
+            }
+            StatementKind::AnnAssign(assign) => {
+                self.visit_type_annotation(&assign.annotation);
+                if let Some(value) = &assign.value {
+                    self.visit_expr(value);
+                }
+
+                match &assign.target.kind {
+                    ExprKind::Identifier(name) => self.bind_at(&node.span, |this| this.bind(name)),
+                    _ => self.visit_expr(&assign.target),
+                }
+            }
+            StatementKind::Declaration(declaration) => {
+                if let Some(annotation) = &declaration.annotation {
+                    self.visit_type_annotation(annotation);
+                }
+
+                if let Some(value) = &declaration.value {
+                    self.visit_expr(value);
+                }
+
+                self.bind_at(&node.span, |this| this.bind(&declaration.name));
+            }
+            StatementKind::TypeAlias(alias) => {
+                self.enter_generics(&alias.generics, &[], |this| {
+                    this.visit_type_annotation(&alias.value)
+                });
+                self.bind_at(&node.span, |this| this.bind(&alias.name));
+            }
+            StatementKind::Global(names) => self.declare(SymbolKind::Global, names),
+            StatementKind::Nonlocal(names) => self.declare(SymbolKind::Nonlocal, names),
+            StatementKind::Import(import) => {
+                // `import a.b` binds `a`.
+                let name = import.alias.as_ref().or_else(|| import.path.names.first());
+                if let Some(name) = name {
+                    self.bind_at(&node.span, |this| this.bind(name));
+                }
+            }
+            StatementKind::ImportFrom(import) => match &import.names {
+                ImportNames::All => self.table.scope_mut(self.scope).has_star_import = true,
+                ImportNames::Names(names) => self.bind_at(&node.span, |this| {
+                    for name in names.iter() {
+                        this.bind(name.alias.as_ref().unwrap_or(&name.name));
+                    }
+                }),
+            },
+            StatementKind::For(node_for) => {
+                self.visit_expr(&node_for.iter);
+                self.in_loop(&node.span, |this| {
+                    let target = &node_for.target;
+                    this.bind_at(&target.span, |this| this.visit_pattern(target));
+                    if let Some(guard) = &node_for.guard {
+                        this.visit_expr(guard);
+                    }
+
+                    node_for
+                        .body
+                        .iter()
+                        .for_each(|statement| this.visit_statement(statement));
+                });
+
+                for statement in node_for.orelse.iter().flatten() {
+                    self.visit_statement(statement);
+                }
+            }
+            StatementKind::While(node_while) => {
+                self.in_loop(&node.span, |this| {
+                    this.visit_expr(&node_while.condition);
+                    if let Some(guard) = &node_while.guard {
+                        this.visit_expr(guard);
+                    }
+
+                    node_while
+                        .body
+                        .iter()
+                        .for_each(|statement| this.visit_statement(statement));
+                });
+
+                for statement in node_while.orelse.iter().flatten() {
+                    self.visit_statement(statement);
+                }
+            }
+            StatementKind::Match(node_match) => {
+                self.visit_expr(&node_match.subject);
+                for case in node_match.cases.iter() {
+                    self.case(&case.pattern, &case.guard, |this| {
+                        case.body
+                            .iter()
+                            .for_each(|statement| this.visit_statement(statement))
+                    });
+                }
+            }
+            StatementKind::FunctionDef(def) => self.function_def(def, &node.span),
+            StatementKind::ClassDef(def) => self.class_def(def, &node.span),
+            StatementKind::InterfaceDef(def) => self.interface_def(def, &node.span),
+            // Macros are expanded before, so only their names are left to bind.
+            StatementKind::MacroDef(def) => self.bind_at(&node.span, |this| this.bind(&def.name)),
+            _ => visit::walk_statement(self, node),
+        }
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        match &node.kind {
+            ExprKind::Identifier(name) => self.load(name),
+            ExprKind::NamedExpr { name, value } => {
+                self.visit_expr(value);
+                let scope = self.named_expr_scope();
+                self.bind_at(&node.span, |this| this.bind_in(scope, name));
+            }
+            ExprKind::Lambda(lambda) => {
+                self.visit_defaults(&lambda.params);
+                self.visit_annotations(&lambda.params);
+                self.enter(ScopeKind::Function, Some(node.id), |this| {
+                    this.bind_params(&lambda.params);
+                    match &lambda.body {
+                        LambdaBody::Expr(body) => this.visit_expr(body),
+                        LambdaBody::Block(body) => body
+                            .iter()
+                            .for_each(|statement| this.visit_statement(statement)),
+                    }
+                });
+            }
+            ExprKind::ListComp(comprehension)
+            | ExprKind::SetComp(comprehension)
+            | ExprKind::Generator(comprehension) => {
+                self.comprehension(node, &comprehension.clauses, |this| {
+                    this.visit_expr(&comprehension.element)
+                });
+            }
+            ExprKind::DictComp(comprehension) => {
+                self.comprehension(node, &comprehension.clauses, |this| {
+                    this.visit_expr(&comprehension.entry.key);
+                    this.visit_expr(&comprehension.entry.value);
+                });
+            }
+            ExprKind::Match(node_match) => {
+                self.visit_expr(&node_match.subject);
+                for case in node_match.cases.iter() {
+                    self.case(&case.pattern, &case.guard, |this| {
+                        this.visit_expr(&case.body)
+                    });
+                }
+            }
+            _ => visit::walk_expr(self, node),
+        }
+    }
+
+    fn visit_pattern(&mut self, node: &Pattern) {
+        match &node.kind {
+            // `None` is the only unit variant that patterns name without a dot.
+            PatternKind::Capture(name) if self.is_case_pattern && name.name == "None" => {
+                self.load(name)
+            }
+            PatternKind::Capture(name) | PatternKind::Rest(Some(name)) => self.bind(name),
+            PatternKind::As { pattern, name } => {
+                self.visit_pattern(pattern);
+                self.bind(name);
+            }
+            PatternKind::Mapping { entries, rest } => {
+                for entry in entries.iter() {
+                    self.visit_pattern(&entry.key);
+                    self.visit_pattern(&entry.value);
+                }
+
+                if let Some(rest) = rest {
+                    self.bind(rest);
+                }
+            }
+            PatternKind::Value(path) => self.load(&path.names[0]),
+            PatternKind::Class {
+                path,
+                patterns,
+                keywords,
+            } => {
+                self.load(&path.names[0]);
+                patterns
+                    .iter()
+                    .for_each(|pattern| self.visit_pattern(pattern));
+                keywords
+                    .iter()
+                    .for_each(|keyword| self.visit_pattern(&keyword.pattern));
+            }
+            _ => visit::walk_pattern(self, node),
+        }
+    }
+
+    fn visit_type_annotation(&mut self, node: &TypeAnnotation) {
+        match &node.kind {
+            TypeAnnotationKind::Identifier(name) => self.load(name),
+            TypeAnnotationKind::Path(path) => self.load(&path.names[0]),
+            TypeAnnotationKind::Generic { name, arguments } => {
+                self.load(name);
+                arguments
+                    .iter()
+                    .for_each(|argument| self.visit_type_annotation(argument));
+            }
+            _ => visit::walk_type_annotation(self, node),
+        }
+    }
+
+    fn visit_decorator(&mut self, node: &Decorator) {
+        self.load(&node.path.names[0]);
+        visit::walk_decorator(self, node);
+    }
+
+    fn visit_generic_bound(&mut self, node: &GenericBound) {
+        self.load(&node.name);
+        self.visit_type_annotation(&node.bound);
+    }
+
+    fn visit_except_handler(&mut self, node: &ExceptHandler) {
+        self.load(&node.exception);
+        if let Some(name) = &node.name {
+            self.bind_at(&name.span, |this| this.bind(name));
+        }
+
+        node.body
+            .iter()
+            .for_each(|statement| self.visit_statement(statement));
+    }
+
+    fn visit_with_item(&mut self, node: &WithItem) {
+        self.visit_expr(&node.context);
+        if let Some(alias) = &node.alias {
+            self.bind_at(&alias.span, |this| this.bind(alias));
+        }
+    }
+}
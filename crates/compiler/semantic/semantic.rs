@@ -1 +1,140 @@
-pub struct SemanticAnalyzer {}
+use crate::ir::Ast;
+
+use super::classes::collect_classes;
+use super::conformance::check_conformances;
+use super::containers::Containers;
+use super::contracts::Contract;
+use super::errors::SemanticError;
+use super::escape::{place_allocations, Placements};
+use super::inference::{infer_contracts, infer_module_contract};
+use super::instances::Instances;
+use super::layout::{check_layouts, Layouts};
+use super::monomorphizer::monomorphize;
+use super::resolver::NameResolver;
+use super::symbols::SymbolTable;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// Checks the meaning of a module after its macros are expanded.
+#[derive(Debug)]
+pub struct SemanticAnalyzer {
+    /// The module as codegen gets it, with the allocations that must live on the heap and the
+    /// fields that the layouts of the classes store behind a pointer boxed.
+    pub ast: Ast,
+    /// The scopes of the module and what each of its names refers to.
+    pub symbols: SymbolTable,
+    /// The interface contracts of the functions and methods, in source order.
+    pub contracts: Vec<Contract>,
+    /// The instances of the functions that the module calls, for the types of their arguments.
+    pub instances: Instances,
+    /// The container literals that the instances type, with how they store their elements.
+    pub containers: Containers,
+    /// The fields of the classes that their layout stores behind a `Box`.
+    pub layouts: Layouts,
+    /// Where the allocations of the module live, on the stack, on the heap or copied by value.
+    pub placements: Placements,
+    /// The errors and warnings found, in source order.
+    pub diagnostics: Vec<SemanticError>,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl SemanticAnalyzer {
+    /// Analyzes a module on its own, where a `from x import *` can bring in any name.
+    pub fn analyze(ast: &Ast) -> Self {
+        Self::analyze_with(ast, None)
+    }
+
+    /// Analyzes a module whose `from x import *` bring in `star_names`, like the
+    /// [`star_names`](crate::loader::Module::star_names) of a loaded module.
+    pub fn analyze_with_star_names(ast: &Ast, star_names: &[String]) -> Self {
+        Self::analyze_with(ast, Some(star_names))
+    }
+
+    fn analyze_with(ast: &Ast, star_names: Option<&[String]>) -> Self {
+        let mut symbols = SymbolTable::default();
+        let mut diagnostics = vec![];
+        NameResolver::resolve(ast, &mut symbols, &mut diagnostics, star_names);
+        let contracts = infer_contracts(ast, &symbols);
+
+        let module = infer_module_contract(ast, &symbols);
+        let classes = collect_classes(ast);
+        check_conformances(&classes, &mut diagnostics);
+        let layouts = check_layouts(ast, &mut diagnostics);
+        let (instances, containers) = monomorphize(&module, &contracts, &classes, &mut diagnostics);
+        let placements = place_allocations(&module, &contracts);
+
+        diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+        Self {
+            ast: layouts.insert_indirections(placements.insert_boxes(ast.clone())),
+            symbols,
+            contracts,
+            instances,
+            containers,
+            layouts,
+            placements,
+            diagnostics,
+        }
+    }
+
+    /// The contract of a function, or of a method named after its class like `Point.__plus__`.
+    pub fn contract(&self, name: &str) -> Option<&Contract> {
+        self.contracts.iter().find(|contract| contract.name == name)
+    }
+
+    /// The contracts in the notation of `IDEAS.md`, one function per line.
+    pub fn dump_contracts(&self) -> String {
+        self.contracts
+            .iter()
+            .map(|contract| format!("{}: {contract}\n", contract.name))
+            .collect()
+    }
+
+    /// The instances in the notation of `IDEAS.md`, one instance per line in the order codegen
+    /// generates them.
+    pub fn dump_instances(&self) -> String {
+        self.instances
+            .worklist()
+            .map(|instance| format!("{instance}\n"))
+            .collect()
+    }
+
+    /// The container literals with the types of their elements and how they store them, like
+    /// `list[StringGiver & IntGiver]: list[dyn gift.1]`, one container per line.
+    pub fn dump_containers(&self) -> String {
+        self.containers
+            .iter()
+            .map(|container| format!("{}: {container}\n", container.ty))
+            .collect()
+    }
+
+    /// The fields that the layouts of the classes box, like `Node.parent: Node? -> Box[Node?]`, one
+    /// field per line.
+    pub fn dump_layouts(&self) -> String {
+        self.layouts
+            .iter()
+            .map(|indirection| format!("{indirection}\n"))
+            .collect()
+    }
+
+    /// The allocations with where they live and why, like
+    /// `get_person: "John" -> heap, captured by Person(name, age), which is returned by get_person`,
+    /// one allocation per line.
+    pub fn dump_allocations(&self) -> String {
+        self.placements
+            .iter()
+            .map(|placement| format!("{}: {placement}\n", placement.function))
+            .collect()
+    }
+
+    /// The diagnostics that are not warnings.
+    pub fn errors(&self) -> impl Iterator<Item = &SemanticError> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| !diagnostic.is_warning())
+    }
+}
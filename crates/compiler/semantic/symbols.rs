@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use crate::ir::NodeId;
+use crate::span::Span;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// The names every module can use without binding them.
+pub const BUILTINS: &[&str] = &[
+    // Types
+    "bool",
+    "int",
+    "float",
+    "complex",
+    "str",
+    "bytes",
+    "list",
+    "dict",
+    "set",
+    "tuple",
+    "object",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "f32",
+    "f64",
+    "byte",
+    "Box",
+    "Option",
+    "Result",
+    "Exception",
+    // Enum variants
+    "Some",
+    "None",
+    "Ok",
+    "Err",
+    // Functions
+    "print",
+    "len",
+    "range",
+    "iter",
+    "next",
+    "abs",
+    "min",
+    "max",
+    "sum",
+    "any",
+    "all",
+    "enumerate",
+    "zip",
+    "map",
+    "filter",
+    "sorted",
+    "reversed",
+    "repr",
+    "hash",
+    "isinstance",
+    "cast",
+    "panic",
+    // Decorators the compiler reads
+    "implements",
+    "inherits",
+];
+
+/// The index of a scope in a [`SymbolTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScopeId(pub usize);
+
+/// The scopes of a module and what each name in it refers to.
+///
+/// The names that bind and use a symbol are keyed by their [`NodeId`], and so are the definitions
+/// that open a scope: the name of a function, class or interface, or the whole expression of a
+/// lambda or comprehension.
+#[derive(Debug, Clone)]
+pub struct SymbolTable {
+    /// The scopes, with the module scope first.
+    pub scopes: Vec<Scope>,
+    resolutions: HashMap<NodeId, Resolution>,
+    definitions: HashMap<NodeId, ScopeId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    /// The scope this one is nested in, which is `None` for the module scope.
+    pub parent: Option<ScopeId>,
+    pub symbols: HashMap<String, Symbol>,
+    /// Whether the scope has a `from x import *`, which can bind any name.
+    pub has_star_import: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    Module,
+    /// A class body, whose names the functions nested in it do not see.
+    Class,
+    /// A function or lambda.
+    Function,
+    /// A list, set, dict or generator comprehension.
+    Comprehension,
+    /// The generic parameters of a definition, which its body and everything nested in it see.
+    Generics,
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    /// The names that bind the symbol in this scope, in source order.
+    pub bindings: Vec<NodeId>,
+    /// The name in the `global` or `nonlocal` statement that declares the symbol.
+    pub declaration: Option<NodeId>,
+    /// The code at whose end each binding takes effect, which is the statement or expression that
+    /// binds.
+    pub(crate) defined_at: Vec<Span>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// A name bound in the scope.
+    Local,
+    /// A name declared `global`, which binds and uses the module's symbol.
+    Global,
+    /// A name declared `nonlocal`, which binds and uses the symbol of an enclosing function.
+    Nonlocal,
+}
+
+/// Where a name refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// A symbol of the function, class or comprehension the name is in.
+    Local(ScopeId),
+    /// A symbol of an enclosing function, comprehension or generic parameter list, which the
+    /// scopes in between capture.
+    Enclosing(ScopeId),
+    /// A symbol of the module, or a name a star import may bring in.
+    Global,
+    Builtin,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl SymbolTable {
+    pub const MODULE: ScopeId = ScopeId(0);
+
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![Scope::new(ScopeKind::Module, None)],
+            resolutions: HashMap::new(),
+            definitions: HashMap::new(),
+        }
+    }
+
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0]
+    }
+
+    /// Where a name that binds or uses a symbol refers to, or `None` if it cannot be resolved.
+    pub fn resolution(&self, name: &NodeId) -> Option<Resolution> {
+        self.resolutions.get(name).copied()
+    }
+
+    /// The scope a definition opens.
+    pub fn definition_scope(&self, definition: &NodeId) -> Option<ScopeId> {
+        self.definitions.get(definition).copied()
+    }
+
+    /// The symbol a resolution refers to, which builtins do not have.
+    pub fn symbol(&self, name: &str, resolution: Resolution) -> Option<&Symbol> {
+        let scope = match resolution {
+            Resolution::Local(scope) | Resolution::Enclosing(scope) => scope,
+            Resolution::Global => Self::MODULE,
+            Resolution::Builtin => return None,
+        };
+
+        self.scope(scope).symbols.get(name)
+    }
+
+    pub(crate) fn scope_mut(&mut self, id: ScopeId) -> &mut Scope {
+        &mut self.scopes[id.0]
+    }
+
+    pub(crate) fn push_scope(&mut self, kind: ScopeKind, parent: ScopeId) -> ScopeId {
+        self.scopes.push(Scope::new(kind, Some(parent)));
+        ScopeId(self.scopes.len() - 1)
+    }
+
+    pub(crate) fn define(&mut self, definition: NodeId, scope: ScopeId) {
+        self.definitions.insert(definition, scope);
+    }
+
+    pub(crate) fn resolve(&mut self, name: NodeId, resolution: Resolution) {
+        self.resolutions.insert(name, resolution);
+    }
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scope {
+    fn new(kind: ScopeKind, parent: Option<ScopeId>) -> Self {
+        Self {
+            kind,
+            parent,
+            symbols: HashMap::new(),
+            has_star_import: false,
+        }
+    }
+}
+
+impl Symbol {
+    pub(crate) fn new(kind: SymbolKind) -> Self {
+        Self {
+            kind,
+            bindings: vec![],
+            declaration: None,
+            defined_at: vec![],
+        }
+    }
+}
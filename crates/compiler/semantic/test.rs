@@ -0,0 +1,1556 @@
+use crate::unparser::Unparser;
+
+use super::{Resolution, ScopeId, ScopeKind, SemanticErrorKind::*};
+
+use utils::*;
+
+#[test]
+fn can_resolve_names_through_enclosing_scopes() {
+    let code = r#"
+x = 1
+def f(y):
+    def g():
+        return x + y + len(z)
+    z = 2
+    return g
+"#;
+    let (ast, analyzer) = analyze(code);
+    let table = &analyzer.symbols;
+
+    assert!(analyzer.diagnostics.is_empty());
+
+    let f = table.definition_scope(&names(&ast, "f")[0]).unwrap();
+    assert_eq!(table.scope(f).kind, ScopeKind::Function);
+
+    assert_eq!(
+        resolution(&ast, &analyzer, "x", 1),
+        Some(Resolution::Global)
+    );
+    assert_eq!(
+        resolution(&ast, &analyzer, "y", 1),
+        Some(Resolution::Enclosing(f))
+    );
+    assert_eq!(
+        resolution(&ast, &analyzer, "z", 0),
+        Some(Resolution::Enclosing(f))
+    );
+    assert_eq!(
+        resolution(&ast, &analyzer, "len", 0),
+        Some(Resolution::Builtin)
+    );
+    assert_eq!(
+        resolution(&ast, &analyzer, "g", 1),
+        Some(Resolution::Local(f))
+    );
+}
+
+#[test]
+fn can_skip_class_scopes_in_nested_functions() {
+    let code = r#"
+x = 1
+class A:
+    x = 2
+    y = x
+    def f(self):
+        return x
+"#;
+    let (ast, analyzer) = analyze(code);
+    let a = analyzer
+        .symbols
+        .definition_scope(&names(&ast, "A")[0])
+        .unwrap();
+
+    assert!(analyzer.diagnostics.is_empty());
+    assert_eq!(
+        resolution(&ast, &analyzer, "x", 2),
+        Some(Resolution::Local(a))
+    );
+    assert_eq!(
+        resolution(&ast, &analyzer, "x", 3),
+        Some(Resolution::Global)
+    );
+}
+
+#[test]
+fn can_resolve_global_and_nonlocal_names() {
+    let code = r#"
+def f():
+    global counter
+    counter = 0
+    total = 0
+    def g():
+        nonlocal total
+        total += counter
+    return g
+counter += 1
+"#;
+    let (ast, analyzer) = analyze(code);
+    let f = analyzer
+        .symbols
+        .definition_scope(&names(&ast, "f")[0])
+        .unwrap();
+
+    assert!(analyzer.diagnostics.is_empty());
+    assert_eq!(
+        resolution(&ast, &analyzer, "counter", 1),
+        Some(Resolution::Global)
+    );
+    assert_eq!(
+        resolution(&ast, &analyzer, "total", 2),
+        Some(Resolution::Enclosing(f))
+    );
+    assert_eq!(
+        resolution(&ast, &analyzer, "counter", 2),
+        Some(Resolution::Global)
+    );
+    assert_eq!(
+        resolution(&ast, &analyzer, "counter", 3),
+        Some(Resolution::Global)
+    );
+}
+
+#[test]
+fn can_scope_comprehension_targets() {
+    let code = r#"
+xs = [1, 2]
+ys = [x * y for x in xs for y in range(x)]
+print(x)
+"#;
+    let (ast, analyzer) = analyze(code);
+    let comprehension = analyzer
+        .symbols
+        .scopes
+        .iter()
+        .position(|scope| scope.kind == ScopeKind::Comprehension)
+        .unwrap();
+
+    assert_eq!(
+        resolution(&ast, &analyzer, "x", 0),
+        Some(Resolution::Local(ScopeId(comprehension)))
+    );
+    assert_eq!(
+        resolution(&ast, &analyzer, "xs", 1),
+        Some(Resolution::Global)
+    );
+    assert_eq!(errors(code), vec![UndefinedName]);
+}
+
+#[test]
+fn can_bind_named_expressions_outside_comprehensions() {
+    let code = r#"
+def f(xs):
+    if any((last := x) > 0 for x in xs):
+        return last
+"#;
+    let (ast, analyzer) = analyze(code);
+    let f = analyzer
+        .symbols
+        .definition_scope(&names(&ast, "f")[0])
+        .unwrap();
+
+    assert!(analyzer.diagnostics.is_empty());
+    assert_eq!(
+        resolution(&ast, &analyzer, "last", 1),
+        Some(Resolution::Local(f))
+    );
+}
+
+#[test]
+fn can_resolve_free_names_of_macros_where_they_are_defined() {
+    let code = r#"
+def g():
+    return "g"
+macro f(): g()
+def h():
+    g = 2
+    return f()
+"#;
+    let (ast, analyzer) = analyze_expanded(code);
+    let h = analyzer
+        .symbols
+        .definition_scope(&names(&ast, "h")[0])
+        .unwrap();
+
+    // The expanded `g()` comes first, as its span points into the macro.
+    assert!(analyzer.diagnostics.is_empty());
+    assert_eq!(
+        resolution(&ast, &analyzer, "g", 1),
+        Some(Resolution::Global)
+    );
+    assert_eq!(
+        resolution(&ast, &analyzer, "g", 2),
+        Some(Resolution::Local(h))
+    );
+}
+
+#[test]
+fn can_call_free_names_of_macros_where_they_are_defined() {
+    let code = r#"
+def g():
+    return "g"
+macro f(): g()
+def h():
+    g = 2
+    return f()
+h()
+"#;
+    let (_, analyzer) = analyze_expanded(code);
+
+    assert!(analyzer.diagnostics.is_empty());
+}
+
+#[test]
+fn can_order_the_names_of_an_expansion_by_their_place_in_the_macro() {
+    let code = r#"
+macro swap(a, b):
+    tmp = a
+    a = b
+    b = tmp
+macro m():
+    t = 1
+    print(t)
+def f():
+    x = 1
+    y = 2
+    swap(x, y)
+    m()
+    m()
+    return x
+"#;
+    let (ast, analyzer) = analyze_expanded(code);
+    let f = analyzer
+        .symbols
+        .definition_scope(&names(&ast, "f")[0])
+        .unwrap();
+
+    assert!(analyzer.diagnostics.is_empty());
+    assert_eq!(
+        resolution(&ast, &analyzer, "tmp_1", 1),
+        Some(Resolution::Local(f))
+    );
+}
+
+#[test]
+fn can_resolve_generic_parameters() {
+    let code = r#"
+def first[T](items: list[T]) -> T:
+    x: T = items[0]
+    return x
+"#;
+
+    assert!(errors(code).is_empty());
+}
+
+#[test]
+fn can_use_names_bound_later_in_a_loop() {
+    let code = r#"
+def f():
+    i = 0
+    while i < 10:
+        if i > 0:
+            print(last)
+        last = i
+        i += 1
+"#;
+
+    assert!(errors(code).is_empty());
+}
+
+#[test]
+fn cannot_use_names_before_definition() {
+    let code = r#"
+def f():
+    print(x)
+    x = 1
+"#;
+
+    assert_eq!(errors(code), vec![UsedBeforeDefinition]);
+    assert_eq!(errors("x = x + 1\n"), vec![UsedBeforeDefinition]);
+    assert_eq!(errors("def f():\n    x += 1\n"), vec![UsedBeforeDefinition]);
+}
+
+#[test]
+fn cannot_use_undefined_names() {
+    let code = r#"
+def f():
+    return g()
+class A:
+    x = 1
+    def f(self):
+        return x
+"#;
+
+    assert_eq!(errors(code), vec![UndefinedName, UndefinedName]);
+    assert!(errors("from a import *\nprint(b)\n").is_empty());
+
+    // Only the names the star imports export are bound once they are known.
+    let code = "from a import *\nprint(b, c)\n";
+    assert_eq!(errors_with_star_names(code, &["b"]), vec![UndefinedName]);
+    assert!(errors_with_star_names(code, &["b", "c"]).is_empty());
+}
+
+#[test]
+fn cannot_declare_invalid_nonlocal_names() {
+    let code = r#"
+x = 1
+def f():
+    nonlocal x
+def g():
+    y = 1
+    def h():
+        nonlocal y, z
+"#;
+
+    assert_eq!(errors(code), vec![InvalidNonlocal, InvalidNonlocal]);
+}
+
+#[test]
+fn cannot_declare_names_after_use() {
+    let code = r#"
+y = 1
+def f(x):
+    global x
+def g():
+    print(y)
+    global y
+"#;
+
+    assert_eq!(errors(code), vec![DeclarationAfterUse, DeclarationAfterUse]);
+}
+
+#[test]
+fn can_warn_about_shadowed_builtins() {
+    let code = r#"
+list = [1]
+def f(len):
+    return len
+enum class Maybe:
+    Some(value: int)
+    None
+"#;
+    let (_, analyzer) = analyze(code);
+
+    assert_eq!(
+        analyzer
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.kind.clone())
+            .collect::<Vec<_>>(),
+        vec![ShadowedBuiltin, ShadowedBuiltin]
+    );
+    assert_eq!(analyzer.errors().count(), 0);
+}
+
+#[test]
+fn can_infer_argument_contracts() {
+    let code = r#"
+def add(a, b):
+    return a + b
+def scale(a, b):
+    return 2 * add(a, b)
+"#;
+
+    assert_eq!(
+        contract(code, "add"),
+        "[T: impl __plus__.2](a: any T.__plus__.0, b: any T.__plus__.1, @returns T.__plus__)"
+    );
+    assert_eq!(
+        contract(code, "scale"),
+        "(a: any add.0, b: any add.1, @where [add returns any int.__mul__.1], @returns int.__mul__)"
+    );
+}
+
+#[test]
+fn can_infer_where_chains() {
+    let code = r#"
+def iterate_gift(giver):
+    for gift in giver.gift():
+        print(f"{gift}")
+def first_gift(giver):
+    return next(iter(giver.gift()))
+"#;
+
+    assert_eq!(
+        contract(code, "iterate_gift"),
+        "[T: impl gift.1, U: impl __iter__.1, V: impl __next__.1, W: impl __str__.1](\
+            giver: any T.gift.0, \
+            @where [\
+                T.gift returns any U.__iter__.0, \
+                U.__iter__ returns any V.__next__.0, \
+                V.__next__ returns any W.__str__.0\
+            ]\
+        )"
+    );
+    assert_eq!(
+        contract(code, "first_gift"),
+        "[T: impl gift.1, U: impl __iter__.1, V: impl __next__.1](\
+            giver: any T.gift.0, \
+            @where [T.gift returns any U.__iter__.0, U.__iter__ returns any V.__next__.0], \
+            @returns V.__next__\
+        )"
+    );
+}
+
+#[test]
+fn can_infer_field_contracts() {
+    let code = r#"
+def who_am_i(something):
+    print(f"I am {something.name!r:>10} {{not_a_field}}")
+class Point:
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+    def __plus__(self, other):
+        return Point(self.x + other.x, self.y + other.y)
+"#;
+
+    assert_eq!(
+        contract(code, "who_am_i"),
+        "[T: impl __str__.1](something: any { name: T.__str__.0 })"
+    );
+    assert_eq!(
+        contract(code, "Point.__init__"),
+        "(self: any { x: any, y: any }, x: any, y: any)"
+    );
+    assert_eq!(
+        contract(code, "Point.__plus__"),
+        "[T: impl __plus__.2, U: impl __plus__.2](\
+            self: any { x: T.__plus__.0, y: U.__plus__.0 }, \
+            other: any { x: T.__plus__.1, y: U.__plus__.1 }, \
+            @where [T.__plus__ returns any Point.__init__.1, U.__plus__ returns any Point.__init__.2], \
+            @returns Point\
+        )"
+    );
+}
+
+#[test]
+fn can_infer_contracts_of_called_values() {
+    let code = r#"
+def twice(f, x):
+    y = x
+    return f(f(y))
+"#;
+
+    assert_eq!(
+        contract(code, "twice"),
+        "[T: impl __call__.2](\
+            f: any T.__call__.0, \
+            x: any T.__call__.1, \
+            @where [T.__call__ returns any T.__call__.1], \
+            @returns T.__call__\
+        )"
+    );
+}
+
+#[test]
+fn can_keep_annotated_params_in_contracts() {
+    let code = r#"
+def first[T](items: list[T], *rest, key: str = "") -> T:
+    print(key.upper())
+    return items[0]
+"#;
+
+    assert_eq!(
+        contract(code, "first"),
+        "[T, U: impl __str__.1](\
+            items: list[T], \
+            *rest: any, \
+            key: str, \
+            @where [str.upper returns any U.__str__.0], \
+            @returns T\
+        )"
+    );
+}
+
+#[test]
+fn can_deduplicate_instances() {
+    let code = r#"
+def add(a, b):
+    return a + b
+
+add(1, 2)
+add(3, 4)
+add(1.0, 2.0)
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec!["add#1(int, int) -> int", "add#2(float, float) -> float"]
+    );
+}
+
+#[test]
+fn can_instantiate_methods_of_classes() {
+    let code = r#"
+class Point:
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+    def __plus__(self, other):
+        return Point(self.x + other.x, self.y + other.y)
+
+def add(a, b):
+    return a + b
+
+add(Point(1, 2), Point(3, 4))
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "Point.__init__#1(Point, int, int) -> None",
+            "Point.__plus__#1(Point, Point) -> Point",
+            "add#1(Point, Point) -> Point",
+        ]
+    );
+}
+
+#[test]
+fn can_order_instances_callees_first() {
+    let code = r#"
+def double(x):
+    return x + x
+
+def quadruple(x):
+    return double(double(x))
+
+def describe(x):
+    return str(quadruple(x)) + "!"
+
+describe(2)
+describe("ab")
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "double#1(int) -> int",
+            "quadruple#1(int) -> int",
+            "describe#1(int) -> str",
+            "double#2(str) -> str",
+            "quadruple#2(str) -> str",
+            "describe#2(str) -> str",
+        ]
+    );
+}
+
+#[test]
+fn cannot_instantiate_unimplemented_methods() {
+    let code = r#"
+class StringGiver:
+    def gift(self):
+        return "string gift"
+
+class IntGiver:
+    def gift(self):
+        return 8080
+
+def iterate_gift(giver):
+    for gift in giver.gift():
+        print(f"{gift}")
+
+iterate_gift(StringGiver())
+iterate_gift(IntGiver())
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec!["UnimplementedMethod: iterate_gift(IntGiver); giver: IntGiver; T.gift returns int; int does not implement __iter__.1"]
+    );
+}
+
+#[test]
+fn cannot_instantiate_with_wrong_argument_counts() {
+    let code = r#"
+data class Pair(first: int, second: int)
+
+def add(a, b):
+    return a + b
+
+add(1)
+add(1, 2, 3)
+add(1, c=2)
+Pair(1)
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "WrongArgumentCount: add is missing argument b",
+            "WrongArgumentCount: add takes 2 arguments but 3 were given",
+            "WrongArgumentCount: add has no parameter c to pass",
+            "WrongArgumentCount: Pair is missing field second",
+        ]
+    );
+}
+
+#[test]
+fn cannot_instantiate_with_mismatched_arguments() {
+    let code = r#"
+data class Pair(first: int, second: int)
+
+def greet(name: str):
+    return "Hello, " + name
+
+def shout(value):
+    return value + "!"
+
+greet(1)
+shout(2)
+Pair(1, "2")
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "MismatchedArgument: greet(int); name: int; int is not str",
+            "MismatchedArgument: shout(int); value: int; int.__plus__ does not take str at 1",
+            "MismatchedArgument: Pair.second: str is not int",
+        ]
+    );
+}
+
+#[test]
+fn cannot_instantiate_with_mismatched_defaults() {
+    let code = r#"
+def concat(a, b="x"):
+    return a + b
+
+def scale(value, factor: int = "2"):
+    return value * factor
+
+concat("a")
+concat(1)
+scale(3)
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "concat#1(str, str) -> str",
+            "concat#2(int, str) -> any",
+            "scale#1(int, str) -> int",
+        ]
+    );
+    assert_eq!(
+        chains(code),
+        vec![
+            "MismatchedArgument: concat(int, str); a: int; b: str; int.__plus__ does not take str at 1",
+            "MismatchedArgument: scale(int, str); factor: str; str is not int",
+        ]
+    );
+}
+
+#[test]
+fn cannot_instantiate_with_missing_fields() {
+    let code = r#"
+data class Pair(first: int, second: int)
+
+def third(pair):
+    return pair.third
+
+third(Pair(1, 2))
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec!["MissingField: third(Pair); pair: Pair; Pair has no field third"]
+    );
+}
+
+#[test]
+fn can_type_collection_literals_with_intersections() {
+    let code = r#"
+def first(values):
+    return values[0]
+
+def lookup(table):
+    return table["a"]
+
+first([5, "Hello", 6])
+lookup({"a": 1, "b": 2.0})
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "first#1(list[int & str]) -> int & str",
+            "lookup#1(dict[str, int & float]) -> int & float",
+        ]
+    );
+}
+
+#[test]
+fn cannot_call_methods_missing_from_variants() {
+    let code = r#"
+ls = [5, "Hello"]
+double = ls[0] + ls[0]
+
+def unsafe(flag):
+    if flag:
+        return 8080
+    return "string gift"
+
+unsafe(true).upper()
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "IncompatibleVariants: list.__getitem__ returns int & str; \
+                int & str has no __plus__[int, str] or __plus__[str, int]",
+            "IncompatibleVariants: unsafe returns int & str; int & str has no upper[int]",
+        ]
+    );
+}
+
+#[test]
+fn can_access_fields_at_any_position_in_intersections() {
+    let code = r#"
+data class A(x: int, y: str)
+data class B(w: str, x: int)
+data class C(x: int)
+data class D(y: int)
+
+def get_x(value: A & B):
+    return value.x
+
+def first_x(values):
+    return values[0].x
+
+def first_y(values):
+    return values[0].y
+
+get_x(C(1))
+get_x(D(1))
+first_x([A(1, "a"), B("b", 2)])
+first_y([A(1, "a"), B("b", 2)])
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "get_x#1(C) -> int",
+            "get_x#2(D) -> int",
+            "first_x#1(list[A & B]) -> int",
+            "first_y#1(list[A & B]) -> any",
+        ]
+    );
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "MismatchedArgument: get_x(D); value: D; D is not A & B",
+            "MissingField: first_y(list[A & B]); values: list[A & B]; \
+                T.__getitem__ returns A & B; B has no field y",
+        ]
+    );
+}
+
+#[test]
+fn cannot_use_members_missing_from_annotated_intersections() {
+    let code = r#"
+def shout(value: int & str):
+    return value.upper()
+
+def increment(value: int & str):
+    return value + 1
+
+def show(value: int & str):
+    return value.__str__()
+
+shout("a")
+increment(1)
+show(1)
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "IncompatibleVariants: shout(str); value: int & str; int & str has no upper[int]",
+            "IncompatibleVariants: increment(int); value: int & str; \
+                int & str has no __plus__[str, int]",
+        ]
+    );
+    assert_eq!(
+        instances(code),
+        vec![
+            "shout#1(str) -> any",
+            "increment#1(int) -> any",
+            "show#1(int) -> str"
+        ]
+    );
+}
+
+#[test]
+fn cannot_use_members_missing_from_annotated_intersections_without_calls() {
+    let code = r#"
+def double(x: int & str):
+    return x + x
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "IncompatibleVariants: double(int & str); x: int & str; \
+                int & str has no __plus__[int, str] or __plus__[str, int]",
+        ]
+    );
+}
+
+#[test]
+fn can_give_names_the_types_of_their_annotations() {
+    let code = r#"
+def get_int_or_str(flag):
+    if flag:
+        return 8080
+    return "string gift"
+
+def shout(flag):
+    gift: int & str = get_int_or_str(flag)
+    return gift.upper()
+
+def count(flag):
+    gift: int & str = get_int_or_str(flag)
+    return gift.__str__()
+
+port: int = "8080"
+name: int & str = "Lorem"
+shout(true)
+count(true)
+"#;
+
+    assert_eq!(
+        contract(code, "count"),
+        "(flag: any get_int_or_str.0, @returns int & str.__str__)"
+    );
+    assert_eq!(
+        instances(code),
+        vec![
+            "get_int_or_str#1(bool) -> int & str",
+            "shout#1(bool) -> any",
+            "count#1(bool) -> str",
+        ]
+    );
+    assert_eq!(
+        chains(code),
+        vec![
+            "MismatchedAnnotation: port: str; str is not int",
+            "IncompatibleVariants: shout(bool); int & str has no upper[int]",
+        ]
+    );
+}
+
+#[test]
+fn can_desugar_enum_variants_into_data_classes() {
+    let code = r#"
+enum class PrimaryColor:
+    Red(t: int)
+    Green(t: int)
+    Blue(t: int)
+
+    def to_byte(self):
+        return self.t
+
+def brightest(color: PrimaryColor):
+    return color.t
+
+def red_to_byte(color: PrimaryColor.Red):
+    return color.t
+
+PrimaryColor.Green(2).to_byte()
+brightest(PrimaryColor.Blue(3))
+red_to_byte(PrimaryColor.Red(1))
+red_to_byte(PrimaryColor.Green(2))
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "PrimaryColor.to_byte#1(PrimaryColor.Green) -> int",
+            "brightest#1(PrimaryColor.Blue) -> int",
+            "red_to_byte#1(PrimaryColor.Red) -> int",
+            "red_to_byte#2(PrimaryColor.Green) -> int",
+        ]
+    );
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "MismatchedArgument: red_to_byte(PrimaryColor.Green); color: PrimaryColor.Green; \
+                PrimaryColor.Green is not PrimaryColor.Red"
+        ]
+    );
+}
+
+#[test]
+fn can_construct_unit_and_positional_variants() {
+    let code = r#"
+enum class Shape:
+    Square(int)
+    Empty
+
+    def side(self: Shape.Square):
+        return self._0
+
+Shape.Square(4).side()
+Shape.Empty
+Shape.Square("four")
+"#;
+
+    assert_eq!(instances(code), vec!["Shape.side#1(Shape.Square) -> int"]);
+    assert_eq!(
+        chains(code),
+        vec!["MismatchedArgument: Shape.Square._0: str is not int"]
+    );
+}
+
+#[test]
+fn can_convert_int_literals_to_integer_types() {
+    let code = r#"
+enum class PrimaryColorA:
+    Red(t: byte)
+    Green(t: byte)
+    Blue(t: byte)
+
+    def to_byte(self):
+        return self.t
+
+def clamp(value: byte, limit: u8):
+    return value
+
+level: byte = 1
+PrimaryColorA.Red(1).to_byte()
+clamp(1, limit=2)
+clamp(level, 2)
+PrimaryColorA.Green(len("1"))
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "PrimaryColorA.to_byte#1(PrimaryColorA.Red) -> byte",
+            "clamp#1(byte, u8) -> byte",
+        ]
+    );
+    assert_eq!(
+        chains(code),
+        vec!["MismatchedArgument: str.__len__ returns int; PrimaryColorA.Green.t: int is not byte"]
+    );
+}
+
+#[test]
+fn cannot_use_members_missing_from_enum_variants() {
+    let code = r#"
+enum class Option[T]:
+    Some(t: T)
+    None
+
+    def unwrap(self):
+        return self.t
+
+    def show(self):
+        return self.render()
+
+    def get(self: Option.Some):
+        return self.t
+
+    def describe(self):
+        return self.show()
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "MissingField: Option.unwrap; self: Option; Option.None has no field t",
+            "UnimplementedMethod: Option.show; self: Option; \
+                Option.Some does not implement render.1; Option.None does not implement render.1",
+        ]
+    );
+}
+
+#[test]
+fn can_implement_abstract_classes_explicitly_and_structurally() {
+    let code = r#"
+abstract class Giver:
+    abstract def gift(self)
+
+@implements(Giver)
+class StringGiver:
+    def gift(self) -> str:
+        return "string gift"
+
+class IntGiver:
+    def gift(self) -> int:
+        return 8080
+
+class NoGiver:
+    def take(self) -> int:
+        return 0
+
+def open_gift(giver: Giver):
+    return giver.gift()
+
+open_gift(StringGiver())
+open_gift(IntGiver())
+open_gift(NoGiver())
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "StringGiver.gift#1(StringGiver) -> str",
+            "open_gift#1(StringGiver) -> str",
+            "IntGiver.gift#1(IntGiver) -> int",
+            "open_gift#2(IntGiver) -> int",
+            "open_gift#3(NoGiver) -> any",
+        ]
+    );
+
+    assert_eq!(
+        chains(code),
+        vec!["MismatchedArgument: open_gift(NoGiver); giver: NoGiver; NoGiver is not Giver"]
+    );
+}
+
+#[test]
+fn cannot_implement_abstract_classes_without_their_methods() {
+    let code = r#"
+abstract class Shape:
+    abstract def area(self)
+    def scale(self, factor: int) -> Shape
+
+@implements(Shape)
+class Square:
+    def area(self, unit):
+        return 1
+
+@implements(Shape)
+class Circle:
+    def area(self, unit = "cm"):
+        return 3
+
+    def scale(self, *factors):
+        return self
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "WrongArgumentCount: Square implements Shape; \
+                Square.area takes 2 arguments but Shape.area takes 1",
+            "UnimplementedMethod: Square implements Shape; Square does not implement scale.2",
+        ]
+    );
+}
+
+#[test]
+fn can_implement_abstract_classes_for_different_instantiations() {
+    let code = r#"
+abstract class Abstract[T]:
+    abstract def abstr(self, value: T)
+
+class Bar:
+    def __init__(self):
+        self.count = 0
+
+@inherits(Bar)
+class Foo:
+    def __init__(self):
+        self.__super__()
+
+@implements(Abstract[T])
+class Foo[T]:
+    def abstr(self, value: T):
+        return value
+
+@implements(Abstract[int])
+class Foo:
+    def abstr(self, value: int):
+        return value + 1
+
+foo = Foo()
+foo.abstr(1)
+foo.abstr("one")
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "Bar.__init__#1(Foo) -> None",
+            "Foo.__init__#1(Foo) -> None",
+            "Foo.abstr#1(Foo, int) -> int",
+            "Foo.abstr#2(Foo, str) -> str",
+        ]
+    );
+    assert_eq!(chains(code), Vec::<String>::new());
+}
+
+#[test]
+fn cannot_implement_overlapping_blocks() {
+    let code = r#"
+abstract class Abstract[T]:
+    abstract def abstr(self, value: T)
+
+@implements(Abstract[T])
+class Foo[T]:
+    def abstr(self, value: T):
+        return value
+
+    def bar(self):
+        return 0
+
+@implements(Abstract[U])
+class Foo[U]:
+    def abstr(self, value: U):
+        return value
+
+    def bar(self):
+        return 1
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "ConflictingImplementations: Foo implements Abstract[U] in more than one block",
+            "ConflictingImplementations: Foo.bar is defined in more than one block",
+        ]
+    );
+}
+
+#[test]
+fn can_infer_dyn_element_types_of_containers() {
+    let code = r#"
+class StringGiver:
+    def gift(self) -> str:
+        return "string gift"
+
+class IntGiver:
+    def gift(self) -> int:
+        return 8080
+
+def open_all(givers):
+    for giver in givers:
+        giver.gift()
+
+def copy_first(values):
+    return values[0]
+
+def describe_keys(table):
+    for key in table:
+        key.__str__()
+
+open_all([StringGiver(), IntGiver()])
+copy_first([5, "Hello"])
+copy_first([1, 2])
+describe_keys({1: "one", 2.0: "two"})
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "StringGiver.gift#1(StringGiver) -> str",
+            "IntGiver.gift#1(IntGiver) -> int",
+            "open_all#1(list[StringGiver & IntGiver]) -> None",
+            "copy_first#1(list[int & str]) -> int & str",
+            "copy_first#2(list[int]) -> int",
+            "describe_keys#1(dict[int & float, str]) -> None",
+        ]
+    );
+
+    assert_eq!(
+        containers(code),
+        vec![
+            "list[StringGiver & IntGiver]: list[dyn gift.1]",
+            "list[int & str]: list[dyn _]",
+            "list[int]: list[int]",
+            "dict[int & float, str]: dict[dyn __str__.1, str]",
+        ]
+    );
+}
+
+#[test]
+fn can_narrow_values_with_casts_and_isinstance() {
+    let code = r#"
+def first_int(values):
+    return cast[int](values[0])
+
+def shout(value):
+    if isinstance(value, str):
+        return value.upper()
+    return value
+
+def number(value):
+    if isinstance(value, (int, float)):
+        return value
+    return 0
+
+ls = [5, "Hello"]
+first_int(ls)
+shout(ls[0])
+number(ls[1])
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec![
+            "first_int#1(list[int & str]) -> int",
+            "shout#1(int & str) -> str & int",
+            "number#1(int & str) -> int",
+        ]
+    );
+    assert_eq!(chains(code), Vec::<String>::new());
+}
+
+#[test]
+fn can_narrow_subjects_with_class_patterns() {
+    let code = r#"
+enum class PrimaryColor:
+    Red(t: int)
+    Green(t: str)
+
+def red_value(color: PrimaryColor):
+    match color:
+        case PrimaryColor.Red(t=t):
+            return t + 1
+        case PrimaryColor.Green():
+            return color.t.upper()
+
+red_value(PrimaryColor.Red(1))
+"#;
+
+    assert_eq!(
+        instances(code),
+        vec!["red_value#1(PrimaryColor.Red) -> int & str"]
+    );
+    assert_eq!(chains(code), Vec::<String>::new());
+}
+
+#[test]
+fn cannot_cast_to_types_values_never_have() {
+    let code = r#"
+abstract class Giver:
+    abstract def gift(self)
+
+class IntGiver:
+    def gift(self) -> int:
+        return 8080
+
+def first_float(values):
+    return cast[float](values[0])
+
+def unwrap_giver(giver: Giver):
+    return cast[IntGiver](giver)
+
+first_float([5, "Hello"])
+unwrap_giver(IntGiver())
+cast[str](5)
+if isinstance(5, str):
+    cast[str](5)
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "ImpossibleCast: first_float(list[int & str]); values: list[int & str]; \
+                T.__getitem__ returns int & str; int & str can never be float",
+            "ImpossibleCast: int can never be str",
+            "ImpossibleCast: int can never be str",
+        ]
+    );
+}
+
+#[test]
+fn can_place_allocations_that_outlive_their_function_on_the_heap() {
+    let code = r#"
+data class Person(name: str, age: int)
+
+class Greeter:
+    def __init__(self, greeting):
+        self.greeting = greeting
+        self.suffix = "!"
+
+def get_person() -> Person:
+    age = 55
+    name = "John"
+    return Person(name, age)
+
+def greet():
+    label = "Hello"
+    print(label)
+    return Greeter("Hi")
+
+def wrap(value):
+    return [value]
+
+def wrap_name():
+    return wrap("Jane")
+
+age = 45
+name = "John"
+john = Person(name, age)
+"#;
+
+    assert_eq!(
+        allocations(code),
+        vec![
+            r#"Greeter.__init__: "!" -> heap, stored in self, which is a parameter of Greeter.__init__"#,
+            "get_person: 55 -> value, copied by value",
+            r#"get_person: "John" -> heap, captured by Person(name, age), which is returned by get_person"#,
+            "get_person: Person(name, age) -> stack, returned by get_person, which copies it",
+            r#"greet: "Hello" -> stack, does not outlive greet"#,
+            r#"greet: "Hi" -> heap, captured by Greeter("Hi"), which is returned by greet"#,
+            r#"greet: Greeter("Hi") -> stack, returned by greet, which copies it"#,
+            "wrap: [value] -> stack, returned by wrap, which copies it",
+            r#"wrap_name: "Jane" -> heap, kept by the result of wrap, which is returned by wrap_name"#,
+            "<module>: 45 -> value, copied by value",
+            r#"<module>: "John" -> stack, lives as long as the module"#,
+            "<module>: Person(name, age) -> stack, lives as long as the module",
+        ]
+    );
+}
+
+#[test]
+fn can_box_allocations_that_live_on_the_heap() {
+    let code = r#"
+data class Person(name: str, age: int)
+
+def get_person() -> Person:
+    age = 55
+    name = "John"
+    return Person(name, age)
+
+def get_boxed() -> Person:
+    name = Box("James")
+    return Person(name, 30)
+"#;
+
+    let (_, analyzer) = analyze(code);
+    assert_eq!(
+        analyzer.dump_allocations().lines().collect::<Vec<_>>(),
+        vec![
+            "get_person: 55 -> value, copied by value",
+            r#"get_person: "John" -> heap, captured by Person(name, age), which is returned by get_person"#,
+            "get_person: Person(name, age) -> stack, returned by get_person, which copies it",
+            r#"get_boxed: "James" -> heap, boxed by the code"#,
+            "get_boxed: 30 -> value, copied by value",
+            "get_boxed: Person(name, 30) -> stack, returned by get_boxed, which copies it",
+        ]
+    );
+
+    let boxed = Unparser::unparse(&analyzer.ast);
+    assert!(boxed.contains(r#"name = Box("John")"#));
+    assert!(boxed.contains(r#"name = Box("James")"#));
+    assert!(!boxed.contains("Box(Box("));
+    assert!(boxed.contains("age = 55"));
+}
+
+#[test]
+fn can_box_fields_of_recursive_classes() {
+    let code = r#"
+data class Node(parent: Node?, children: [Node])
+
+data class Employee(name: str, team: Team?)
+
+data class Team(lead: Employee?)
+
+enum class Tree:
+    Leaf(value: int)
+    Branch(left: Tree, right: Tree)
+"#;
+
+    let (_, analyzer) = analyze(code);
+    assert_eq!(analyzer.errors().count(), 0);
+    assert_eq!(
+        analyzer.dump_layouts().lines().collect::<Vec<_>>(),
+        vec![
+            "Node.parent: Node? -> Box[Node?]",
+            "Employee.team: Team? -> Box[Team?]",
+            "Tree.Branch.left: Tree -> Box[Tree]",
+            "Tree.Branch.right: Tree -> Box[Tree]",
+        ]
+    );
+
+    let code = Unparser::unparse(&analyzer.ast);
+    assert!(code.contains("data class Node(parent: Box[Node?], children: [Node])"));
+    assert!(code.contains("data class Team(lead: Employee?)"));
+    assert!(code.contains("Branch(left: Box[Tree], right: Box[Tree])"));
+}
+
+#[test]
+fn can_box_fields_that_sibling_fields_reference() {
+    let code = r#"
+data class Context()
+
+data class Module(context: Context)
+
+class Engine:
+    def __init__(self):
+        self.context = Context()
+        self.module = Module(self.context)
+        self.backup = Module(context=self.context)
+"#;
+
+    let (_, analyzer) = analyze(code);
+    assert_eq!(
+        analyzer.dump_layouts().lines().collect::<Vec<_>>(),
+        vec!["Engine.context -> Box, referenced by Engine.module, Engine.backup"]
+    );
+
+    let code = Unparser::unparse(&analyzer.ast);
+    assert!(code.contains("self.context = Box(Context())"));
+    assert!(code.contains("self.module = Box(Module(self.context.val))"));
+    assert!(code.contains("self.backup = Box(Module(context=self.context.val))"));
+    assert!(!code.contains("Box(Box("));
+}
+
+#[test]
+fn cannot_lay_out_infinite_or_self_referencing_classes() {
+    let code = r#"
+data class Loop(next: Loop)
+
+data class Ping(pong: Pong)
+
+data class Pong(ping: Ping)
+
+data class Module(owner: Engine)
+
+class Engine:
+    def __init__(self):
+        self.module = Module(self)
+"#;
+
+    assert_eq!(
+        chains(code),
+        vec![
+            "InfiniteSize: Loop.next holds a Loop",
+            "InfiniteSize: Ping.pong holds a Pong; Pong.ping holds a Ping",
+            "SelfReference: Engine.module holds a reference to its Engine",
+        ]
+    );
+}
+
+mod utils {
+    use crate::expander::Expander;
+    use crate::ir::{visit, Ast, Identifier, NodeId, Visitor};
+    use crate::parser::Parser;
+    use crate::semantic::{Resolution, SemanticAnalyzer, SemanticErrorKind};
+
+    struct Names<'a> {
+        name: &'a str,
+        /// The ids of the names with where they start.
+        ids: Vec<(u32, NodeId)>,
+    }
+
+    impl Visitor for Names<'_> {
+        fn visit_identifier(&mut self, node: &Identifier) {
+            if node.name == self.name {
+                self.ids.push((node.span.start, node.id));
+            }
+
+            visit::walk_identifier(self, node);
+        }
+    }
+
+    pub(super) fn analyze(code: &str) -> (Ast, SemanticAnalyzer) {
+        let ast = Parser::parse(code).unwrap();
+        let analyzer = SemanticAnalyzer::analyze(&ast);
+        (ast, analyzer)
+    }
+
+    /// Analyzes the code with its macros expanded.
+    pub(super) fn analyze_expanded(code: &str) -> (Ast, SemanticAnalyzer) {
+        let ast = Expander::expand(Parser::parse(code).unwrap()).unwrap();
+        let analyzer = SemanticAnalyzer::analyze(&ast);
+        (ast, analyzer)
+    }
+
+    /// The ids of the names in the code, in source order.
+    pub(super) fn names(ast: &Ast, name: &str) -> Vec<NodeId> {
+        let mut names = Names { name, ids: vec![] };
+        ast.statements
+            .iter()
+            .for_each(|statement| names.visit_statement(statement));
+
+        names.ids.sort_by_key(|(start, _)| *start);
+        names.ids.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// What the nth occurrence of a name refers to.
+    pub(super) fn resolution(
+        ast: &Ast,
+        analyzer: &SemanticAnalyzer,
+        name: &str,
+        nth: usize,
+    ) -> Option<Resolution> {
+        analyzer.symbols.resolution(&names(ast, name)[nth])
+    }
+
+    /// The contract of a function in the notation of `IDEAS.md`.
+    pub(super) fn contract(code: &str, name: &str) -> String {
+        analyze(code).1.contract(name).unwrap().to_string()
+    }
+
+    /// The instances in the order codegen generates them.
+    pub(super) fn instances(code: &str) -> Vec<String> {
+        analyze(code)
+            .1
+            .instances
+            .worklist()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// The container literals with how they store their elements.
+    pub(super) fn containers(code: &str) -> Vec<String> {
+        analyze(code)
+            .1
+            .dump_containers()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    /// The allocations with where they live and why.
+    pub(super) fn allocations(code: &str) -> Vec<String> {
+        analyze(code)
+            .1
+            .dump_allocations()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    /// The errors with the steps that lead to them, like `MissingField: pair: Pair; ...`.
+    pub(super) fn chains(code: &str) -> Vec<String> {
+        analyze(code)
+            .1
+            .errors()
+            .map(|error| format!("{:?}: {}", error.kind, error.chain.join("; ")))
+            .collect()
+    }
+
+    pub(super) fn errors(code: &str) -> Vec<SemanticErrorKind> {
+        analyze(code)
+            .1
+            .errors()
+            .map(|error| error.kind.clone())
+            .collect()
+    }
+
+    /// The errors of a module whose star imports bring in `star_names`.
+    pub(super) fn errors_with_star_names(
+        code: &str,
+        star_names: &[&str],
+    ) -> Vec<SemanticErrorKind> {
+        let star_names = star_names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        SemanticAnalyzer::analyze_with_star_names(&Parser::parse(code).unwrap(), &star_names)
+            .errors()
+            .map(|error| error.kind.clone())
+            .collect()
+    }
+}
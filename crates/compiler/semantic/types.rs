@@ -0,0 +1,185 @@
+use std::fmt::{self, Display};
+
+use crate::ir::{Expr, ExprKind, SubscriptIndex, TypeAnnotation, TypeAnnotationKind};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// A type the code names, either in an annotation or as the type of a literal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    /// A builtin type, class or generic parameter with its generic arguments, like `int` or
+    /// `list[T]`.
+    Named { name: String, arguments: Vec<Type> },
+    /// `(params) -> return_type`
+    Function {
+        params: Vec<Type>,
+        return_type: Box<Type>,
+    },
+    /// `[element, ...]`
+    List(Vec<Type>),
+    /// `(element, ...)`
+    Tuple(Vec<Type>),
+    /// `a | b`
+    Union(Vec<Type>),
+    /// `a & b`
+    Intersection(Vec<Type>),
+    /// `a?`
+    Optional(Box<Type>),
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Type {
+    /// A type without generic arguments.
+    pub fn named(name: impl Into<String>) -> Self {
+        Self::Named {
+            name: name.into(),
+            arguments: vec![],
+        }
+    }
+
+    pub fn from_annotation(annotation: &TypeAnnotation) -> Self {
+        let all = |annotations: &[TypeAnnotation]| {
+            annotations.iter().map(Self::from_annotation).collect()
+        };
+        match &annotation.kind {
+            TypeAnnotationKind::Identifier(name) => Self::named(&name.name),
+            TypeAnnotationKind::Path(path) => Self::named(
+                path.names
+                    .iter()
+                    .map(|name| name.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join("."),
+            ),
+            TypeAnnotationKind::Generic { name, arguments } => Self::Named {
+                name: name.name.clone(),
+                arguments: all(arguments),
+            },
+            TypeAnnotationKind::Function {
+                params,
+                return_type,
+            } => Self::Function {
+                params: all(params),
+                return_type: Box::new(Self::from_annotation(return_type)),
+            },
+            TypeAnnotationKind::List(types) => Self::List(all(types)),
+            TypeAnnotationKind::Tuple(types) => Self::Tuple(all(types)),
+            TypeAnnotationKind::Union(types) => Self::Union(all(types)),
+            TypeAnnotationKind::Intersection(types) => Self::Intersection(all(types)),
+            TypeAnnotationKind::Optional(annotation) => {
+                Self::Optional(Box::new(Self::from_annotation(annotation)))
+            }
+        }
+    }
+
+    /// The type an expression names where a type is passed as a value, like `Abstract[int]` in
+    /// `@implements(Abstract[int])`, `int` in `isinstance(x, int)` or `PrimaryColor.Red`.
+    pub fn from_expr(expr: &Expr) -> Option<Self> {
+        match &expr.kind {
+            ExprKind::Identifier(name) => Some(Self::named(&name.name)),
+            ExprKind::Attribute { value, attribute } => match Self::from_expr(value)? {
+                Self::Named { name, arguments } if arguments.is_empty() => {
+                    Some(Self::named(format!("{name}.{}", attribute.name)))
+                }
+                _ => None,
+            },
+            ExprKind::Subscript { value, indices } => {
+                let name = match &value.kind {
+                    ExprKind::Identifier(name) => name.name.clone(),
+                    _ => return None,
+                };
+
+                let arguments = indices
+                    .iter()
+                    .map(|index| match index {
+                        SubscriptIndex::Index(expr) => Self::from_expr(expr),
+                        SubscriptIndex::Slice { .. } => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+
+                Some(Self::Named { name, arguments })
+            }
+            ExprKind::Tuple(elements) => elements
+                .iter()
+                .map(Self::from_expr)
+                .collect::<Option<Vec<_>>>()
+                .map(Self::Tuple),
+            _ => None,
+        }
+    }
+
+    /// The intersection of types, without repeats and with the intersections among them flattened,
+    /// which is the type itself for one type and nothing for none.
+    pub fn intersection(types: impl IntoIterator<Item = Type>) -> Option<Self> {
+        let mut variants: Vec<Type> = vec![];
+        for ty in types {
+            for variant in ty.variants() {
+                if !variants.contains(variant) {
+                    variants.push(variant.clone());
+                }
+            }
+        }
+
+        match variants.len() {
+            0 => None,
+            1 => variants.pop(),
+            _ => Some(Self::Intersection(variants)),
+        }
+    }
+
+    /// The types an intersection is made of, or the type itself.
+    pub fn variants(&self) -> Vec<&Type> {
+        match self {
+            Self::Intersection(types) => types.iter().flat_map(Type::variants).collect(),
+            ty => vec![ty],
+        }
+    }
+
+    /// The name of a named type.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Named { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named { name, arguments } => {
+                write!(f, "{name}")?;
+                if !arguments.is_empty() {
+                    write!(f, "[{}]", join(arguments, ", "))?;
+                }
+
+                Ok(())
+            }
+            Self::Function {
+                params,
+                return_type,
+            } => write!(f, "({}) -> {return_type}", join(params, ", ")),
+            Self::List(types) => write!(f, "[{}]", join(types, ", ")),
+            Self::Tuple(types) => write!(f, "({})", join(types, ", ")),
+            Self::Union(types) => write!(f, "{}", join(types, " | ")),
+            Self::Intersection(types) => write!(f, "{}", join(types, " & ")),
+            Self::Optional(inner) => write!(f, "{inner}?"),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+fn join(types: &[Type], separator: &str) -> String {
+    types
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
@@ -0,0 +1,90 @@
+use crate::ir::{Param, Params};
+use crate::span::Span;
+
+/// The parameters in the order they are declared.
+pub(crate) fn all_params(params: &Params) -> impl Iterator<Item = &Param> {
+    params
+        .positional_only
+        .iter()
+        .chain(params.params.iter())
+        .chain(params.vararg.iter())
+        .chain(params.keyword_only.iter())
+        .chain(params.kwarg.iter())
+}
+
+/// The span of the code as written in the module, which for expanded code is its macro call.
+pub(crate) fn site(span: &Span) -> &Span {
+    match &span.call_site {
+        Some(call_site) => site(call_site),
+        None => span,
+    }
+}
+
+/// Two spans whose offsets can be compared: themselves if they come from the same expansion, where
+/// they keep their order in the macro body, or else their sites in the module.
+pub(crate) fn comparable<'a>(a: &'a Span, b: &'a Span) -> (&'a Span, &'a Span) {
+    match a.call_site == b.call_site {
+        true => (a, b),
+        false => (site(a), site(b)),
+    }
+}
+
+/// The expressions of the replacement fields of an f-string, without their conversions and format
+/// specs.
+pub(crate) fn format_placeholders(string: &str) -> Vec<String> {
+    let mut placeholders = vec![];
+    let mut chars = string.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                let mut depth = 0;
+                for char in chars.by_ref() {
+                    match char {
+                        '(' | '[' | '{' => depth += 1,
+                        '}' if depth == 0 => break,
+                        ')' | ']' | '}' => depth -= 1,
+                        _ => (),
+                    }
+
+                    placeholder.push(char);
+                }
+
+                placeholders.push(format_expression(&placeholder).to_string());
+            }
+            _ => (),
+        }
+    }
+
+    placeholders
+}
+
+/// The expression of a replacement field, which ends at a top-level `!` conversion or `:` format
+/// spec, and before the `=` of a self-documenting field like `{value=}`.
+fn format_expression(placeholder: &str) -> &str {
+    let bytes = placeholder.as_bytes();
+    let mut depth = 0;
+    let mut end = placeholder.len();
+    for (index, byte) in bytes.iter().enumerate() {
+        let next = bytes.get(index + 1);
+        match byte {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b'!' | b':' if depth == 0 && next != Some(&b'=') => {
+                end = index;
+                break;
+            }
+            _ => (),
+        }
+    }
+
+    let expression = placeholder[..end].trim_end();
+    match expression.strip_suffix('=') {
+        Some(stripped) if !stripped.ends_with(['=', '!', '<', '>']) => stripped.trim_end(),
+        _ => expression,
+    }
+    .trim()
+}
@@ -1,11 +1,18 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Span {
     pub start: u32,
     pub end: u32,
+    /// The span of the macro call this code was expanded at, if it comes from a macro definition.
+    pub call_site: Option<Box<Span>>,
 }
 
 impl Span {
     pub fn new(start: u32, end: u32) -> Self {
-        Self { start, end }
+        Self {
+            start,
+            end,
+            call_site: None,
+        }
     }
 }
@@ -0,0 +1,7 @@
+#![allow(clippy::module_inception)]
+mod unparser;
+
+pub use unparser::*;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,253 @@
+use crate::ir::{BinaryOp, ExprKind, StatementKind, UnaryOp};
+
+use super::Unparser;
+
+use utils::*;
+
+#[test]
+fn can_unparse_statements() {
+    let code = r#"import .a.b as c
+from .. import *
+x: int | str? & [T]? = (a, *b)
+x **= 2
+@d(1, e=2)
+def f[T](a, /, b: int = 1, *c, d, **e) -> (T) -> [T]:
+    """A
+    docstring."""
+    if (a := b):
+        pass
+    elif c:
+        return (a,)
+    else:
+        raise E from e
+    for x, y in z if x:
+        break
+    while a:
+        continue
+class A(B):
+    try:
+        pass
+    except E as e:
+        pass
+    finally:
+        pass
+with a as b, c:
+    f((lambda x:
+        return x
+    ), 1)
+"#;
+
+    assert_eq!(Unparser::unparse(&parse(code)), code);
+}
+
+#[test]
+fn can_unparse_declarations_and_passing_modes() {
+    let code = "let a = ref b
+var c: int
+const d: str = -val e.f
+def g(ref mut h, val i, ptr j: int = ptr k, mut l):
+    pass
+";
+
+    assert_eq!(Unparser::unparse(&parse(code)), code);
+    assert_eq!(unparse_expr("ref (a + b)"), "ref (a + b)");
+}
+
+#[test]
+fn can_unparse_class_forms() {
+    let code = r#"@implements(Iterator[T])
+data class A[T](xs: [T], index=0)
+data class B():
+    pass
+enum class Option[T]:
+    Some(t: T)
+    None
+    Ok(T)
+    (unwrap)
+    (print(Ok))
+    def unwrap(self):
+        pass
+union class C:
+    int: i64
+abstract class D:
+    abstract def f(self)
+    def g(self) -> int
+"#;
+
+    assert_eq!(Unparser::unparse(&parse(code)), code);
+}
+
+#[test]
+fn can_unparse_interfaces_type_aliases_and_bounds() {
+    let code = r#"typealias PrimaryColor = Red & Green & Blue
+typealias Pair[T] = (T, T) | [T]
+@inline
+@where(T: Seq, U: Seq & Sized)
+def zip[T, U](a: T, b: U):
+    pass
+@where(T: Seq)
+interface Iter[T](Sized):
+    "An iterator."
+    typealias Item = T
+    length: int
+    def next(self) -> Item
+"#;
+
+    assert_eq!(Unparser::unparse(&parse(code)), code);
+}
+
+#[test]
+fn can_unparse_macros() {
+    let code = "macro swap(a, b):\n    a, b = (b, a)\nmacro unit():\n    ()\n";
+
+    assert_eq!(Unparser::unparse(&parse(code)), code);
+}
+
+#[test]
+fn can_unparse_async_functions_and_generators() {
+    let code = r#"async def f(a):
+    async for b in a:
+        x = yield await b.c()
+    async with d as e:
+        y += yield from await e ** 2
+    return [(yield), √await a]
+abstract async def g()
+"#;
+
+    assert_eq!(Unparser::unparse(&parse(code)), code);
+}
+
+#[test]
+fn can_unparse_comprehensions() {
+    let code = r#"a = [b for b, c in d if (e := c) if not b for f in (g if b else h)]
+i = {j: k for j, k in l}
+m = ({n for n in o}, f(p for p in q), f((r for r in s), t))
+async def u():
+    return [await v async for v in w]
+"#;
+
+    assert_eq!(Unparser::unparse(&parse(code)), code);
+}
+
+#[test]
+fn can_unparse_destructuring_targets() {
+    let code = r#"a, [b, *c], d.e = f
+g()[0], *_ = [h] = i
+for [j, k], l.m in n:
+    pass
+"#;
+
+    assert_eq!(Unparser::unparse(&parse(code)), code);
+}
+
+#[test]
+fn can_unparse_match() {
+    let code = r#"match (a, b):
+    case [Point(0, y=-1.5) | Origin(), *_] if y:
+        pass
+    case {"a": [*rest], Color.Red: _, **others}:
+        pass
+    case (A() as a) | b as c:
+        x = (match c:
+            case B(d): d
+            case _: lambda: d
+        )
+"#;
+
+    assert_eq!(Unparser::unparse(&parse(code)), code);
+}
+
+#[test]
+fn can_parenthesize_by_precedence() {
+    assert_eq!(unparse_expr("(a + b) * c"), "(a + b) * c");
+    assert_eq!(unparse_expr("a - (b - c)"), "a - (b - c)");
+    assert_eq!(unparse_expr("(a - b) - c"), "a - b - c");
+    assert_eq!(unparse_expr("(a ** b) ** c"), "(a ** b) ** c");
+    assert_eq!(unparse_expr("a ** (b ** c)"), "a ** b ** c");
+    assert_eq!(unparse_expr("(-a) ** b"), "(-a) ** b");
+    assert_eq!(unparse_expr("√(a²)"), "√(a²)");
+    assert_eq!(unparse_expr("(not a) == b"), "(not a) == b");
+    assert_eq!(
+        unparse_expr("(a if b else c) if d else e"),
+        "(a if b else c) if d else e"
+    );
+    assert_eq!(unparse_expr("(lambda: a)(b)"), "(lambda: a)(b)");
+    assert_eq!(unparse_expr("(1).a"), "(1).a");
+    assert_eq!(unparse_expr("f(*a, **b, c=d)"), "f(*a, **b, c=d)");
+    assert_eq!(unparse_expr("(a + b)?"), "(a + b)?");
+    assert_eq!(unparse_expr("-(a?)"), "-a?");
+    assert_eq!(unparse_expr("f()?.b?[c]"), "f()?.b?[c]");
+}
+
+#[test]
+fn can_unparse_strings_with_matching_quotes() {
+    assert_eq!(unparse_expr(r#"'a"b'"#), r#"'a"b'"#);
+    assert_eq!(unparse_expr(r#"rb'a' f"b""#), r#"rb"a" f"b""#);
+    assert_eq!(unparse_expr("'''a\nb'''"), "\"\"\"a\nb\"\"\"");
+}
+
+#[test]
+fn can_unparse_constructed_ast() {
+    let expr = binary(
+        unary(UnaryOp::Minus, name("a")),
+        BinaryOp::Pow,
+        binary(name("b"), BinaryOp::Or, name("c")),
+    );
+
+    assert_eq!(Unparser::unparse_expr(&expr), "(-a) ** (b or c)");
+    assert!(matches!(
+        parse(&Unparser::unparse_expr(&expr)).statements[0].kind,
+        StatementKind::Expr(ref parsed) if matches!(parsed.kind, ExprKind::BinaryOp { .. })
+    ));
+}
+
+mod utils {
+    use crate::ir::{Ast, BinaryOp, Expr, ExprKind, Identifier, NodeId, StatementKind, UnaryOp};
+    use crate::parser::Parser;
+    use crate::span::Span;
+    use crate::unparser::Unparser;
+
+    pub(super) fn parse(code: &str) -> Ast {
+        Parser::parse(code).unwrap()
+    }
+
+    /// Parses an expression statement and unparses the expression.
+    pub(super) fn unparse_expr(code: &str) -> String {
+        match &parse(code).statements[0].kind {
+            StatementKind::Expr(expr) => Unparser::unparse_expr(expr),
+            kind => panic!("expected an expression, found {kind:?}"),
+        }
+    }
+
+    fn expr(kind: ExprKind) -> Expr {
+        Expr {
+            kind,
+            id: NodeId::default(),
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub(super) fn name(name: &str) -> Expr {
+        expr(ExprKind::Identifier(Identifier {
+            name: name.into(),
+            id: NodeId::default(),
+            span: Span::new(0, 0),
+            context: None,
+        }))
+    }
+
+    pub(super) fn binary(lhs: Expr, op: BinaryOp, rhs: Expr) -> Expr {
+        expr(ExprKind::BinaryOp {
+            lhs: Box::new(lhs),
+            op,
+            rhs: Box::new(rhs),
+        })
+    }
+
+    pub(super) fn unary(op: UnaryOp, operand: Expr) -> Expr {
+        expr(ExprKind::UnaryOp {
+            op,
+            operand: Box::new(operand),
+        })
+    }
+}
@@ -0,0 +1,1271 @@
+use crate::ir::{
+    Argument, Ast, BinaryOp, ClassKind, ComparisonOp, Comprehension, ComprehensionFor,
+    DeclarationKind, Decorator, DictEntry, Expr, ExprKind, GenericBound, Identifier, ImportNames,
+    ImportPath, LambdaBody, Param, Params, PassingMode, Path, Pattern, PatternKind, Statement,
+    StatementKind, StringLiteral, SubscriptIndex, TypeAnnotation, TypeAnnotationKind, UnaryOp,
+    Variant, VariantField,
+};
+use crate::lexer::{BytesKind, IntegerKind, StringKind};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// Turns an AST back into readable source code.
+///
+/// Parentheses are only added where precedence requires them, so parsing the output gives back the
+/// same AST apart from spans. The exceptions are ASTs the parser cannot produce, like a union type
+/// inside an intersection or optional type, or a string with both quote characters.
+#[derive(Debug, Default)]
+pub struct Unparser {
+    /// The code written so far.
+    code: String,
+    /// The current indentation level.
+    indent_level: usize,
+}
+
+/// The binding strength of an expression, from loosest to tightest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    /// `*rest` and `name := value`, which are always parenthesized as operands.
+    Rest,
+    Lambda,
+    Ternary,
+    Or,
+    And,
+    Not,
+    Comparison,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
+    Sum,
+    Mul,
+    Unary,
+    Power,
+    Sqrt,
+    Await,
+    Atom,
+}
+
+/// The binding strength of a pattern, from loosest to tightest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum PatternPrecedence {
+    As,
+    Or,
+    Closed,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Unparser {
+    /// Returns the source code of an AST.
+    pub fn unparse(ast: &Ast) -> String {
+        let mut unparser = Unparser::default();
+        unparser.write_statements(&ast.statements);
+        unparser.code
+    }
+
+    /// Returns the source code of an expression.
+    pub fn unparse_expr(expr: &Expr) -> String {
+        let mut unparser = Unparser::default();
+        unparser.write_expr(expr, Precedence::Rest);
+        unparser.code
+    }
+
+    fn write(&mut self, code: &str) {
+        self.code.push_str(code);
+    }
+
+    fn write_indent(&mut self) {
+        for _ in 0..self.indent_level {
+            self.code.push_str("    ");
+        }
+    }
+
+    /// Writes items separated by `, `.
+    fn write_list<T>(&mut self, items: &[T], mut write_item: impl FnMut(&mut Self, &T)) {
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.write(", ");
+            }
+
+            write_item(self, item);
+        }
+    }
+}
+
+/// Statement writing functions.
+impl Unparser {
+    fn write_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.write_indent();
+            self.write_statement(statement);
+        }
+    }
+
+    /// Writes `:` followed by an indented block.
+    fn write_block(&mut self, statements: &[Statement]) {
+        self.write(":\n");
+        self.indent_level += 1;
+        self.write_statements(statements);
+        self.indent_level -= 1;
+    }
+
+    /// Writes an `else` clause if there is one.
+    fn write_else(&mut self, orelse: &Option<Vec<Statement>>) {
+        if let Some(orelse) = orelse {
+            self.write_indent();
+            self.write("else");
+            self.write_block(orelse);
+        }
+    }
+
+    fn write_statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::Expr(expr) => self.write_value(expr),
+            StatementKind::Assign(assign) => {
+                for target in assign.targets.iter() {
+                    self.write_target(target);
+                    self.write(" = ");
+                }
+
+                self.write_value(&assign.value);
+            }
+            StatementKind::AugAssign(assign) => {
+                self.write_expr(&assign.target, Precedence::Lambda);
+                self.write(&format!(" {}= ", binary_op(assign.op)));
+                self.write_value(&assign.value);
+            }
+            StatementKind::AnnAssign(assign) => {
+                self.write_expr(&assign.target, Precedence::Lambda);
+                self.write(": ");
+                self.write_type(&assign.annotation);
+                if let Some(value) = &assign.value {
+                    self.write(" = ");
+                    self.write_value(value);
+                }
+            }
+            StatementKind::Declaration(declaration) => {
+                self.write(match declaration.kind {
+                    DeclarationKind::Let => "let ",
+                    DeclarationKind::Var => "var ",
+                    DeclarationKind::Const => "const ",
+                });
+                self.write(&declaration.name.name);
+
+                if let Some(annotation) = &declaration.annotation {
+                    self.write(": ");
+                    self.write_type(annotation);
+                }
+
+                if let Some(value) = &declaration.value {
+                    self.write(" = ");
+                    self.write_value(value);
+                }
+            }
+            StatementKind::TypeAlias(alias) => {
+                self.write("typealias ");
+                self.write(&alias.name.name);
+                self.write_generics(&alias.generics);
+                self.write(" = ");
+                self.write_type(&alias.value);
+            }
+            StatementKind::Pass => self.write("pass"),
+            StatementKind::Break => self.write("break"),
+            StatementKind::Continue => self.write("continue"),
+            StatementKind::Return(value) => {
+                self.write("return");
+                if let Some(value) = value {
+                    self.write(" ");
+                    self.write_expr(value, Precedence::Lambda);
+                }
+            }
+            StatementKind::Raise(raise) => {
+                self.write("raise");
+                if let Some(exception) = &raise.exception {
+                    self.write(" ");
+                    self.write_expr(exception, Precedence::Lambda);
+                    if let Some(cause) = &raise.cause {
+                        self.write(" from ");
+                        self.write_expr(cause, Precedence::Lambda);
+                    }
+                }
+            }
+            StatementKind::Global(names) => {
+                self.write("global ");
+                self.write_list(names, |this, name| this.write(&name.name));
+            }
+            StatementKind::Nonlocal(names) => {
+                self.write("nonlocal ");
+                self.write_list(names, |this, name| this.write(&name.name));
+            }
+            StatementKind::Assert(assert) => {
+                self.write("assert ");
+                self.write_expr(&assert.test, Precedence::Lambda);
+                if let Some(message) = &assert.message {
+                    self.write(", ");
+                    self.write_expr(message, Precedence::Lambda);
+                }
+            }
+            StatementKind::Import(import) => {
+                self.write("import ");
+                self.write_import_path(&import.path);
+                if let Some(alias) = &import.alias {
+                    self.write(" as ");
+                    self.write(&alias.name);
+                }
+            }
+            StatementKind::ImportFrom(import) => {
+                self.write("from ");
+                self.write_import_path(&import.path);
+                self.write(" import ");
+                match &import.names {
+                    ImportNames::All => self.write("*"),
+                    ImportNames::Names(names) => self.write_list(names, |this, name| {
+                        this.write(&name.name.name);
+                        if let Some(alias) = &name.alias {
+                            this.write(" as ");
+                            this.write(&alias.name);
+                        }
+                    }),
+                }
+            }
+            StatementKind::If(if_) => {
+                self.write("if ");
+                self.write_expr(&if_.condition, Precedence::Ternary);
+                self.write_block(&if_.body);
+
+                for elif in if_.elifs.iter() {
+                    self.write_indent();
+                    self.write("elif ");
+                    self.write_expr(&elif.condition, Precedence::Ternary);
+                    self.write_block(&elif.body);
+                }
+
+                self.write_else(&if_.orelse);
+                return;
+            }
+            StatementKind::While(while_) => {
+                // A ternary condition would take the guard as its condition.
+                self.write("while ");
+                self.write_expr(&while_.condition, Precedence::Or);
+                self.write_guard(&while_.guard);
+                self.write_block(&while_.body);
+                self.write_else(&while_.orelse);
+                return;
+            }
+            StatementKind::For(for_) => {
+                if for_.is_async {
+                    self.write("async ");
+                }
+
+                self.write("for ");
+                self.write_target(&for_.target);
+                self.write(" in ");
+                // Like `while` conditions, the iterable cannot be a ternary.
+                self.write_expr(&for_.iter, Precedence::Or);
+
+                self.write_guard(&for_.guard);
+                self.write_block(&for_.body);
+                self.write_else(&for_.orelse);
+                return;
+            }
+            StatementKind::Try(try_) => {
+                self.write("try");
+                self.write_block(&try_.body);
+
+                for handler in try_.handlers.iter() {
+                    self.write_indent();
+                    self.write("except ");
+                    self.write(&handler.exception.name);
+                    if let Some(name) = &handler.name {
+                        self.write(" as ");
+                        self.write(&name.name);
+                    }
+
+                    self.write_block(&handler.body);
+                }
+
+                self.write_else(&try_.orelse);
+
+                if let Some(finalbody) = &try_.finalbody {
+                    self.write_indent();
+                    self.write("finally");
+                    self.write_block(finalbody);
+                }
+
+                return;
+            }
+            StatementKind::With(with) => {
+                if with.is_async {
+                    self.write("async ");
+                }
+
+                self.write("with ");
+                self.write_list(&with.items, |this, item| {
+                    this.write_expr(&item.context, Precedence::Lambda);
+                    if let Some(alias) = &item.alias {
+                        this.write(" as ");
+                        this.write(&alias.name);
+                    }
+                });
+
+                self.write_block(&with.body);
+                return;
+            }
+            StatementKind::Match(match_) => {
+                self.write_match_subject(&match_.subject);
+                self.indent_level += 1;
+
+                for case in match_.cases.iter() {
+                    self.write_case_head(&case.pattern, &case.guard);
+                    self.write_block(&case.body);
+                }
+
+                self.indent_level -= 1;
+                return;
+            }
+            StatementKind::FunctionDef(function) => {
+                self.write_decorators(&function.decorators, &function.bounds);
+                if function.is_abstract {
+                    self.write("abstract ");
+                }
+
+                if function.is_async {
+                    self.write("async ");
+                }
+
+                self.write("def ");
+                self.write(&function.name.name);
+                self.write_generics(&function.generics);
+                self.write("(");
+                self.write_params(&function.params);
+                self.write(")");
+
+                if let Some(return_type) = &function.return_type {
+                    self.write(" -> ");
+                    self.write_type(return_type);
+                }
+
+                if function.body.is_empty() {
+                    self.write("\n");
+                } else {
+                    self.write_block(&function.body);
+                }
+
+                return;
+            }
+            StatementKind::ClassDef(class) => {
+                self.write_decorators(&class.decorators, &class.bounds);
+                self.write(match class.kind {
+                    ClassKind::Class => "class ",
+                    ClassKind::Data => "data class ",
+                    ClassKind::Enum => "enum class ",
+                    ClassKind::Union => "union class ",
+                    ClassKind::Abstract => "abstract class ",
+                });
+                self.write(&class.name.name);
+                self.write_generics(&class.generics);
+
+                if class.kind == ClassKind::Data {
+                    self.write("(");
+                    self.write_list(&class.fields, Unparser::write_param);
+                    self.write(")");
+                }
+
+                self.write_bases(&class.bases);
+
+                if class.variants.is_empty() && class.body.is_empty() {
+                    self.write("\n");
+                    return;
+                }
+
+                self.write(":\n");
+                self.indent_level += 1;
+
+                for variant in class.variants.iter() {
+                    self.write_indent();
+                    self.write_variant(variant);
+                    self.write("\n");
+                }
+
+                // A name or a call on a name on its own line would be read as a variant.
+                let is_variant = |expr: &Expr| match &expr.kind {
+                    ExprKind::Identifier(_) => true,
+                    ExprKind::Call { func, .. } => matches!(func.kind, ExprKind::Identifier(_)),
+                    _ => false,
+                };
+
+                for statement in class.body.iter() {
+                    self.write_indent();
+                    match &statement.kind {
+                        StatementKind::Expr(expr)
+                            if class.kind == ClassKind::Enum && is_variant(expr) =>
+                        {
+                            self.write("(");
+                            self.write_value(expr);
+                            self.write(")\n");
+                        }
+                        _ => self.write_statement(statement),
+                    }
+                }
+
+                self.indent_level -= 1;
+                return;
+            }
+            StatementKind::InterfaceDef(interface) => {
+                self.write_decorators(&interface.decorators, &interface.bounds);
+                self.write("interface ");
+                self.write(&interface.name.name);
+                self.write_generics(&interface.generics);
+                self.write_bases(&interface.bases);
+                self.write_block(&interface.body);
+                return;
+            }
+            StatementKind::MacroDef(macro_def) => {
+                self.write("macro ");
+                self.write(&macro_def.name.name);
+                self.write("(");
+                self.write_list(&macro_def.params, |this, param| this.write(&param.name));
+                self.write(")");
+                self.write_block(&macro_def.body);
+                return;
+            }
+        }
+
+        self.write("\n");
+    }
+
+    fn write_guard(&mut self, guard: &Option<Expr>) {
+        if let Some(guard) = guard {
+            self.write(" if ");
+            self.write_expr(guard, Precedence::Lambda);
+        }
+    }
+
+    fn write_import_path(&mut self, path: &ImportPath) {
+        self.write(&".".repeat(path.level));
+
+        for (index, name) in path.names.iter().enumerate() {
+            if index > 0 {
+                self.write(".");
+            }
+
+            self.write(&name.name);
+        }
+    }
+
+    /// Writes each decorator and then the `@where` clause on their own lines, leaving the cursor
+    /// indented for the definition.
+    fn write_decorators(&mut self, decorators: &[Decorator], bounds: &[GenericBound]) {
+        for decorator in decorators {
+            self.write("@");
+
+            self.write_path(&decorator.path);
+
+            if let Some(arguments) = &decorator.arguments {
+                self.write("(");
+                self.write_list(arguments, Unparser::write_argument);
+                self.write(")");
+            }
+
+            self.write("\n");
+            self.write_indent();
+        }
+
+        if !bounds.is_empty() {
+            self.write("@where(");
+            self.write_list(bounds, |this, bound| {
+                this.write(&bound.name.name);
+                this.write(": ");
+                this.write_type(&bound.bound);
+            });
+            self.write(")\n");
+            self.write_indent();
+        }
+    }
+
+    fn write_bases(&mut self, bases: &[Identifier]) {
+        if !bases.is_empty() {
+            self.write("(");
+            self.write_list(bases, |this, base| this.write(&base.name));
+            self.write(")");
+        }
+    }
+
+    /// Writes `match subject:` up to the indent of its cases.
+    fn write_match_subject(&mut self, subject: &Expr) {
+        self.write("match ");
+        self.write_expr(subject, Precedence::Ternary);
+        self.write(":\n");
+    }
+
+    /// Writes `case pattern if guard` on its own line.
+    fn write_case_head(&mut self, pattern: &Pattern, guard: &Option<Expr>) {
+        self.write_indent();
+        self.write("case ");
+        self.write_pattern(pattern, PatternPrecedence::As);
+        self.write_guard(guard);
+    }
+
+    fn write_path(&mut self, path: &Path) {
+        for (index, name) in path.names.iter().enumerate() {
+            if index > 0 {
+                self.write(".");
+            }
+
+            self.write(&name.name);
+        }
+    }
+
+    fn write_variant(&mut self, variant: &Variant) {
+        self.write(&variant.name.name);
+
+        if let Some(fields) = &variant.fields {
+            self.write("(");
+            self.write_list(fields, |this, field| match field {
+                VariantField::Named { name, annotation } => {
+                    this.write(&name.name);
+                    this.write(": ");
+                    this.write_type(annotation);
+                }
+                VariantField::Positional(annotation) => this.write_type(annotation),
+            });
+            self.write(")");
+        }
+    }
+
+    fn write_generics(&mut self, generics: &[Identifier]) {
+        if !generics.is_empty() {
+            self.write("[");
+            self.write_list(generics, |this, generic| this.write(&generic.name));
+            self.write("]");
+        }
+    }
+
+    fn write_params(&mut self, params: &Params) {
+        // Each part is a prefix and a parameter, with no parameter for the `/` separator.
+        let mut parts: Vec<(&str, Option<&Param>)> = vec![];
+
+        parts.extend(params.positional_only.iter().map(|param| ("", Some(param))));
+
+        if !params.positional_only.is_empty() {
+            parts.push(("/", None));
+        }
+
+        parts.extend(params.params.iter().map(|param| ("", Some(param))));
+
+        if let Some(vararg) = &params.vararg {
+            parts.push(("*", Some(vararg)));
+        }
+
+        parts.extend(params.keyword_only.iter().map(|param| ("", Some(param))));
+
+        if let Some(kwarg) = &params.kwarg {
+            parts.push(("**", Some(kwarg)));
+        }
+
+        self.write_list(&parts, |this, (prefix, param)| {
+            this.write(prefix);
+
+            if let Some(param) = param {
+                this.write_param(param);
+            }
+        });
+    }
+
+    fn write_param(&mut self, param: &Param) {
+        if let Some(mode) = param.mode {
+            self.write(match mode {
+                PassingMode::Ref => "ref ",
+                PassingMode::Val => "val ",
+                PassingMode::Ptr => "ptr ",
+            });
+        }
+
+        if param.is_mut {
+            self.write("mut ");
+        }
+
+        self.write(&param.name.name);
+
+        if let Some(annotation) = &param.annotation {
+            self.write(": ");
+            self.write_type(annotation);
+        }
+
+        if let Some(default) = &param.default {
+            self.write(match param.annotation {
+                Some(_) => " = ",
+                None => "=",
+            });
+
+            self.write_expr(default, Precedence::Lambda);
+        }
+    }
+}
+
+/// Expression writing functions.
+impl Unparser {
+    /// Writes an expression, parenthesizing it if it binds looser than `min`.
+    fn write_expr(&mut self, expr: &Expr, min: Precedence) {
+        let parenthesize = precedence(expr) < min;
+        if parenthesize {
+            self.write("(");
+        }
+
+        match &expr.kind {
+            ExprKind::Identifier(identifier) => self.write(&identifier.name),
+            ExprKind::Integer(digits, kind) => {
+                self.write(match kind {
+                    IntegerKind::Bin => "0b",
+                    IntegerKind::Oct => "0o",
+                    IntegerKind::Dec => "",
+                    IntegerKind::Hex => "0x",
+                });
+
+                self.write(digits);
+            }
+            ExprKind::Float(string) => self.write(string),
+            ExprKind::Imag(string) => {
+                self.write(string);
+                self.write("im");
+            }
+            ExprKind::Strings(strings) => {
+                for (index, string) in strings.iter().enumerate() {
+                    if index > 0 {
+                        self.write(" ");
+                    }
+
+                    self.write_string(string);
+                }
+            }
+            ExprKind::Bool(value) => self.write(if *value { "true" } else { "false" }),
+            ExprKind::Tuple(elements) => {
+                // Tuples are always parenthesized.
+                self.write("(");
+                self.write_list(elements, Unparser::write_element);
+                if elements.len() == 1 {
+                    self.write(",");
+                }
+
+                self.write(")");
+            }
+            ExprKind::List(elements) => {
+                self.write("[");
+                self.write_list(elements, Unparser::write_element);
+                self.write("]");
+            }
+            ExprKind::Set(elements) => {
+                self.write("{");
+                self.write_list(elements, Unparser::write_element);
+                self.write("}");
+            }
+            ExprKind::Dict(entries) => {
+                self.write("{");
+                self.write_list(entries, Unparser::write_dict_entry);
+                self.write("}");
+            }
+            ExprKind::ListComp(comprehension) => {
+                self.write("[");
+                self.write_comprehension(comprehension);
+                self.write("]");
+            }
+            ExprKind::SetComp(comprehension) => {
+                self.write("{");
+                self.write_comprehension(comprehension);
+                self.write("}");
+            }
+            ExprKind::DictComp(comprehension) => {
+                self.write("{");
+                self.write_dict_entry(&comprehension.entry);
+                self.write_comprehension_fors(&comprehension.clauses);
+                self.write("}");
+            }
+            ExprKind::Generator(comprehension) => {
+                self.write("(");
+                self.write_comprehension(comprehension);
+                self.write(")");
+            }
+            ExprKind::Rest(value) => {
+                self.write("*");
+                self.write_expr(value, Precedence::Lambda);
+            }
+            ExprKind::DictRest(value) => {
+                self.write("**");
+                self.write_expr(value, Precedence::Lambda);
+            }
+            ExprKind::NamedExpr { name, value } => {
+                self.write(&name.name);
+                self.write(" := ");
+                self.write_expr(value, Precedence::Ternary);
+            }
+            ExprKind::UnaryOp { op, operand } => match op {
+                UnaryOp::Not => {
+                    self.write("not ");
+                    self.write_expr(operand, Precedence::Not);
+                }
+                UnaryOp::Sqrt => {
+                    self.write("√");
+                    self.write_expr(operand, Precedence::Await);
+                }
+                UnaryOp::Square => {
+                    self.write_expr(operand, Precedence::Sqrt);
+                    self.write("²");
+                }
+                UnaryOp::Plus | UnaryOp::Minus | UnaryOp::BitNot => {
+                    self.write(unary_op(*op));
+                    self.write_expr(operand, Precedence::Unary);
+                }
+                UnaryOp::Ref | UnaryOp::Val | UnaryOp::Ptr => {
+                    self.write(unary_op(*op));
+                    self.write(" ");
+                    self.write_expr(operand, Precedence::Unary);
+                }
+            },
+            ExprKind::BinaryOp { lhs, op, rhs } => {
+                // Binary operations are left-associative except for the power operator.
+                let (lhs_min, rhs_min) = match op {
+                    BinaryOp::Pow => (Precedence::Sqrt, Precedence::Unary),
+                    _ => {
+                        let precedence = precedence(expr);
+                        (precedence, next(precedence))
+                    }
+                };
+
+                self.write_expr(lhs, lhs_min);
+                self.write(&format!(" {} ", binary_op(*op)));
+                self.write_expr(rhs, rhs_min);
+            }
+            ExprKind::Comparison { lhs, comparisons } => {
+                self.write_expr(lhs, Precedence::BitOr);
+                for comparison in comparisons.iter() {
+                    self.write(&format!(" {} ", comparison_op(comparison.op)));
+                    self.write_expr(&comparison.rhs, Precedence::BitOr);
+                }
+            }
+            ExprKind::Ternary {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.write_expr(then, Precedence::Or);
+                self.write(" if ");
+                self.write_expr(condition, Precedence::Lambda);
+                self.write(" else ");
+                self.write_expr(otherwise, Precedence::Lambda);
+            }
+            ExprKind::Lambda(lambda) => {
+                self.write("lambda");
+
+                let params = &lambda.params;
+                let annotated = params
+                    .positional_only
+                    .iter()
+                    .chain(params.params.iter())
+                    .chain(params.vararg.iter())
+                    .chain(params.keyword_only.iter())
+                    .chain(params.kwarg.iter())
+                    .any(|param| param.annotation.is_some());
+
+                if annotated {
+                    self.write(" (");
+                    self.write_params(params);
+                    self.write(")");
+                } else if *params != Params::default() {
+                    self.write(" ");
+                    self.write_params(params);
+                }
+
+                match &lambda.body {
+                    LambdaBody::Expr(body) => {
+                        self.write(": ");
+                        self.write_expr(body, Precedence::Lambda);
+                    }
+                    LambdaBody::Block(statements) => {
+                        // Block lambdas are always parenthesized, so the block is closed by the
+                        // dedent to the parenthesis.
+                        self.write_block(statements);
+                        self.write_indent();
+                    }
+                }
+            }
+            ExprKind::Match(match_) => {
+                // Match expressions are always parenthesized, so the cases are closed by the
+                // dedent to the parenthesis.
+                self.write_match_subject(&match_.subject);
+                self.indent_level += 1;
+
+                for case in match_.cases.iter() {
+                    self.write_case_head(&case.pattern, &case.guard);
+                    self.write(": ");
+                    self.write_expr(&case.body, Precedence::Lambda);
+                    self.write("\n");
+                }
+
+                self.indent_level -= 1;
+                self.write_indent();
+            }
+            ExprKind::Call { func, arguments } => {
+                self.write_expr(func, Precedence::Atom);
+                self.write("(");
+                match arguments.as_slice() {
+                    // A generator that is the only argument shares the parentheses of the call.
+                    [Argument::Positional(Expr {
+                        kind: ExprKind::Generator(comprehension),
+                        ..
+                    })] => self.write_comprehension(comprehension),
+                    _ => self.write_list(arguments, Unparser::write_argument),
+                }
+                self.write(")");
+            }
+            ExprKind::Subscript { value, indices } => {
+                self.write_expr(value, Precedence::Atom);
+                self.write("[");
+                self.write_list(indices, Unparser::write_subscript_index);
+                self.write("]");
+            }
+            ExprKind::Attribute { value, attribute } => {
+                // An integer followed by a dot would be lexed as a float.
+                match value.kind {
+                    ExprKind::Integer(..) => {
+                        self.write("(");
+                        self.write_expr(value, Precedence::Atom);
+                        self.write(")");
+                    }
+                    _ => self.write_expr(value, Precedence::Atom),
+                }
+
+                self.write(".");
+                self.write(&attribute.name);
+            }
+            ExprKind::Propagate(value) => {
+                self.write_expr(value, Precedence::Atom);
+                self.write("?");
+            }
+            ExprKind::Await(value) => {
+                self.write("await ");
+                self.write_expr(value, Precedence::Atom);
+            }
+            ExprKind::Yield(value) => {
+                self.write("yield");
+                if let Some(value) = value {
+                    self.write(" ");
+                    self.write_element(value);
+                }
+            }
+            ExprKind::YieldFrom(value) => {
+                self.write("yield from ");
+                self.write_expr(value, Precedence::Lambda);
+            }
+        }
+
+        if parenthesize {
+            self.write(")");
+        }
+    }
+
+    /// Writes the value of a statement, where a `yield` needs no parentheses.
+    fn write_value(&mut self, value: &Expr) {
+        match value.kind {
+            ExprKind::Yield(_) | ExprKind::YieldFrom(_) => self.write_expr(value, Precedence::Rest),
+            _ => self.write_element(value),
+        }
+    }
+
+    fn write_dict_entry(&mut self, entry: &DictEntry) {
+        self.write_expr(&entry.key, Precedence::Ternary);
+        self.write(": ");
+        self.write_expr(&entry.value, Precedence::Lambda);
+    }
+
+    /// Writes the element and clauses of a comprehension without its brackets.
+    fn write_comprehension(&mut self, comprehension: &Comprehension) {
+        self.write_element(&comprehension.element);
+        self.write_comprehension_fors(&comprehension.clauses);
+    }
+
+    fn write_comprehension_fors(&mut self, clauses: &[ComprehensionFor]) {
+        for clause in clauses.iter() {
+            if clause.is_async {
+                self.write(" async");
+            }
+
+            self.write(" for ");
+            self.write_target(&clause.target);
+
+            // Like the iterable of a `for` statement, these cannot be ternaries.
+            self.write(" in ");
+            self.write_expr(&clause.iter, Precedence::Or);
+            for condition in clause.conditions.iter() {
+                self.write(" if ");
+                self.write_expr(condition, Precedence::Or);
+            }
+        }
+    }
+
+    /// Writes an assignment or `for` target, whose top-level sequence needs no brackets.
+    fn write_target(&mut self, target: &Pattern) {
+        match &target.kind {
+            PatternKind::Sequence(patterns) if patterns.len() > 1 => {
+                self.write_list(patterns, |this, pattern| {
+                    this.write_pattern(pattern, PatternPrecedence::As)
+                });
+            }
+            _ => self.write_pattern(target, PatternPrecedence::As),
+        }
+    }
+
+    /// Writes an element of a tuple, list or set.
+    fn write_element(&mut self, element: &Expr) {
+        match element.kind {
+            ExprKind::Rest(_) | ExprKind::DictRest(_) => self.write_expr(element, Precedence::Rest),
+            _ => self.write_expr(element, Precedence::Lambda),
+        }
+    }
+
+    fn write_argument(&mut self, argument: &Argument) {
+        match argument {
+            Argument::Positional(value) => self.write_element(value),
+            Argument::Keyword { name, value } => {
+                self.write(&name.name);
+                self.write("=");
+                self.write_expr(value, Precedence::Lambda);
+            }
+        }
+    }
+
+    fn write_subscript_index(&mut self, index: &SubscriptIndex) {
+        match index {
+            SubscriptIndex::Index(value) => self.write_expr(value, Precedence::Ternary),
+            SubscriptIndex::Slice { lower, upper, step } => {
+                if let Some(lower) = lower {
+                    self.write_expr(lower, Precedence::Ternary);
+                }
+
+                self.write(":");
+                if let Some(upper) = upper {
+                    self.write_expr(upper, Precedence::Ternary);
+                }
+
+                if let Some(step) = step {
+                    self.write(":");
+                    self.write_expr(step, Precedence::Ternary);
+                }
+            }
+        }
+    }
+
+    /// Writes a string with quotes that do not appear in its content.
+    fn write_string(&mut self, string: &StringLiteral) {
+        let (prefix, content) = match string {
+            StringLiteral::Str(content, kind) => (
+                match kind {
+                    StringKind::Str => "",
+                    StringKind::RawStr => "r",
+                    StringKind::Format => "f",
+                    StringKind::RawFormat => "rf",
+                },
+                content,
+            ),
+            StringLiteral::Bytes(content, kind) => (
+                match kind {
+                    BytesKind::Bytes => "b",
+                    BytesKind::RawBytes => "rb",
+                },
+                content,
+            ),
+        };
+
+        let quote = match content.contains('"') {
+            true => "'",
+            false => "\"",
+        };
+
+        // Only long strings can span lines.
+        let quotes = match content.contains(['\n', '\r']) {
+            true => quote.repeat(3),
+            false => quote.to_string(),
+        };
+
+        self.write(prefix);
+        self.write(&quotes);
+        self.write(content);
+        self.write(&quotes);
+    }
+}
+
+/// Pattern writing functions.
+impl Unparser {
+    fn write_pattern(&mut self, pattern: &Pattern, min: PatternPrecedence) {
+        let parenthesize = pattern_precedence(pattern) < min;
+        if parenthesize {
+            self.write("(");
+        }
+
+        match &pattern.kind {
+            PatternKind::Wildcard => self.write("_"),
+            PatternKind::Capture(name) => self.write(&name.name),
+            PatternKind::Literal(literal) => self.write_expr(literal, Precedence::Unary),
+            PatternKind::Value(path) => self.write_path(path),
+            PatternKind::Sequence(patterns) => {
+                self.write("[");
+                self.write_list(patterns, |this, pattern| {
+                    this.write_pattern(pattern, PatternPrecedence::As)
+                });
+                self.write("]");
+            }
+            PatternKind::Rest(name) => {
+                self.write("*");
+                self.write(name.as_ref().map_or("_", |name| &name.name));
+            }
+            PatternKind::Mapping { entries, rest } => {
+                self.write("{");
+                self.write_list(entries, |this, entry| {
+                    this.write_pattern(&entry.key, PatternPrecedence::Closed);
+                    this.write(": ");
+                    this.write_pattern(&entry.value, PatternPrecedence::As);
+                });
+
+                if let Some(rest) = rest {
+                    if !entries.is_empty() {
+                        self.write(", ");
+                    }
+
+                    self.write("**");
+                    self.write(&rest.name);
+                }
+
+                self.write("}");
+            }
+            PatternKind::Class {
+                path,
+                patterns,
+                keywords,
+            } => {
+                self.write_path(path);
+                self.write("(");
+                self.write_list(patterns, |this, pattern| {
+                    this.write_pattern(pattern, PatternPrecedence::As)
+                });
+
+                if !patterns.is_empty() && !keywords.is_empty() {
+                    self.write(", ");
+                }
+
+                self.write_list(keywords, |this, keyword| {
+                    this.write(&keyword.name.name);
+                    this.write("=");
+                    this.write_pattern(&keyword.pattern, PatternPrecedence::As);
+                });
+                self.write(")");
+            }
+            PatternKind::Or(patterns) => {
+                for (index, pattern) in patterns.iter().enumerate() {
+                    if index > 0 {
+                        self.write(" | ");
+                    }
+
+                    self.write_pattern(pattern, PatternPrecedence::Closed);
+                }
+            }
+            PatternKind::As { pattern, name } => {
+                self.write_pattern(pattern, PatternPrecedence::Or);
+                self.write(" as ");
+                self.write(&name.name);
+            }
+            PatternKind::Store(target) => self.write_expr(target, Precedence::Atom),
+        }
+
+        if parenthesize {
+            self.write(")");
+        }
+    }
+}
+
+/// Type annotation writing functions.
+impl Unparser {
+    fn write_type(&mut self, annotation: &TypeAnnotation) {
+        match &annotation.kind {
+            TypeAnnotationKind::Identifier(identifier) => self.write(&identifier.name),
+            TypeAnnotationKind::Path(path) => self.write_path(path),
+            TypeAnnotationKind::Generic { name, arguments } => {
+                self.write(&name.name);
+                self.write("[");
+                self.write_list(arguments, Unparser::write_type);
+                self.write("]");
+            }
+            TypeAnnotationKind::Function {
+                params,
+                return_type,
+            } => {
+                self.write("(");
+                self.write_list(params, Unparser::write_type);
+                self.write(") -> ");
+                self.write_type(return_type);
+            }
+            TypeAnnotationKind::List(types) => {
+                self.write("[");
+                self.write_list(types, Unparser::write_type);
+                self.write("]");
+            }
+            TypeAnnotationKind::Tuple(types) => {
+                self.write("(");
+                self.write_list(types, Unparser::write_type);
+                self.write(")");
+            }
+            TypeAnnotationKind::Union(types) => {
+                for (index, annotation) in types.iter().enumerate() {
+                    if index > 0 {
+                        self.write(" | ");
+                    }
+
+                    self.write_type(annotation);
+                }
+            }
+            TypeAnnotationKind::Intersection(types) => {
+                for (index, annotation) in types.iter().enumerate() {
+                    if index > 0 {
+                        self.write(" & ");
+                    }
+
+                    self.write_type(annotation);
+                }
+            }
+            TypeAnnotationKind::Optional(annotation) => {
+                self.write_type(annotation);
+                self.write("?");
+            }
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Returns how tightly an expression binds.
+fn precedence(expr: &Expr) -> Precedence {
+    match &expr.kind {
+        ExprKind::Rest(_) | ExprKind::DictRest(_) | ExprKind::NamedExpr { .. } => Precedence::Rest,
+        ExprKind::Lambda(lambda) => match lambda.body {
+            // Block lambdas are always parenthesized.
+            LambdaBody::Block(_) => Precedence::Rest,
+            LambdaBody::Expr(_) => Precedence::Lambda,
+        },
+        ExprKind::Match(_) | ExprKind::Yield(_) | ExprKind::YieldFrom(_) => Precedence::Rest,
+        ExprKind::Ternary { .. } => Precedence::Ternary,
+        ExprKind::Comparison { .. } => Precedence::Comparison,
+        ExprKind::UnaryOp { op, .. } => match op {
+            UnaryOp::Not => Precedence::Not,
+            UnaryOp::Plus
+            | UnaryOp::Minus
+            | UnaryOp::BitNot
+            | UnaryOp::Ref
+            | UnaryOp::Val
+            | UnaryOp::Ptr => Precedence::Unary,
+            UnaryOp::Square => Precedence::Power,
+            UnaryOp::Sqrt => Precedence::Sqrt,
+        },
+        ExprKind::Await(_) => Precedence::Await,
+        ExprKind::BinaryOp { op, .. } => match op {
+            BinaryOp::Or => Precedence::Or,
+            BinaryOp::And => Precedence::And,
+            BinaryOp::BitOr => Precedence::BitOr,
+            BinaryOp::BitXor => Precedence::BitXor,
+            BinaryOp::BitAnd => Precedence::BitAnd,
+            BinaryOp::ShiftL | BinaryOp::ShiftR => Precedence::Shift,
+            BinaryOp::Plus | BinaryOp::Minus => Precedence::Sum,
+            BinaryOp::Mul | BinaryOp::MatMul | BinaryOp::Div | BinaryOp::IntDiv | BinaryOp::Mod => {
+                Precedence::Mul
+            }
+            BinaryOp::Pow => Precedence::Power,
+        },
+        _ => Precedence::Atom,
+    }
+}
+
+/// Returns how tightly a pattern binds.
+fn pattern_precedence(pattern: &Pattern) -> PatternPrecedence {
+    match pattern.kind {
+        PatternKind::As { .. } => PatternPrecedence::As,
+        PatternKind::Or(_) => PatternPrecedence::Or,
+        _ => PatternPrecedence::Closed,
+    }
+}
+
+/// Returns the precedence that binds one step tighter.
+fn next(precedence: Precedence) -> Precedence {
+    use Precedence::*;
+    match precedence {
+        Rest => Lambda,
+        Lambda => Ternary,
+        Ternary => Or,
+        Or => And,
+        And => Not,
+        Not => Comparison,
+        Comparison => BitOr,
+        BitOr => BitXor,
+        BitXor => BitAnd,
+        BitAnd => Shift,
+        Shift => Sum,
+        Sum => Mul,
+        Mul => Unary,
+        Unary => Power,
+        Power => Sqrt,
+        Sqrt => Await,
+        Await | Atom => Atom,
+    }
+}
+
+fn unary_op(op: UnaryOp) -> &'static str {
+    match op {
+        UnaryOp::Plus => "+",
+        UnaryOp::Minus => "-",
+        UnaryOp::BitNot => "~",
+        UnaryOp::Not => "not",
+        UnaryOp::Sqrt => "√",
+        UnaryOp::Square => "²",
+        UnaryOp::Ref => "ref",
+        UnaryOp::Val => "val",
+        UnaryOp::Ptr => "ptr",
+    }
+}
+
+fn binary_op(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Or => "or",
+        BinaryOp::And => "and",
+        BinaryOp::BitOr => "|",
+        BinaryOp::BitXor => "^",
+        BinaryOp::BitAnd => "&",
+        BinaryOp::ShiftL => "<<",
+        BinaryOp::ShiftR => ">>",
+        BinaryOp::Plus => "+",
+        BinaryOp::Minus => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::MatMul => "@",
+        BinaryOp::Div => "/",
+        BinaryOp::IntDiv => "//",
+        BinaryOp::Mod => "%",
+        BinaryOp::Pow => "**",
+    }
+}
+
+fn comparison_op(op: ComparisonOp) -> &'static str {
+    match op {
+        ComparisonOp::Less => "<",
+        ComparisonOp::Greater => ">",
+        ComparisonOp::Eq => "==",
+        ComparisonOp::GreaterEq => ">=",
+        ComparisonOp::LessEq => "<=",
+        ComparisonOp::NotEq => "!=",
+        ComparisonOp::In => "in",
+        ComparisonOp::NotIn => "not in",
+        ComparisonOp::Is => "is",
+        ComparisonOp::IsNot => "is not",
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use raccoon_compiler::lexer::TokenKind;
+use raccoon_compiler::Lexer;
+
+use crate::grammar::{Grammar, Item};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// A mistake in the rules of a grammar that still parses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub kind: ProblemKind,
+    /// The rule the mistake is in.
+    pub rule: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemKind {
+    /// A reference to a rule that no grammar defines.
+    UndefinedRule(String),
+    /// A rule that cannot be reached from where a grammar starts.
+    UnusedRule,
+    /// A literal that the lexer does not read as one token.
+    NotAToken(String),
+    /// A word that the lexer reads as an identifier, which is not listed in `soft_keyword`.
+    NotAKeyword(String),
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ProblemKind::UndefinedRule(name) => {
+                write!(f, "`{}` refers to undefined rule `{name}`", self.rule)
+            }
+            ProblemKind::UnusedRule => write!(f, "`{}` is never used", self.rule),
+            ProblemKind::NotAToken(literal) => {
+                write!(f, "`{}` has '{literal}', which is not a token", self.rule)
+            }
+            ProblemKind::NotAKeyword(literal) => {
+                write!(f, "`{}` has '{literal}', which is not a keyword", self.rule)
+            }
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Finds undefined and unused rules, and literals of the rules above the tokens that the lexer
+/// would not read as written.
+///
+/// The first rule and the first token rule are where the grammar starts. `soft_keyword` lists the
+/// words that are only keywords where a rule expects them, so it needs no uses.
+pub fn check(grammar: &Grammar) -> Vec<Problem> {
+    let mut problems = vec![];
+    let soft_keywords = grammar
+        .rule("soft_keyword")
+        .map(|rule| literals(&rule.alternatives))
+        .unwrap_or_default();
+
+    for rule in grammar.rules.iter() {
+        let mut undefined = vec![];
+        for name in references(&rule.alternatives) {
+            if grammar.rule(name).is_none() && !undefined.contains(&name) {
+                undefined.push(name);
+            }
+        }
+
+        problems.extend(undefined.into_iter().map(|name| Problem {
+            kind: ProblemKind::UndefinedRule(name.into()),
+            rule: rule.name.clone(),
+        }));
+
+        // Token rules spell out the characters of tokens rather than whole tokens.
+        if rule.is_token {
+            continue;
+        }
+
+        for literal in literals(&rule.alternatives) {
+            let tokens = Lexer::tokenize(literal).collect::<Vec<_>>();
+            let kind = match tokens.as_slice() {
+                [Ok(token)] => match &token.kind {
+                    TokenKind::Identifier(_) if !soft_keywords.contains(&literal) => {
+                        ProblemKind::NotAKeyword(literal.into())
+                    }
+                    _ => continue,
+                },
+                _ => ProblemKind::NotAToken(literal.into()),
+            };
+
+            problems.push(Problem {
+                kind,
+                rule: rule.name.clone(),
+            });
+        }
+    }
+
+    let used = used_rules(grammar);
+    problems.extend(
+        grammar
+            .rules
+            .iter()
+            .filter(|rule| !used.contains(rule.name.as_str()) && rule.name != "soft_keyword")
+            .map(|rule| Problem {
+                kind: ProblemKind::UnusedRule,
+                rule: rule.name.clone(),
+            }),
+    );
+
+    problems
+}
+
+/// The rules that the start rules lead to.
+fn used_rules(grammar: &Grammar) -> HashSet<&str> {
+    let starts = [
+        grammar.rules.iter().find(|rule| !rule.is_token),
+        grammar.rules.iter().find(|rule| rule.is_token),
+    ];
+
+    let mut stack = starts
+        .into_iter()
+        .flatten()
+        .map(|rule| rule.name.as_str())
+        .collect::<Vec<_>>();
+    let mut used = HashSet::new();
+
+    while let Some(name) = stack.pop() {
+        if !used.insert(name) {
+            continue;
+        }
+
+        if let Some(rule) = grammar.rule(name) {
+            stack.extend(references(&rule.alternatives));
+        }
+    }
+
+    used
+}
+
+/// The names of the rules that items refer to, in order.
+fn references(items: &[Item]) -> Vec<&str> {
+    let mut names = vec![];
+    for item in items {
+        match item {
+            Item::Sequence(items) | Item::Choice(items) => names.extend(references(items)),
+            Item::Optional(item) | Item::Many(item) | Item::OneOrMore(item) => {
+                names.extend(references(std::slice::from_ref(item)))
+            }
+            Item::Rule(name) => names.push(name.as_str()),
+            Item::Literal(_) | Item::Class(_) | Item::Prose(_) => (),
+        }
+    }
+
+    names
+}
+
+/// The distinct literals of items, in order.
+fn literals(items: &[Item]) -> Vec<&str> {
+    let mut literals = vec![];
+    for item in items {
+        let inner = match item {
+            Item::Sequence(items) | Item::Choice(items) => self::literals(items),
+            Item::Optional(item) | Item::Many(item) | Item::OneOrMore(item) => {
+                self::literals(std::slice::from_ref(item))
+            }
+            Item::Literal(literal) => vec![literal.as_str()],
+            Item::Rule(_) | Item::Class(_) | Item::Prose(_) => vec![],
+        };
+
+        for literal in inner {
+            if !literals.contains(&literal) {
+                literals.push(literal);
+            }
+        }
+    }
+
+    literals
+}
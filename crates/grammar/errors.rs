@@ -0,0 +1,57 @@
+use std::fmt::Display;
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GrammarErrorKind {
+    /// A character that starts no item, like `,`.
+    UnexpectedCharacter,
+    /// A quoted literal that is not closed on its line.
+    UnterminatedLiteral,
+    /// A `[` character class that is not closed on its line.
+    UnterminatedClass,
+    /// A `<` prose description that is not closed on its line.
+    UnterminatedProse,
+    /// Something other than a rule name at the start of a line.
+    ExpectedRuleName,
+    /// A rule name that is not followed by `=`.
+    ExpectedEquals,
+    /// An empty alternative or group.
+    ExpectedItem,
+    /// A `(` group that is not closed before the end of its rule.
+    ExpectedClosingParen,
+    /// A `)` without a group to close.
+    UnexpectedClosingParen,
+    /// A rule with the name of an earlier rule.
+    DuplicateRule,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrammarError {
+    pub(crate) kind: GrammarErrorKind,
+    /// The line in the grammar, starting at 1.
+    pub(crate) line: usize,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl GrammarError {
+    pub fn new(kind: GrammarErrorKind, line: usize) -> Self {
+        Self { kind, line }
+    }
+}
+
+impl std::error::Error for GrammarError {}
+
+impl Display for GrammarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrammarError")
+            .field("kind", &self.kind)
+            .field("line", &self.line)
+            .finish()
+    }
+}
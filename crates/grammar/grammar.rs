@@ -0,0 +1,362 @@
+use anyhow::{bail, Result};
+
+use crate::errors::{GrammarError, GrammarErrorKind::*};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// The rules of a `.grammar` file, which are written like
+///
+/// ```text
+/// rule =
+///     | item* ('|' item)? # A comment
+/// ```
+///
+/// The first rule is where the language starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar {
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub name: String,
+    pub alternatives: Vec<Item>,
+    /// The line of the name, starting at 1.
+    pub line: usize,
+    /// Whether the rule comes from a grammar of tokens, whose items are not separated by spaces.
+    pub is_token: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Sequence(Vec<Item>),
+    /// `a | b`
+    Choice(Vec<Item>),
+    /// `item?`
+    Optional(Box<Item>),
+    /// `item*`
+    Many(Box<Item>),
+    /// `item+`
+    OneOrMore(Box<Item>),
+    /// `'text'` or `"text"`
+    Literal(String),
+    /// `[A-Za-z_]`, as the ranges of characters it matches.
+    Class(Vec<(char, char)>),
+    /// `<any character except newline>`, which describes text in prose.
+    Prose(String),
+    Rule(String),
+}
+
+/// A token of a `.grammar` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Name(String),
+    Equals,
+    Bar,
+    LParen,
+    RParen,
+    Question,
+    Star,
+    Plus,
+    Literal(String),
+    Class(Vec<(char, char)>),
+    Prose(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    /// Whether the token starts its line, which only the name of a rule does.
+    is_line_start: bool,
+}
+
+/// Reads rules from the tokens of a `.grammar` file.
+struct GrammarParser {
+    tokens: Vec<Token>,
+    cursor: usize,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Grammar {
+    /// Parses the rules of a `.grammar` file.
+    pub fn parse(code: &str) -> Result<Grammar> {
+        let mut parser = GrammarParser {
+            tokens: tokenize(code)?,
+            cursor: 0,
+        };
+
+        let mut grammar = Grammar { rules: vec![] };
+        while parser.peek().is_some() {
+            let rule = parser.parse_rule()?;
+            grammar.push(rule)?;
+        }
+
+        Ok(grammar)
+    }
+
+    /// Adds the rules of a grammar of tokens, which the rules of this one can refer to.
+    pub fn with_tokens(mut self, tokens: Grammar) -> Result<Grammar> {
+        for rule in tokens.rules {
+            self.push(Rule {
+                is_token: true,
+                ..rule
+            })?;
+        }
+
+        Ok(self)
+    }
+
+    pub fn rule(&self, name: &str) -> Option<&Rule> {
+        self.rules.iter().find(|rule| rule.name == name)
+    }
+
+    fn push(&mut self, rule: Rule) -> Result<()> {
+        if self.rule(&rule.name).is_some() {
+            bail!(GrammarError::new(DuplicateRule, rule.line));
+        }
+
+        self.rules.push(rule);
+        Ok(())
+    }
+}
+
+impl Rule {
+    /// Whether the rule only refers to itself, like `indent = | indent`, which stands for a token
+    /// that the lexer makes without a rule for it.
+    pub fn is_placeholder(&self) -> bool {
+        matches!(self.alternatives.as_slice(), [Item::Rule(name)] if *name == self.name)
+    }
+}
+
+impl GrammarParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.cursor)
+    }
+
+    /// The line of the next token, or of the last one at the end of the grammar.
+    fn line(&self) -> usize {
+        self.peek()
+            .or_else(|| self.tokens.last())
+            .map_or(1, |token| token.line)
+    }
+
+    /// Whether the next token ends the alternatives of the current rule.
+    fn is_rule_end(&self) -> bool {
+        self.peek().is_none_or(|token| token.is_line_start)
+    }
+
+    fn eat_if(&mut self, kind: &TokenKind) -> bool {
+        match self.peek() {
+            Some(token) if token.kind == *kind && !token.is_line_start => {
+                self.cursor += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Parses `name = | alternative | ...`.
+    fn parse_rule(&mut self) -> Result<Rule> {
+        let line = self.line();
+        let name = match self.peek() {
+            Some(Token {
+                kind: TokenKind::Name(name),
+                is_line_start: true,
+                ..
+            }) => name.clone(),
+            _ => bail!(GrammarError::new(ExpectedRuleName, line)),
+        };
+
+        self.cursor += 1;
+        if !self.eat_if(&TokenKind::Equals) {
+            bail!(GrammarError::new(ExpectedEquals, line));
+        }
+
+        // The bar before the first alternative is optional.
+        self.eat_if(&TokenKind::Bar);
+
+        let mut alternatives = vec![self.parse_sequence()?];
+        while self.eat_if(&TokenKind::Bar) {
+            alternatives.push(self.parse_sequence()?);
+        }
+
+        match self.is_rule_end() {
+            true => Ok(Rule {
+                name,
+                alternatives,
+                line,
+                is_token: false,
+            }),
+            false => bail!(GrammarError::new(UnexpectedClosingParen, self.line())),
+        }
+    }
+
+    /// Parses items up to the next `|` or `)`, as a single item if there is only one.
+    fn parse_sequence(&mut self) -> Result<Item> {
+        let mut items = vec![];
+        while !self.is_rule_end() {
+            let kind = self.peek().unwrap().kind.clone();
+            if matches!(kind, TokenKind::Bar | TokenKind::RParen) {
+                break;
+            }
+
+            self.cursor += 1;
+            let item = match kind {
+                TokenKind::Bar | TokenKind::RParen => unreachable!(),
+                TokenKind::LParen => {
+                    let mut alternatives = vec![self.parse_sequence()?];
+                    while self.eat_if(&TokenKind::Bar) {
+                        alternatives.push(self.parse_sequence()?);
+                    }
+
+                    if !self.eat_if(&TokenKind::RParen) {
+                        bail!(GrammarError::new(ExpectedClosingParen, self.line()));
+                    }
+
+                    match alternatives.len() {
+                        1 => alternatives.pop().unwrap(),
+                        _ => Item::Choice(alternatives),
+                    }
+                }
+                TokenKind::Name(name) => Item::Rule(name),
+                TokenKind::Literal(text) => Item::Literal(text),
+                TokenKind::Class(ranges) => Item::Class(ranges),
+                TokenKind::Prose(text) => Item::Prose(text),
+                TokenKind::Equals | TokenKind::Question | TokenKind::Star | TokenKind::Plus => {
+                    bail!(GrammarError::new(ExpectedItem, self.line()))
+                }
+            };
+
+            items.push(self.parse_postfix(item));
+        }
+
+        match items.len() {
+            0 => bail!(GrammarError::new(ExpectedItem, self.line())),
+            1 => Ok(items.pop().unwrap()),
+            _ => Ok(Item::Sequence(items)),
+        }
+    }
+
+    /// Parses the `?`, `*` and `+` after an item.
+    fn parse_postfix(&mut self, mut item: Item) -> Item {
+        loop {
+            item = if self.eat_if(&TokenKind::Question) {
+                Item::Optional(Box::new(item))
+            } else if self.eat_if(&TokenKind::Star) {
+                Item::Many(Box::new(item))
+            } else if self.eat_if(&TokenKind::Plus) {
+                Item::OneOrMore(Box::new(item))
+            } else {
+                return item;
+            };
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Splits a `.grammar` file into tokens, skipping `#` and `//` comments.
+fn tokenize(code: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+
+    for (index, text) in code.lines().enumerate() {
+        let line = index + 1;
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut cursor = 0;
+
+        while let Some(&char) = chars.get(cursor) {
+            let start = cursor;
+            cursor += 1;
+
+            let error = |kind| GrammarError::new(kind, line);
+            let kind = match char {
+                ' ' | '\t' => continue,
+                '#' => break,
+                '/' if chars.get(cursor) == Some(&'/') => break,
+                '=' => TokenKind::Equals,
+                '|' => TokenKind::Bar,
+                '(' => TokenKind::LParen,
+                ')' => TokenKind::RParen,
+                '?' => TokenKind::Question,
+                '*' => TokenKind::Star,
+                '+' => TokenKind::Plus,
+                '\'' | '"' => TokenKind::Literal(unescape(
+                    &take_until(&chars, &mut cursor, char)
+                        .ok_or_else(|| error(UnterminatedLiteral))?,
+                )),
+                '<' => TokenKind::Prose(
+                    take_until(&chars, &mut cursor, '>').ok_or_else(|| error(UnterminatedProse))?,
+                ),
+                '[' => TokenKind::Class(class_ranges(
+                    &take_until(&chars, &mut cursor, ']')
+                        .ok_or_else(|| error(UnterminatedClass))?,
+                )),
+                'a'..='z' | 'A'..='Z' | '_' => {
+                    while matches!(
+                        chars.get(cursor),
+                        Some('a'..='z' | 'A'..='Z' | '0'..='9' | '_')
+                    ) {
+                        cursor += 1;
+                    }
+
+                    TokenKind::Name(chars[start..cursor].iter().collect())
+                }
+                _ => bail!(error(UnexpectedCharacter)),
+            };
+
+            tokens.push(Token {
+                kind,
+                line,
+                is_line_start: start == 0,
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Takes the characters up to a closing one, which it eats.
+fn take_until(chars: &[char], cursor: &mut usize, closing: char) -> Option<String> {
+    let length = chars[*cursor..].iter().position(|char| *char == closing)?;
+    let text = chars[*cursor..*cursor + length].iter().collect();
+    *cursor += length + 1;
+    Some(text)
+}
+
+/// Replaces the `\r`, `\n` and `\t` in a literal with the characters they stand for. Any other
+/// backslash stands for itself, like the one in `"\"`.
+fn unescape(text: &str) -> String {
+    text.replace("\\r", "\r")
+        .replace("\\n", "\n")
+        .replace("\\t", "\t")
+}
+
+/// The ranges of a character class like `A-Za-z_`.
+fn class_ranges(text: &str) -> Vec<(char, char)> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut ranges = vec![];
+    let mut index = 0;
+
+    while index < chars.len() {
+        match chars.get(index + 1..index + 3) {
+            Some(['-', end]) => {
+                ranges.push((chars[index], *end));
+                index += 3;
+            }
+            _ => {
+                ranges.push((chars[index], chars[index]));
+                index += 1;
+            }
+        }
+    }
+
+    ranges
+}
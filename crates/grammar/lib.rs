@@ -0,0 +1,12 @@
+mod check;
+mod errors;
+mod grammar;
+mod sampler;
+
+pub use check::*;
+pub use errors::*;
+pub use grammar::*;
+pub use sampler::*;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use proptest::strategy::{Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+use raccoon_grammar::{check, Grammar, Sampler};
+
+#[derive(Parser, Debug)]
+#[clap(about, version, author)]
+struct Args {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Reports undefined and unused rules, and literals that the lexer would not read as written.
+    Check {
+        #[clap(flatten)]
+        grammar: GrammarArgs,
+    },
+    /// Prints code sampled from a rule.
+    Sample {
+        #[clap(flatten)]
+        grammar: GrammarArgs,
+
+        /// The rule to sample, which is the first one by default.
+        #[clap(long)]
+        rule: Option<String>,
+
+        /// A rule or literal to leave out of the samples.
+        #[clap(long = "without", value_name = "NAME")]
+        excluded: Vec<String>,
+
+        #[clap(long, default_value = "1")]
+        count: usize,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct GrammarArgs {
+    /// The `.grammar` file to read, which can be left out to only read the tokens.
+    #[clap(parse(from_os_str), required_unless_present = "tokens")]
+    file: Option<PathBuf>,
+
+    /// The `.grammar` file of the tokens that the rules refer to.
+    #[clap(long, value_name = "FILE", parse(from_os_str))]
+    tokens: Option<PathBuf>,
+}
+
+fn main() {
+    if let Err(err) = run(Args::parse()) {
+        eprintln!("raccoon-grammar: {err:#}");
+        process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    match args.command {
+        Command::Check { grammar: args } => {
+            let grammar = load(&args)?;
+            let problems = check(&grammar);
+
+            for problem in problems.iter() {
+                let rule = grammar.rule(&problem.rule).unwrap();
+                let path = match rule.is_token {
+                    true => args.tokens.as_ref().unwrap(),
+                    false => args.file.as_ref().unwrap(),
+                };
+
+                println!("{}:{}: {problem}", path.display(), rule.line);
+            }
+
+            if !problems.is_empty() {
+                process::exit(1);
+            }
+        }
+        Command::Sample {
+            grammar: args,
+            rule,
+            excluded,
+            count,
+        } => {
+            let grammar = load(&args)?;
+            let rule = rule.unwrap_or_else(|| grammar.rules[0].name.clone());
+            let excluded = excluded.iter().map(String::as_str).collect::<Vec<_>>();
+            let strategy = Sampler::new(&grammar)
+                .without(&excluded)
+                .strategy(&rule)
+                .with_context(|| format!("cannot sample `{rule}`"))?;
+
+            let mut runner = TestRunner::default();
+            for _ in 0..count {
+                let code = strategy.new_tree(&mut runner).unwrap().current();
+                println!("{code}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn load(args: &GrammarArgs) -> Result<Grammar> {
+    let parse = |path: &Path| {
+        let code = fs::read_to_string(path)?;
+        Grammar::parse(&code).with_context(|| path.display().to_string())
+    };
+
+    let grammar = match &args.file {
+        Some(file) => parse(file)?,
+        None => Grammar { rules: vec![] },
+    };
+
+    match &args.tokens {
+        Some(tokens) => grammar
+            .with_tokens(parse(tokens)?)
+            .with_context(|| tokens.display().to_string()),
+        None => Ok(grammar),
+    }
+}
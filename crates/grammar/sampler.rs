@@ -0,0 +1,313 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use proptest::prelude::*;
+use proptest::strategy::Union;
+use raccoon_compiler::lexer::Keyword;
+
+use crate::grammar::{Grammar, Item};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// Builds proptest strategies that sample code from the rules of a grammar.
+///
+/// The items of a token rule are joined without spaces, and the tokens of the other rules with a
+/// space. `newline`, `indent` and `dedent` lay out lines instead, and `identifier`s are never
+/// keywords. A `<prose>` item only samples letters, digits and spaces.
+#[derive(Debug)]
+pub struct Sampler<'a> {
+    grammar: &'a Grammar,
+    /// The rules and literals to leave out, like the ones only some contexts allow.
+    excluded: HashSet<String>,
+    /// How many rules deep a sample can go.
+    depth: usize,
+    /// The fewest rules deep that each rule can be derived.
+    heights: HashMap<&'a str, usize>,
+    /// The strategies for each rule at each depth left.
+    strategies: RefCell<HashMap<(&'a str, usize), Option<Pieces>>>,
+}
+
+/// A strategy for the parts of sampled code.
+type Pieces = BoxedStrategy<Vec<Piece>>;
+
+/// A part of sampled code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Newline,
+    Indent,
+    Dedent,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl<'a> Sampler<'a> {
+    pub fn new(grammar: &'a Grammar) -> Self {
+        let mut sampler = Self {
+            grammar,
+            excluded: HashSet::new(),
+            depth: 40,
+            heights: HashMap::new(),
+            strategies: RefCell::new(HashMap::new()),
+        };
+
+        sampler.heights = sampler.heights();
+        sampler
+    }
+
+    /// Leaves out the rules and literals with the given names, along with every alternative that
+    /// needs them.
+    pub fn without(mut self, names: &[&str]) -> Self {
+        self.excluded
+            .extend(names.iter().map(|name| name.to_string()));
+        self.heights = self.heights();
+        self.strategies.borrow_mut().clear();
+        self
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self.strategies.borrow_mut().clear();
+        self
+    }
+
+    /// Samples code from a rule, or gives `None` if the rule cannot be derived within the depth.
+    pub fn strategy(&self, rule: &str) -> Option<BoxedStrategy<String>> {
+        let name = self.grammar.rule(rule)?.name.as_str();
+        let strategy = self.rule(name, self.depth)?;
+
+        Some(match self.grammar.rule(rule)?.is_token {
+            true => strategy.prop_map(|pieces| join(&pieces)).boxed(),
+            false => strategy.prop_map(|pieces| render(&pieces)).boxed(),
+        })
+    }
+
+    fn rule(&self, name: &'a str, depth: usize) -> Option<Pieces> {
+        if let Some(strategy) = self.strategies.borrow().get(&(name, depth)) {
+            return strategy.clone();
+        }
+
+        let strategy = self.build_rule(name, depth);
+        self.strategies
+            .borrow_mut()
+            .insert((name, depth), strategy.clone());
+        strategy
+    }
+
+    fn build_rule(&self, name: &'a str, depth: usize) -> Option<Pieces> {
+        if self.heights.get(name).is_none_or(|height| *height > depth) {
+            return None;
+        }
+
+        let rule = self.grammar.rule(name)?;
+        match name {
+            "indent" if rule.is_placeholder() => return Some(Just(vec![Piece::Indent]).boxed()),
+            "dedent" if rule.is_placeholder() => return Some(Just(vec![Piece::Dedent]).boxed()),
+            _ => (),
+        }
+
+        let alternatives = rule
+            .alternatives
+            .iter()
+            .filter_map(|item| self.item(item, depth - 1, rule.is_token))
+            .collect::<Vec<_>>();
+
+        let strategy = match alternatives.len() {
+            0 => return None,
+            _ => Union::new(alternatives).boxed(),
+        };
+
+        Some(match name {
+            "identifier" => strategy
+                .prop_filter("keywords are not identifiers", |pieces| {
+                    !Keyword::is_valid(&join(pieces))
+                })
+                .boxed(),
+            _ => strategy,
+        })
+    }
+
+    fn item(&self, item: &'a Item, depth: usize, is_token: bool) -> Option<Pieces> {
+        let empty = || Just(vec![]).boxed();
+
+        Some(match item {
+            Item::Sequence(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.item(item, depth, is_token))
+                    .collect::<Option<Vec<_>>>()?;
+
+                items.prop_map(|pieces| pieces.concat()).boxed()
+            }
+            Item::Choice(items) => {
+                let items = items
+                    .iter()
+                    .filter_map(|item| self.item(item, depth, is_token))
+                    .collect::<Vec<_>>();
+
+                match items.len() {
+                    0 => return None,
+                    _ => Union::new(items).boxed(),
+                }
+            }
+            Item::Optional(item) => match self.item(item, depth, is_token) {
+                Some(item) => Union::new_weighted(vec![(2, empty()), (1, item)]).boxed(),
+                None => empty(),
+            },
+            Item::Many(item) => match self.item(item, depth, is_token) {
+                Some(item) => repeat(item, 0).boxed(),
+                None => empty(),
+            },
+            Item::OneOrMore(item) => repeat(self.item(item, depth, is_token)?, 1).boxed(),
+            Item::Literal(text) if self.excluded.contains(text) => return None,
+            Item::Literal(text) => Just(vec![Piece::Text(text.clone())]).boxed(),
+            Item::Class(ranges) => {
+                let chars = ranges
+                    .iter()
+                    .flat_map(|(start, end)| *start..=*end)
+                    .map(|char| vec![Piece::Text(char.into())])
+                    .collect::<Vec<_>>();
+
+                proptest::sample::select(chars).boxed()
+            }
+            Item::Prose(_) => {
+                let chars = ('a'..='z').chain('0'..='9').chain([' ']);
+                let chars = chars.map(|char| vec![Piece::Text(char.into())]);
+
+                proptest::sample::select(chars.collect::<Vec<_>>()).boxed()
+            }
+            Item::Rule(name) => {
+                let rule = self.grammar.rule(name)?;
+                if name == "newline" && rule.is_token && !is_token {
+                    return Some(Just(vec![Piece::Newline]).boxed());
+                }
+
+                let strategy = self.rule(rule.name.as_str(), depth)?;
+                match rule.is_token && !is_token {
+                    // A token is sampled as a whole from the rules above the tokens.
+                    true => strategy
+                        .prop_map(|pieces| vec![Piece::Text(join(&pieces))])
+                        .boxed(),
+                    false => strategy,
+                }
+            }
+        })
+    }
+
+    /// Finds the fewest rules deep that each rule can be derived, without the excluded ones.
+    fn heights(&self) -> HashMap<&'a str, usize> {
+        let mut heights = HashMap::new();
+
+        loop {
+            let mut is_changed = false;
+            for rule in self.grammar.rules.iter() {
+                if self.excluded.contains(&rule.name) {
+                    continue;
+                }
+
+                let height = match rule.is_placeholder() {
+                    true => Some(0),
+                    false => rule
+                        .alternatives
+                        .iter()
+                        .filter_map(|item| self.height(item, &heights))
+                        .min(),
+                };
+
+                if let Some(height) = height.map(|height| height + 1) {
+                    if heights
+                        .get(rule.name.as_str())
+                        .is_none_or(|old| height < *old)
+                    {
+                        heights.insert(rule.name.as_str(), height);
+                        is_changed = true;
+                    }
+                }
+            }
+
+            if !is_changed {
+                return heights;
+            }
+        }
+    }
+
+    fn height(&self, item: &Item, heights: &HashMap<&str, usize>) -> Option<usize> {
+        match item {
+            Item::Sequence(items) => items
+                .iter()
+                .map(|item| self.height(item, heights))
+                .collect::<Option<Vec<_>>>()
+                .map(|heights| heights.into_iter().max().unwrap_or(0)),
+            Item::Choice(items) => items
+                .iter()
+                .filter_map(|item| self.height(item, heights))
+                .min(),
+            Item::Optional(_) | Item::Many(_) => Some(0),
+            Item::OneOrMore(item) => self.height(item, heights),
+            Item::Literal(text) if self.excluded.contains(text) => None,
+            Item::Literal(_) | Item::Class(_) | Item::Prose(_) => Some(0),
+            Item::Rule(name) => heights.get(name.as_str()).copied(),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+/// Repeats an item at least `min` times, mostly as few times as it can so that samples stay small.
+fn repeat(item: Pieces, min: usize) -> impl Strategy<Value = Vec<Piece>> {
+    let counts = Union::new_weighted(vec![(6, Just(min)), (2, Just(min + 1)), (1, Just(min + 2))]);
+
+    counts
+        .prop_flat_map(move |count| vec![item.clone(); count])
+        .prop_map(|pieces| pieces.concat())
+}
+
+/// Joins the pieces of a token.
+fn join(pieces: &[Piece]) -> String {
+    pieces
+        .iter()
+        .filter_map(|piece| match piece {
+            Piece::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Lays out the pieces as lines of code indented by four spaces a level.
+fn render(pieces: &[Piece]) -> String {
+    let mut code = String::new();
+    let mut level = 0;
+    let mut is_line_start = true;
+
+    for piece in pieces {
+        if let Piece::Text(text) = piece {
+            match is_line_start {
+                true => code.push_str(&"    ".repeat(level)),
+                false => code.push(' '),
+            }
+
+            code.push_str(text);
+            is_line_start = false;
+            continue;
+        }
+
+        if !is_line_start {
+            code.push('\n');
+            is_line_start = true;
+        }
+
+        match piece {
+            Piece::Indent => level += 1,
+            Piece::Dedent => level = level.saturating_sub(1),
+            _ => (),
+        }
+    }
+
+    code
+}
@@ -0,0 +1,243 @@
+use proptest::strategy::{Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use crate::{
+    check, Grammar, GrammarError, GrammarErrorKind::*, Item, Problem, ProblemKind, Sampler,
+};
+
+use utils::*;
+
+#[test]
+fn can_parse_rules() {
+    let grammar = Grammar::parse(
+        r#"
+# The first rule
+list = # Lists of names
+    | '[' (name (',' name)*)? ']'
+    | "<>"
+
+name =
+    | [a-z_]+ <any digit>?
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        grammar
+            .rules
+            .iter()
+            .map(|rule| rule.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["list", "name"]
+    );
+
+    assert_eq!(grammar.rules[0].line, 3);
+    assert_eq!(
+        grammar.rules[0].alternatives,
+        vec![
+            Item::Sequence(vec![
+                literal("["),
+                Item::Optional(Box::new(Item::Sequence(vec![
+                    rule("name"),
+                    Item::Many(Box::new(Item::Sequence(vec![literal(","), rule("name")]))),
+                ]))),
+                literal("]"),
+            ]),
+            literal("<>"),
+        ]
+    );
+
+    assert_eq!(
+        grammar.rules[1].alternatives,
+        vec![Item::Sequence(vec![
+            Item::OneOrMore(Box::new(Item::Class(vec![('a', 'z'), ('_', '_')]))),
+            Item::Optional(Box::new(Item::Prose("any digit".into()))),
+        ])]
+    );
+}
+
+#[test]
+fn can_parse_escapes_in_literals() {
+    let grammar = Grammar::parse(
+        r#"
+newline = "\r" "\n"? | "\"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        grammar.rules[0].alternatives,
+        vec![
+            Item::Sequence(vec![literal("\r"), Item::Optional(Box::new(literal("\n")))]),
+            literal("\\"),
+        ]
+    );
+}
+
+#[test]
+fn cannot_parse_invalid_rules() {
+    assert_eq!(
+        parse_error("a = 'b"),
+        GrammarError::new(UnterminatedLiteral, 1)
+    );
+    assert_eq!(
+        parse_error("a = [b"),
+        GrammarError::new(UnterminatedClass, 1)
+    );
+    assert_eq!(
+        parse_error("a = <b"),
+        GrammarError::new(UnterminatedProse, 1)
+    );
+    assert_eq!(
+        parse_error("a = b, c"),
+        GrammarError::new(UnexpectedCharacter, 1)
+    );
+    assert_eq!(
+        parse_error("'a' = b"),
+        GrammarError::new(ExpectedRuleName, 1)
+    );
+    assert_eq!(parse_error("a b"), GrammarError::new(ExpectedEquals, 1));
+    assert_eq!(parse_error("a = b |"), GrammarError::new(ExpectedItem, 1));
+    assert_eq!(
+        parse_error("a = (b\nc = d"),
+        GrammarError::new(ExpectedClosingParen, 2)
+    );
+    assert_eq!(
+        parse_error("a = b)"),
+        GrammarError::new(UnexpectedClosingParen, 1)
+    );
+    assert_eq!(
+        parse_error("a = b\n\na = c"),
+        GrammarError::new(DuplicateRule, 3)
+    );
+}
+
+#[test]
+fn can_check_rules() {
+    let grammar = Grammar::parse(
+        r#"
+program = statement*
+
+soft_keyword = 'data'
+
+statement =
+    | 'pass'
+    | 'data' 'class' identifier
+    | 'thing' undefined
+    | '$'
+
+unused = 'pass'
+"#,
+    )
+    .unwrap()
+    .with_tokens(Grammar::parse("identifier = [a-z]+").unwrap())
+    .unwrap();
+
+    assert_eq!(
+        check(&grammar),
+        vec![
+            problem(ProblemKind::UndefinedRule("undefined".into()), "statement"),
+            problem(ProblemKind::NotAKeyword("thing".into()), "statement"),
+            problem(ProblemKind::NotAToken("$".into()), "statement"),
+            problem(ProblemKind::UnusedRule, "unused"),
+        ]
+    );
+}
+
+#[test]
+fn can_check_raccoon_grammars() {
+    let grammar = Grammar::parse(include_str!("../compiler/parser/parser.grammar"))
+        .unwrap()
+        .with_tokens(Grammar::parse(include_str!("../compiler/lexer/lexer.grammar")).unwrap())
+        .unwrap();
+
+    assert_eq!(check(&grammar), vec![]);
+}
+
+#[test]
+fn can_sample_rules() {
+    let grammar = Grammar::parse(
+        r#"
+program = 'if' condition ':' newline indent statement+ dedent
+
+condition = 'true' | 'false'
+
+statement = 'pass' newline | 'while' condition ':' newline indent statement+ dedent
+
+indent = indent
+
+dedent = dedent
+"#,
+    )
+    .unwrap()
+    .with_tokens(Grammar::parse("newline = '\\n'").unwrap())
+    .unwrap();
+
+    let samples = sample(&Sampler::new(&grammar).without(&["while"]), "program", 16);
+
+    assert!(samples.iter().all(|sample| {
+        sample.starts_with("if true :\n    pass\n") || sample.starts_with("if false :\n    pass\n")
+    }));
+    assert!(samples
+        .iter()
+        .all(|sample| sample.lines().skip(1).all(|line| line == "    pass")));
+}
+
+#[test]
+fn can_sample_tokens() {
+    let grammar = Grammar { rules: vec![] }
+        .with_tokens(Grammar::parse("integer = [1-9] [0-9]*").unwrap())
+        .unwrap();
+
+    let samples = sample(&Sampler::new(&grammar), "integer", 16);
+
+    assert!(samples.iter().all(|sample| sample.parse::<u64>().is_ok()));
+}
+
+#[test]
+fn cannot_sample_rules_without_derivations() {
+    let grammar = Grammar::parse("a = 'a' b\n\nb = 'b' | a").unwrap();
+
+    assert!(Sampler::new(&grammar).strategy("a").is_some());
+    assert!(Sampler::new(&grammar)
+        .without(&["b"])
+        .strategy("a")
+        .is_none());
+    assert!(Sampler::new(&grammar).with_depth(1).strategy("a").is_none());
+    assert!(Sampler::new(&grammar).strategy("c").is_none());
+}
+
+mod utils {
+    use super::*;
+
+    pub(super) fn literal(text: &str) -> Item {
+        Item::Literal(text.into())
+    }
+
+    pub(super) fn rule(name: &str) -> Item {
+        Item::Rule(name.into())
+    }
+
+    pub(super) fn problem(kind: ProblemKind, rule: &str) -> Problem {
+        Problem {
+            kind,
+            rule: rule.into(),
+        }
+    }
+
+    pub(super) fn parse_error(code: &str) -> GrammarError {
+        Grammar::parse(code)
+            .unwrap_err()
+            .downcast::<GrammarError>()
+            .unwrap()
+    }
+
+    pub(super) fn sample(sampler: &Sampler, rule: &str, count: usize) -> Vec<String> {
+        let strategy = sampler.strategy(rule).unwrap();
+        let mut runner = TestRunner::deterministic();
+
+        (0..count)
+            .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+            .collect()
+    }
+}
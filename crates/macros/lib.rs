@@ -0,0 +1,33 @@
+mod visitor;
+
+use proc_macro::TokenStream;
+use visitor::Mode;
+
+/// Generates a read-only `Visitor` trait and its `walk_*` functions.
+///
+/// Takes the path of the AST source file relative to the crate manifest, followed by any
+/// external leaf types that should get a hook without being walked into.
+///
+/// ```ignore
+/// raccoon_macros::visitor!("ir/ast.rs", Span);
+/// ```
+#[proc_macro]
+pub fn visitor(input: TokenStream) -> TokenStream {
+    visitor::expand(input, Mode::Visit)
+}
+
+/// Generates a `VisitorMut` trait and its `walk_*` functions that take nodes by mutable reference.
+///
+/// Accepts the same input as [`visitor!`](macro@visitor).
+#[proc_macro]
+pub fn visitor_mut(input: TokenStream) -> TokenStream {
+    visitor::expand(input, Mode::VisitMut)
+}
+
+/// Generates a `Fold` trait and its `fold_*` functions that take nodes by value and rebuild them.
+///
+/// Accepts the same input as [`visitor!`](macro@visitor).
+#[proc_macro]
+pub fn fold(input: TokenStream) -> TokenStream {
+    visitor::expand(input, Mode::Fold)
+}
@@ -0,0 +1,600 @@
+use std::{collections::HashSet, env, fs, path::PathBuf};
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Fields, GenericArgument, Ident, Item, LitStr, PathArguments, Token, Type,
+};
+
+//------------------------------------------------------------------------------
+// Type Definitions
+//------------------------------------------------------------------------------
+
+/// The kind of traversal to generate.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    Visit,
+    VisitMut,
+    Fold,
+}
+
+/// The macro input: `"path/to/ast.rs", LeafType, ...`.
+struct Input {
+    path: LitStr,
+    leaves: Vec<Ident>,
+}
+
+/// A struct or enum found in the AST source file.
+enum Node {
+    Struct {
+        name: Ident,
+        fields: Fields,
+    },
+    Enum {
+        name: Ident,
+        variants: Vec<(Ident, Fields)>,
+    },
+}
+
+/// How a field type is traversed.
+enum Shape<'a> {
+    /// A type defined in the AST file or a declared leaf type.
+    Node(&'a Ident),
+    Box(&'a Type),
+    Vec(&'a Type),
+    Option(&'a Type),
+    Tuple(Vec<&'a Type>),
+    /// Any other type. It is copied or moved as is.
+    Opaque,
+}
+
+/// Code generator for one traversal mode.
+struct Generator {
+    mode: Mode,
+    /// Names of every type that gets its own hook.
+    hooks: HashSet<String>,
+}
+
+//------------------------------------------------------------------------------
+// Implementations
+//------------------------------------------------------------------------------
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let mut leaves = Vec::new();
+        if input.parse::<Option<Token![,]>>()?.is_some() {
+            let rest = Punctuated::<Ident, Token![,]>::parse_terminated(input)?;
+            leaves.extend(rest);
+        }
+
+        Ok(Self { path, leaves })
+    }
+}
+
+impl Node {
+    fn name(&self) -> &Ident {
+        match self {
+            Node::Struct { name, .. } | Node::Enum { name, .. } => name,
+        }
+    }
+}
+
+impl Generator {
+    /// Generates the trait and free functions for every node.
+    fn generate(&self, nodes: &[Node], leaves: &[Ident]) -> TokenStream2 {
+        let (trait_name, trait_doc) = match self.mode {
+            Mode::Visit => (
+                format_ident!("Visitor"),
+                "Read-only traversal over the AST.\n\nEvery hook defaults to the matching `walk_*` function, which visits the children of the node.",
+            ),
+            Mode::VisitMut => (
+                format_ident!("VisitorMut"),
+                "In-place traversal over the AST.\n\nEvery hook defaults to the matching `walk_*` function, which visits the children of the node.",
+            ),
+            Mode::Fold => (
+                format_ident!("Fold"),
+                "Owning traversal over the AST that rebuilds every node.\n\nEvery hook defaults to the matching `fold_*` function, which folds the children of the node.",
+            ),
+        };
+
+        let node_methods = nodes
+            .iter()
+            .map(|node| self.trait_method(node.name(), true));
+        let leaf_methods = leaves.iter().map(|leaf| self.trait_method(leaf, false));
+        let functions = nodes.iter().map(|node| self.function(node));
+
+        quote! {
+            #[doc = #trait_doc]
+            pub trait #trait_name: Sized {
+                #(#node_methods)*
+                #(#leaf_methods)*
+            }
+
+            #(#functions)*
+        }
+    }
+
+    /// Generates the trait hook for a type.
+    fn trait_method(&self, name: &Ident, walks: bool) -> TokenStream2 {
+        let method = self.method_name(name);
+        let function = self.function_name(name);
+        match (self.mode, walks) {
+            (Mode::Visit, true) => quote! {
+                fn #method(&mut self, node: &#name) {
+                    #function(self, node)
+                }
+            },
+            (Mode::VisitMut, true) => quote! {
+                fn #method(&mut self, node: &mut #name) {
+                    #function(self, node)
+                }
+            },
+            (Mode::Fold, true) => quote! {
+                fn #method(&mut self, node: #name) -> #name {
+                    #function(self, node)
+                }
+            },
+            (Mode::Visit, false) => quote! {
+                fn #method(&mut self, _node: &#name) {}
+            },
+            (Mode::VisitMut, false) => quote! {
+                fn #method(&mut self, _node: &mut #name) {}
+            },
+            (Mode::Fold, false) => quote! {
+                fn #method(&mut self, node: #name) -> #name {
+                    node
+                }
+            },
+        }
+    }
+
+    /// Generates the free function that walks or folds the children of a node.
+    fn function(&self, node: &Node) -> TokenStream2 {
+        let name = node.name();
+        let function = self.function_name(name);
+        let doc = match self.mode {
+            Mode::Fold => format!("Folds the children of a [`{name}`]."),
+            _ => format!("Visits the children of a [`{name}`]."),
+        };
+
+        if self.mode == Mode::Fold {
+            let body = self.fold_body(node);
+            let trait_name = format_ident!("Fold");
+            return quote! {
+                #[doc = #doc]
+                pub fn #function<F: #trait_name>(folder: &mut F, node: #name) -> #name {
+                    #body
+                }
+            };
+        }
+
+        let body = self.walk_body(node);
+        let (visitor, node_param) = if body.is_empty() {
+            (format_ident!("_visitor"), format_ident!("_node"))
+        } else {
+            (format_ident!("visitor"), format_ident!("node"))
+        };
+        let (trait_name, reference) = match self.mode {
+            Mode::VisitMut => (format_ident!("VisitorMut"), quote!(&mut)),
+            _ => (format_ident!("Visitor"), quote!(&)),
+        };
+
+        quote! {
+            #[doc = #doc]
+            pub fn #function<V: #trait_name>(#visitor: &mut V, #node_param: #reference #name) {
+                #body
+            }
+        }
+    }
+
+    /// Generates the statements of a `walk_*` function.
+    fn walk_body(&self, node: &Node) -> TokenStream2 {
+        let reference = self.reference();
+        match node {
+            Node::Struct { fields, .. } => fields
+                .iter()
+                .enumerate()
+                .filter_map(|(index, field)| {
+                    let member = match &field.ident {
+                        Some(ident) => quote!(#ident),
+                        None => {
+                            let index = syn::Index::from(index);
+                            quote!(#index)
+                        }
+                    };
+                    self.walk_type(&field.ty, quote!(#reference node.#member), 0)
+                })
+                .collect(),
+            Node::Enum { name, variants } => {
+                if variants
+                    .iter()
+                    .all(|(_, fields)| fields.iter().all(|field| !self.is_traversed(&field.ty)))
+                {
+                    return TokenStream2::new();
+                }
+
+                let arms = variants.iter().map(|(variant, fields)| {
+                    let (pattern, bindings) = self.variant_pattern(name, variant, fields, true);
+                    let statements = bindings
+                        .iter()
+                        .filter_map(|(binding, ty)| self.walk_type(ty, quote!(#binding), 0));
+                    quote! {
+                        #pattern => {
+                            #(#statements)*
+                        }
+                    }
+                });
+
+                quote! {
+                    match node {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates the expression of a `fold_*` function.
+    fn fold_body(&self, node: &Node) -> TokenStream2 {
+        match node {
+            Node::Struct { name, fields } => {
+                let (pattern, bindings) = self.fields_pattern(quote!(#name), fields, false);
+                let rebuilt = self.rebuild(quote!(#name), fields, &bindings);
+                quote! {
+                    let #pattern = node;
+                    #rebuilt
+                }
+            }
+            Node::Enum { name, variants } => {
+                let arms = variants.iter().map(|(variant, fields)| {
+                    let (pattern, bindings) = self.variant_pattern(name, variant, fields, false);
+                    let rebuilt = self.rebuild(quote!(#name::#variant), fields, &bindings);
+                    quote!(#pattern => #rebuilt,)
+                });
+
+                quote! {
+                    match node {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rebuilds a struct or variant from its folded bindings.
+    fn rebuild(
+        &self,
+        path: TokenStream2,
+        fields: &Fields,
+        bindings: &[(TokenStream2, &Type)],
+    ) -> TokenStream2 {
+        let values = bindings
+            .iter()
+            .map(|(binding, ty)| self.fold_type(ty, binding.clone(), 0));
+        match fields {
+            Fields::Named(named) => {
+                let names = named
+                    .named
+                    .iter()
+                    .map(|field| field.ident.as_ref().unwrap());
+                quote!(#path { #(#names: #values),* })
+            }
+            Fields::Unnamed(_) => quote!(#path(#(#values),*)),
+            Fields::Unit => path,
+        }
+    }
+
+    /// Builds a `match` pattern for an enum variant and returns its bindings.
+    ///
+    /// When `skip_opaque` is set, fields that are never traversed are not bound.
+    fn variant_pattern<'a>(
+        &self,
+        name: &Ident,
+        variant: &Ident,
+        fields: &'a Fields,
+        skip_opaque: bool,
+    ) -> (TokenStream2, Vec<(TokenStream2, &'a Type)>) {
+        self.fields_pattern(quote!(#name::#variant), fields, skip_opaque)
+    }
+
+    /// Builds a destructuring pattern for a set of fields and returns its bindings.
+    fn fields_pattern<'a>(
+        &self,
+        path: TokenStream2,
+        fields: &'a Fields,
+        skip_opaque: bool,
+    ) -> (TokenStream2, Vec<(TokenStream2, &'a Type)>) {
+        let mut bindings = Vec::new();
+        let pattern = match fields {
+            Fields::Named(named) => {
+                let parts = named.named.iter().map(|field| {
+                    let ident = field.ident.as_ref().unwrap();
+                    if skip_opaque && !self.is_traversed(&field.ty) {
+                        quote!(#ident: _)
+                    } else {
+                        bindings.push((quote!(#ident), &field.ty));
+                        quote!(#ident)
+                    }
+                });
+                let parts = parts.collect::<Vec<_>>();
+                quote!(#path { #(#parts),* })
+            }
+            Fields::Unnamed(unnamed) => {
+                let parts = unnamed.unnamed.iter().enumerate().map(|(index, field)| {
+                    if skip_opaque && !self.is_traversed(&field.ty) {
+                        quote!(_)
+                    } else {
+                        let ident = format_ident!("field_{}", index);
+                        bindings.push((quote!(#ident), &field.ty));
+                        quote!(#ident)
+                    }
+                });
+                let parts = parts.collect::<Vec<_>>();
+                quote!(#path(#(#parts),*))
+            }
+            Fields::Unit => path,
+        };
+
+        (pattern, bindings)
+    }
+
+    /// Generates the statements that visit a value of type `ty` given a reference `expr` to it.
+    ///
+    /// Returns `None` if the type contains nothing to visit.
+    fn walk_type(&self, ty: &Type, expr: TokenStream2, depth: usize) -> Option<TokenStream2> {
+        if !self.is_traversed(ty) {
+            return None;
+        }
+
+        let item = format_ident!("item_{}", depth);
+        let reference = self.reference();
+        Some(match self.shape(ty) {
+            Shape::Node(name) => {
+                let method = self.method_name(name);
+                quote!(visitor.#method(#expr);)
+            }
+            Shape::Box(inner) => self.walk_type(inner, quote!(#reference **(#expr)), depth + 1)?,
+            Shape::Vec(inner) => {
+                let body = self.walk_type(inner, quote!(#item), depth + 1)?;
+                let iter = match self.mode {
+                    Mode::VisitMut => quote!(iter_mut),
+                    _ => quote!(iter),
+                };
+                quote! {
+                    for #item in (#expr).#iter() {
+                        #body
+                    }
+                }
+            }
+            Shape::Option(inner) => {
+                let body = self.walk_type(inner, quote!(#item), depth + 1)?;
+                quote! {
+                    if let Some(#item) = #expr {
+                        #body
+                    }
+                }
+            }
+            Shape::Tuple(elements) => {
+                let names = (0..elements.len())
+                    .map(|index| format_ident!("item_{}_{}", depth, index))
+                    .collect::<Vec<_>>();
+                let bodies = elements
+                    .iter()
+                    .zip(&names)
+                    .filter_map(|(ty, name)| self.walk_type(ty, quote!(#name), depth + 1));
+                quote! {
+                    let (#(#names),*) = #expr;
+                    #(#bodies)*
+                }
+            }
+            Shape::Opaque => return None,
+        })
+    }
+
+    /// Generates an expression that folds the owned value `expr` of type `ty`.
+    fn fold_type(&self, ty: &Type, expr: TokenStream2, depth: usize) -> TokenStream2 {
+        if !self.is_traversed(ty) {
+            return expr;
+        }
+
+        let item = format_ident!("item_{}", depth);
+        match self.shape(ty) {
+            Shape::Node(name) => {
+                let method = self.method_name(name);
+                quote!(folder.#method(#expr))
+            }
+            Shape::Box(inner) => {
+                let inner = self.fold_type(inner, quote!(*#expr), depth + 1);
+                quote!(Box::new(#inner))
+            }
+            Shape::Vec(inner) => {
+                let inner = self.fold_type(inner, quote!(#item), depth + 1);
+                quote!((#expr).into_iter().map(|#item| #inner).collect())
+            }
+            Shape::Option(inner) => {
+                let inner = self.fold_type(inner, quote!(#item), depth + 1);
+                quote!((#expr).map(|#item| #inner))
+            }
+            Shape::Tuple(elements) => {
+                let names = (0..elements.len())
+                    .map(|index| format_ident!("item_{}_{}", depth, index))
+                    .collect::<Vec<_>>();
+                let values = elements
+                    .iter()
+                    .zip(&names)
+                    .map(|(ty, name)| self.fold_type(ty, quote!(#name), depth + 1));
+                quote!({
+                    let (#(#names),*) = #expr;
+                    (#(#values),*)
+                })
+            }
+            Shape::Opaque => expr,
+        }
+    }
+
+    /// Checks if a type contains any node or leaf type.
+    fn is_traversed(&self, ty: &Type) -> bool {
+        match self.shape(ty) {
+            Shape::Node(_) => true,
+            Shape::Box(inner) | Shape::Vec(inner) | Shape::Option(inner) => {
+                self.is_traversed(inner)
+            }
+            Shape::Tuple(elements) => elements.iter().any(|ty| self.is_traversed(ty)),
+            Shape::Opaque => false,
+        }
+    }
+
+    /// Classifies a field type.
+    fn shape<'a>(&self, ty: &'a Type) -> Shape<'a> {
+        match ty {
+            Type::Tuple(tuple) => Shape::Tuple(tuple.elems.iter().collect()),
+            Type::Paren(paren) => self.shape(&paren.elem),
+            Type::Path(path) if path.qself.is_none() => {
+                let segment = match path.path.segments.last() {
+                    Some(segment) => segment,
+                    None => return Shape::Opaque,
+                };
+
+                let argument = match &segment.arguments {
+                    PathArguments::AngleBracketed(arguments) => {
+                        arguments.args.iter().find_map(|argument| match argument {
+                            GenericArgument::Type(ty) => Some(ty),
+                            _ => None,
+                        })
+                    }
+                    _ => None,
+                };
+
+                match (segment.ident.to_string().as_str(), argument) {
+                    ("Box", Some(inner)) => Shape::Box(inner),
+                    ("Vec", Some(inner)) => Shape::Vec(inner),
+                    ("Option", Some(inner)) => Shape::Option(inner),
+                    (name, None) if self.hooks.contains(name) => Shape::Node(&segment.ident),
+                    _ => Shape::Opaque,
+                }
+            }
+            _ => Shape::Opaque,
+        }
+    }
+
+    fn reference(&self) -> TokenStream2 {
+        match self.mode {
+            Mode::VisitMut => quote!(&mut),
+            _ => quote!(&),
+        }
+    }
+
+    /// The trait hook for a type, e.g. `visit_type_annotation`.
+    fn method_name(&self, name: &Ident) -> Ident {
+        let prefix = match self.mode {
+            Mode::Fold => "fold",
+            _ => "visit",
+        };
+
+        format_ident!("{}_{}", prefix, to_snake_case(&name.to_string()))
+    }
+
+    /// The free function for a type, e.g. `walk_type_annotation`.
+    fn function_name(&self, name: &Ident) -> Ident {
+        let prefix = match self.mode {
+            Mode::Fold => "fold",
+            _ => "walk",
+        };
+
+        format_ident!("{}_{}", prefix, to_snake_case(&name.to_string()))
+    }
+}
+
+//------------------------------------------------------------------------------
+// Functions
+//------------------------------------------------------------------------------
+
+pub(crate) fn expand(input: TokenStream, mode: Mode) -> TokenStream {
+    let input = parse_macro_input!(input as Input);
+    match generate(&input, mode) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn generate(input: &Input, mode: Mode) -> syn::Result<TokenStream2> {
+    let root = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = PathBuf::from(root).join(input.path.value());
+    let source = fs::read_to_string(&path).map_err(|err| {
+        syn::Error::new(
+            input.path.span(),
+            format!("cannot read `{}`: {err}", path.display()),
+        )
+    })?;
+
+    let file = syn::parse_file(&source).map_err(|err| {
+        syn::Error::new(
+            input.path.span(),
+            format!("cannot parse `{}`: {err}", path.display()),
+        )
+    })?;
+
+    let nodes = file
+        .items
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::Struct(item) => Some(Node::Struct {
+                name: respan(&item.ident),
+                fields: item.fields,
+            }),
+            Item::Enum(item) => Some(Node::Enum {
+                name: respan(&item.ident),
+                variants: item
+                    .variants
+                    .into_iter()
+                    .map(|variant| (respan(&variant.ident), variant.fields))
+                    .collect(),
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let hooks = nodes
+        .iter()
+        .map(|node| node.name().to_string())
+        .chain(input.leaves.iter().map(|leaf| leaf.to_string()))
+        .collect();
+
+    let generator = Generator { mode, hooks };
+    let tokens = generator.generate(&nodes, &input.leaves);
+
+    // Makes the including crate rebuild when the AST file changes.
+    let path = path.display().to_string();
+    Ok(quote! {
+        const _: &str = include_str!(#path);
+
+        #tokens
+    })
+}
+
+/// Gives an identifier parsed from the AST file a call-site span so it resolves in the caller.
+fn respan(ident: &Ident) -> Ident {
+    Ident::new(&ident.to_string(), Span::call_site())
+}
+
+/// Converts a type name like `TypeAnnotation` to `type_annotation`.
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (index, char) in name.chars().enumerate() {
+        if char.is_uppercase() {
+            if index != 0 {
+                snake.push('_');
+            }
+            snake.extend(char.to_lowercase());
+        } else {
+            snake.push(char);
+        }
+    }
+
+    snake
+}
@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use raccoon_compiler::lexer::LexerError;
+use raccoon_compiler::parser::ParserError;
+use raccoon_compiler::Parser;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Err(err) = Parser::parse(s) {
+            assert!(err.is::<LexerError>() || err.is::<ParserError>(), "{:?}", err);
+        }
+    }
+});
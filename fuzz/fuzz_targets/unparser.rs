@@ -0,0 +1,771 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use std::mem;
+
+use raccoon_compiler::ir::{
+    visit, visit_mut, Argument, Ast, BinaryOp, ClassKind, Comprehension, ComprehensionFor,
+    DeclarationKind, DictComprehension, Expr, ExprKind, Fold, Identifier, Import, ImportName,
+    ImportNames, LambdaBody, MatchCase, MatchExprCase, NodeId, Params, Path, Pattern, PatternKind,
+    Statement, StatementKind, StringLiteral, SubscriptIndex, TypeAnnotation, TypeAnnotationKind,
+    UnaryOp, Visitor, VisitorMut, WithItem,
+};
+use raccoon_compiler::lexer::{IntegerKind, StringKind, TokenKind};
+use raccoon_compiler::span::Span;
+use raccoon_compiler::{Lexer, Parser, Unparser};
+
+struct SpanEraser;
+
+impl Fold for SpanEraser {
+    fn fold_span(&mut self, _node: Span) -> Span {
+        Span::new(0, 0)
+    }
+
+    fn fold_node_id(&mut self, _node: NodeId) -> NodeId {
+        NodeId::default()
+    }
+}
+
+/// Turns an arbitrary AST into one the parser accepts, by replacing what breaks a rule the types
+/// of the AST do not capture. Names and literals the lexer would not give back as the same token
+/// become `x` and `0`, empty blocks get a `pass`, and the expressions, patterns and statements that
+/// are invalid where they are, like a `yield` outside a function, are replaced with valid ones.
+#[derive(Default)]
+struct Sanitizer {
+    /// The innermost function, with whether it is async.
+    function: Option<bool>,
+    /// Whether the walk is in the part of a comprehension that is a scope of its own.
+    is_comprehension: bool,
+}
+
+impl Sanitizer {
+    /// Walks the body of a function, a lambda or a class, which is `None`.
+    fn in_function(&mut self, function: Option<bool>, walk: impl FnOnce(&mut Self)) {
+        let outer = mem::replace(&mut self.function, function);
+        let is_comprehension = mem::replace(&mut self.is_comprehension, false);
+        walk(self);
+        self.function = outer;
+        self.is_comprehension = is_comprehension;
+    }
+
+    fn in_comprehension(&mut self, walk: impl FnOnce(&mut Self)) {
+        let is_comprehension = mem::replace(&mut self.is_comprehension, true);
+        walk(self);
+        self.is_comprehension = is_comprehension;
+    }
+
+    fn is_async(&self) -> bool {
+        self.function == Some(true)
+    }
+
+    fn block(&mut self, body: &mut Vec<Statement>) {
+        if body.is_empty() {
+            body.push(statement(StatementKind::Pass));
+        }
+
+        body.iter_mut()
+            .for_each(|statement| self.visit_statement(statement));
+    }
+
+    fn optional_block(&mut self, body: &mut Option<Vec<Statement>>) {
+        if let Some(body) = body {
+            self.block(body);
+        }
+    }
+
+    /// Repairs the target of an assignment or a `for`, which destructures without matching.
+    fn target(&mut self, target: &mut Pattern) {
+        match &mut target.kind {
+            PatternKind::Wildcard | PatternKind::Capture(_) => (),
+            PatternKind::Store(value) if is_store(value) => (),
+            PatternKind::Sequence(patterns) if !patterns.is_empty() => {
+                let mut has_rest = false;
+                for pattern in patterns.iter_mut() {
+                    match pattern.kind {
+                        PatternKind::Rest(_) if !has_rest => has_rest = true,
+                        _ => self.target(pattern),
+                    }
+                }
+            }
+            _ => target.kind = PatternKind::Capture(name()),
+        }
+
+        visit_mut::walk_pattern(self, target);
+    }
+}
+
+impl VisitorMut for Sanitizer {
+    fn visit_identifier(&mut self, node: &mut Identifier) {
+        if tokens(&node.name) != Some(vec![TokenKind::Identifier(node.name.clone())]) {
+            node.name = "x".to_string();
+        }
+    }
+
+    fn visit_path(&mut self, node: &mut Path) {
+        if node.names.is_empty() {
+            node.names.push(name());
+        }
+
+        visit_mut::walk_path(self, node);
+    }
+
+    fn visit_statement(&mut self, node: &mut Statement) {
+        match &mut node.kind {
+            StatementKind::Assign(assign) => {
+                assign
+                    .targets
+                    .iter_mut()
+                    .for_each(|target| self.target(target));
+                return self.visit_expr(&mut assign.value);
+            }
+            StatementKind::AugAssign(assign) => {
+                if matches!(assign.op, BinaryOp::Or | BinaryOp::And) {
+                    assign.op = BinaryOp::Plus;
+                }
+
+                if !is_target(&assign.target) {
+                    assign.target = identifier();
+                }
+            }
+            StatementKind::AnnAssign(assign) if !is_target(&assign.target) => {
+                assign.target = identifier()
+            }
+            StatementKind::Declaration(declaration)
+                if declaration.kind == DeclarationKind::Const && declaration.value.is_none() =>
+            {
+                declaration.value = Some(integer())
+            }
+            StatementKind::Global(names) | StatementKind::Nonlocal(names) if names.is_empty() => {
+                names.push(name())
+            }
+            StatementKind::Import(Import { path, .. })
+                if path.level == 0 && path.names.is_empty() =>
+            {
+                path.names.push(name())
+            }
+            StatementKind::ImportFrom(import) => {
+                if import.path.level == 0 && import.path.names.is_empty() {
+                    import.path.names.push(name());
+                }
+
+                if let ImportNames::Names(names) = &mut import.names {
+                    if names.is_empty() {
+                        names.push(ImportName {
+                            name: name(),
+                            alias: None,
+                        });
+                    }
+                }
+            }
+            StatementKind::If(node) => {
+                self.visit_expr(&mut node.condition);
+                self.block(&mut node.body);
+                for elif in node.elifs.iter_mut() {
+                    self.visit_expr(&mut elif.condition);
+                    self.block(&mut elif.body);
+                }
+
+                return self.optional_block(&mut node.orelse);
+            }
+            StatementKind::While(node) => {
+                self.visit_expr(&mut node.condition);
+                if let Some(guard) = &mut node.guard {
+                    self.visit_expr(guard);
+                }
+
+                self.block(&mut node.body);
+                return self.optional_block(&mut node.orelse);
+            }
+            StatementKind::For(node) => {
+                node.is_async &= self.is_async();
+                self.target(&mut node.target);
+                self.visit_expr(&mut node.iter);
+                if let Some(guard) = &mut node.guard {
+                    self.visit_expr(guard);
+                }
+
+                self.block(&mut node.body);
+                return self.optional_block(&mut node.orelse);
+            }
+            StatementKind::Try(node) => {
+                if node.handlers.is_empty() {
+                    node.orelse = None;
+                    node.finalbody.get_or_insert_with(Vec::new);
+                }
+
+                self.block(&mut node.body);
+                for handler in node.handlers.iter_mut() {
+                    self.visit_identifier(&mut handler.exception);
+                    if let Some(name) = &mut handler.name {
+                        self.visit_identifier(name);
+                    }
+
+                    self.block(&mut handler.body);
+                }
+
+                self.optional_block(&mut node.orelse);
+                return self.optional_block(&mut node.finalbody);
+            }
+            StatementKind::With(node) => {
+                node.is_async &= self.is_async();
+                if node.items.is_empty() {
+                    node.items.push(WithItem {
+                        context: identifier(),
+                        alias: None,
+                    });
+                }
+
+                for item in node.items.iter_mut() {
+                    self.visit_with_item(item);
+                }
+
+                return self.block(&mut node.body);
+            }
+            StatementKind::Match(node) => {
+                self.visit_expr(&mut node.subject);
+                if node.cases.is_empty() {
+                    node.cases.push(MatchCase {
+                        pattern: wildcard(),
+                        guard: None,
+                        body: vec![],
+                    });
+                }
+
+                for case in node.cases.iter_mut() {
+                    self.visit_pattern(&mut case.pattern);
+                    if let Some(guard) = &mut case.guard {
+                        self.visit_expr(guard);
+                    }
+
+                    self.block(&mut case.body);
+                }
+
+                return;
+            }
+            StatementKind::FunctionDef(def) => {
+                def.decorators
+                    .iter_mut()
+                    .for_each(|decorator| self.visit_decorator(decorator));
+                def.generics
+                    .iter_mut()
+                    .for_each(|name| self.visit_identifier(name));
+                def.bounds
+                    .iter_mut()
+                    .for_each(|bound| self.visit_generic_bound(bound));
+                self.visit_identifier(&mut def.name);
+                self.visit_params(&mut def.params);
+                if let Some(return_type) = &mut def.return_type {
+                    self.visit_type_annotation(return_type);
+                }
+
+                let function = Some(def.is_async);
+                let body = &mut def.body;
+                return self.in_function(function, |this| {
+                    body.iter_mut()
+                        .for_each(|statement| this.visit_statement(statement))
+                });
+            }
+            StatementKind::ClassDef(def) => {
+                // Only data classes have fields instead of bases, and only enums have variants.
+                match def.kind {
+                    ClassKind::Data => def.bases.clear(),
+                    _ => def.fields.clear(),
+                }
+
+                if def.kind != ClassKind::Enum {
+                    def.variants.clear();
+                }
+
+                def.decorators
+                    .iter_mut()
+                    .for_each(|decorator| self.visit_decorator(decorator));
+                def.generics
+                    .iter_mut()
+                    .for_each(|name| self.visit_identifier(name));
+                def.bounds
+                    .iter_mut()
+                    .for_each(|bound| self.visit_generic_bound(bound));
+                def.bases.iter_mut().for_each(|base| self.visit_identifier(base));
+                self.visit_identifier(&mut def.name);
+                def.fields.iter_mut().for_each(|field| self.visit_param(field));
+                def.variants.iter_mut().for_each(|variant| self.visit_variant(variant));
+
+                // A data class can end without a body, and so can an enum with variants.
+                let needs_body = match def.kind {
+                    ClassKind::Data => false,
+                    ClassKind::Enum => def.variants.is_empty(),
+                    _ => true,
+                };
+
+                let body = &mut def.body;
+                return self.in_function(None, |this| match needs_body {
+                    true => this.block(body),
+                    false => body
+                        .iter_mut()
+                        .for_each(|statement| this.visit_statement(statement)),
+                });
+            }
+            StatementKind::InterfaceDef(def) => {
+                def.decorators
+                    .iter_mut()
+                    .for_each(|decorator| self.visit_decorator(decorator));
+                def.generics
+                    .iter_mut()
+                    .for_each(|name| self.visit_identifier(name));
+                def.bounds
+                    .iter_mut()
+                    .for_each(|bound| self.visit_generic_bound(bound));
+                def.bases.iter_mut().for_each(|base| self.visit_identifier(base));
+                self.visit_identifier(&mut def.name);
+                for member in def.body.iter_mut() {
+                    match &mut member.kind {
+                        StatementKind::FunctionDef(def) => def.body.clear(),
+                        StatementKind::AnnAssign(assign) => assign.value = None,
+                        StatementKind::TypeAlias(_) | StatementKind::Pass => (),
+                        StatementKind::Expr(Expr {
+                            kind: ExprKind::Strings(_),
+                            ..
+                        }) => (),
+                        _ => member.kind = StatementKind::Pass,
+                    }
+                }
+
+                return self.in_function(None, |this| this.block(&mut def.body));
+            }
+            StatementKind::MacroDef(def) => {
+                self.visit_identifier(&mut def.name);
+                def.params
+                    .iter_mut()
+                    .for_each(|param| self.visit_identifier(param));
+                return self.block(&mut def.body);
+            }
+            _ => (),
+        }
+
+        visit_mut::walk_statement(self, node);
+    }
+
+    fn visit_expr(&mut self, node: &mut Expr) {
+        let expected = match &mut node.kind {
+            ExprKind::Integer(digits, kind) => {
+                vec![TokenKind::Integer(digits.clone(), kind.clone())]
+            }
+            ExprKind::Float(digits) => vec![TokenKind::Float(digits.clone())],
+            ExprKind::Imag(digits) => vec![TokenKind::Imag(digits.clone())],
+            ExprKind::Strings(strings) => strings
+                .iter()
+                .map(|string| match string {
+                    StringLiteral::Str(content, kind) => {
+                        TokenKind::Str(content.clone(), kind.clone())
+                    }
+                    StringLiteral::Bytes(content, kind) => {
+                        TokenKind::ByteStr(content.clone(), kind.clone())
+                    }
+                })
+                .collect(),
+            ExprKind::Yield(_) | ExprKind::YieldFrom(_)
+                if self.function.is_none() || self.is_comprehension =>
+            {
+                return *node = identifier();
+            }
+            ExprKind::Await(_) if !self.is_async() => return *node = identifier(),
+            ExprKind::Rest(_) | ExprKind::DictRest(_) => return *node = identifier(),
+            ExprKind::Tuple(elements) | ExprKind::List(elements) | ExprKind::Set(elements) => {
+                return elements.iter_mut().for_each(|element| self.element(element));
+            }
+            ExprKind::Call { func, arguments } => {
+                self.visit_expr(func);
+                for argument in arguments.iter_mut() {
+                    match argument {
+                        Argument::Positional(value) => self.element(value),
+                        Argument::Keyword { name, value } => {
+                            self.visit_identifier(name);
+                            self.visit_expr(value);
+                        }
+                    }
+                }
+
+                return;
+            }
+            ExprKind::Subscript { indices, .. } if indices.is_empty() => {
+                indices.push(SubscriptIndex::Index(integer()));
+                return visit_mut::walk_expr(self, node);
+            }
+            ExprKind::ListComp(comprehension)
+            | ExprKind::SetComp(comprehension)
+            | ExprKind::Generator(comprehension) => {
+                let Comprehension { element, clauses } = &mut **comprehension;
+                self.clauses(clauses);
+                return self.in_comprehension(|this| this.visit_expr(element));
+            }
+            ExprKind::DictComp(comprehension) => {
+                let DictComprehension { entry, clauses } = &mut **comprehension;
+                self.clauses(clauses);
+                return self.in_comprehension(|this| this.visit_dict_entry(entry));
+            }
+            ExprKind::Lambda(lambda) => {
+                self.visit_params(&mut lambda.params);
+                return self.in_function(Some(false), |this| match &mut lambda.body {
+                    LambdaBody::Expr(body) => this.visit_expr(body),
+                    LambdaBody::Block(body) => this.block(body),
+                });
+            }
+            ExprKind::Match(node) => {
+                self.visit_expr(&mut node.subject);
+                if node.cases.is_empty() {
+                    node.cases.push(MatchExprCase {
+                        pattern: wildcard(),
+                        guard: None,
+                        body: integer(),
+                    });
+                }
+
+                for case in node.cases.iter_mut() {
+                    self.visit_pattern(&mut case.pattern);
+                    if let Some(guard) = &mut case.guard {
+                        self.visit_expr(guard);
+                    }
+
+                    self.visit_expr(&mut case.body);
+                }
+
+                return;
+            }
+            _ => return visit_mut::walk_expr(self, node),
+        };
+
+        if expected.is_empty() || tokens(&Unparser::unparse_expr(node)) != Some(expected) {
+            node.kind = match node.kind {
+                ExprKind::Strings(_) => ExprKind::Strings(vec![StringLiteral::Str(
+                    String::new(),
+                    StringKind::Str,
+                )]),
+                _ => ExprKind::Integer("0".to_string(), IntegerKind::Dec),
+            };
+        }
+    }
+
+    fn visit_params(&mut self, node: &mut Params) {
+        // Keyword-only parameters come after a `*vararg`, as there is no bare `*`.
+        if node.vararg.is_none() {
+            node.params.append(&mut node.keyword_only);
+        }
+
+        visit_mut::walk_params(self, node);
+    }
+
+    fn visit_pattern(&mut self, node: &mut Pattern) {
+        match &mut node.kind {
+            PatternKind::Literal(literal) if !is_literal(literal) => {
+                node.kind = PatternKind::Wildcard
+            }
+            PatternKind::Store(_) => node.kind = PatternKind::Wildcard,
+            // A single name is a capture, so a value is a dotted path.
+            PatternKind::Value(path) => {
+                while path.names.len() < 2 {
+                    path.names.push(name());
+                }
+            }
+            PatternKind::Or(patterns) if patterns.is_empty() => node.kind = PatternKind::Wildcard,
+            PatternKind::Sequence(patterns) => {
+                let mut has_rest = false;
+                for pattern in patterns.iter_mut() {
+                    match pattern.kind {
+                        PatternKind::Rest(_) if !has_rest => has_rest = true,
+                        PatternKind::Rest(_) => pattern.kind = PatternKind::Wildcard,
+                        _ => (),
+                    }
+                }
+            }
+            PatternKind::Mapping { entries, .. } => {
+                for entry in entries.iter_mut() {
+                    if !matches!(&entry.key.kind, PatternKind::Literal(key) if is_literal(key))
+                        && !matches!(entry.key.kind, PatternKind::Value(_))
+                    {
+                        entry.key.kind = PatternKind::Literal(integer());
+                    }
+                }
+            }
+            _ => (),
+        }
+
+        // A `*rest` is only valid as an element of a sequence.
+        if let PatternKind::Sequence(patterns) = &mut node.kind {
+            for pattern in patterns.iter_mut() {
+                match &mut pattern.kind {
+                    PatternKind::Rest(Some(name)) => self.visit_identifier(name),
+                    _ => self.visit_pattern(pattern),
+                }
+            }
+
+            return;
+        }
+
+        if let PatternKind::Rest(_) = node.kind {
+            node.kind = PatternKind::Wildcard;
+        }
+
+        visit_mut::walk_pattern(self, node);
+    }
+
+    fn visit_type_annotation(&mut self, node: &mut TypeAnnotation) {
+        // Types are not parenthesized, so only an atom can be made optional without `a??`.
+        while let TypeAnnotationKind::Optional(annotation) = &node.kind {
+            match &annotation.kind {
+                TypeAnnotationKind::Identifier(_)
+                | TypeAnnotationKind::Path(_)
+                | TypeAnnotationKind::Generic { .. }
+                | TypeAnnotationKind::List(_)
+                | TypeAnnotationKind::Tuple(_) => break,
+                _ => node.kind = annotation.kind.clone(),
+            }
+        }
+
+        match &node.kind {
+            TypeAnnotationKind::Union(types) | TypeAnnotationKind::Intersection(types)
+                if types.is_empty() =>
+            {
+                node.kind = TypeAnnotationKind::Identifier(name())
+            }
+            _ => (),
+        }
+
+        visit_mut::walk_type_annotation(self, node);
+    }
+}
+
+impl Sanitizer {
+    /// Walks an element of a tuple, list, set or call, the only places a `*rest` is valid.
+    fn element(&mut self, element: &mut Expr) {
+        match &mut element.kind {
+            ExprKind::Rest(value) | ExprKind::DictRest(value) => self.visit_expr(value),
+            _ => self.visit_expr(element),
+        }
+    }
+
+    /// Walks the clauses of a comprehension, where only the first iterable is in the enclosing
+    /// scope.
+    fn clauses(&mut self, clauses: &mut [ComprehensionFor]) {
+        for (index, clause) in clauses.iter_mut().enumerate() {
+            clause.is_async &= self.is_async();
+            self.target(&mut clause.target);
+            match index {
+                0 => self.visit_expr(&mut clause.iter),
+                _ => self.in_comprehension(|this| this.visit_expr(&mut clause.iter)),
+            }
+
+            let conditions = &mut clause.conditions;
+            self.in_comprehension(|this| {
+                conditions
+                    .iter_mut()
+                    .for_each(|condition| this.visit_expr(condition))
+            });
+        }
+    }
+}
+
+/// Finds whether an AST nests deeper than the parser parses, without walking any deeper.
+#[derive(Default)]
+struct NestingMeter {
+    depth: usize,
+    is_too_deep: bool,
+}
+
+impl NestingMeter {
+    fn nested(&mut self, walk: impl FnOnce(&mut Self)) {
+        self.nested_by(1, walk);
+    }
+
+    fn nested_by(&mut self, levels: usize, walk: impl FnOnce(&mut Self)) {
+        if self.depth + levels > Parser::MAX_NESTING {
+            self.is_too_deep = true;
+            return;
+        }
+
+        self.depth += levels;
+        walk(self);
+        self.depth -= levels;
+    }
+}
+
+impl Visitor for NestingMeter {
+    fn visit_statement(&mut self, node: &Statement) {
+        self.nested(|this| visit::walk_statement(this, node));
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        // The parser counts each operation of a chain like `a + b + c` or `a.b()` as a level, so
+        // all of its operands are as deep as the chain is long.
+        let mut links = vec![];
+        let mut operand = node;
+        while let Some(inner) = chained(operand) {
+            links.push(operand);
+            operand = inner;
+        }
+
+        self.nested_by(links.len() + 1, |this| {
+            visit::walk_expr(this, operand);
+            for link in links {
+                match &link.kind {
+                    ExprKind::BinaryOp { rhs, .. } => this.visit_expr(rhs),
+                    ExprKind::Call { arguments, .. } => arguments
+                        .iter()
+                        .for_each(|argument| this.visit_argument(argument)),
+                    ExprKind::Subscript { indices, .. } => indices
+                        .iter()
+                        .for_each(|index| this.visit_subscript_index(index)),
+                    _ => (),
+                }
+            }
+        });
+    }
+
+    fn visit_pattern(&mut self, node: &Pattern) {
+        self.nested(|this| visit::walk_pattern(this, node));
+    }
+
+    fn visit_type_annotation(&mut self, node: &TypeAnnotation) {
+        self.nested(|this| visit::walk_type_annotation(this, node));
+    }
+}
+
+fn name() -> Identifier {
+    Identifier {
+        name: "x".to_string(),
+        id: NodeId::default(),
+        span: Span::new(0, 0),
+        context: None,
+    }
+}
+
+fn expr(kind: ExprKind) -> Expr {
+    Expr {
+        kind,
+        id: NodeId::default(),
+        span: Span::new(0, 0),
+    }
+}
+
+fn identifier() -> Expr {
+    expr(ExprKind::Identifier(name()))
+}
+
+fn integer() -> Expr {
+    expr(ExprKind::Integer("0".to_string(), IntegerKind::Dec))
+}
+
+fn statement(kind: StatementKind) -> Statement {
+    Statement {
+        kind,
+        id: NodeId::default(),
+        span: Span::new(0, 0),
+    }
+}
+
+fn wildcard() -> Pattern {
+    Pattern {
+        kind: PatternKind::Wildcard,
+        span: Span::new(0, 0),
+    }
+}
+
+/// The expression an operation of a left-associative chain applies to, like `a + b` in
+/// `a + b + c`.
+fn chained(expr: &Expr) -> Option<&Expr> {
+    match &expr.kind {
+        ExprKind::BinaryOp { lhs: value, .. }
+        | ExprKind::Call { func: value, .. }
+        | ExprKind::Subscript { value, .. }
+        | ExprKind::Attribute { value, .. }
+        | ExprKind::Propagate(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Whether an expression can be the target of an augmented or annotated assignment.
+fn is_target(target: &Expr) -> bool {
+    match &target.kind {
+        ExprKind::Identifier(_) => true,
+        _ => is_store(target),
+    }
+}
+
+/// Whether an expression is an attribute or item of a name, which assignments can store into.
+fn is_store(target: &Expr) -> bool {
+    match &target.kind {
+        ExprKind::Attribute { value, .. } | ExprKind::Subscript { value, .. } => {
+            is_rooted_at_identifier(value)
+        }
+        _ => false,
+    }
+}
+
+fn is_rooted_at_identifier(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Identifier(_) => true,
+        ExprKind::Attribute { value, .. }
+        | ExprKind::Subscript { value, .. }
+        | ExprKind::Call { func: value, .. }
+        | ExprKind::Propagate(value) => is_rooted_at_identifier(value),
+        _ => false,
+    }
+}
+
+/// Whether an expression is a string, a boolean or an optionally negated number, like literal
+/// patterns.
+fn is_literal(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Strings(_) | ExprKind::Bool(_) => true,
+        ExprKind::UnaryOp {
+            op: UnaryOp::Minus,
+            operand,
+        } => matches!(
+            operand.kind,
+            ExprKind::Integer(..) | ExprKind::Float(_) | ExprKind::Imag(_)
+        ),
+        _ => matches!(
+            expr.kind,
+            ExprKind::Integer(..) | ExprKind::Float(_) | ExprKind::Imag(_)
+        ),
+    }
+}
+
+/// The tokens of code without its layout, or `None` if it does not lex.
+fn tokens(code: &str) -> Option<Vec<TokenKind>> {
+    Lexer::tokenize(code)
+        .map(|token| token.ok().map(|token| token.kind))
+        .filter(|kind| {
+            !matches!(
+                kind,
+                Some(TokenKind::Newline | TokenKind::Indent | TokenKind::Dedent)
+            )
+        })
+        .collect()
+}
+
+// An arbitrary AST can break rules its types do not capture, like `await` outside an async
+// function, so it is sanitized first, and the code it unparses to must then parse and survive
+// another round trip unchanged. ASTs nested deeper than the parser parses are skipped before
+// anything recurses through them.
+fuzz_target!(|ast: Ast| {
+    let mut meter = NestingMeter::default();
+    meter.visit_ast(&ast);
+    if meter.is_too_deep {
+        return;
+    }
+
+    let mut ast = ast;
+    Sanitizer::default().visit_ast(&mut ast);
+    let code = Unparser::unparse(&ast);
+    let parsed = match Parser::parse(&code) {
+        Ok(parsed) => SpanEraser.fold_ast(parsed),
+        Err(error) => panic!("{}\ncode:\n{}", error, code),
+    };
+    let unparsed = Unparser::unparse(&parsed);
+    let reparsed = Parser::parse(&unparsed).map(|ast| SpanEraser.fold_ast(ast));
+
+    assert_eq!(
+        reparsed.ok(),
+        Some(parsed),
+        "code:\n{code}\nunparsed:\n{unparsed}"
+    );
+});
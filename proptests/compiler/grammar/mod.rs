@@ -0,0 +1,2 @@
+mod strategy;
+mod test;
@@ -0,0 +1,24 @@
+use proptest::prelude::*;
+use raccoon_grammar::{Grammar, Sampler};
+
+fn grammar() -> Grammar {
+    let parser = include_str!("../../../crates/compiler/parser/parser.grammar");
+    let lexer = include_str!("../../../crates/compiler/lexer/lexer.grammar");
+
+    Grammar::parse(parser)
+        .unwrap()
+        .with_tokens(Grammar::parse(lexer).unwrap())
+        .unwrap()
+}
+
+pub(super) fn token() -> impl Strategy<Value = String> {
+    Sampler::new(&grammar()).strategy("token").unwrap()
+}
+
+/// Programs without the expressions that only some functions allow.
+pub(super) fn program() -> impl Strategy<Value = String> {
+    Sampler::new(&grammar())
+        .without(&["yield_expr", "await", "async"])
+        .strategy("program")
+        .unwrap()
+}
@@ -0,0 +1,30 @@
+use proptest::{prop_assert, prop_assert_eq};
+use raccoon_compiler::{Lexer, Parser};
+use test_strategy::proptest;
+
+use super::strategy::{program, token};
+
+#[proptest]
+fn can_tokenize_sampled_tokens(#[strategy(token())] token: String) {
+    let tokens = Lexer::tokenize(&token).collect::<Vec<_>>();
+
+    prop_assert_eq!(tokens.len(), 1, "token: {:?}\ntokens: {:?}", token, tokens);
+    prop_assert!(
+        tokens[0].is_ok(),
+        "token: {:?}\ntokens: {:?}",
+        token,
+        tokens
+    );
+}
+
+#[proptest]
+fn can_parse_sampled_programs(#[strategy(program())] code: String) {
+    let result = Parser::parse(&code);
+
+    prop_assert!(
+        result.is_ok(),
+        "code:\n{}\nerror: {:?}",
+        code,
+        result.unwrap_err()
+    );
+}
@@ -1 +1,3 @@
+mod grammar;
 mod lexer;
+mod parser;
@@ -0,0 +1,1204 @@
+use std::mem;
+
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+use raccoon_compiler::ir::*;
+use raccoon_compiler::lexer::{BytesKind, IntegerKind, Keyword, StringKind};
+use raccoon_compiler::span::Span;
+
+// Generated ASTs only have the shapes the parser produces, with empty spans and literals in the
+// form the lexer normalizes them to.
+
+fn span() -> Span {
+    Span::new(0, 0)
+}
+
+fn node(kind: ExprKind) -> Expr {
+    Expr {
+        kind,
+        id: NodeId::default(),
+        span: span(),
+    }
+}
+
+fn statement_node(kind: StatementKind) -> Statement {
+    Statement {
+        kind,
+        id: NodeId::default(),
+        span: span(),
+    }
+}
+
+fn type_node(kind: TypeAnnotationKind) -> TypeAnnotation {
+    TypeAnnotation { kind, span: span() }
+}
+
+fn identifier() -> impl Strategy<Value = Identifier> {
+    r"[a-z_][a-z0-9_]{0,4}"
+        .prop_filter("identifiers cannot be keywords", |name| {
+            !Keyword::is_valid(name)
+        })
+        .prop_map(|name| Identifier {
+            name,
+            id: NodeId::default(),
+            span: span(),
+            context: None,
+        })
+}
+
+fn string_literal() -> impl Strategy<Value = StringLiteral> {
+    // Raw strings and bytes only allow ASCII characters.
+    let content = prop_oneof![r"[a-zé√ '\n]{0,6}", r#"[a-zé√ "\n]{0,6}"#];
+    let ascii_content = prop_oneof![r"[a-z '\n]{0,6}", r#"[a-z "\n]{0,6}"#];
+
+    prop_oneof![
+        (
+            content,
+            prop_oneof![Just(StringKind::Str), Just(StringKind::Format)]
+        )
+            .prop_map(|(content, kind)| StringLiteral::Str(content, kind)),
+        (
+            ascii_content.clone(),
+            prop_oneof![Just(StringKind::RawStr), Just(StringKind::RawFormat)]
+        )
+            .prop_map(|(content, kind)| StringLiteral::Str(content, kind)),
+        (
+            ascii_content,
+            prop_oneof![Just(BytesKind::Bytes), Just(BytesKind::RawBytes)]
+        )
+            .prop_map(|(content, kind)| StringLiteral::Bytes(content, kind)),
+    ]
+}
+
+fn literal() -> impl Strategy<Value = ExprKind> {
+    prop_oneof![
+        "0|[1-9][0-9]{0,3}".prop_map(|digits| ExprKind::Integer(digits, IntegerKind::Dec)),
+        "[01]{1,8}".prop_map(|digits| ExprKind::Integer(digits, IntegerKind::Bin)),
+        "[0-7]{1,4}".prop_map(|digits| ExprKind::Integer(digits, IntegerKind::Oct)),
+        "[0-9a-f]{1,4}".prop_map(|digits| ExprKind::Integer(digits, IntegerKind::Hex)),
+        r"(0|[1-9][0-9]{0,2})\.[0-9]{1,3}".prop_map(ExprKind::Float),
+        "[1-9][0-9]{0,2}".prop_map(ExprKind::Imag),
+        vec(string_literal(), 1..3).prop_map(ExprKind::Strings),
+        any::<bool>().prop_map(ExprKind::Bool),
+    ]
+}
+
+fn unary_op() -> impl Strategy<Value = UnaryOp> {
+    prop_oneof![
+        Just(UnaryOp::Plus),
+        Just(UnaryOp::Minus),
+        Just(UnaryOp::BitNot),
+        Just(UnaryOp::Not),
+        Just(UnaryOp::Sqrt),
+        Just(UnaryOp::Square),
+        Just(UnaryOp::Ref),
+        Just(UnaryOp::Val),
+        Just(UnaryOp::Ptr),
+    ]
+}
+
+/// Binary operators that also have an augmented assignment form.
+fn arithmetic_op() -> impl Strategy<Value = BinaryOp> {
+    prop_oneof![
+        Just(BinaryOp::BitOr),
+        Just(BinaryOp::BitXor),
+        Just(BinaryOp::BitAnd),
+        Just(BinaryOp::ShiftL),
+        Just(BinaryOp::ShiftR),
+        Just(BinaryOp::Plus),
+        Just(BinaryOp::Minus),
+        Just(BinaryOp::Mul),
+        Just(BinaryOp::MatMul),
+        Just(BinaryOp::Div),
+        Just(BinaryOp::IntDiv),
+        Just(BinaryOp::Mod),
+        Just(BinaryOp::Pow),
+    ]
+}
+
+fn binary_op() -> impl Strategy<Value = BinaryOp> {
+    prop_oneof![
+        1 => Just(BinaryOp::Or),
+        1 => Just(BinaryOp::And),
+        6 => arithmetic_op(),
+    ]
+}
+
+fn comparison_op() -> impl Strategy<Value = ComparisonOp> {
+    prop_oneof![
+        Just(ComparisonOp::Less),
+        Just(ComparisonOp::Greater),
+        Just(ComparisonOp::Eq),
+        Just(ComparisonOp::GreaterEq),
+        Just(ComparisonOp::LessEq),
+        Just(ComparisonOp::NotEq),
+        Just(ComparisonOp::In),
+        Just(ComparisonOp::NotIn),
+        Just(ComparisonOp::Is),
+        Just(ComparisonOp::IsNot),
+    ]
+}
+
+fn type_annotation() -> BoxedStrategy<TypeAnnotation> {
+    let leaf = identifier().prop_map(|name| type_node(TypeAnnotationKind::Identifier(name)));
+
+    leaf.prop_recursive(3, 16, 3, |inner| {
+        let atom = prop_oneof![
+            identifier().prop_map(|name| type_node(TypeAnnotationKind::Identifier(name))),
+            path(2).prop_map(|path| type_node(TypeAnnotationKind::Path(path))),
+            (identifier(), vec(inner.clone(), 1..3)).prop_map(|(name, arguments)| {
+                type_node(TypeAnnotationKind::Generic { name, arguments })
+            }),
+            vec(inner.clone(), 1..3).prop_map(|types| type_node(TypeAnnotationKind::List(types))),
+            vec(inner.clone(), 0..3).prop_map(|types| type_node(TypeAnnotationKind::Tuple(types))),
+        ]
+        .boxed();
+
+        // Unions, intersections and optionals cannot hold function types or be nested without
+        // brackets.
+        let atom = prop_oneof![
+            3 => atom.clone(),
+            1 => atom.prop_map(|annotation| type_node(TypeAnnotationKind::Optional(Box::new(annotation)))),
+        ]
+        .boxed();
+
+        let intersection = vec(atom.clone(), 2..4)
+            .prop_map(|types| type_node(TypeAnnotationKind::Intersection(types)))
+            .boxed();
+
+        let union = vec(prop_oneof![atom.clone(), intersection.clone()], 2..4)
+            .prop_map(|types| type_node(TypeAnnotationKind::Union(types)));
+
+        let function = (vec(inner.clone(), 0..3), inner).prop_map(|(params, return_type)| {
+            type_node(TypeAnnotationKind::Function {
+                params,
+                return_type: Box::new(return_type),
+            })
+        });
+
+        prop_oneof![atom, intersection, union, function]
+    })
+    .boxed()
+}
+
+fn param(default: BoxedStrategy<Expr>, annotated: bool) -> BoxedStrategy<Param> {
+    let annotation = match annotated {
+        true => option::of(type_annotation()).boxed(),
+        false => Just(None).boxed(),
+    };
+
+    let mode = prop_oneof![
+        Just(PassingMode::Ref),
+        Just(PassingMode::Val),
+        Just(PassingMode::Ptr),
+    ];
+
+    (
+        option::of(mode),
+        any::<bool>(),
+        identifier(),
+        annotation,
+        option::of(default),
+    )
+        .prop_map(|(mode, is_mut, name, annotation, default)| Param {
+            mode,
+            is_mut,
+            name,
+            annotation,
+            default,
+            span: span(),
+        })
+        .boxed()
+}
+
+fn params(default: BoxedStrategy<Expr>, annotated: bool) -> impl Strategy<Value = Params> {
+    let param = param(default, annotated);
+
+    (
+        vec(param.clone(), 0..2),
+        vec(param.clone(), 0..3),
+        option::of(param.clone()),
+        vec(param.clone(), 0..2),
+        option::of(param),
+    )
+        .prop_map(
+            |(positional_only, params, vararg, keyword_only, kwarg)| Params {
+                positional_only,
+                params,
+                // Keyword-only parameters can only follow a `*vararg`.
+                keyword_only: match vararg {
+                    Some(_) => keyword_only,
+                    None => vec![],
+                },
+                vararg,
+                kwarg,
+            },
+        )
+}
+
+fn argument(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = Argument> {
+    prop_oneof![
+        3 => expr.clone().prop_map(Argument::Positional),
+        1 => expr.clone().prop_map(|value| Argument::Positional(node(ExprKind::Rest(Box::new(value))))),
+        1 => expr.clone().prop_map(|value| Argument::Positional(node(ExprKind::DictRest(Box::new(value))))),
+        2 => (identifier(), expr).prop_map(|(name, value)| Argument::Keyword { name, value }),
+    ]
+}
+
+fn subscript_index(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = SubscriptIndex> {
+    prop_oneof![
+        expr.clone().prop_map(SubscriptIndex::Index),
+        (
+            option::of(expr.clone()),
+            option::of(expr.clone()),
+            option::of(expr)
+        )
+            .prop_map(|(lower, upper, step)| SubscriptIndex::Slice { lower, upper, step }),
+    ]
+}
+
+fn comprehension_for(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = ComprehensionFor> {
+    (
+        any::<bool>(),
+        target(expr.clone()),
+        expr.clone(),
+        vec(expr, 0..2),
+    )
+        .prop_map(|(is_async, target, iter, conditions)| ComprehensionFor {
+            is_async,
+            target,
+            iter,
+            conditions,
+        })
+}
+
+fn comprehension(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = Comprehension> {
+    (expr.clone(), vec(comprehension_for(expr), 1..3))
+        .prop_map(|(element, clauses)| Comprehension { element, clauses })
+}
+
+/// An element of a tuple, list or set, which may be a `*rest` expression.
+fn element(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = Expr> {
+    prop_oneof![
+        4 => expr.clone(),
+        1 => expr.prop_map(|value| node(ExprKind::Rest(Box::new(value)))),
+    ]
+}
+
+fn expr() -> BoxedStrategy<Expr> {
+    let leaf = prop_oneof![identifier().prop_map(ExprKind::Identifier), literal()].prop_map(node);
+
+    leaf.prop_recursive(4, 32, 4, |inner| {
+        let lambda_body = prop_oneof![
+            3 => inner.clone().prop_map(|body| LambdaBody::Expr(Box::new(body))),
+            1 => vec(simple_statement(inner.clone()), 1..3).prop_map(LambdaBody::Block),
+        ];
+
+        prop_oneof![
+            vec(element(inner.clone()), 0..3).prop_map(|elements| node(ExprKind::Tuple(elements))),
+            vec(element(inner.clone()), 0..3).prop_map(|elements| node(ExprKind::List(elements))),
+            vec(element(inner.clone()), 1..3).prop_map(|elements| node(ExprKind::Set(elements))),
+            vec(
+                (inner.clone(), inner.clone()).prop_map(|(key, value)| DictEntry { key, value }),
+                0..3
+            )
+            .prop_map(|entries| node(ExprKind::Dict(entries))),
+            comprehension(inner.clone())
+                .prop_map(|comprehension| node(ExprKind::ListComp(Box::new(comprehension)))),
+            comprehension(inner.clone())
+                .prop_map(|comprehension| node(ExprKind::SetComp(Box::new(comprehension)))),
+            (
+                inner.clone(),
+                inner.clone(),
+                vec(comprehension_for(inner.clone()), 1..3)
+            )
+                .prop_map(|(key, value, clauses)| node(ExprKind::DictComp(Box::new(
+                    DictComprehension {
+                        entry: DictEntry { key, value },
+                        clauses
+                    }
+                )))),
+            comprehension(inner.clone())
+                .prop_map(|comprehension| node(ExprKind::Generator(Box::new(comprehension)))),
+            (identifier(), inner.clone()).prop_map(|(name, value)| node(ExprKind::NamedExpr {
+                name,
+                value: Box::new(value)
+            })),
+            (unary_op(), inner.clone()).prop_map(|(op, operand)| node(ExprKind::UnaryOp {
+                op,
+                operand: Box::new(operand)
+            })),
+            (inner.clone(), binary_op(), inner.clone()).prop_map(|(lhs, op, rhs)| node(
+                ExprKind::BinaryOp {
+                    lhs: Box::new(lhs),
+                    op,
+                    rhs: Box::new(rhs)
+                }
+            )),
+            (
+                inner.clone(),
+                vec(
+                    (comparison_op(), inner.clone()).prop_map(|(op, rhs)| Comparison { op, rhs }),
+                    1..3
+                )
+            )
+                .prop_map(|(lhs, comparisons)| node(ExprKind::Comparison {
+                    lhs: Box::new(lhs),
+                    comparisons
+                })),
+            (inner.clone(), inner.clone(), inner.clone()).prop_map(
+                |(condition, then, otherwise)| node(ExprKind::Ternary {
+                    condition: Box::new(condition),
+                    then: Box::new(then),
+                    otherwise: Box::new(otherwise)
+                })
+            ),
+            (params(inner.clone(), false), lambda_body.clone()).prop_map(|(params, body)| node(
+                ExprKind::Lambda(Box::new(Lambda {
+                    params,
+                    body,
+                    is_generator: false
+                }))
+            )),
+            (params(inner.clone(), true), lambda_body).prop_map(|(params, body)| node(
+                ExprKind::Lambda(Box::new(Lambda {
+                    params,
+                    body,
+                    is_generator: false
+                }))
+            )),
+            (inner.clone(), vec(argument(inner.clone()), 0..3)).prop_map(|(func, arguments)| {
+                node(ExprKind::Call {
+                    func: Box::new(func),
+                    arguments,
+                })
+            }),
+            (inner.clone(), vec(subscript_index(inner.clone()), 1..3)).prop_map(
+                |(value, indices)| node(ExprKind::Subscript {
+                    value: Box::new(value),
+                    indices
+                })
+            ),
+            (inner.clone(), identifier()).prop_map(|(value, attribute)| node(
+                ExprKind::Attribute {
+                    value: Box::new(value),
+                    attribute
+                }
+            )),
+            inner
+                .clone()
+                .prop_map(|value| node(ExprKind::Propagate(Box::new(value)))),
+            inner
+                .clone()
+                .prop_map(|value| node(ExprKind::Await(Box::new(value)))),
+            option::of(element(inner.clone()))
+                .prop_map(|value| node(ExprKind::Yield(value.map(Box::new)))),
+            inner
+                .clone()
+                .prop_map(|value| node(ExprKind::YieldFrom(Box::new(value)))),
+            (
+                inner.clone(),
+                vec(
+                    (pattern(), option::of(inner.clone()), inner).prop_map(
+                        |(pattern, guard, body)| MatchExprCase {
+                            pattern,
+                            guard,
+                            body
+                        }
+                    ),
+                    1..3
+                )
+            )
+                .prop_map(|(subject, cases)| node(ExprKind::Match(Box::new(
+                    MatchExpr { subject, cases }
+                )))),
+        ]
+    })
+    .boxed()
+}
+
+fn pattern_node(kind: PatternKind) -> Pattern {
+    Pattern { kind, span: span() }
+}
+
+/// An identifier that is not `_`, which is the wildcard in patterns.
+fn binding() -> impl Strategy<Value = Identifier> {
+    identifier().prop_filter("`_` is not a binding", |name| name.name != "_")
+}
+
+fn path(min: usize) -> impl Strategy<Value = Path> {
+    vec(identifier(), min..3).prop_map(|names| Path { names })
+}
+
+fn literal_pattern() -> impl Strategy<Value = PatternKind> {
+    let number = || {
+        prop_oneof![
+            "0|[1-9][0-9]{0,3}".prop_map(|digits| ExprKind::Integer(digits, IntegerKind::Dec)),
+            r"(0|[1-9][0-9]{0,2})\.[0-9]{1,3}".prop_map(ExprKind::Float),
+            "[1-9][0-9]{0,2}".prop_map(ExprKind::Imag),
+        ]
+        .prop_map(node)
+    };
+
+    prop_oneof![
+        literal().prop_map(node),
+        number().prop_map(|number| node(ExprKind::UnaryOp {
+            op: UnaryOp::Minus,
+            operand: Box::new(number)
+        })),
+    ]
+    .prop_map(PatternKind::Literal)
+}
+
+fn pattern() -> BoxedStrategy<Pattern> {
+    let leaf = prop_oneof![
+        Just(PatternKind::Wildcard),
+        binding().prop_map(PatternKind::Capture),
+        literal_pattern(),
+        path(2).prop_map(PatternKind::Value),
+    ]
+    .prop_map(pattern_node);
+
+    leaf.prop_recursive(3, 16, 3, |inner| {
+        let mapping_key = prop_oneof![literal_pattern(), path(2).prop_map(PatternKind::Value)]
+            .prop_map(pattern_node);
+
+        prop_oneof![
+            // A sequence can only have one `*rest`.
+            (
+                vec(inner.clone(), 0..3),
+                option::of((any::<prop::sample::Index>(), option::of(binding())))
+            )
+                .prop_map(|(mut patterns, rest)| {
+                    if let Some((index, name)) = rest {
+                        let index = index.index(patterns.len() + 1);
+                        patterns.insert(index, pattern_node(PatternKind::Rest(name)));
+                    }
+
+                    pattern_node(PatternKind::Sequence(patterns))
+                }),
+            (
+                vec(
+                    (mapping_key, inner.clone())
+                        .prop_map(|(key, value)| MappingPatternEntry { key, value }),
+                    0..3
+                ),
+                option::of(identifier())
+            )
+                .prop_map(|(entries, rest)| pattern_node(PatternKind::Mapping { entries, rest })),
+            (
+                path(1),
+                vec(inner.clone(), 0..3),
+                vec(
+                    (identifier(), inner.clone())
+                        .prop_map(|(name, pattern)| KeywordPattern { name, pattern }),
+                    0..2
+                )
+            )
+                .prop_map(|(path, patterns, keywords)| pattern_node(
+                    PatternKind::Class {
+                        path,
+                        patterns,
+                        keywords
+                    }
+                )),
+            vec(inner.clone(), 2..4).prop_map(|patterns| pattern_node(PatternKind::Or(patterns))),
+            (inner, identifier()).prop_map(|(pattern, name)| pattern_node(PatternKind::As {
+                pattern: Box::new(pattern),
+                name
+            })),
+        ]
+    })
+    .boxed()
+}
+
+/// A target of an augmented or annotated assignment.
+fn single_target(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = Expr> {
+    let name = || identifier().prop_map(|name| node(ExprKind::Identifier(name)));
+    let root = prop_oneof![
+        name(),
+        (name(), identifier()).prop_map(|(value, attribute)| node(ExprKind::Attribute {
+            value: Box::new(value),
+            attribute
+        })),
+        (name(), vec(argument(expr.clone()), 0..2)).prop_map(|(func, arguments)| node(
+            ExprKind::Call {
+                func: Box::new(func),
+                arguments
+            }
+        )),
+    ]
+    .boxed();
+
+    prop_oneof![
+        name(),
+        (root.clone(), identifier()).prop_map(|(value, attribute)| node(ExprKind::Attribute {
+            value: Box::new(value),
+            attribute
+        })),
+        (root, vec(subscript_index(expr), 1..3)).prop_map(|(value, indices)| node(
+            ExprKind::Subscript {
+                value: Box::new(value),
+                indices
+            }
+        )),
+    ]
+}
+
+/// A target of an assignment or `for`, lowered to a pattern.
+fn target(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = Pattern> {
+    let leaf = single_target(expr)
+        .prop_map(|target| match target.kind {
+            ExprKind::Identifier(name) if name.name == "_" => PatternKind::Wildcard,
+            ExprKind::Identifier(name) => PatternKind::Capture(name),
+            _ => PatternKind::Store(Box::new(target)),
+        })
+        .prop_map(pattern_node);
+
+    leaf.prop_recursive(2, 8, 3, |inner| {
+        // A sequence can only have one `*rest`.
+        (
+            vec(inner, 1..3),
+            option::of((any::<prop::sample::Index>(), option::of(binding()))),
+        )
+            .prop_map(|(mut patterns, rest)| {
+                if let Some((index, name)) = rest {
+                    let index = index.index(patterns.len() + 1);
+                    patterns.insert(index, pattern_node(PatternKind::Rest(name)));
+                }
+
+                pattern_node(PatternKind::Sequence(patterns))
+            })
+    })
+}
+
+fn import_path() -> impl Strategy<Value = ImportPath> {
+    (0..3usize, vec(identifier(), 0..3)).prop_map(|(level, names)| ImportPath {
+        // Absolute paths need at least a name.
+        level: match names.is_empty() {
+            true => level.max(1),
+            false => level,
+        },
+        names,
+    })
+}
+
+fn import_names() -> impl Strategy<Value = ImportNames> {
+    prop_oneof![
+        1 => Just(ImportNames::All),
+        3 => vec(
+            (identifier(), option::of(identifier())).prop_map(|(name, alias)| ImportName { name, alias }),
+            1..3
+        )
+        .prop_map(ImportNames::Names),
+    ]
+}
+
+fn simple_statement(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = Statement> {
+    let value = element(expr.clone()).boxed();
+
+    prop_oneof![
+        4 => expr.clone().prop_map(StatementKind::Expr),
+        3 => (vec(target(expr.clone()), 1..3), value.clone())
+            .prop_map(|(targets, value)| StatementKind::Assign(Assign { targets, value })),
+        1 => (single_target(expr.clone()), arithmetic_op(), value.clone())
+            .prop_map(|(target, op, value)| StatementKind::AugAssign(AugAssign { target, op, value })),
+        1 => (single_target(expr.clone()), type_annotation(), option::of(value.clone()))
+            .prop_map(|(target, annotation, value)| StatementKind::AnnAssign(AnnAssign {
+                target,
+                annotation,
+                value
+            })),
+        1 => (
+            prop_oneof![
+                Just(DeclarationKind::Let),
+                Just(DeclarationKind::Var),
+                Just(DeclarationKind::Const)
+            ],
+            identifier(),
+            option::of(type_annotation()),
+            option::of(value)
+        )
+            .prop_map(|(kind, name, annotation, value)| StatementKind::Declaration(Declaration {
+                kind,
+                name,
+                annotation,
+                // A `const` needs a value.
+                value: match kind {
+                    DeclarationKind::Const => Some(value.unwrap_or_else(|| node(ExprKind::Bool(true)))),
+                    _ => value,
+                },
+            })),
+        1 => type_alias().prop_map(StatementKind::TypeAlias),
+        1 => Just(StatementKind::Pass),
+        1 => Just(StatementKind::Break),
+        1 => Just(StatementKind::Continue),
+        1 => option::of(expr.clone()).prop_map(StatementKind::Return),
+        1 => option::of((expr.clone(), option::of(expr.clone()))).prop_map(|raise| {
+            let (exception, cause) = match raise {
+                Some((exception, cause)) => (Some(exception), cause),
+                None => (None, None),
+            };
+
+            StatementKind::Raise(Raise { exception, cause })
+        }),
+        1 => vec(identifier(), 1..3).prop_map(StatementKind::Global),
+        1 => vec(identifier(), 1..3).prop_map(StatementKind::Nonlocal),
+        1 => (expr.clone(), option::of(expr))
+            .prop_map(|(test, message)| StatementKind::Assert(Assert { test, message })),
+        1 => (import_path(), option::of(identifier()))
+            .prop_map(|(path, alias)| StatementKind::Import(Import { path, alias })),
+        1 => (import_path(), import_names())
+            .prop_map(|(path, names)| StatementKind::ImportFrom(ImportFrom { path, names })),
+    ]
+    .prop_map(statement_node)
+}
+
+fn decorator(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = Decorator> {
+    (
+        vec(identifier(), 1..3),
+        option::of(vec(argument(expr), 0..3)),
+    )
+        .prop_map(|(names, arguments)| Decorator {
+            path: Path { names },
+            arguments,
+            span: span(),
+        })
+}
+
+fn bounds() -> impl Strategy<Value = Vec<GenericBound>> {
+    vec(
+        (identifier(), type_annotation()).prop_map(|(name, bound)| GenericBound { name, bound }),
+        0..2,
+    )
+}
+
+fn type_alias() -> impl Strategy<Value = TypeAlias> {
+    (identifier(), vec(identifier(), 0..2), type_annotation()).prop_map(
+        |(name, generics, value)| TypeAlias {
+            name,
+            generics,
+            value,
+        },
+    )
+}
+
+fn statement() -> impl Strategy<Value = Statement> {
+    let expr = expr();
+
+    simple_statement(expr.clone()).prop_recursive(3, 24, 3, move |inner| {
+        let body = vec(inner, 1..3).boxed();
+        let orelse = option::of(body.clone()).boxed();
+
+        prop_oneof![
+            (
+                expr.clone(),
+                body.clone(),
+                vec(
+                    (expr.clone(), body.clone())
+                        .prop_map(|(condition, body)| Elif { condition, body }),
+                    0..2
+                ),
+                orelse.clone()
+            )
+                .prop_map(|(condition, body, elifs, orelse)| StatementKind::If(If {
+                    condition,
+                    body,
+                    elifs,
+                    orelse
+                })),
+            (
+                expr.clone(),
+                option::of(expr.clone()),
+                body.clone(),
+                orelse.clone()
+            )
+                .prop_map(|(condition, guard, body, orelse)| StatementKind::While(
+                    While {
+                        condition,
+                        guard,
+                        body,
+                        orelse
+                    }
+                )),
+            (
+                any::<bool>(),
+                target(expr.clone()),
+                expr.clone(),
+                option::of(expr.clone()),
+                body.clone(),
+                orelse.clone()
+            )
+                .prop_map(|(is_async, target, iter, guard, body, orelse)| {
+                    StatementKind::For(For {
+                        is_async,
+                        target,
+                        iter,
+                        guard,
+                        body,
+                        orelse,
+                    })
+                }),
+            (
+                body.clone(),
+                vec(
+                    (identifier(), option::of(identifier()), body.clone()).prop_map(
+                        |(exception, name, body)| ExceptHandler {
+                            exception,
+                            name,
+                            body
+                        }
+                    ),
+                    0..2
+                ),
+                orelse.clone(),
+                orelse
+            )
+                .prop_map(|(body, handlers, orelse, finalbody)| {
+                    // A `try` needs a handler or a `finally`, and `else` needs a handler.
+                    let finalbody = match handlers.is_empty() {
+                        true => Some(
+                            finalbody.unwrap_or_else(|| vec![statement_node(StatementKind::Pass)]),
+                        ),
+                        false => finalbody,
+                    };
+
+                    StatementKind::Try(Try {
+                        orelse: orelse.filter(|_| !handlers.is_empty()),
+                        body,
+                        handlers,
+                        finalbody,
+                    })
+                }),
+            (
+                any::<bool>(),
+                vec(
+                    (expr.clone(), option::of(identifier()))
+                        .prop_map(|(context, alias)| WithItem { context, alias }),
+                    1..3
+                ),
+                body.clone()
+            )
+                .prop_map(|(is_async, items, body)| StatementKind::With(With {
+                    is_async,
+                    items,
+                    body
+                })),
+            (
+                expr.clone(),
+                vec(
+                    (pattern(), option::of(expr.clone()), body.clone()).prop_map(
+                        |(pattern, guard, body)| MatchCase {
+                            pattern,
+                            guard,
+                            body
+                        }
+                    ),
+                    1..3
+                )
+            )
+                .prop_map(|(subject, cases)| StatementKind::Match(Match { subject, cases })),
+            (
+                vec(decorator(expr.clone()), 0..2),
+                (any::<bool>(), any::<bool>()),
+                identifier(),
+                vec(identifier(), 0..2),
+                bounds(),
+                params(expr.clone(), true),
+                option::of(type_annotation()),
+                option::of(body.clone())
+            )
+                .prop_map(
+                    |(
+                        decorators,
+                        (is_abstract, is_async),
+                        name,
+                        generics,
+                        bounds,
+                        params,
+                        return_type,
+                        body,
+                    )| {
+                        StatementKind::FunctionDef(Box::new(FunctionDef {
+                            decorators,
+                            is_abstract,
+                            is_async,
+                            is_generator: false,
+                            name,
+                            generics,
+                            bounds,
+                            params,
+                            return_type,
+                            body: body.unwrap_or_default(),
+                        }))
+                    }
+                ),
+            class_def(expr.clone(), body.clone()),
+            interface_def(expr.clone()),
+            (identifier(), vec(identifier(), 0..3), body).prop_map(|(name, params, body)| {
+                StatementKind::MacroDef(Box::new(MacroDef { name, params, body }))
+            }),
+        ]
+        .prop_map(statement_node)
+    })
+}
+
+fn variant() -> impl Strategy<Value = Variant> {
+    let field = prop_oneof![
+        (identifier(), type_annotation())
+            .prop_map(|(name, annotation)| VariantField::Named { name, annotation }),
+        type_annotation().prop_map(VariantField::Positional),
+    ];
+
+    (identifier(), option::of(vec(field, 0..3))).prop_map(|(name, fields)| Variant {
+        name,
+        fields,
+        span: span(),
+    })
+}
+
+fn class_kind() -> impl Strategy<Value = ClassKind> {
+    prop_oneof![
+        Just(ClassKind::Class),
+        Just(ClassKind::Data),
+        Just(ClassKind::Enum),
+        Just(ClassKind::Union),
+        Just(ClassKind::Abstract),
+    ]
+}
+
+fn class_def(
+    expr: BoxedStrategy<Expr>,
+    body: BoxedStrategy<Vec<Statement>>,
+) -> impl Strategy<Value = StatementKind> {
+    (
+        vec(decorator(expr.clone()), 0..2),
+        class_kind(),
+        identifier(),
+        vec(identifier(), 0..2),
+        bounds(),
+        vec(identifier(), 0..3),
+        vec(param(expr, true), 0..3),
+        vec(variant(), 0..3),
+        option::of(body),
+    )
+        .prop_map(
+            |(decorators, kind, name, generics, bounds, bases, fields, variants, body)| {
+                let mut body = body.unwrap_or_default();
+
+                // Lines starting with an expression or an assignment could be read as variants.
+                if kind == ClassKind::Enum {
+                    body.retain(|statement| {
+                        !matches!(
+                            statement.kind,
+                            StatementKind::Expr(_)
+                                | StatementKind::Assign(_)
+                                | StatementKind::AugAssign(_)
+                                | StatementKind::AnnAssign(_)
+                        )
+                    });
+                }
+
+                // Only data classes can omit their body, and only enum classes have variants.
+                let variants = match kind {
+                    ClassKind::Enum => variants,
+                    _ => vec![],
+                };
+
+                if body.is_empty() && variants.is_empty() && kind != ClassKind::Data {
+                    body.push(statement_node(StatementKind::Pass));
+                }
+
+                StatementKind::ClassDef(Box::new(ClassDef {
+                    decorators,
+                    kind,
+                    name,
+                    generics,
+                    bounds,
+                    // Data classes use their parentheses for fields.
+                    bases: match kind {
+                        ClassKind::Data => vec![],
+                        _ => bases,
+                    },
+                    fields: match kind {
+                        ClassKind::Data => fields,
+                        _ => vec![],
+                    },
+                    variants,
+                    body,
+                }))
+            },
+        )
+}
+
+fn interface_member(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = Statement> {
+    prop_oneof![
+        3 => (
+            any::<bool>(),
+            identifier(),
+            vec(identifier(), 0..2),
+            params(expr, true),
+            option::of(type_annotation())
+        )
+            .prop_map(|(is_async, name, generics, params, return_type)| {
+                StatementKind::FunctionDef(Box::new(FunctionDef {
+                    decorators: vec![],
+                    is_abstract: false,
+                    is_async,
+                    is_generator: false,
+                    name,
+                    generics,
+                    bounds: vec![],
+                    params,
+                    return_type,
+                    body: vec![],
+                }))
+            }),
+        1 => (identifier(), type_annotation()).prop_map(|(name, annotation)| {
+            StatementKind::AnnAssign(AnnAssign {
+                target: node(ExprKind::Identifier(name)),
+                annotation,
+                value: None,
+            })
+        }),
+        1 => type_alias().prop_map(StatementKind::TypeAlias),
+        1 => string_literal().prop_map(|string| StatementKind::Expr(node(ExprKind::Strings(vec![string])))),
+        1 => Just(StatementKind::Pass),
+    ]
+    .prop_map(statement_node)
+}
+
+fn interface_def(expr: BoxedStrategy<Expr>) -> impl Strategy<Value = StatementKind> {
+    (
+        vec(decorator(expr.clone()), 0..2),
+        identifier(),
+        vec(identifier(), 0..2),
+        bounds(),
+        vec(identifier(), 0..3),
+        vec(interface_member(expr), 1..3),
+    )
+        .prop_map(|(decorators, name, generics, bounds, bases, body)| {
+            StatementKind::InterfaceDef(Box::new(InterfaceDef {
+                decorators,
+                name,
+                generics,
+                bounds,
+                bases,
+                body,
+            }))
+        })
+}
+
+/// What the innermost function of a node allows and contains.
+#[derive(Default, Clone, Copy)]
+struct FunctionContext {
+    is_async: bool,
+    is_generator: bool,
+    /// Whether the node is in the scope of a comprehension, which cannot `yield`.
+    in_comprehension: bool,
+}
+
+/// Removes `await`, `yield` and `async` where the parser does not allow them and marks generators.
+#[derive(Default)]
+struct ContextFixer {
+    function: Option<FunctionContext>,
+}
+
+impl ContextFixer {
+    /// Visits with `function` as the innermost function and returns whether it became a generator.
+    fn visit_in(
+        &mut self,
+        function: Option<FunctionContext>,
+        visit: impl FnOnce(&mut ContextFixer),
+    ) -> bool {
+        let outer = mem::replace(&mut self.function, function);
+        visit(self);
+        let function = mem::replace(&mut self.function, outer);
+
+        function.is_some_and(|function| function.is_generator)
+    }
+
+    fn is_async(&self) -> bool {
+        self.function.is_some_and(|function| function.is_async)
+    }
+
+    /// Visits the parts of a comprehension, all of which except the first iterable are in its scope.
+    fn visit_comprehension_scope(
+        &mut self,
+        elements: Vec<&mut Expr>,
+        clauses: &mut [ComprehensionFor],
+    ) {
+        if let Some(clause) = clauses.first_mut() {
+            self.visit_expr(&mut clause.iter);
+        }
+
+        let outer = self
+            .function
+            .as_mut()
+            .map(|function| mem::replace(&mut function.in_comprehension, true));
+
+        elements
+            .into_iter()
+            .for_each(|element| self.visit_expr(element));
+        for (index, clause) in clauses.iter_mut().enumerate() {
+            clause.is_async &= self.is_async();
+            self.visit_pattern(&mut clause.target);
+            if index > 0 {
+                self.visit_expr(&mut clause.iter);
+            }
+
+            clause
+                .conditions
+                .iter_mut()
+                .for_each(|condition| self.visit_expr(condition));
+        }
+
+        if let (Some(function), Some(outer)) = (self.function.as_mut(), outer) {
+            function.in_comprehension = outer;
+        }
+    }
+}
+
+impl VisitorMut for ContextFixer {
+    fn visit_expr(&mut self, node: &mut Expr) {
+        visit_mut::walk_expr(self, node);
+
+        match &mut node.kind {
+            ExprKind::Await(value) if !self.is_async() => *node = (**value).clone(),
+            ExprKind::Yield(_) | ExprKind::YieldFrom(_) => match self.function.as_mut() {
+                Some(function) if !function.in_comprehension => function.is_generator = true,
+                _ => node.kind = ExprKind::Tuple(vec![]),
+            },
+            _ => (),
+        }
+    }
+
+    fn visit_comprehension(&mut self, node: &mut Comprehension) {
+        self.visit_comprehension_scope(vec![&mut node.element], &mut node.clauses);
+    }
+
+    fn visit_dict_comprehension(&mut self, node: &mut DictComprehension) {
+        let entry = &mut node.entry;
+        self.visit_comprehension_scope(vec![&mut entry.key, &mut entry.value], &mut node.clauses);
+    }
+
+    fn visit_for(&mut self, node: &mut For) {
+        node.is_async &= self.is_async();
+        visit_mut::walk_for(self, node);
+    }
+
+    fn visit_with(&mut self, node: &mut With) {
+        node.is_async &= self.is_async();
+        visit_mut::walk_with(self, node);
+    }
+
+    fn visit_lambda(&mut self, node: &mut Lambda) {
+        self.visit_params(&mut node.params);
+        let body = &mut node.body;
+        node.is_generator = self.visit_in(Some(FunctionContext::default()), |this| {
+            this.visit_lambda_body(body)
+        });
+    }
+
+    fn visit_function_def(&mut self, node: &mut FunctionDef) {
+        node.decorators
+            .iter_mut()
+            .for_each(|decorator| self.visit_decorator(decorator));
+        self.visit_params(&mut node.params);
+
+        let function = FunctionContext {
+            is_async: node.is_async,
+            ..Default::default()
+        };
+        let body = &mut node.body;
+        node.is_generator = self.visit_in(Some(function), |this| {
+            body.iter_mut()
+                .for_each(|statement| this.visit_statement(statement))
+        });
+    }
+
+    fn visit_class_def(&mut self, node: &mut ClassDef) {
+        node.decorators
+            .iter_mut()
+            .for_each(|decorator| self.visit_decorator(decorator));
+        node.fields
+            .iter_mut()
+            .for_each(|field| self.visit_param(field));
+
+        let (variants, body) = (&mut node.variants, &mut node.body);
+        self.visit_in(None, |this| {
+            variants
+                .iter_mut()
+                .for_each(|variant| this.visit_variant(variant));
+            body.iter_mut()
+                .for_each(|statement| this.visit_statement(statement));
+        });
+    }
+
+    fn visit_interface_def(&mut self, node: &mut InterfaceDef) {
+        node.decorators
+            .iter_mut()
+            .for_each(|decorator| self.visit_decorator(decorator));
+
+        let body = &mut node.body;
+        self.visit_in(None, |this| {
+            body.iter_mut()
+                .for_each(|statement| this.visit_statement(statement))
+        });
+    }
+}
+
+pub(super) fn ast() -> impl Strategy<Value = Ast> {
+    vec(statement(), 0..4).prop_map(|statements| {
+        let mut ast = Ast {
+            statements,
+            id: NodeId::default(),
+            span: span(),
+        };
+
+        ContextFixer::default().visit_ast(&mut ast);
+        ast
+    })
+}
+
+/// Edits as a start that wraps around the code, whether to move it to the start of its line, a
+/// number of characters to remove and a text to insert. The text is often a line or a piece of
+/// layout that moves code in or out of a block.
+pub(super) fn edits() -> impl Strategy<Value = Vec<(usize, bool, usize, String)>> {
+    let text = prop_oneof![
+        Just(String::new()),
+        r"[a-z0-9 ]{1,3}",
+        prop::sample::select(vec![
+            "\n",
+            "    ",
+            "\t",
+            ":",
+            "(",
+            ")",
+            "[",
+            "]",
+            ",",
+            ";",
+            "#",
+            "'''",
+            "\\\n",
+            "\r\n",
+            "a = b\n",
+            "    pass\n",
+            "if a:\n    ",
+            "def f():\n    ",
+            "async def f():\n    ",
+            "    yield a\n",
+            "await a",
+            "else:\n",
+            "elif b:\n",
+            "    return\n",
+            "@d\n",
+            "class A:\n",
+        ])
+        .prop_map(String::from),
+    ];
+
+    vec((0..1000usize, any::<bool>(), 0..8usize, text), 1..8)
+}
@@ -0,0 +1,92 @@
+use proptest::prop_assert_eq;
+use raccoon_compiler::ir::{Ast, Fold, NodeId};
+use raccoon_compiler::parser::{Edit, IncrementalParser};
+use raccoon_compiler::span::Span;
+use raccoon_compiler::{Parser, Unparser};
+use test_strategy::proptest;
+
+use super::strategy::{ast, edits};
+
+/// Erases the spans and ids of a parsed AST, which the generated ones do not have.
+struct SpanEraser;
+
+/// Erases the ids of a parsed AST, which differ between parses of the same code.
+struct IdEraser;
+
+impl Fold for SpanEraser {
+    fn fold_span(&mut self, _node: Span) -> Span {
+        Span::new(0, 0)
+    }
+
+    fn fold_node_id(&mut self, _node: NodeId) -> NodeId {
+        NodeId::default()
+    }
+}
+
+impl Fold for IdEraser {
+    fn fold_node_id(&mut self, _node: NodeId) -> NodeId {
+        NodeId::default()
+    }
+}
+
+#[proptest]
+fn can_parse_unparsed_ast(#[strategy(ast())] ast: Ast) {
+    let code = Unparser::unparse(&ast);
+    let parsed = Parser::parse(&code).map(|parsed| SpanEraser.fold_ast(parsed));
+
+    prop_assert_eq!(parsed.ok(), Some(ast), "code:\n{}", code);
+}
+
+/// Edits code with the incremental parser and checks it gives the same AST as parsing from scratch.
+/// An edit that makes the code invalid is undone, so that the next ones edit valid code.
+#[proptest]
+fn can_reparse_edited_code(
+    #[strategy(ast())] ast: Ast,
+    #[strategy(edits())] edits: Vec<(usize, bool, usize, String)>,
+) {
+    let mut parser = IncrementalParser::new(Unparser::unparse(&ast));
+
+    for (start, is_line_start, length, text) in edits {
+        let chars = parser.code().chars().collect::<Vec<_>>();
+        let mut start = start % (chars.len() + 1);
+        if is_line_start {
+            start = chars[..start]
+                .iter()
+                .rposition(|char| *char == '\n')
+                .map_or(0, |index| index + 1);
+        }
+
+        let end = (start + length).min(chars.len());
+        let undo = Edit {
+            span: Span::new(start as u32, (start + text.chars().count()) as u32),
+            text: chars[start..end].iter().collect(),
+        };
+        let edit = Edit {
+            span: Span::new(start as u32, end as u32),
+            text,
+        };
+
+        for edit in [edit, undo] {
+            let reparsed = parser
+                .edit(&edit)
+                .ok()
+                .cloned()
+                .map(|ast| IdEraser.fold_ast(ast));
+            let parsed = Parser::parse(parser.code())
+                .ok()
+                .map(|ast| IdEraser.fold_ast(ast));
+            let is_valid = parsed.is_some();
+
+            prop_assert_eq!(
+                reparsed,
+                parsed,
+                "edit: {:?}\ncode:\n{}",
+                edit,
+                parser.code()
+            );
+            if is_valid {
+                break;
+            }
+        }
+    }
+}
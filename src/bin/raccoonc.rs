@@ -1,10 +1,127 @@
+use std::path::PathBuf;
+use std::process;
+
 use clap::Parser;
+use raccoon_compiler::semantic::SemanticAnalyzer;
+use raccoon_compiler::{Expander, ModuleLoader};
 
 #[derive(Parser, Debug)]
 #[clap(about, version, author)]
-struct Args {}
+struct Args {
+    /// The entry module to compile.
+    #[clap(parse(from_os_str))]
+    file: Option<PathBuf>,
+
+    /// A directory to look up imported modules in, after the directory of the entry module.
+    #[clap(long = "search-path", value_name = "DIR", parse(from_os_str))]
+    search_paths: Vec<PathBuf>,
+
+    /// The directory `std.*` imports map to, for a compiler run away from the source tree whose
+    /// `std/` it bundles.
+    #[clap(long = "std-path", value_name = "DIR", parse(from_os_str))]
+    std_path: Option<PathBuf>,
+
+    /// Prints the interface contracts inferred for the functions of each module.
+    #[clap(long = "dump-contracts")]
+    dump_contracts: bool,
+
+    /// Prints the instances of the functions that each module calls, in the order codegen
+    /// generates them.
+    #[clap(long = "dump-instances")]
+    dump_instances: bool,
+
+    /// Prints the container literals of each module with how they store their elements, like
+    /// `list[dyn gift.1]`.
+    #[clap(long = "dump-containers")]
+    dump_containers: bool,
+
+    /// Prints the fields of the classes of each module that their layout stores behind a `Box`,
+    /// like `Node.parent: Node? -> Box[Node?]`.
+    #[clap(long = "dump-layouts")]
+    dump_layouts: bool,
+
+    /// Prints where each allocation of each module lives, on the stack, on the heap or copied by
+    /// value, with why.
+    #[clap(long = "explain-alloc")]
+    explain_alloc: bool,
+}
 
 fn main() {
-    let _ = Args::parse();
-    println!("raccoonc: The Raccoon Compiler CLI");
+    let args = Args::parse();
+    let file = match args.file {
+        Some(file) => file,
+        None => {
+            println!("raccoonc: The Raccoon Compiler CLI");
+            return;
+        }
+    };
+
+    let mut loader = ModuleLoader::new(args.search_paths);
+    if let Some(std_path) = args.std_path {
+        loader = loader.with_std_path(std_path);
+    }
+
+    let graph = match loader.load(&file) {
+        Ok(graph) => graph,
+        Err(err) => {
+            eprintln!("raccoonc: {err:#}");
+            process::exit(1);
+        }
+    };
+
+    let mut has_errors = false;
+    for module in graph.order() {
+        println!("{} ({})", module.name, module.path.display());
+        let ast = match Expander::expand(module.ast.clone()) {
+            Ok(ast) => ast,
+            Err(err) => {
+                eprintln!("raccoonc: {err:#}");
+                process::exit(1);
+            }
+        };
+
+        let analyzer = SemanticAnalyzer::analyze_with_star_names(&ast, &module.star_names);
+        for diagnostic in analyzer.diagnostics.iter() {
+            let severity = if diagnostic.is_warning() {
+                "warning"
+            } else {
+                "error"
+            };
+            eprintln!(
+                "raccoonc: {}: {severity}: {diagnostic}",
+                module.path.display()
+            );
+        }
+
+        has_errors |= analyzer.errors().next().is_some();
+
+        let mut dump = String::new();
+        if args.dump_contracts {
+            dump.push_str(&analyzer.dump_contracts());
+        }
+
+        if args.dump_instances {
+            dump.push_str(&analyzer.dump_instances());
+        }
+
+        if args.dump_containers {
+            dump.push_str(&analyzer.dump_containers());
+        }
+
+        if args.dump_layouts {
+            dump.push_str(&analyzer.dump_layouts());
+        }
+
+        if args.explain_alloc {
+            dump.push_str(&analyzer.dump_allocations());
+        }
+
+        for line in dump.lines() {
+            println!("    {line}");
+        }
+    }
+
+    if has_errors {
+        process::exit(1);
+    }
 }